@@ -0,0 +1,107 @@
+//! Counts the allocations made by looking up the model's keys while hydrating 10,000 rows,
+//! comparing the keys that `Model::keys` collects once with collecting them again for every row.
+//! Only the allocations made by the lookup are counted, since cloning and deserializing the rows allocates the same either way.
+//!
+//! Run it with `cargo bench -p ensemble --bench keys`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use ensemble::{rbs, Model};
+
+const ROWS: usize = 10_000;
+
+/// Counts every allocation, so the benchmark can report how many each approach makes.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[derive(Debug, Model)]
+struct Flight {
+    id: u64,
+    name: String,
+    airline: String,
+    seats: u32,
+}
+
+fn rows() -> Vec<rbs::Value> {
+    (0..ROWS)
+        .map(|id| {
+            rbs::to_value(Flight {
+                id: id as u64,
+                name: format!("Flight {id}"),
+                airline: "Ensemble Air".to_string(),
+                seats: 180,
+            })
+            .unwrap()
+        })
+        .collect()
+}
+
+/// Hydrate every row, calling `keys` for each one.
+/// Returns the number of allocations made by `keys` alone (leaving out the ones made to clone and deserialize the rows), and how long hydrating took.
+fn hydrate<K: AsRef<[&'static str]>>(rows: &[rbs::Value], keys: fn() -> K) -> (usize, Duration) {
+    let start = Instant::now();
+    let mut allocations = 0;
+
+    for row in rows {
+        let flight: Flight = rbs::from_value(row.clone()).unwrap();
+
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let keys = keys();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+        black_box((flight, keys.as_ref().len()));
+    }
+
+    (allocations, start.elapsed())
+}
+
+/// How `keys` used to work, collecting the names into a new `Vec` on every call.
+fn collected_keys() -> Vec<&'static str> {
+    Flight::columns()
+        .iter()
+        .filter(|column| !column.db_generated)
+        .map(|column| column.name)
+        .collect()
+}
+
+fn main() {
+    let rows = rows();
+
+    // warm up, so collecting the keys once isn't counted
+    hydrate(&rows[..1], Flight::keys);
+
+    let (collected, collected_time) = hydrate(&rows, collected_keys);
+    let (cached, cached_time) = hydrate(&rows, Flight::keys);
+
+    println!("hydrating {ROWS} rows:");
+    println!("  keys collected per call: {collected:>6} allocations by keys, {collected_time:?} in total");
+    println!(
+        "  keys collected once:     {cached:>6} allocations by keys, {cached_time:?} in total"
+    );
+
+    assert_eq!(
+        collected, ROWS,
+        "collecting the keys should allocate once per row"
+    );
+    assert_eq!(cached, 0, "keys collected once shouldn't allocate again");
+}
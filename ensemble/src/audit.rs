@@ -0,0 +1,210 @@
+//! An audit trail for models.
+//!
+//! Models marked with `#[ensemble(auditable)]` record an [`Audit`] every time they are created, saved or deleted,
+//! in the same transaction as the change itself. Each audit holds the values of the columns that changed, before and after the change,
+//! along with the user responsible for it, if the change was made inside [`as_user`].
+//!
+//! Only changes made through model instances are audited: mass updates and deletes made through the query builder are not.
+//!
+//! Add the `audits` table to your migrations with [`CreateAuditsTable`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::{Model, audit::{self, Audit}};
+//! #[derive(Debug, Model)]
+//! #[ensemble(auditable)]
+//! struct Invoice {
+//!     id: u64,
+//!     total: u64,
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! let mut invoice = Invoice::find(1).await?;
+//! invoice.total = 200;
+//!
+//! // records the old and new totals, attributed to user 42
+//! audit::as_user(42_u64, invoice.save()).await?;
+//!
+//! let history = Audit::for_model::<Invoice>(1).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use rbs::Value;
+use serde_json::Map;
+use std::future::Future;
+
+use crate::{
+    outbox::EventType,
+    types::{DateTime, Json},
+    value::{self, Change},
+    Error, Model,
+};
+
+tokio::task_local! {
+    static USER: Value;
+}
+
+/// A change to a model, as recorded in the `audits` table.
+#[derive(Debug, Model)]
+#[ensemble(table = "audits", read_only)]
+pub struct Audit {
+    pub id: u64,
+    /// The name of the model that changed (e.g. `Invoice`).
+    pub auditable_type: String,
+    /// The primary key of the model that changed.
+    pub auditable_id: String,
+    /// What happened to the model: `created`, `updated` or `deleted`.
+    pub event: String,
+    /// The values of the changed columns before the change. Empty for created models.
+    pub old_values: Json,
+    /// The values of the changed columns after the change. Empty for deleted models.
+    pub new_values: Json,
+    /// The user who made the change, if it was made inside [`as_user`].
+    pub user_id: Option<String>,
+    /// When the change was made.
+    pub created_at: DateTime,
+}
+
+impl Audit {
+    /// Get the history of the model with the given primary key, oldest change first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn for_model<M: Model>(key: M::PrimaryKey) -> Result<Vec<Self>, Error> {
+        Self::query()
+            .r#where("auditable_type", "=", M::NAME)
+            .r#where("auditable_id", "=", key.to_string())
+            .order_by("id", "asc")
+            .get()
+            .await
+    }
+}
+
+/// Run `future`, attributing every change it audits to `user`.
+///
+/// The user is only visible to the task running `future`; tasks spawned from it must be wrapped in their own `as_user` call.
+pub async fn as_user<T: Into<Value>, F: Future>(user: T, future: F) -> F::Output {
+    USER.scope(user.into(), future).await
+}
+
+/// The user changes are currently attributed to, if any.
+#[must_use]
+pub fn current_user() -> Option<Value> {
+    USER.try_with(Clone::clone).ok()
+}
+
+/// The statement recording a change to a model in the audit trail, to run in the same transaction as the change itself.
+/// For updates, the values are only recorded if at least one column changed.
+pub(crate) fn record<M: Model>(
+    key: &M::PrimaryKey,
+    event: EventType,
+    old: Option<&M>,
+    new: Option<&M>,
+) -> Result<Option<(String, Vec<Value>)>, Error> {
+    let columns = |model: Option<&M>| -> Result<Map<String, serde_json::Value>, Error> {
+        let Some(Value::Map(columns)) = model.map(value::for_db).transpose()? else {
+            return Ok(Map::new());
+        };
+
+        Ok(columns
+            .into_iter()
+            .map(|(column, value)| (column.into_string().unwrap_or_default(), json(value)))
+            .collect())
+    };
+
+    let (old_values, new_values) = match (old, new) {
+        (Some(old), Some(new)) => {
+            let changes = old.diff(new);
+            if changes.is_empty() {
+                return Ok(None);
+            }
+
+            changes
+                .into_iter()
+                .map(|Change { column, old, new }| {
+                    ((column.clone(), json(old)), (column, json(new)))
+                })
+                .unzip()
+        }
+        (old, new) => {
+            let mut new = columns(new)?;
+
+            // created models might not have their generated key yet
+            if let Some(column) = new.get_mut(M::PRIMARY_KEY) {
+                *column = serde_json::to_value(key).unwrap_or_default();
+            }
+
+            (columns(old)?, new)
+        }
+    };
+
+    let user = current_user().map_or(Value::Null, |user| match user {
+        Value::String(user) => Value::String(user),
+        user => Value::String(user.to_string()),
+    });
+
+    Ok(Some((
+        "INSERT INTO audits (auditable_type, auditable_id, event, old_values, new_values, user_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)".to_string(),
+        vec![
+            Value::String(M::NAME.to_string()),
+            Value::String(key.to_string()),
+            Value::String(event.as_str().to_string()),
+            value::for_db(Json(serde_json::Value::Object(old_values)))?,
+            value::for_db(Json(serde_json::Value::Object(new_values)))?,
+            user,
+            value::for_db(DateTime::now())?,
+        ],
+    )))
+}
+
+fn json(value: Value) -> serde_json::Value {
+    // JSON columns are bound as strings, but should be recorded as the values they hold
+    if let Value::Ext("Json", json) = &value {
+        if let Some(Ok(json)) = json.as_str().map(serde_json::from_str) {
+            return json;
+        }
+    }
+
+    // every other value that comes out of `for_db` can be represented as JSON
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// A migration creating the `audits` table.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use ensemble::{migrate, audit::CreateAuditsTable};
+/// # async fn run() -> Result<(), ensemble::migrations::Error> {
+/// migrate!(CreateAuditsTable).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[derive(Debug, Default)]
+pub struct CreateAuditsTable;
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[async_trait::async_trait]
+impl crate::migrations::Migration for CreateAuditsTable {
+    async fn up(&self) -> Result<(), crate::migrations::Error> {
+        crate::migrations::Schema::create("audits", |table| {
+            table.id();
+            table.string("auditable_type");
+            table.string("auditable_id");
+            table.string("event");
+            table.json("old_values");
+            table.json("new_values");
+            table.string("user_id").nullable(true);
+            table.timestamp("created_at").use_current(true);
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), crate::migrations::Error> {
+        crate::migrations::Schema::drop("audits").await
+    }
+}
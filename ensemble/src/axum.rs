@@ -0,0 +1,261 @@
+//! Integration with the [axum](https://docs.rs/axum) web framework.
+//!
+//! Models serialize with their hidden fields removed, so they can be returned from handlers wrapped in [`Json`].
+//! Handlers can also return a [`Paginator`] or [`CursorPaginator`] as they are, or an [`Error`], which responds with an appropriate status code.
+
+use ::axum::{
+    async_trait,
+    extract::{rejection::PathRejection, FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    query::{CursorPaginator, Paginator},
+    Error, Model,
+};
+
+/// Extracts the primary key from the request path and finds the matching model, responding with `404 Not Found` when it doesn't exist.
+///
+/// ```rust,ignore
+/// async fn show(ModelById(user): ModelById<User>) -> Json<User> {
+///     Json(user)
+/// }
+///
+/// let app = Router::new().route("/users/:id", get(show));
+/// ```
+#[derive(Debug)]
+pub struct ModelById<M: Model>(pub M);
+
+#[async_trait]
+impl<M: Model, S: Send + Sync> FromRequestParts<S> for ModelById<M> {
+    type Rejection = Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(key) = Path::<M::PrimaryKey>::from_request_parts(parts, state).await?;
+
+        Ok(Self(M::find(key).await?))
+    }
+}
+
+impl<M: Model> IntoResponse for ModelById<M> {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
+
+impl<T: Serialize> IntoResponse for Paginator<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+impl<M: Model> IntoResponse for CursorPaginator<M> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// The rejection returned by [`ModelById`].
+#[derive(Debug)]
+pub enum Rejection {
+    /// The primary key could not be parsed from the request path.
+    Path(PathRejection),
+    /// The model could not be retrieved.
+    Model(Error),
+}
+
+impl From<PathRejection> for Rejection {
+    fn from(rejection: PathRejection) -> Self {
+        Self::Path(rejection)
+    }
+}
+
+impl From<Error> for Rejection {
+    fn from(error: Error) -> Self {
+        Self::Model(error)
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Path(rejection) => rejection.into_response(),
+            Self::Model(error) => error.into_response(),
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UniqueViolation => StatusCode::CONFLICT,
+            Self::Required(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            #[cfg(feature = "validator")]
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        // don't leak database errors to clients
+        if status.is_server_error() {
+            tracing::error!(error = %self, "Responding with an internal server error.");
+
+            return (status, Json(json!({ "message": "Server Error" }))).into_response();
+        }
+
+        #[cfg(feature = "validator")]
+        if let Self::Validation(errors) = &self {
+            return (
+                status,
+                Json(json!({ "message": self.to_string(), "errors": errors })),
+            )
+                .into_response();
+        }
+
+        (status, Json(json!({ "message": self.to_string() }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::axum::{body::HttpBody, http::Request, routing::get, Router};
+    use std::future::Future;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[derive(Debug, Model)]
+    struct User {
+        id: u64,
+        name: String,
+        #[model(hide)]
+        password: String,
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// The status of a response, along with its JSON body.
+    fn read(response: Response) -> (StatusCode, serde_json::Value) {
+        let status = response.status();
+        let mut body = response.into_body();
+
+        let mut bytes = vec![];
+        while let Some(chunk) = block_on(body.data()) {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn errors_respond_with_their_status() {
+        let (status, body) = read(Error::NotFound.into_response());
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, json!({ "message": "The model could not be found." }));
+
+        let (status, _) = read(Error::UniqueViolation.into_response());
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, body) = read(Error::Required("name").into_response());
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body, json!({ "message": "The name field is required." }));
+    }
+
+    #[test]
+    fn server_errors_are_not_shown_to_clients() {
+        let error = Error::Database("table `users` doesn't exist".to_string());
+
+        let (status, body) = read(error.into_response());
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body, json!({ "message": "Server Error" }));
+    }
+
+    #[test]
+    fn paginators_respond_with_their_page() {
+        let user = User {
+            id: 1,
+            name: "Taylor".to_string(),
+            password: "secret".to_string(),
+        };
+
+        let page = Paginator {
+            data: vec![user],
+            total: 16,
+            per_page: 15,
+            current_page: 2,
+            last_page: 2,
+        };
+
+        let (status, body) = read(page.into_response());
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            json!({
+                "data": [{ "id": 1, "name": "Taylor" }],
+                "total": 16,
+                "per_page": 15,
+                "current_page": 2,
+                "last_page": 2,
+            })
+        );
+
+        let page = CursorPaginator::<User> {
+            data: vec![],
+            per_page: 15,
+            next_cursor: None,
+        };
+
+        let (_, body) = read(page.into_response());
+        assert_eq!(
+            body,
+            json!({ "data": [], "per_page": 15, "next_cursor": null })
+        );
+    }
+
+    #[test]
+    fn unparsable_keys_are_rejected() {
+        async fn show(ModelById(user): ModelById<User>) -> Json<User> {
+            Json(user)
+        }
+
+        let app = Router::new().route("/users/:id", get(show));
+        let request = Request::builder()
+            .uri("/users/taylor")
+            .body(::axum::body::Body::empty())
+            .unwrap();
+
+        let response = block_on(app.oneshot(request)).unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn missing_models_are_not_found() {
+        async fn show(ModelById(user): ModelById<User>) -> Json<User> {
+            Json(user)
+        }
+
+        let app = Router::new().route("/users/:id", get(show));
+        let request = Request::builder()
+            .uri("/users/1")
+            .body(::axum::body::Body::empty())
+            .unwrap();
+
+        let fake = crate::testing::fake_connection();
+        let response = block_on(fake.run(app.oneshot(request))).unwrap();
+
+        let (status, body) = read(response);
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, json!({ "message": "The model could not be found." }));
+        assert_eq!(fake.statements().len(), 1);
+    }
+}
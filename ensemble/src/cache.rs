@@ -0,0 +1,366 @@
+//! A compact binary encoding of models, for caching them outside of the database.
+//!
+//! The encoding starts with a version byte, followed by the model's name, its columns, and (optionally) its loaded relationships.
+//! Columns are stored by name rather than by position, so bytes written by a different version of the model never end up in the wrong field:
+//! decoding fails with an [`Error::InvalidCache`] if a column is missing, or if there's a column the model doesn't have.
+
+use rbs::{value::map::ValueMap, Value};
+use std::sync::Mutex;
+
+use crate::{value, Error, Model};
+
+/// The version of the encoding, bumped whenever it changes.
+const VERSION: u8 = 1;
+
+mod tag {
+    pub const NULL: u8 = 0;
+    pub const FALSE: u8 = 1;
+    pub const TRUE: u8 = 2;
+    pub const I32: u8 = 3;
+    pub const I64: u8 = 4;
+    pub const U32: u8 = 5;
+    pub const U64: u8 = 6;
+    pub const F32: u8 = 7;
+    pub const F64: u8 = 8;
+    pub const STRING: u8 = 9;
+    pub const BINARY: u8 = 10;
+    pub const ARRAY: u8 = 11;
+    pub const MAP: u8 = 12;
+    pub const EXT: u8 = 13;
+}
+
+/// Encode a model, along with its loaded relationships if `relations` is true.
+pub fn to_bytes<M: Model>(model: &M, relations: bool) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![VERSION];
+    encode(
+        &mut bytes,
+        &Value::Array(vec![
+            Value::String(M::NAME.to_string()),
+            to_value(model, relations)?,
+        ]),
+    );
+
+    Ok(bytes)
+}
+
+/// Decode a model encoded with [`to_bytes`].
+pub fn from_bytes<M: Model>(bytes: &[u8]) -> Result<M, Error> {
+    let Some((&version, mut bytes)) = bytes.split_first() else {
+        return Err(invalid("it is empty"));
+    };
+    if version != VERSION {
+        return Err(invalid(format!(
+            "it was encoded with version {version}, not {VERSION}"
+        )));
+    }
+
+    let value = decode(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(invalid("there are bytes left over"));
+    }
+
+    let Value::Array(mut parts) = value else {
+        return Err(invalid("it is not a model"));
+    };
+    let (Some(model), Some(Value::String(name)), None) = (parts.pop(), parts.pop(), parts.pop())
+    else {
+        return Err(invalid("it is not a model"));
+    };
+
+    if name != M::NAME {
+        return Err(invalid(format!("it holds a {name}, not a {}", M::NAME)));
+    }
+
+    from_value(model)
+}
+
+/// A model's columns (including the ones generated by the database), followed by its loaded relationships or `Null`.
+pub fn to_value<M: Model>(model: &M, relations: bool) -> Result<Value, Error> {
+    let Value::Map(mut columns) = value::for_db(model)? else {
+        return Err(invalid("the model is not a map"));
+    };
+    for (column, value) in model.generated_columns()? {
+        columns.insert(Value::String(column.to_string()), value);
+    }
+
+    let relations = if relations {
+        let mut map = ValueMap::new();
+        for (relation, value) in model.cached_relations()? {
+            map.insert(Value::String(relation.to_string()), value);
+        }
+
+        Value::Map(map)
+    } else {
+        Value::Null
+    };
+
+    Ok(Value::Array(vec![Value::Map(columns), relations]))
+}
+
+/// Rebuild a model from [`to_value`], as if it was retrieved from the database.
+pub fn from_value<M: Model>(value: Value) -> Result<M, Error> {
+    let Value::Array(mut parts) = value else {
+        return Err(invalid("it is not a model"));
+    };
+    let (Some(relations), Some(columns @ Value::Map(_)), None) =
+        (parts.pop(), parts.pop(), parts.pop())
+    else {
+        return Err(invalid("it is not a model"));
+    };
+
+    let mut model =
+        value::from::<M>(columns).map_err(|error| invalid(format!("{}: {error}", M::NAME)))?;
+
+    if let Value::Map(relations) = relations {
+        for (relation, value) in relations {
+            let Value::String(relation) = relation else {
+                return Err(invalid("a relationship name is not a string"));
+            };
+
+            model.restore_relation(&relation, value)?;
+        }
+    }
+
+    Ok(model)
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidCache(reason.into())
+}
+
+fn encode(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => bytes.push(tag::NULL),
+        Value::Bool(false) => bytes.push(tag::FALSE),
+        Value::Bool(true) => bytes.push(tag::TRUE),
+        Value::I32(value) => {
+            bytes.push(tag::I32);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::I64(value) => {
+            bytes.push(tag::I64);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::U32(value) => {
+            bytes.push(tag::U32);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::U64(value) => {
+            bytes.push(tag::U64);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::F32(value) => {
+            bytes.push(tag::F32);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::F64(value) => {
+            bytes.push(tag::F64);
+            bytes.extend(value.to_le_bytes());
+        }
+        Value::String(value) => {
+            bytes.push(tag::STRING);
+            encode_bytes(bytes, value.as_bytes());
+        }
+        Value::Binary(value) => {
+            bytes.push(tag::BINARY);
+            encode_bytes(bytes, value);
+        }
+        Value::Array(values) => {
+            bytes.push(tag::ARRAY);
+            encode_len(bytes, values.len());
+            for value in values {
+                encode(bytes, value);
+            }
+        }
+        Value::Map(map) => {
+            bytes.push(tag::MAP);
+            encode_len(bytes, map.len());
+            for (key, value) in map {
+                encode(bytes, key);
+                encode(bytes, value);
+            }
+        }
+        Value::Ext(name, value) => {
+            bytes.push(tag::EXT);
+            encode_bytes(bytes, name.as_bytes());
+            encode(bytes, value);
+        }
+    }
+}
+
+/// Lengths are written as LEB128, so short strings and collections only take a byte.
+fn encode_len(bytes: &mut Vec<u8>, mut len: usize) {
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+
+        if len == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn encode_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    encode_len(bytes, value.len());
+    bytes.extend(value);
+}
+
+fn decode(bytes: &mut &[u8]) -> Result<Value, Error> {
+    Ok(match take::<1>(bytes)?[0] {
+        tag::NULL => Value::Null,
+        tag::FALSE => Value::Bool(false),
+        tag::TRUE => Value::Bool(true),
+        tag::I32 => Value::I32(i32::from_le_bytes(take(bytes)?)),
+        tag::I64 => Value::I64(i64::from_le_bytes(take(bytes)?)),
+        tag::U32 => Value::U32(u32::from_le_bytes(take(bytes)?)),
+        tag::U64 => Value::U64(u64::from_le_bytes(take(bytes)?)),
+        tag::F32 => Value::F32(f32::from_le_bytes(take(bytes)?)),
+        tag::F64 => Value::F64(f64::from_le_bytes(take(bytes)?)),
+        tag::STRING => Value::String(decode_string(bytes)?),
+        tag::BINARY => Value::Binary(decode_bytes(bytes)?.to_vec()),
+        tag::ARRAY => {
+            let len = decode_len(bytes)?;
+            let mut values = Vec::with_capacity(len.min(bytes.len()));
+            for _ in 0..len {
+                values.push(decode(bytes)?);
+            }
+
+            Value::Array(values)
+        }
+        tag::MAP => {
+            let len = decode_len(bytes)?;
+            let mut map = ValueMap::with_capacity(len.min(bytes.len()));
+            for _ in 0..len {
+                let key = decode(bytes)?;
+                map.0.push((key, decode(bytes)?));
+            }
+
+            Value::Map(map)
+        }
+        tag::EXT => {
+            let name = intern(decode_string(bytes)?);
+
+            Value::Ext(name, Box::new(decode(bytes)?))
+        }
+        tag => return Err(invalid(format!("{tag} is not a valid tag"))),
+    })
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], Error> {
+    if bytes.len() < N {
+        return Err(invalid("it ends unexpectedly"));
+    }
+
+    let (value, rest) = bytes.split_at(N);
+    *bytes = rest;
+
+    Ok(value.try_into().unwrap_or([0; N]))
+}
+
+fn decode_len(bytes: &mut &[u8]) -> Result<usize, Error> {
+    let mut len = 0_usize;
+
+    for shift in (0..usize::BITS).step_by(7) {
+        let [byte] = take::<1>(bytes)?;
+        len |= usize::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(len);
+        }
+    }
+
+    Err(invalid("a length is too long"))
+}
+
+fn decode_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = decode_len(bytes)?;
+    if bytes.len() < len {
+        return Err(invalid("it ends unexpectedly"));
+    }
+
+    let (value, rest) = bytes.split_at(len);
+    *bytes = rest;
+
+    Ok(value)
+}
+
+fn decode_string(bytes: &mut &[u8]) -> Result<String, Error> {
+    String::from_utf8(decode_bytes(bytes)?.to_vec()).map_err(|_| invalid("a string is not UTF-8"))
+}
+
+/// The names of extension values are `'static`, so each distinct name is leaked once and reused afterwards.
+fn intern(name: String) -> &'static str {
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(vec![]);
+
+    let mut names = NAMES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    if let Some(name) = names.iter().find(|known| **known == name) {
+        return name;
+    }
+
+    let name: &'static str = Box::leak(name.into_boxed_str());
+    names.push(name);
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &Value) -> Value {
+        let mut bytes = vec![];
+        encode(&mut bytes, value);
+
+        let mut slice = bytes.as_slice();
+        let decoded = decode(&mut slice).unwrap();
+        assert!(slice.is_empty());
+
+        decoded
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let mut map = ValueMap::new();
+        map.insert(Value::from("id"), Value::U64(u64::MAX));
+        map.insert(Value::from("balance"), Value::I64(-42));
+        map.insert(
+            Value::from("created_at"),
+            Value::Ext("DateTime", Box::new(Value::from("2023-10-01T00:00:00Z"))),
+        );
+
+        let value = Value::Array(vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::I32(-1),
+            Value::U32(7),
+            Value::F32(1.5),
+            Value::F64(-0.25),
+            Value::from("x".repeat(300)),
+            Value::Binary(vec![0, 159, 255]),
+            Value::Map(map),
+        ]);
+
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn truncated_values_are_rejected() {
+        let mut bytes = vec![];
+        encode(&mut bytes, &Value::from("hello"));
+        bytes.pop();
+
+        assert!(matches!(
+            decode(&mut bytes.as_slice()),
+            Err(Error::InvalidCache(_))
+        ));
+        assert!(matches!(
+            decode(&mut [99_u8].as_slice()),
+            Err(Error::InvalidCache(_))
+        ));
+    }
+}
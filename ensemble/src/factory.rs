@@ -0,0 +1,227 @@
+//! Factories building models with made-up attributes, for seeding databases and setting up tests.
+//!
+//! A model gets a factory by implementing [`Factory`], whose [`definition`](Factory::definition) returns a new instance with its attributes filled in.
+//! Crates like [`fake`](https://docs.rs/fake) are a good fit for generating them. [`factory`](Factory::factory) then returns a [`ModelFactory`],
+//! which builds any number of models, optionally tweaked with [`state`](ModelFactory::state) or [`sequence`](ModelFactory::sequence),
+//! along with the models related to each of them with [`has`](ModelFactory::has).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::{factory::Factory, relationships::HasMany, Model};
+//! # #[derive(Debug, Model)]
+//! # struct Post {
+//! #     id: u64,
+//! #     user_id: u64,
+//! #     title: String,
+//! # }
+//! # impl Factory for Post {
+//! #     fn definition() -> Self {
+//! #         Self { title: "Hello, world!".to_string(), ..Self::default() }
+//! #     }
+//! # }
+//! #[derive(Debug, Model)]
+//! struct User {
+//!     id: u64,
+//!     name: String,
+//!     admin: bool,
+//!     posts: HasMany<User, Post>,
+//! }
+//!
+//! impl Factory for User {
+//!     fn definition() -> Self {
+//!         Self {
+//!             name: "Taylor".to_string(),
+//!             ..Self::default()
+//!         }
+//!     }
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! // 50 users, with 3 posts each
+//! let users = User::factory()
+//!     .count(50)
+//!     .has(Post::factory().count(3))
+//!     .create()
+//!     .await?;
+//!
+//! // an admin that isn't saved to the database
+//! let admin = User::factory().state(|user| user.admin = true).make_one();
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+use crate::{naming, relationships, Error, Model};
+
+/// A model that can be built by a [`ModelFactory`].
+pub trait Factory: Model + 'static {
+    /// A new instance of the model, with its attributes filled in. It's called once for every model the factory builds, so the attributes may be random.
+    fn definition() -> Self;
+
+    /// Begin building models of this type.
+    #[must_use]
+    fn factory() -> ModelFactory<Self> {
+        ModelFactory::new()
+    }
+}
+
+type State<M> = Box<dyn Fn(&mut M, usize) + Send + Sync>;
+
+/// Builds models from their [`Factory::definition`], returned by [`Factory::factory`].
+pub struct ModelFactory<M: Factory> {
+    count: usize,
+    states: Vec<State<M>>,
+    related: Vec<Box<dyn Related<M>>>,
+}
+
+impl<M: Factory> ModelFactory<M> {
+    fn new() -> Self {
+        Self {
+            count: 1,
+            states: vec![],
+            related: vec![],
+        }
+    }
+
+    /// Set the number of models to build, which is 1 by default.
+    #[must_use]
+    pub const fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Change every model after it's defined, like to override some of its attributes. States are applied in the order they're added.
+    #[must_use]
+    pub fn state(mut self, state: impl Fn(&mut M) + Send + Sync + 'static) -> Self {
+        self.states.push(Box::new(move |model, _| state(model)));
+        self
+    }
+
+    /// Like [`state`](Self::state), also given the position of the model among the ones being built (starting at 0), like to give each model a distinct name.
+    #[must_use]
+    pub fn sequence(mut self, state: impl Fn(&mut M, usize) + Send + Sync + 'static) -> Self {
+        self.states.push(Box::new(state));
+        self
+    }
+
+    /// Create the models built by `factory` for each model created by this one, referencing it.
+    ///
+    /// The column referencing the model is the foreign key of its `HasMany` or `HasOne` relationship to the related model,
+    /// or the one given by the [naming strategy](crate::naming) if it doesn't have one.
+    /// Related models are only created by [`create`](Self::create), not by [`make`](Self::make).
+    #[must_use]
+    pub fn has<R: Factory>(mut self, factory: ModelFactory<R>) -> Self {
+        self.related.push(Box::new(factory));
+        self
+    }
+
+    /// Build the models without saving them to the database.
+    #[must_use]
+    pub fn make(&self) -> Vec<M> {
+        (0..self.count).map(|index| self.build(index)).collect()
+    }
+
+    /// Build a single model without saving it to the database, ignoring the [`count`](Self::count).
+    #[must_use]
+    pub fn make_one(&self) -> M {
+        self.build(0)
+    }
+
+    /// Build the models and create them in the database, one by one, along with their related models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the models (or their related models) cannot be created, or if a connection to the database cannot be established.
+    /// The models created before the error are left in the database.
+    pub async fn create(&self) -> Result<Vec<M>, Error> {
+        let mut models = Vec::with_capacity(self.count);
+
+        for index in 0..self.count {
+            models.push(self.create_nth(index).await?);
+        }
+
+        Ok(models)
+    }
+
+    /// Build a single model and create it in the database along with its related models, ignoring the [`count`](Self::count).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model (or its related models) cannot be created, or if a connection to the database cannot be established.
+    pub async fn create_one(&self) -> Result<M, Error> {
+        self.create_nth(0).await
+    }
+
+    fn build(&self, index: usize) -> M {
+        let mut model = M::definition();
+        for state in &self.states {
+            state(&mut model, index);
+        }
+
+        model
+    }
+
+    async fn create_nth(&self, index: usize) -> Result<M, Error> {
+        let model = self.build(index).create().await?;
+
+        for related in &self.related {
+            related.create_for(&model).await?;
+        }
+
+        Ok(model)
+    }
+}
+
+impl<M: Factory> Debug for ModelFactory<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelFactory")
+            .field("model", &M::NAME)
+            .field("count", &self.count)
+            .field("states", &self.states.len())
+            .field("related", &self.related.len())
+            .finish()
+    }
+}
+
+/// Models created for each model of another factory, see [`ModelFactory::has`].
+#[async_trait]
+trait Related<Parent: Model>: Send + Sync {
+    async fn create_for(&self, parent: &Parent) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl<Parent: Model, M: Factory> Related<Parent> for ModelFactory<M> {
+    async fn create_for(&self, parent: &Parent) -> Result<(), Error> {
+        let foreign_key = Parent::relations()
+            .iter()
+            .find(|relation| {
+                relation.related == M::NAME
+                    && matches!(
+                        relation.kind,
+                        relationships::RelationKind::HasMany | relationships::RelationKind::HasOne
+                    )
+            })
+            .map_or_else(
+                || naming::strategy().foreign_key(Parent::NAME, Parent::PRIMARY_KEY),
+                |relation| relation.foreign_key.clone(),
+            );
+
+        for index in 0..self.count {
+            let model = relationships::with_foreign_key(
+                self.build(index),
+                &foreign_key,
+                parent.primary_key(),
+            )?;
+
+            let model = model.create().await?;
+            for related in &self.related {
+                related.create_for(&model).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
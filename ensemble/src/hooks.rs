@@ -0,0 +1,125 @@
+//! Hooks called once a model's writes are committed, or rolled back.
+//!
+//! Hooks are plain functions taking the model and the kind of write, set with `#[ensemble(after_commit = "...")]` and `#[ensemble(after_rollback = "...")]`.
+//! When a write runs in a transaction (like the writes of outbox and auditable models, [`create_and_fetch`](crate::Model::create_and_fetch) and [`save_many`](crate::Model::save_many)),
+//! its hooks are queued on the transaction, and only called once it ends: `after_commit` if it was committed, `after_rollback` otherwise.
+//! Writes run inside a [`transaction`](crate::transaction) closure queue their hooks on it the same way.
+//! Other writes call `after_commit` right after their statement succeeds.
+//!
+//! That makes `after_commit` the place for side effects that must not happen for a change that never made it to the database,
+//! like sending an email or invalidating a cache. Hooks are called synchronously, so spawn a task for anything slow.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use ensemble::{Model, hooks::EventType};
+//! #[derive(Debug, Model)]
+//! #[ensemble(after_commit = "Self::notify")]
+//! struct Order {
+//!     id: u64,
+//!     total: u64,
+//! }
+//!
+//! impl Order {
+//!     fn notify(&self, event: EventType) {
+//!         if event == EventType::Created {
+//!             // send the confirmation email
+//!         }
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::{
+    cache,
+    connection::{self, Transaction},
+    value, Error, Model,
+};
+
+/// The kind of write a hook (or an [outbox event](crate::outbox::Event)) is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventType {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventType {
+    /// The name of the event, as stored in the outbox's `event` column.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+impl Display for EventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Call the model's `after_commit` hook for a write that just succeeded, or queue its hooks on the current task's [`transaction`](crate::transaction) if it runs in one.
+///
+/// This is used internally by the `Model` derive, and should not be called directly.
+///
+/// # Errors
+///
+/// Returns an error if the model can't be copied for its queued hooks.
+#[doc(hidden)]
+pub async fn written<M: Model>(model: &M, event: EventType) -> Result<(), Error> {
+    let Some(transaction) = connection::current() else {
+        model.after_commit(event);
+        return Ok(());
+    };
+
+    let mut transaction = transaction.lock().await;
+    defer(&mut transaction, model, model.primary_key(), event)
+}
+
+/// Queue the model's hooks on `transaction`, to be called once it ends.
+/// The hooks get a copy of the model as it is now, with `key` as its primary key (since created models might not have their generated key yet).
+pub(crate) fn defer<M: Model>(
+    transaction: &mut Transaction,
+    model: &M,
+    key: &M::PrimaryKey,
+    event: EventType,
+) -> Result<(), Error> {
+    if !M::HOOKS {
+        return Ok(());
+    }
+
+    let mut snapshot = cache::to_value(model, true)?;
+    if let rbs::Value::Array(parts) = &mut snapshot {
+        if let Some(rbs::Value::Map(columns)) = parts.first_mut() {
+            columns.insert(M::PRIMARY_KEY.into(), value::for_db(key)?);
+        }
+    }
+    // `M` might not be `'static`, so the callback holds the columns and a function pointer instead of the model itself
+    let call: fn(rbs::Value, EventType, bool) = call::<M>;
+    transaction.defer(move |committed| call(snapshot, event, committed));
+
+    Ok(())
+}
+
+fn call<M: Model>(snapshot: rbs::Value, event: EventType, committed: bool) {
+    let model = match cache::from_value::<M>(snapshot) {
+        Ok(model) => model,
+        Err(error) => {
+            tracing::warn!(model = M::NAME, error = %error, "Failed to restore a model for its hooks");
+            return;
+        }
+    };
+
+    if committed {
+        model.after_commit(event);
+    } else {
+        model.after_rollback(event);
+    }
+}
@@ -0,0 +1,262 @@
+//! Memoizing [`find`](crate::Model::find) within a task, so a model looked up from several places is only fetched once.
+//!
+//! Inside [`scoped_cache`], models returned by `find` are remembered by their table and primary key, and finding them again
+//! returns a copy of the remembered model without querying the database. Creating, saving or deleting a model through its own methods
+//! forgets it, so the next `find` fetches it again. Since the models changed by writes made through the query builder (like mass updates) aren't known,
+//! those forget every remembered model of their table. Writes made by other tasks or outside of Ensemble aren't tracked, so keep scopes short-lived, like a single request.
+//!
+//! The cache is off by default, and only visible to the task running the scope: tasks spawned from it must be wrapped in their own `scoped_cache` call.
+//! It holds at most [`DEFAULT_CAPACITY`] models (or the capacity given to [`scoped_cache_with_capacity`]), forgetting the oldest ones first.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::Model;
+//! #[derive(Debug, Model)]
+//! struct Settings {
+//!     id: u64,
+//!     theme: String,
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! ensemble::scoped_cache(async {
+//!     let settings = Settings::find(1).await?;
+//!
+//!     // returned from the cache, without querying the database
+//!     let settings = Settings::find(1).await?;
+//!     # Ok::<(), ensemble::Error>(())
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use rbs::Value;
+use serde::Serialize;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    future::Future,
+};
+
+use crate::{cache, tenancy, value, Error, Model};
+
+/// The number of models a scope created with [`scoped_cache`] holds.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+tokio::task_local! {
+    static SCOPE: RefCell<IdentityMap>;
+}
+
+/// The table of a model, followed by its primary key (and its tenant, for models scoped to one).
+type Key = (&'static str, String);
+
+struct IdentityMap {
+    capacity: usize,
+    models: HashMap<Key, Value>,
+    order: VecDeque<Key>,
+}
+
+impl IdentityMap {
+    fn insert(&mut self, key: Key, model: Value) {
+        if self.models.insert(key.clone(), model).is_some() {
+            return;
+        }
+
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.models.remove(&oldest);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &Key) {
+        if self.models.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    fn remove_table(&mut self, table: &str) {
+        self.models.retain(|(t, _), _| *t != table);
+        self.order.retain(|(t, _)| *t != table);
+    }
+}
+
+/// Run `future`, memoizing the models found inside it.
+pub async fn scoped_cache<F: Future>(future: F) -> F::Output {
+    scoped_cache_with_capacity(DEFAULT_CAPACITY, future).await
+}
+
+/// Run `future`, memoizing up to `capacity` of the models found inside it.
+pub async fn scoped_cache_with_capacity<F: Future>(capacity: usize, future: F) -> F::Output {
+    let map = IdentityMap {
+        capacity,
+        models: HashMap::new(),
+        order: VecDeque::new(),
+    };
+
+    SCOPE.scope(RefCell::new(map), future).await
+}
+
+/// Whether the current task is running inside [`scoped_cache`].
+fn is_scoped() -> bool {
+    SCOPE.try_with(|_| ()).is_ok()
+}
+
+fn key<M: Model>(primary_key: impl Serialize) -> Result<Key, Error> {
+    let mut key = value::for_db(primary_key)?.to_string();
+
+    if M::TENANT_COLUMN.is_some() {
+        if let Some(tenant) = tenancy::current() {
+            key = format!("{key}@{tenant}");
+        }
+    }
+
+    Ok((M::QUALIFIED_TABLE, key))
+}
+
+#[doc(hidden)]
+/// The remembered model with the given primary key, if there is one. Not intended to be used directly.
+pub fn get<M: Model>(primary_key: &M::PrimaryKey) -> Result<Option<M>, Error> {
+    if !is_scoped() {
+        return Ok(None);
+    }
+
+    let key = key::<M>(primary_key)?;
+    SCOPE
+        .with(|map| map.borrow().models.get(&key).cloned())
+        .map(cache::from_value)
+        .transpose()
+}
+
+#[doc(hidden)]
+/// Remember a model that was just found. Not intended to be used directly.
+pub fn remember<M: Model>(model: &M) -> Result<(), Error> {
+    if !is_scoped() {
+        return Ok(());
+    }
+
+    let key = key::<M>(model.primary_key())?;
+    let value = cache::to_value(model, false)?;
+    SCOPE.with(|map| map.borrow_mut().insert(key, value));
+
+    Ok(())
+}
+
+#[doc(hidden)]
+/// Forget the model with the given primary key, after it was written to. Not intended to be used directly.
+pub fn forget<M: Model>(primary_key: &M::PrimaryKey) -> Result<(), Error> {
+    if !is_scoped() {
+        return Ok(());
+    }
+
+    let key = key::<M>(primary_key)?;
+    SCOPE.with(|map| map.borrow_mut().remove(&key));
+
+    Ok(())
+}
+
+/// Forget every remembered model of `table`, after a write through the query builder.
+pub(crate) fn forget_table(table: &str) {
+    if is_scoped() {
+        SCOPE.with(|map| map.borrow_mut().remove_table(table));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+
+    #[derive(Debug, Model)]
+    struct Settings {
+        id: u64,
+        theme: String,
+    }
+
+    fn settings(id: u64) -> Settings {
+        Settings {
+            id,
+            theme: "dark".to_string(),
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn found_models_are_returned_without_querying() {
+        use crate::testing::{assert_query_count, fake_connection};
+
+        let fake = fake_connection().returning(vec![rbs::to_value(settings(1)).unwrap()]);
+
+        block_on(fake.run(scoped_cache(async {
+            let found = assert_query_count(1, Settings::find(1)).await.unwrap();
+            let again = assert_query_count(0, Settings::find(1)).await.unwrap();
+
+            assert_eq!(found.theme, "dark");
+            assert_eq!(again.theme, "dark");
+        })));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn mass_writes_forget_the_models_of_their_table() {
+        let fake = crate::testing::fake_connection();
+
+        block_on(fake.run(scoped_cache(async {
+            remember(&settings(1)).unwrap();
+            remember(&settings(2)).unwrap();
+
+            Settings::query()
+                .r#where("theme", "=", "dark")
+                .update(vec![("theme", "light")])
+                .await
+                .unwrap();
+
+            assert!(get::<Settings>(&1).unwrap().is_none());
+            assert!(get::<Settings>(&2).unwrap().is_none());
+        })));
+    }
+
+    #[test]
+    fn writes_forget_models() {
+        block_on(scoped_cache(async {
+            remember(&settings(1)).unwrap();
+            forget::<Settings>(&1).unwrap();
+
+            assert!(get::<Settings>(&1).unwrap().is_none());
+            assert!(Settings::find(1).await.is_err());
+        }));
+    }
+
+    #[test]
+    fn models_are_only_remembered_inside_a_scope() {
+        remember(&settings(1)).unwrap();
+        assert!(get::<Settings>(&1).unwrap().is_none());
+
+        block_on(async {
+            scoped_cache(async { remember(&settings(1)).unwrap() }).await;
+            scoped_cache(async { assert!(get::<Settings>(&1).unwrap().is_none()) }).await;
+        });
+    }
+
+    #[test]
+    fn the_oldest_models_are_forgotten_first() {
+        block_on(scoped_cache_with_capacity(2, async {
+            for id in 1..=3 {
+                remember(&settings(id)).unwrap();
+            }
+
+            assert!(get::<Settings>(&1).unwrap().is_none());
+            assert!(get::<Settings>(&2).unwrap().is_some());
+            assert!(get::<Settings>(&3).unwrap().is_some());
+        }));
+    }
+}
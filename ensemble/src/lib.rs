@@ -3,6 +3,9 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::inconsistent_struct_constructor)]
 
+// lets the `Model` derive be used inside this crate
+extern crate self as ensemble;
+
 #[doc(hidden)]
 pub use async_trait::async_trait;
 use connection::ConnectError;
@@ -11,6 +14,9 @@ pub use inflector::Inflector;
 #[doc(hidden)]
 pub use rbs;
 #[doc(hidden)]
+#[cfg(feature = "schema")]
+pub use schemars;
+#[doc(hidden)]
 pub use serde;
 #[doc(hidden)]
 #[cfg(feature = "json")]
@@ -21,17 +27,51 @@ use serde::{de::DeserializeOwned, Serialize};
 use std::{
     collections::HashMap,
     fmt::{Debug, Display},
+    future::Future,
+    pin::Pin,
 };
 
+#[cfg(feature = "json")]
+pub mod audit;
+#[cfg(feature = "axum")]
+pub mod axum;
+mod cache;
 mod connection;
+pub mod factory;
+pub mod hooks;
+pub mod identity_map;
+pub mod lock;
+pub mod metrics;
 pub mod migrations;
+pub mod naming;
+#[cfg(feature = "json")]
+pub mod outbox;
 pub mod query;
+pub mod redaction;
 pub mod relationships;
+mod strict;
+pub mod tenancy;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod types;
 pub mod value;
-#[cfg(any(feature = "mysql", feature = "postgres"))]
-pub use connection::setup;
-pub use ensemble_derive::Model;
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub mod write;
+#[cfg(feature = "tracing")]
+pub use connection::TracingConfig;
+pub use connection::{
+    ping, shutdown, transaction, ConnectionConfig, HealthCheck, InvalidUrl, QueryRetryPolicy,
+    RetryOn, Transaction,
+};
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+pub use connection::{setup, setup_with, SetupError};
+pub use ensemble_derive::{FromRow, Model};
+pub use identity_map::scoped_cache;
+pub use metrics::snapshot as metrics_snapshot;
+pub use naming::set_naming_strategy;
+pub use redaction::set_binding_logging;
+pub use strict::{allow_lazy, is_strict, strict_mode};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -49,18 +89,137 @@ pub enum Error {
     Required(&'static str),
 
     #[error("Failed to serialize model.")]
-    Serialization(#[from] rbs::value::ext::Error),
+    Serialization(#[source] rbs::value::ext::Error),
+
+    #[error("The integer {value} is out of range for {target}.")]
+    OutOfRange { value: String, target: String },
 
     #[error("The model could not be found.")]
     NotFound,
 
+    #[error("The cached model can't be decoded, as {0}.")]
+    InvalidCache(String),
+
+    #[error("Expected a single model, but found at least {count_hint}.")]
+    MultipleRecordsFound { count_hint: usize },
+
     #[error("The unique constraint was violated.")]
     UniqueViolation,
 
     #[error("The query is invalid.")]
     InvalidQuery,
+
+    #[error("The database pool is shutting down.")]
+    ShuttingDown,
+
+    #[error("The query timed out: {0}")]
+    QueryTimeout(String),
+
+    #[error("Refusing to run a bulk operation without any where clauses. Use `unconstrained` to affect every row.")]
+    Unconstrained,
+
+    #[error("No tenant is set, so the {0} table can't be queried. Use `with_tenant`, or `without_tenancy` to query every tenant.")]
+    NoTenant(&'static str),
+
+    #[error("The {0} table is read-only.")]
+    ReadOnly(&'static str),
+
+    #[error("The {0} column can't be changed once the model is created.")]
+    ImmutableColumn(&'static str),
+
+    #[error("The {0} lock is held by another session.")]
+    LockNotAcquired(String),
+
+    #[error("The rows are locked by another transaction.")]
+    LockNotAvailable,
+
+    #[error("Invalid row lock: {0}.")]
+    InvalidLock(&'static str),
+
+    #[error("The database doesn't support {0}.")]
+    Unsupported(&'static str),
+
+    #[error("A savepoint was dropped without being committed or rolled back, so the transaction was rolled back.")]
+    AbandonedSavepoint,
+
+    #[error("The models at indices {0:?} have not been saved to the database yet.")]
+    Unpersisted(Vec<usize>),
+
+    #[error("The parent {0} model has not been saved to the database yet.")]
+    ParentUnpersisted(&'static str),
+
+    #[error("The query uses the :{0} parameter, but no value was given for it.")]
+    MissingParameter(String),
+
+    #[error("The {0} parameter was given, but the query never uses it.")]
+    UnusedParameter(String),
+
+    #[error("The {relation} relationship of the {model} model was not eager loaded, and strict mode forbids lazy loading it.")]
+    LazyLoadViolation {
+        model: &'static str,
+        relation: &'static str,
+    },
+
+    #[error("The {model} model has no {relation} relationship.")]
+    UnknownRelation {
+        model: &'static str,
+        relation: String,
+    },
+
+    #[error("The row has no {0} column.")]
+    UnknownColumn(String),
+}
+
+impl From<rbs::value::ext::Error> for Error {
+    /// Integers that don't fit in their field are reported by the deserializer as an `rbs` error, and surfaced as [`Error::OutOfRange`].
+    fn from(error: rbs::value::ext::Error) -> Self {
+        value::out_of_range(&error).map_or(Self::Serialization(error), |(value, target)| {
+            Self::OutOfRange { value, target }
+        })
+    }
+}
+
+/// An error that stopped [`Model::chunk_by_id_from`], along with the key of the last model that was fully processed.
+/// Pass that key back as `start_after` to resume where the chunking stopped.
+#[derive(Debug, thiserror::Error)]
+pub enum ChunkError<K, E> {
+    #[error("Failed to retrieve the next chunk: {error}")]
+    Database { error: Error, last_key: Option<K> },
+
+    #[error("The chunk callback failed.")]
+    Callback { error: E, last_key: Option<K> },
 }
 
+impl<K, E> ChunkError<K, E> {
+    /// The key of the last model in the last chunk that was processed successfully, if any.
+    pub const fn last_key(&self) -> Option<&K> {
+        match self {
+            Self::Database { last_key, .. } | Self::Callback { last_key, .. } => last_key.as_ref(),
+        }
+    }
+}
+
+/// An error that stopped [`Builder::delete_in_chunks`] or [`Builder::update_in_chunks`], along with the number of rows the previous batches affected.
+#[derive(Debug, thiserror::Error)]
+#[error("Failed after affecting {affected} rows: {error}")]
+pub struct ChunkedWriteError {
+    #[source]
+    pub error: Error,
+    pub affected: u64,
+}
+
+impl From<Error> for ChunkedWriteError {
+    fn from(error: Error) -> Self {
+        Self { error, affected: 0 }
+    }
+}
+
+/// A type that can be hydrated from the rows returned by a query, like the results of a join or an aggregate.
+///
+/// Implement it with `#[derive(FromRow)]`, which supports the same `column` and `get` field attributes as models,
+/// then retrieve rows with [`Builder::get_as`] or [`Builder::first_as`].
+pub trait FromRow: DeserializeOwned {}
+
 #[async_trait]
 pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + Default {
     /// The type of the primary key for the model.
@@ -79,12 +238,90 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     /// The name of the table for the model
     const TABLE_NAME: &'static str;
 
+    /// The schema (or `MySQL` database) holding the model's table, when it isn't the connection's default.
+    const SCHEMA: Option<&'static str> = None;
+
+    /// The name of the table, qualified with its [schema](Self::SCHEMA) if it has one. Used in every statement run for the model.
+    /// The schema is quoted so its case is kept, like `"billing".invoices` on Postgres and `SQLite` or `` `billing`.invoices `` on `MySQL`.
+    const QUALIFIED_TABLE: &'static str = Self::TABLE_NAME;
+
     /// The name of the primary key field for the model.
     const PRIMARY_KEY: &'static str;
 
+    /// Whether the model is backed by a view or a read-only table, and can't be written to.
+    const READ_ONLY: bool = false;
+
+    /// The relationships loaded by every query for the model, marked with `#[model(eager)]`. A query can opt out with [`without`](Builder::without).
+    const EAGER_LOAD: &'static [&'static str] = &[];
+
+    /// The columns holding the model's creation and update timestamps.
+    const TIMESTAMPS: &'static [&'static str] = &[];
+
+    /// The column scoping the model to the current [tenant](crate::tenancy), if any.
+    const TENANT_COLUMN: Option<&'static str> = None;
+
+    /// The [global scopes](query::GlobalScope) constraining every query for the model, set with `#[ensemble(global_scope = "...")]`.
+    const GLOBAL_SCOPES: &'static [query::GlobalScope] = &[];
+
+    /// Whether every change to the model is recorded in the [outbox](crate::outbox).
+    const OUTBOX: bool = false;
+
+    /// Whether every change to the model is recorded in the [audit trail](crate::audit).
+    const AUDITABLE: bool = false;
+
+    /// Whether the model is soft deleted, set with `#[ensemble(soft_deletes)]`.
+    /// Deleting it only sets its `deleted_at` column, and its queries leave out soft deleted models unless they ask for them with [`with_trashed`](Builder::with_trashed).
+    const SOFT_DELETES: bool = false;
+
+    /// Whether the model has [hooks](crate::hooks), set with `#[ensemble(after_commit = "...")]` or `#[ensemble(after_rollback = "...")]`.
+    const HOOKS: bool = false;
+
     /// Returns the value of the model's primary key.
     fn primary_key(&self) -> &Self::PrimaryKey;
 
+    /// The columns persisted by the model, in the order their fields are declared.
+    /// Foreign keys of `BelongsTo` relationships are listed where the relationship field is declared, other relationships are skipped.
+    fn columns() -> &'static [value::ColumnDef];
+
+    /// The relationships declared on the model, in the order their fields are declared.
+    #[must_use]
+    fn relations() -> &'static [relationships::RelationDef] {
+        &[]
+    }
+
+    /// The relationship held by the field named `name`, if there is one.
+    #[must_use]
+    fn relation(name: &str) -> Option<&'static relationships::RelationDef> {
+        Self::relations()
+            .iter()
+            .find(|relation| relation.name == name)
+    }
+
+    /// Make sure every one of `names` is a relationship of the model, before eager loading them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownRelation`] for the first name that isn't a relationship of the model.
+    #[doc(hidden)]
+    fn ensure_relations<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), Error> {
+        names
+            .into_iter()
+            .find(|name| Self::relation(name).is_none())
+            .map_or(Ok(()), |name| {
+                Err(Error::UnknownRelation {
+                    model: Self::NAME,
+                    relation: name.to_string(),
+                })
+            })
+    }
+
+    /// The names of the columns written when the model is inserted or saved.
+    ///
+    /// These follow the order the fields are declared in, which is also the order of the columns in the model's `INSERT` and `UPDATE` statements.
+    /// Columns marked with `#[model(db_generated)]` are left out, since the database populates them itself.
+    /// The names are only collected once, so calling this is free.
+    fn keys() -> &'static [&'static str];
+
     /// Get all of the models from the database.
     ///
     /// # Errors
@@ -94,6 +331,232 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
         Self::query().get().await
     }
 
+    /// Get the number of models in the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    async fn count() -> Result<u64, Error> {
+        Self::query().count().await
+    }
+
+    /// Process every model in chunks of `size`, ordered by primary key, so the whole table never has to be held in memory.
+    /// See [`chunk_by_id_from`](Self::chunk_by_id_from) to resume from a given key.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkError`] if a chunk cannot be retrieved or `callback` fails, holding the key of the last model in the last chunk that was processed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::Model;
+    /// # #[derive(Debug, Model)]
+    /// # struct User {
+    /// #   id: u64,
+    /// #   name: String,
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::ChunkError<u64, ensemble::Error>> {
+    /// User::chunk(500, |users| async move {
+    ///     for user in users {
+    ///         println!("{}", user.name);
+    ///     }
+    ///
+    ///     Ok(())
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn chunk<F, Fut, E>(
+        size: usize,
+        callback: F,
+    ) -> Result<(), ChunkError<Self::PrimaryKey, E>>
+    where
+        F: FnMut(Vec<Self>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: Send,
+    {
+        Self::chunk_by_id_from(None, size, callback)
+            .await
+            .map(|_| ())
+    }
+
+    /// Stream every model, ordered by primary key. Models are retrieved `size` at a time, the same way [`chunk`](Self::chunk) retrieves them,
+    /// and the next chunk is only requested once the previous one has been consumed.
+    ///
+    /// The stream ends after the first error.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::Model;
+    /// use futures_util::TryStreamExt;
+    /// # #[derive(Debug, Model)]
+    /// # struct User {
+    /// #   id: u64,
+    /// #   name: String,
+    /// # }
+    ///
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut users = User::stream_by_id(500);
+    ///
+    /// while let Some(user) = users.try_next().await? {
+    ///     println!("{}", user.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    fn stream_by_id(
+        size: usize,
+    ) -> Pin<Box<dyn futures_util::Stream<Item = Result<Self, Error>> + Send>>
+    where
+        Self: 'static,
+    {
+        use futures_util::{stream, StreamExt, TryStreamExt};
+
+        // the key of the last model retrieved, and whether there might be more after it
+        let chunks = stream::try_unfold((None, true), move |(last_key, more)| async move {
+            if !more {
+                return Ok::<_, Error>(None);
+            }
+
+            let chunk = Self::query()
+                .when_some(last_key.as_ref(), |query, key| {
+                    query.r#where(Self::PRIMARY_KEY, ">", key)
+                })
+                .order_by(Self::PRIMARY_KEY, "asc")
+                .limit(size)
+                .get::<Self>()
+                .await?;
+
+            let more = size > 0 && chunk.len() == size;
+            let last_key = chunk.last().map(|model| model.primary_key().clone());
+
+            Ok(Some((chunk, (last_key, more))))
+        });
+
+        chunks
+            .map_ok(|chunk| stream::iter(chunk.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Process every model in chunks of `size`, ordered by primary key, starting after `start_after` (or from the first model, if `None`).
+    /// Returns the key of the last model processed, which is `start_after` if there were no models left.
+    ///
+    /// Chunks are retrieved by key instead of by offset, so gaps in the keys are skipped over,
+    /// and models inserted past the current position (or deleted before it) don't shift the remaining chunks.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkError`] if a chunk cannot be retrieved or `callback` fails, holding the key of the last model in the last chunk that was processed.
+    async fn chunk_by_id_from<F, Fut, E>(
+        start_after: Option<Self::PrimaryKey>,
+        size: usize,
+        callback: F,
+    ) -> Result<Option<Self::PrimaryKey>, ChunkError<Self::PrimaryKey, E>>
+    where
+        F: FnMut(Vec<Self>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: Send,
+    {
+        Self::chunk_by_id_from_with_progress(start_after, size, callback, |_, _| {}).await
+    }
+
+    /// Like [`chunk_by_id_from`](Self::chunk_by_id_from), calling `progress` after every chunk with the number of models processed so far, and the key of the last one.
+    ///
+    /// Since a job might be killed at any point, `progress` is also the place to persist the key to resume from.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkError`] if a chunk cannot be retrieved or `callback` fails, holding the key of the last model in the last chunk that was processed.
+    async fn chunk_by_id_from_with_progress<F, Fut, E, P>(
+        start_after: Option<Self::PrimaryKey>,
+        size: usize,
+        mut callback: F,
+        mut progress: P,
+    ) -> Result<Option<Self::PrimaryKey>, ChunkError<Self::PrimaryKey, E>>
+    where
+        F: FnMut(Vec<Self>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: Send,
+        P: FnMut(u64, &Self::PrimaryKey) + Send,
+    {
+        let mut last_key = start_after;
+        let mut processed = 0;
+
+        loop {
+            let chunk = Self::query()
+                .when_some(last_key.as_ref(), |query, key| {
+                    query.r#where(Self::PRIMARY_KEY, ">", key)
+                })
+                .order_by(Self::PRIMARY_KEY, "asc")
+                .limit(size)
+                .get::<Self>()
+                .await;
+
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(error) => return Err(ChunkError::Database { error, last_key }),
+            };
+
+            let Some(key) = chunk.last().map(|model| model.primary_key().clone()) else {
+                return Ok(last_key);
+            };
+
+            let count = chunk.len();
+            if let Err(error) = callback(chunk).await {
+                return Err(ChunkError::Callback { error, last_key });
+            }
+
+            processed += count as u64;
+            progress(processed, &key);
+            last_key = Some(key);
+
+            if count < size {
+                return Ok(last_key);
+            }
+        }
+    }
+
+    /// Get the sum of the values of a given column, as a `T`. See [`Builder::sum`](query::Builder::sum).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the sum cannot be deserialized into `T`.
+    async fn sum<T: DeserializeOwned + Default>(column: &str) -> Result<T, Error> {
+        Self::query().sum(column).await
+    }
+
+    /// Get the average of the values of a given column.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the average isn't a number.
+    async fn avg(column: &str) -> Result<Option<f64>, Error> {
+        Self::query().avg(column).await
+    }
+
+    /// Get the minimum value of a given column.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the value cannot be deserialized into `T`.
+    async fn min<T: DeserializeOwned>(column: &str) -> Result<Option<T>, Error> {
+        Self::query().min(column).await
+    }
+
+    /// Get the maximum value of a given column.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the value cannot be deserialized into `T`.
+    async fn max<T: DeserializeOwned>(column: &str) -> Result<Option<T>, Error> {
+        Self::query().max(column).await
+    }
+
     /// Find a model by its primary key.
     ///
     /// # Errors
@@ -101,6 +564,55 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     /// Returns an error if the model cannot be found, or if a connection to the database cannot be established.
     async fn find(key: Self::PrimaryKey) -> Result<Self, Error>;
 
+    /// Get the only model matching a basic where clause.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if no model matches, [`Error::MultipleRecordsFound`] if more than one does,
+    /// or an error if the query fails or a connection to the database cannot be established.
+    async fn sole_where<T, Op>(column: &str, operator: Op, value: T) -> Result<Self, Error>
+    where
+        Op: Into<query::Operator> + Send,
+        T: Serialize + Send,
+    {
+        Self::query().r#where(column, operator, value).sole().await
+    }
+
+    /// Find a model by a column other than its primary key, like an email address.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuery`] if `column` isn't a column of the model, or an error if the query fails, or if a connection to the database cannot be established.
+    async fn find_by<T: Serialize + Send + Sync>(
+        column: &str,
+        value: T,
+    ) -> Result<Option<Self>, Error> {
+        Self::find_by_columns(&[(column, value)]).await
+    }
+
+    /// Find a model by several columns at once, like the columns of a composite unique index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuery`] if there are no columns, or if one of them isn't a column of the model,
+    /// or an error if the query fails, or if a connection to the database cannot be established.
+    async fn find_by_columns<T: Serialize + Sync>(
+        columns: &[(&str, T)],
+    ) -> Result<Option<Self>, Error> {
+        let keys = Self::keys();
+        if columns.is_empty() || columns.iter().any(|(column, _)| !keys.contains(column)) {
+            return Err(Error::InvalidQuery);
+        }
+
+        let query = columns
+            .iter()
+            .try_fold(Self::query(), |query, (column, value)| {
+                Ok::<_, Error>(query.r#where(column, "=", value::for_db(value)?))
+            })?;
+
+        query.first().await
+    }
+
     /// Insert a new model into the database.
     ///
     /// # Errors
@@ -108,6 +620,103 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     /// Returns an error if the model cannot be inserted, or if a connection to the database cannot be established.
     async fn create(self) -> Result<Self, Error>;
 
+    /// Insert a new model into the database, returning it as stored by the database.
+    ///
+    /// Unlike [`create`](Self::create), every column is read back, so values filled in by column defaults or triggers are included.
+    /// On Postgres and `SQLite` this is a single `INSERT ... RETURNING` statement, while on `MySQL` the model is selected by primary key after the insert, in the same transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model cannot be inserted, or if a connection to the database cannot be established.
+    #[cfg(feature = "json")]
+    async fn create_and_fetch(mut self) -> Result<Self, Error> {
+        self.prepare_create()?;
+
+        write::insert_and_fetch(&self).await
+    }
+
+    /// Insert every one of `models` with a single multi-row `INSERT` statement, returning the number of inserted rows.
+    ///
+    /// The models are prepared like [`create`](Self::create) would (their timestamps and defaults are filled in, and their required fields checked),
+    /// but they aren't read back, so the keys generated by the database aren't known. No hooks run for the inserted models either.
+    /// Databases limit the number of values a single statement can bind, so split very large sets of models with [`chunks`](slice::chunks).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unsupported`] for auditable or outbox models, whose inserts are recorded one by one.
+    /// Returns an error if any model fails its checks, if the models cannot be inserted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::Model;
+    /// # #[derive(Debug, Model)]
+    /// # struct User {
+    /// #   id: u64,
+    /// #   name: String,
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let users = (1..=1000)
+    ///     .map(|i| User { name: format!("User {i}"), ..Default::default() })
+    ///     .collect();
+    ///
+    /// User::insert_many(users).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn insert_many(mut models: Vec<Self>) -> Result<u64, Error> {
+        if Self::AUDITABLE || Self::OUTBOX {
+            return Err(Error::Unsupported(
+                "bulk inserts of auditable or outbox models",
+            ));
+        }
+
+        for model in &mut models {
+            model.prepare_create()?;
+        }
+
+        query::insert_many(&models).await
+    }
+
+    /// Get the first model with the same values for `columns` as this one, or insert this one if there isn't any.
+    ///
+    /// The columns should be covered by a unique index. Then, if a concurrent request inserts a matching model between the lookup and the insert,
+    /// the resulting unique violation is caught and the lookup retried, so both requests end up with the same model.
+    /// The lookup runs on the primary database, since a read replica may not have the other request's model yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuery`] if one of `columns` isn't a column of the model, or an error if the model cannot be retrieved or inserted.
+    async fn first_or_create(self, columns: &[&str]) -> Result<Self, Error> {
+        let rbs::Value::Map(values) = value::for_db(&self)? else {
+            return Err(Error::InvalidQuery);
+        };
+
+        let constraints = columns
+            .iter()
+            .map(|column| {
+                values
+                    .0
+                    .iter()
+                    .find(|(name, _)| name.as_str() == Some(column))
+                    .map(|(_, value)| (*column, value.clone()))
+                    .ok_or(Error::InvalidQuery)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let find = || {
+            let query = constraints
+                .iter()
+                .fold(Self::query().on_primary(), |query, (column, value)| {
+                    query.r#where(column, "=", value)
+                });
+
+            query.first::<Self>()
+        };
+
+        query::first_or_create(Self::QUALIFIED_TABLE, find, || self.create()).await
+    }
+
     /// Update the model in the database.
     ///
     /// # Errors
@@ -115,26 +724,234 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     /// Returns an error if the model cannot be updated, or if a connection to the database cannot be established.
     async fn save(&mut self) -> Result<(), Error>;
 
-    /// Delete the model from the database.
+    /// Update a set of models in the database, using a single transaction. Returns the number of affected rows.
+    ///
+    /// Either every model is saved, or none of them are. Models that have not been inserted yet (their primary key is still the default value) are not saved,
+    /// and their indices are returned in an [`Error::Unpersisted`] error instead. Models whose [`Tracker`](value::Tracker) saw no change are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the models cannot be updated, or if a connection to the database cannot be established.
+    async fn save_many(models: &mut [Self]) -> Result<u64, Error> {
+        if Self::READ_ONLY {
+            return Err(Error::ReadOnly(Self::TABLE_NAME));
+        }
+
+        let unpersisted = models
+            .iter()
+            .enumerate()
+            .filter(|(_, model)| model.primary_key() == &Self::PrimaryKey::default())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        if !unpersisted.is_empty() {
+            return Err(Error::Unpersisted(unpersisted));
+        }
+
+        let mut models = models
+            .iter_mut()
+            .filter(|model| !value::unchanged(&**model))
+            .collect::<Vec<_>>();
+
+        if models.is_empty() {
+            return Ok(0);
+        }
+
+        for model in &mut models {
+            model.prepare_save()?;
+        }
+
+        let rows_affected = 'update: {
+            #[cfg(feature = "json")]
+            if Self::OUTBOX || Self::AUDITABLE {
+                break 'update write::update_many(&models).await?;
+            }
+
+            let statements = models
+                .iter()
+                .map(|model| {
+                    Self::without_global_scopes()
+                        .r#where(Self::PRIMARY_KEY, "=", value::for_db(model.primary_key())?)
+                        .update_sql(value::for_update(&**model)?)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let mut transaction = connection::Transaction::begin(Self::QUALIFIED_TABLE).await?;
+            for model in &models {
+                hooks::defer(&mut transaction, &**model, model.primary_key(), hooks::EventType::Updated)?;
+            }
+
+            connection::exec_in_transaction(transaction, statements).await?
+        };
+
+        for model in models {
+            identity_map::forget::<Self>(model.primary_key())?;
+            if model.tracker().is_none() {
+                continue;
+            }
+
+            let columns = value::for_db(&*model)?;
+            if let Some(tracker) = model.tracker_mut() {
+                tracker.persist(columns);
+            }
+        }
+
+        Ok(rows_affected)
+    }
+
+    /// Delete the model from the database, or soft delete it if the model is marked with `soft_deletes`.
     ///
     /// # Errors
     ///
     /// Returns an error if the model cannot be deleted, or if a connection to the database cannot be established.
-    async fn delete(mut self) -> Result<(), Error> {
-        let rows_affected = Self::query()
-            .r#where(
-                Self::PRIMARY_KEY,
-                "=",
-                value::for_db(self.primary_key()).unwrap(),
-            )
-            .delete()
+    async fn delete(self) -> Result<(), Error> {
+        query::operation("delete", Self::QUALIFIED_TABLE, async move {
+            if !Self::SOFT_DELETES {
+                return self.force_delete().await;
+            }
+
+            #[cfg(feature = "json")]
+            if Self::OUTBOX || Self::AUDITABLE {
+                if write::soft_delete(&self).await? != 1 {
+                    return Err(Error::UniqueViolation);
+                }
+
+                return identity_map::forget::<Self>(self.primary_key());
+            }
+
+            let rows_affected = Self::without_global_scopes()
+                .r#where(Self::PRIMARY_KEY, "=", value::for_db(self.primary_key())?)
+                .delete()
+                .await?;
+
+            if rows_affected != 1 {
+                return Err(Error::UniqueViolation);
+            }
+            hooks::written(&self, hooks::EventType::Deleted).await?;
+
+            identity_map::forget::<Self>(self.primary_key())
+        })
+        .await
+    }
+
+    /// Delete the model from the database, even if it's marked with `soft_deletes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model cannot be deleted, or if a connection to the database cannot be established.
+    async fn force_delete(self) -> Result<(), Error> {
+        #[cfg(feature = "json")]
+        if Self::OUTBOX || Self::AUDITABLE {
+            if write::delete(&self).await? != 1 {
+                return Err(Error::UniqueViolation);
+            }
+
+            return identity_map::forget::<Self>(self.primary_key());
+        }
+
+        let rows_affected = Self::without_global_scopes()
+            .with_trashed()
+            .r#where(Self::PRIMARY_KEY, "=", value::for_db(self.primary_key())?)
+            .force_delete()
             .await?;
 
         if rows_affected != 1 {
             return Err(Error::UniqueViolation);
         }
+        hooks::written(&self, hooks::EventType::Deleted).await?;
 
-        Ok(())
+        identity_map::forget::<Self>(self.primary_key())
+    }
+
+    /// Called once a create, save or delete of the model is committed. Set it with `#[ensemble(after_commit = "...")]`.
+    ///
+    /// Writes that run in a transaction only call it once the transaction is committed, and others right after their statement. See [`hooks`] for details.
+    fn after_commit(&self, _event: hooks::EventType) {}
+
+    /// Called when the transaction a create, save or delete of the model ran in is rolled back, instead of [`after_commit`](Self::after_commit).
+    /// Set it with `#[ensemble(after_rollback = "...")]`.
+    fn after_rollback(&self, _event: hooks::EventType) {}
+
+    /// Encode the model's columns (including hidden ones) in a compact binary format, to cache it outside of the database.
+    /// Decode it with [`from_cached_bytes`](Self::from_cached_bytes).
+    ///
+    /// The columns are stored by name, so bytes cached by a different version of the model fail to decode instead of filling the wrong fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model cannot be serialized.
+    fn to_cached_bytes(&self) -> Result<Vec<u8>, Error> {
+        cache::to_bytes(self, false)
+    }
+
+    /// Like [`to_cached_bytes`](Self::to_cached_bytes), but also encodes the model's loaded relationships (and theirs), which are loaded again when decoding it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model or one of its related models cannot be serialized.
+    fn to_cached_bytes_with_relations(&self) -> Result<Vec<u8>, Error> {
+        cache::to_bytes(self, true)
+    }
+
+    /// Decode a model encoded with [`to_cached_bytes`](Self::to_cached_bytes), as if it was retrieved from the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidCache`] if the bytes were written by a different version of the encoding, hold a different model,
+    /// or don't hold exactly the model's columns (e.g. because a field was added to or removed from the model since they were cached).
+    fn from_cached_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        cache::from_bytes(bytes)
+    }
+
+    /// Create a model from a row retrieved outside of Ensemble (like a raw driver call or an import), as if it was returned by a query.
+    /// Columns are mapped to fields with the same names, accessors and conversions used when hydrating query results.
+    ///
+    /// If the row holds the model's primary key, the model is treated as already stored in the database, so its [`Tracker`](value::Tracker) (if any)
+    /// remembers the row's columns and the next save only reports the columns changed since.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a column is missing or can't be converted to its field, or if the row holds a column the model doesn't have.
+    fn hydrate(row: HashMap<String, rbs::Value>) -> Result<Self, Error> {
+        let mut model = value::from::<Self>(value::for_db(row)?)?;
+
+        if value::for_db(model.primary_key())? == value::for_db(Self::PrimaryKey::default())? {
+            if let Some(tracker) = model.tracker_mut() {
+                *tracker = value::Tracker::default();
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Create a model from each of the given rows, see [`hydrate`](Self::hydrate).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error hydrating one of the rows.
+    fn hydrate_many(rows: Vec<HashMap<String, rbs::Value>>) -> Result<Vec<Self>, Error> {
+        rows.into_iter().map(Self::hydrate).collect()
+    }
+
+    /// Execute a raw SQL query with positional `?` bindings, and hydrate the rows it returns into models.
+    /// The relationships the model [eager loads](Self::EAGER_LOAD) are loaded for them, like for any other query.
+    ///
+    /// The query must be a string literal, so values can only make it into the query through its bindings. It isn't constrained by tenancy,
+    /// soft deletes or global scopes, so it's up to the query to leave out the rows the model's usual queries wouldn't return.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a row can't be hydrated into the model, or if a connection to the database cannot be established.
+    async fn from_raw(sql: &'static str, bindings: Vec<rbs::Value>) -> Result<Vec<Self>, Error> {
+        let rows = connection::fetch(
+            Self::QUALIFIED_TABLE,
+            sql,
+            bindings,
+            connection::QueryOptions::ONCE,
+        )
+        .await?;
+
+        Self::without_global_scopes().hydrate(rows).await
     }
 
     /// Reload a fresh model instance from the database.
@@ -146,7 +963,55 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     /// Begin querying the model.
     #[must_use]
     fn query() -> Builder {
-        Builder::new(Self::TABLE_NAME.to_string())
+        scoped_query::<Self>(|_| true)
+    }
+
+    /// Begin querying the model, leaving out the [global scope](query::GlobalScope) with the given name.
+    #[must_use]
+    fn without_global_scope(name: &str) -> Builder {
+        scoped_query::<Self>(|scope| scope.name != name)
+    }
+
+    /// Begin querying the model, leaving out all of its [global scopes](query::GlobalScope).
+    /// Tenancy and soft deletes still apply, see [`without_tenancy`](Builder::without_tenancy) and [`with_trashed`](Builder::with_trashed) to opt out of them.
+    #[must_use]
+    fn without_global_scopes() -> Builder {
+        scoped_query::<Self>(|_| false)
+    }
+
+    /// Render the `INSERT` statement creating the model as SQL text, without a connection to the database.
+    /// Useful for generating seed files and data migrations, with the output's dialect picked independently of the connected database.
+    ///
+    /// Like [`create`](Self::create), incrementing keys that haven't been set are left for the database to generate, and the tenant's column is filled in.
+    /// Unlike `create`, the model's timestamps are written as they are, and its required fields aren't checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model is read-only, if no tenant is set for a multi-tenant model, or if one of its values can't be represented in `dialect`.
+    fn to_insert_sql(&self, dialect: query::Dialect) -> Result<String, Error> {
+        query::insert_sql(std::slice::from_ref(self), dialect)
+    }
+
+    /// Render a single `INSERT` statement creating every one of `models`, as [`to_insert_sql`](Self::to_insert_sql) would.
+    /// Returns an empty string if there are no models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model is read-only, if no tenant is set for a multi-tenant model, or if one of the values can't be represented in `dialect`.
+    fn to_insert_sql_many(models: &[Self], dialect: query::Dialect) -> Result<String, Error> {
+        query::insert_sql(models, dialect)
+    }
+
+    /// Like [`to_insert_sql`](Self::to_insert_sql), but with placeholders for the model's values, which are returned alongside the statement to be bound to them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model is read-only, or if no tenant is set for a multi-tenant model.
+    fn to_insert_statement(
+        &self,
+        dialect: query::Dialect,
+    ) -> Result<(String, Vec<rbs::Value>), Error> {
+        query::insert_statement(self, dialect)
     }
 
     /// Begin querying a model with eager loading.
@@ -155,7 +1020,10 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     }
 
     async fn load<T: Into<EagerLoad> + Send>(&mut self, relation: T) -> Result<(), Error> {
-        for relation in relation.into().list() {
+        let relations = relation.into().list();
+        Self::ensure_relations(relations.iter().map(String::as_str))?;
+
+        for relation in relations {
             let rows = self.eager_load(&relation, &[&self]).get_rows().await?;
 
             self.fill_relation(&relation, &rows)?;
@@ -165,6 +1033,7 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
     }
 
     /// Convert the model to a JSON value.
+    /// Relationships that have been loaded are included under their field's name (along with the relationships loaded on them), while the rest are left out.
     ///
     /// # Panics
     ///
@@ -174,6 +1043,96 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
         serde_json::to_value(self).unwrap()
     }
 
+    /// Convert the model to a JSON value with only its own columns, leaving out its relationships even if they are loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model cannot be converted to JSON. Since Ensemble manually implement Serialize, this should never happen.
+    #[cfg(feature = "json")]
+    fn json_without_relations(&self) -> serde_json::Value {
+        value::Visible::new(self).without_relations().json()
+    }
+
+    /// Serialize the model with some of its hidden fields included, without touching any other serialization of it.
+    ///
+    /// ```rust
+    /// # use ensemble::Model;
+    /// #[derive(Debug, Model)]
+    /// struct User {
+    ///     id: u64,
+    ///     #[model(hide)]
+    ///     verification_token: String,
+    /// }
+    ///
+    /// let user = User { id: 1, verification_token: "secret".to_string() };
+    ///
+    /// assert!(user.json().get("verification_token").is_none());
+    /// assert_eq!(user.make_visible(&["verification_token"]).json()["verification_token"], "secret");
+    /// ```
+    #[cfg(feature = "json")]
+    fn make_visible<'a>(&'a self, columns: &[&'a str]) -> value::Visible<'a, Self> {
+        value::Visible::new(self).make_visible(columns)
+    }
+
+    /// Serialize the model with some of its fields left out, without touching any other serialization of it.
+    #[cfg(feature = "json")]
+    fn make_hidden<'a>(&'a self, columns: &[&'a str]) -> value::Visible<'a, Self> {
+        value::Visible::new(self).make_hidden(columns)
+    }
+
+    /// Serialize the model (outside of the database) with the given visibility overrides.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[cfg(feature = "json")]
+    #[doc(hidden)]
+    fn serialize_visible<S: serde::Serializer>(
+        &self,
+        serializer: S,
+        visibility: &value::Visibility<'_>,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Compare every column the model persists with those of `other`, returning the ones that differ.
+    ///
+    /// Values are compared as they would be stored in the database, so fields with a `set` mutator are compared after it runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either model cannot be serialized. Since Ensemble manually implement Serialize, this should never happen.
+    fn diff(&self, other: &Self) -> Vec<value::Change> {
+        value::diff(value::for_db(self).unwrap(), value::for_db(other).unwrap())
+    }
+
+    /// Like [`diff`](Self::diff), but ignoring the model's [timestamps](Self::TIMESTAMPS).
+    fn diff_without_timestamps(&self, other: &Self) -> Vec<value::Change> {
+        let mut changes = self.diff(other);
+        changes.retain(|change| !Self::TIMESTAMPS.contains(&change.column.as_str()));
+
+        changes
+    }
+
+    /// The model's [`Tracker`](value::Tracker) field, if it has one.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn tracker(&self) -> Option<&value::Tracker> {
+        None
+    }
+
+    /// The model's [`Tracker`](value::Tracker) field, if it has one.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn tracker_mut(&mut self) -> Option<&mut value::Tracker> {
+        None
+    }
+
+    /// Fill in the model's timestamps and tenant, then run its validations and check its required fields before it is created.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn prepare_create(&mut self) -> Result<(), Error>;
+
+    /// Update the model's timestamps and run its validations before it is saved.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn prepare_save(&mut self) -> Result<(), Error>;
+
     /// Eager load a relationship for a set of models.
     /// This method is used internally by Ensemble, and should not be called directly.
     #[doc(hidden)]
@@ -187,6 +1146,69 @@ pub trait Model: DeserializeOwned + Serialize + Sized + Send + Sync + Debug + De
         relation: &str,
         related: &[HashMap<String, rbs::Value>],
     ) -> Result<(), Error>;
+
+    /// Check which of a set of models have related models in a relationship.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn exists_query(&self, relation: &str, related: &[&Self]) -> Builder;
+
+    /// Record whether the model has related models in a relationship.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn fill_exists(&mut self, relation: &str, keys: &[rbs::Value]) -> Result<(), Error>;
+
+    /// The values of the columns generated by the database, which aren't serialized for it.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn generated_columns(&self) -> Result<Vec<(&'static str, rbs::Value)>, Error> {
+        Ok(vec![])
+    }
+
+    /// The cached values of the model's loaded relationships.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn cached_relations(&self) -> Result<Vec<(&'static str, rbs::Value)>, Error> {
+        Ok(vec![])
+    }
+
+    /// Load a relationship from its cached value.
+    /// This method is used internally by Ensemble, and should not be called directly.
+    #[doc(hidden)]
+    fn restore_relation(&mut self, relation: &str, _value: rbs::Value) -> Result<(), Error> {
+        Err(Error::InvalidCache(format!(
+            "{} has no {relation} relationship",
+            Self::NAME
+        )))
+    }
+}
+
+/// The query every other query for `M` starts from, applying the global scopes `keep` accepts.
+fn scoped_query<M: Model>(keep: impl Fn(&query::GlobalScope) -> bool) -> Builder {
+    let mut query = Builder::new(M::QUALIFIED_TABLE.to_string())
+        .keyed(M::PRIMARY_KEY)
+        .described_by(M::columns());
+
+    if let Some(column) = M::TENANT_COLUMN {
+        query = query.tenant(M::TABLE_NAME, column);
+    }
+
+    if M::SOFT_DELETES {
+        query = query.soft_deletes();
+    }
+
+    for scope in M::GLOBAL_SCOPES.iter().filter(|scope| keep(scope)) {
+        query = (scope.apply)(query);
+    }
+
+    if !M::EAGER_LOAD.is_empty() {
+        query = query.with(M::EAGER_LOAD);
+    }
+
+    if M::READ_ONLY {
+        return query.read_only(M::TABLE_NAME);
+    }
+
+    query
 }
 
 #[async_trait]
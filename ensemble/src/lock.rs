@@ -0,0 +1,242 @@
+//! Advisory locks, for mutual exclusion between processes that only share the database (like a cron job scheduled on several servers).
+//!
+//! Locks are implemented with `GET_LOCK` on `MySQL` and `pg_advisory_lock` on Postgres. The database ties a lock to the session that took it,
+//! so [`advisory`] checks a connection out of the pool and keeps it until the returned [`LockGuard`] is released.
+//! Dropping the guard (including while unwinding from a panic) releases the lock in a background task, and if it can't be released,
+//! the connection is closed instead, which releases it as well.
+//!
+//! If the connection is lost while the lock is held, the database releases the lock on its own, so keep critical sections short.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! match ensemble::lock::advisory("refresh-rollups", Duration::ZERO).await {
+//!     Ok(lock) => {
+//!         // only one process gets here at a time
+//!         lock.release().await?;
+//!     }
+//!     Err(ensemble::Error::LockNotAcquired(_)) => {
+//!         // another process is already refreshing the rollups
+//!     }
+//!     Err(error) => return Err(error),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use rbs::Value;
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    connection::{self, Connection},
+    query::{scalar, Dialect},
+    Error,
+};
+
+/// How often Postgres is asked for a lock that's held by another session, since `pg_advisory_lock` can't give up after a timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Acquire the advisory lock called `name`, waiting up to `timeout` for another session holding it to let it go.
+/// A zero `timeout` only tries to acquire the lock, without waiting.
+///
+/// # Errors
+///
+/// Returns an [`Error::LockNotAcquired`] if the lock is still held by another session after `timeout`, an [`Error::Unsupported`] on `SQLite`,
+/// or an error if the query fails, or if a connection to the database cannot be established.
+pub async fn advisory(name: &str, timeout: Duration) -> Result<LockGuard, Error> {
+    if Dialect::default() == Dialect::Sqlite {
+        return Err(Error::Unsupported("advisory locks"));
+    }
+
+    let mut conn = connection::checkout().await?;
+
+    let acquired = if Dialect::default() == Dialect::Postgres {
+        try_pg_lock(&mut conn, name, timeout).await?
+    } else {
+        let rows = connection::fetch_on(
+            &mut conn,
+            "",
+            "SELECT GET_LOCK(?, ?)",
+            vec![Value::from(name), Value::from(wait_seconds(timeout))],
+        )
+        .await?;
+
+        is_acquired(rows)
+    };
+
+    if !acquired {
+        return Err(Error::LockNotAcquired(name.to_string()));
+    }
+
+    tracing::debug!(lock = name, "Acquired advisory lock");
+
+    Ok(LockGuard {
+        conn: Some(conn),
+        name: name.to_string(),
+    })
+}
+
+/// An acquired advisory lock, holding on to the connection that took it. The lock is released when the guard is dropped.
+pub struct LockGuard {
+    conn: Option<Connection>,
+    name: String,
+}
+
+impl LockGuard {
+    /// The name of the lock.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Release the lock, and return the connection to the pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock can't be released. The connection is closed instead, which releases the lock anyway.
+    pub async fn release(mut self) -> Result<(), Error> {
+        let Some(conn) = self.conn.take() else {
+            return Ok(());
+        };
+
+        release(conn, &self.name).await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+
+        // releasing the lock needs a query, so it can only happen on a runtime
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                let name = std::mem::take(&mut self.name);
+                runtime.spawn(async move { release(conn, &name).await.ok() });
+            }
+            Err(_) => drop(Connection::take(conn)),
+        }
+    }
+}
+
+impl Debug for LockGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LockGuard")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+async fn release(mut conn: Connection, name: &str) -> Result<(), Error> {
+    let sql = if Dialect::default() == Dialect::Postgres {
+        "SELECT pg_advisory_unlock(hashtext(?))"
+    } else {
+        "SELECT RELEASE_LOCK(?)"
+    };
+
+    match connection::fetch_on(&mut conn, "", sql, vec![Value::from(name)]).await {
+        Ok(_) => {
+            tracing::debug!(lock = name, "Released advisory lock");
+            Ok(())
+        }
+        Err(error) => {
+            tracing::warn!(lock = name, error = %error, "Failed to release advisory lock, closing its connection");
+
+            // the lock belongs to the session, so closing it releases the lock
+            drop(Connection::take(conn));
+            Err(error)
+        }
+    }
+}
+
+/// Try to take the lock until `timeout` runs out.
+async fn try_pg_lock(conn: &mut Connection, name: &str, timeout: Duration) -> Result<bool, Error> {
+    let deadline = Instant::now().checked_add(timeout);
+
+    loop {
+        let rows = connection::fetch_on(
+            conn,
+            "",
+            "SELECT pg_try_advisory_lock(hashtext(?))",
+            vec![Value::from(name)],
+        )
+        .await?;
+
+        if is_acquired(rows) {
+            return Ok(true);
+        }
+
+        let remaining = deadline.map_or(POLL_INTERVAL, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        });
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+
+        tokio::time::sleep(remaining.min(POLL_INTERVAL)).await;
+    }
+}
+
+/// The timeout given to `GET_LOCK`, in whole seconds (rounded up, so short timeouts still wait). Timeouts too long to represent wait forever.
+fn wait_seconds(timeout: Duration) -> i64 {
+    let seconds = timeout
+        .as_secs()
+        .saturating_add(u64::from(timeout.subsec_nanos() > 0));
+
+    i64::try_from(seconds).unwrap_or(-1)
+}
+
+/// Whether the lock function returned a truthy value. `GET_LOCK` returns `1` when the lock was acquired, `0` on timeouts and `NULL` on errors.
+fn is_acquired(rows: Vec<Value>) -> bool {
+    match rows.into_iter().next().and_then(scalar) {
+        Some(Value::Bool(acquired)) => acquired,
+        Some(value) => value.as_i64() == Some(1),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_are_rounded_up_to_seconds() {
+        assert_eq!(wait_seconds(Duration::ZERO), 0);
+        assert_eq!(wait_seconds(Duration::from_millis(1)), 1);
+        assert_eq!(wait_seconds(Duration::from_secs(10)), 10);
+        assert_eq!(wait_seconds(Duration::MAX), -1);
+    }
+
+    #[test]
+    fn lock_results_are_decoded() {
+        let row = |value: Value| vec![Value::Map(rbs::value_map! { "acquired": value, })];
+
+        assert!(is_acquired(row(Value::I64(1))));
+        assert!(is_acquired(row(Value::Bool(true))));
+        assert!(!is_acquired(row(Value::I64(0))));
+        assert!(!is_acquired(row(Value::Bool(false))));
+        assert!(!is_acquired(row(Value::Null)));
+        assert!(!is_acquired(vec![]));
+    }
+
+    #[test]
+    fn locks_need_a_connection() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let result = runtime.block_on(advisory("refresh-rollups", Duration::ZERO));
+        if Dialect::default() == Dialect::Sqlite {
+            assert!(matches!(result, Err(Error::Unsupported(_))));
+        } else {
+            assert!(matches!(result, Err(Error::Connection(_))));
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Aggregate counters for the queries run by `ensemble`.
+//!
+//! Every counter is a relaxed atomic, so recording is cheap and safe to do from any number of tasks.
+//! Call [`snapshot`] to read the current values and export them however you like.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::Error;
+
+/// The upper bounds (in milliseconds) of the latency histogram buckets. Slower queries are counted in a final, unbounded bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 11] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+static COUNTERS: Counters = Counters::new();
+
+/// Every counter, grouped in a struct so tests can record into a set of their own.
+struct Counters {
+    queries: [AtomicU64; 5],
+    errors: [AtomicU64; 8],
+    latency: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    latency_sum_us: AtomicU64,
+    checkouts: AtomicU64,
+    checkout_wait_us: AtomicU64,
+    evictions: AtomicU64,
+    commits: AtomicU64,
+    rollbacks: AtomicU64,
+}
+
+/// A point-in-time copy of every counter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The number of queries run, by kind.
+    pub queries: QueryCounts,
+    /// The number of queries that failed, by [`Error`] variant.
+    pub errors: ErrorCounts,
+    /// How long queries took to run, including retries.
+    pub latency: Histogram,
+    /// How often connections were checked out of the pool, how long that took, and how many broken connections were replaced.
+    pub pool: PoolCounts,
+    /// The number of transactions that were committed or rolled back.
+    pub transactions: TransactionCounts,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCounts {
+    pub select: u64,
+    pub insert: u64,
+    pub update: u64,
+    pub delete: u64,
+    /// Any other statement (`TRUNCATE`, raw SQL, ...).
+    pub other: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    /// [`Error::Connection`]
+    pub connection: u64,
+    /// [`Error::Database`]
+    pub database: u64,
+    /// [`Error::ShuttingDown`]
+    pub shutting_down: u64,
+    /// [`Error::QueryTimeout`]
+    pub timed_out: u64,
+    /// [`Error::UniqueViolation`]
+    pub unique_violation: u64,
+    /// [`Error::LockNotAvailable`]
+    pub lock_not_available: u64,
+    /// [`Error::NotFound`]
+    pub not_found: u64,
+    /// Any other error, like a value that's out of range for its column.
+    pub other: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// The number of queries in each bucket. The bucket at index `i` counts queries that took at most `LATENCY_BUCKETS_MS[i]` milliseconds, and the last bucket counts the rest.
+    pub buckets: Vec<u64>,
+    /// The total number of recorded queries.
+    pub count: u64,
+    /// The combined duration of every recorded query.
+    pub sum: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolCounts {
+    /// The number of connections checked out of the pool.
+    pub checkouts: u64,
+    /// The combined time spent waiting for a connection.
+    pub wait_time: Duration,
+    /// The number of connections that were closed instead of being returned to the pool, either because they failed a health check, lost their connection mid-query,
+    /// or were still running a query that [timed out](crate::query::Builder::timeout).
+    pub evictions: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionCounts {
+    pub committed: u64,
+    pub rolled_back: u64,
+}
+
+/// Read the current value of every counter.
+#[must_use]
+pub fn snapshot() -> Snapshot {
+    COUNTERS.snapshot()
+}
+
+/// Record a query that has finished running.
+pub(crate) fn record_query(sql: &str, elapsed: Duration, error: Option<&Error>) {
+    COUNTERS.record_query(sql, elapsed, error);
+}
+
+/// Record a transaction being committed or rolled back.
+pub(crate) fn record_transaction(committed: bool) {
+    let counter = if committed {
+        &COUNTERS.commits
+    } else {
+        &COUNTERS.rollbacks
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record a connection being checked out of the pool.
+pub(crate) fn record_checkout(wait: Duration) {
+    COUNTERS.checkouts.fetch_add(1, Ordering::Relaxed);
+    COUNTERS
+        .checkout_wait_us
+        .fetch_add(micros(wait), Ordering::Relaxed);
+}
+
+/// Record a broken connection being closed instead of returned to the pool.
+pub(crate) fn record_eviction() {
+    COUNTERS.evictions.fetch_add(1, Ordering::Relaxed);
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            queries: [const { AtomicU64::new(0) }; 5],
+            errors: [const { AtomicU64::new(0) }; 8],
+            latency: [const { AtomicU64::new(0) }; LATENCY_BUCKETS_MS.len() + 1],
+            latency_sum_us: AtomicU64::new(0),
+            checkouts: AtomicU64::new(0),
+            checkout_wait_us: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            commits: AtomicU64::new(0),
+            rollbacks: AtomicU64::new(0),
+        }
+    }
+
+    fn record_query(&self, sql: &str, elapsed: Duration, error: Option<&Error>) {
+        let kind = sql.split_whitespace().next().unwrap_or_default();
+        let kind = ["select", "insert", "update", "delete"]
+            .iter()
+            .position(|k| kind.eq_ignore_ascii_case(k))
+            .unwrap_or(4);
+        self.queries[kind].fetch_add(1, Ordering::Relaxed);
+
+        let ms = elapsed.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= u128::from(bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_us
+            .fetch_add(micros(elapsed), Ordering::Relaxed);
+
+        let error = match error {
+            None => return,
+            Some(Error::Connection(_)) => 0,
+            Some(Error::Database(_)) => 1,
+            Some(Error::ShuttingDown) => 2,
+            Some(Error::QueryTimeout(_)) => 3,
+            Some(Error::UniqueViolation) => 4,
+            Some(Error::LockNotAvailable) => 5,
+            Some(Error::NotFound) => 6,
+            Some(_) => 7,
+        };
+        self.errors[error].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let [select, insert, update, delete, other] = self.queries.each_ref().map(load);
+        let [connection, database, shutting_down, timed_out, unique_violation, lock_not_available, not_found, other_errors] =
+            self.errors.each_ref().map(load);
+        let buckets: Vec<u64> = self.latency.iter().map(load).collect();
+
+        Snapshot {
+            queries: QueryCounts {
+                select,
+                insert,
+                update,
+                delete,
+                other,
+            },
+            errors: ErrorCounts {
+                connection,
+                database,
+                shutting_down,
+                timed_out,
+                unique_violation,
+                lock_not_available,
+                not_found,
+                other: other_errors,
+            },
+            latency: Histogram {
+                count: buckets.iter().sum(),
+                sum: Duration::from_micros(load(&self.latency_sum_us)),
+                buckets,
+            },
+            pool: PoolCounts {
+                checkouts: load(&self.checkouts),
+                wait_time: Duration::from_micros(load(&self.checkout_wait_us)),
+                evictions: load(&self.evictions),
+            },
+            transactions: TransactionCounts {
+                committed: load(&self.commits),
+                rolled_back: load(&self.rollbacks),
+            },
+        }
+    }
+}
+
+fn load(counter: &AtomicU64) -> u64 {
+    counter.load(Ordering::Relaxed)
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_counted_by_kind() {
+        let counters = Counters::new();
+        for sql in [
+            "SELECT 1",
+            "select * from users",
+            "INSERT INTO users",
+            "  UPDATE users",
+            "DELETE FROM users",
+            "TRUNCATE users",
+            "",
+        ] {
+            counters.record_query(sql, Duration::ZERO, None);
+        }
+
+        let queries = counters.snapshot().queries;
+        assert_eq!(
+            queries,
+            QueryCounts {
+                select: 2,
+                insert: 1,
+                update: 1,
+                delete: 1,
+                other: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn latencies_are_bucketed_by_their_upper_bound() {
+        let counters = Counters::new();
+        for ms in [0, 1, 2, 5, 5000, 5001, 60_000] {
+            counters.record_query("SELECT 1", Duration::from_millis(ms), None);
+        }
+
+        let latency = counters.snapshot().latency;
+        let mut expected = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        expected[0] = 2;
+        expected[1] = 2;
+        expected[LATENCY_BUCKETS_MS.len() - 1] = 1;
+        expected[LATENCY_BUCKETS_MS.len()] = 2;
+
+        assert_eq!(latency.buckets, expected);
+        assert_eq!(latency.count, 7);
+        assert_eq!(latency.sum, Duration::from_millis(70_009));
+    }
+
+    #[test]
+    fn errors_are_counted_by_variant() {
+        let counters = Counters::new();
+        let errors = [
+            Error::Database("syntax error".to_string()),
+            Error::ShuttingDown,
+            Error::QueryTimeout("SELECT 1".to_string()),
+            Error::UniqueViolation,
+            Error::UniqueViolation,
+            Error::LockNotAvailable,
+            Error::NotFound,
+            Error::InvalidQuery,
+        ];
+        for error in &errors {
+            counters.record_query("SELECT 1", Duration::ZERO, Some(error));
+        }
+        counters.record_query("SELECT 1", Duration::ZERO, None);
+
+        assert_eq!(
+            counters.snapshot().errors,
+            ErrorCounts {
+                connection: 0,
+                database: 1,
+                shutting_down: 1,
+                timed_out: 1,
+                unique_violation: 2,
+                lock_not_available: 1,
+                not_found: 1,
+                other: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Error, Migrator};
+
+/// A command run by a migrations binary, parsed from its arguments.
+///
+/// ```rust,no_run
+/// # use ensemble::{migrations::{Command, Migration, Error}, Model};
+/// # #[derive(Debug, Default)]
+/// # struct CreateUsersTable;
+/// # #[ensemble::async_trait]
+/// # impl Migration for CreateUsersTable {
+/// #     async fn up(&self) -> Result<(), Error> { Ok(()) }
+/// #     async fn down(&self) -> Result<(), Error> { Ok(()) }
+/// # }
+/// // src/bin/migrate.rs, run with `cargo run --bin migrate -- migrate:rollback --step=2`
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     ensemble::setup(&std::env::var("DATABASE_URL")?).await?;
+///
+///     let command = Command::from_env()?;
+///     ensemble::migrator!(CreateUsersTable).await?.execute(command).await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Run the pending migrations (`migrate`, the default when no command is given).
+    Migrate,
+    /// Revert the last `steps` batches of migrations (`migrate:rollback`, with `--step=N` defaulting to 1).
+    Rollback { steps: u64 },
+    /// List the registered migrations, and whether they've been run (`migrate:status`).
+    Status,
+}
+
+impl Command {
+    /// Parse a command from the arguments of the current process, leaving out the binary's name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidCommand`] if the arguments aren't a known command.
+    pub fn from_env() -> Result<Self, Error> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parse a command from a list of arguments, like `["migrate:rollback", "--step=2"]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidCommand`] if the arguments aren't a known command.
+    pub fn parse<I, S>(args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let words = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+        match words[..] {
+            [] | ["migrate"] => Ok(Self::Migrate),
+            ["migrate:status"] => Ok(Self::Status),
+            ["migrate:rollback"] => Ok(Self::Rollback { steps: 1 }),
+            ["migrate:rollback", "--step", steps] => Ok(Self::Rollback {
+                steps: parse_steps(steps)?,
+            }),
+            ["migrate:rollback", option] => match option.strip_prefix("--step=") {
+                Some(steps) => Ok(Self::Rollback {
+                    steps: parse_steps(steps)?,
+                }),
+                None => Err(Error::InvalidCommand(args.join(" "))),
+            },
+            _ => Err(Error::InvalidCommand(args.join(" "))),
+        }
+    }
+}
+
+fn parse_steps(steps: &str) -> Result<u64, Error> {
+    match steps.parse() {
+        Ok(steps) if steps > 0 => Ok(steps),
+        _ => Err(Error::InvalidCommand(format!("--step={steps}"))),
+    }
+}
+
+impl Migrator {
+    /// Run a [`Command`], printing the status of the migrations for [`Command::Status`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the migrations fail, or if a connection to the database cannot be established.
+    pub async fn execute(self, command: Command) -> Result<(), Error> {
+        match command {
+            Command::Migrate => self.run().await,
+            Command::Rollback { steps } => self.rollback(steps).await,
+            Command::Status => {
+                for (migration, batch) in self.report() {
+                    match batch {
+                        Some(batch) => println!("Ran      {migration} (batch {batch})"),
+                        None => println!("Pending  {migration}"),
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
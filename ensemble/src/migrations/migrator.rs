@@ -48,12 +48,18 @@ impl Migrator {
         })
     }
 
+    /// Registers a migration to run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a migration with the same name has already been registered.
     pub fn register(&mut self, name: String, migration: Box<dyn Migration>) {
         tracing::trace!("Registered migration [{name}]");
 
-        if self.migrations.iter().any(|(n, _)| n == &name) {
-            panic!("A migration with the name [{name}] has already been registered.");
-        }
+        assert!(
+            !self.migrations.iter().any(|(n, _)| n == &name),
+            "A migration with the name [{name}] has already been registered."
+        );
 
         self.migrations.push((name, migration));
     }
@@ -74,6 +80,22 @@ impl Migrator {
             .collect()
     }
 
+    /// The registered migrations in the order they were registered, along with the batch they were run in, or `None` if they're pending.
+    pub(super) fn report(&self) -> Vec<(&str, Option<u64>)> {
+        self.migrations
+            .iter()
+            .map(|(name, _)| {
+                let batch = self
+                    .state
+                    .iter()
+                    .find(|m| &m.migration == name)
+                    .map(|m| m.batch);
+
+                (name.as_str(), batch)
+            })
+            .collect()
+    }
+
     /// Runs the migrations.
     ///
     /// # Errors
@@ -120,7 +142,7 @@ impl Migrator {
             self.connection
                 .exec(
                     "insert into migrations (migration, batch) values (?, ?)",
-                    vec![value::for_db(&name)?, value::for_db(&self.batch)?],
+                    vec![value::for_db(name)?, value::for_db(self.batch)?],
                 )
                 .await
                 .map_err(|e| Error::Database(e.to_string()))?;
@@ -133,7 +155,7 @@ impl Migrator {
             self.state.push(StoredMigration {
                 id: 0,
                 batch: self.batch,
-                migration: name.to_string(),
+                migration: name.clone(),
             });
 
             tracing::info!("Successfully ran migration [{name}].");
@@ -158,8 +180,7 @@ impl Migrator {
             let (name, migration) = self
                 .migrations
                 .iter()
-                .filter(|(name, _)| name == &record.migration)
-                .next()
+                .find(|(name, _)| name == &record.migration)
                 .ok_or_else(|| Error::NotFound(record.migration.clone()))?;
 
             self.connection
@@ -236,7 +257,7 @@ pub struct StoredMigration {
     pub migration: String,
 }
 
-fn migrations_table_query() -> &'static str {
+const fn migrations_table_query() -> &'static str {
     use crate::connection::Database;
 
     match connection::which_db() {
@@ -254,5 +275,12 @@ fn migrations_table_query() -> &'static str {
                 batch int not null
             )"
         }
+        Database::SQLite => {
+            "create table if not exists migrations (
+                id integer primary key autoincrement,
+                migration varchar(255) not null unique,
+                batch integer not null
+            )"
+        }
     }
 }
@@ -3,12 +3,14 @@ use std::fmt::Debug;
 
 use crate::connection::ConnectError;
 
-#[cfg(any(feature = "mysql", feature = "postgres"))]
-pub use {migrator::Migrator, schema::Schema};
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+pub use {cli::Command, migrator::Migrator, schema::Schema};
 
-#[cfg(any(feature = "mysql", feature = "postgres"))]
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+mod cli;
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
 mod migrator;
-#[cfg(any(feature = "mysql", feature = "postgres"))]
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
 mod schema;
 
 #[derive(Debug, thiserror::Error)]
@@ -30,11 +32,25 @@ pub enum Error {
 
     #[error("Failed to deserialize migration data.")]
     Decode(#[from] rbs::Error),
+
+    #[error("Unknown migration command `{0}`, expected `migrate`, `migrate:rollback [--step=N]` or `migrate:status`.")]
+    InvalidCommand(String),
 }
 
 /// Accepts a list of structs that implement the [`Migration`] trait, and runs them.
 #[macro_export]
 macro_rules! migrate {
+    ($($migration:ty),*) => {
+        async move {
+            $crate::migrator!($($migration),*).await?.run().await
+        }
+    };
+}
+
+/// Accepts a list of structs that implement the [`Migration`] trait, and returns a [`Migrator`] they're registered with,
+/// to run a [`Command`] like rolling them back.
+#[macro_export]
+macro_rules! migrator {
     ($($migration:ty),*) => {
         async move {
             let mut migrator = $crate::migrations::Migrator::new().await?;
@@ -43,7 +59,7 @@ macro_rules! migrate {
                 migrator.register(stringify!($migration).to_string(), Box::new(<$migration>::default()));
             )*
 
-            migrator.run().await
+            Ok::<_, $crate::migrations::Error>(migrator)
         }
     };
 }
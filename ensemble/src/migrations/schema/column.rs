@@ -2,7 +2,10 @@ use ensemble_derive::Column;
 #[cfg(feature = "mysql")]
 use itertools::Itertools;
 use rbs::Value;
-use std::{fmt::Display, sync::mpsc};
+use std::{
+    fmt::{Display, Write},
+    sync::mpsc,
+};
 
 use super::Schemable;
 use crate::{connection, value};
@@ -22,6 +25,16 @@ pub enum Type {
 
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // SQLite only has a few storage classes, picked from the name of the type. `integer` is also what makes a primary key an alias of the row id,
+        // and anything that isn't recognised as text would have numeric-looking strings (like a JSON `"1"`) stored as numbers.
+        if connection::which_db().is_sqlite() {
+            return match self {
+                Self::Boolean | Self::BigInteger => f.write_str("integer"),
+                Self::Timestamp => f.write_str("datetime"),
+                _ => f.write_str("text"),
+            };
+        }
+
         match self {
             Self::Json => f.write_str("json"),
             Self::Uuid => f.write_str("uuid"),
@@ -83,10 +96,10 @@ pub struct Column {
     #[cfg(feature = "mysql")]
     #[builder(type = Type::BigInteger)]
     unsigned: bool,
-    /// Set the TIMESTAMP column to use CURRENT_TIMESTAMP as default value
+    /// Set the TIMESTAMP column to use `CURRENT_TIMESTAMP` as default value
     #[builder(type = Type::Timestamp)]
     use_current: bool,
-    /// Set the TIMESTAMP column to use CURRENT_TIMESTAMP when updating
+    /// Set the TIMESTAMP column to use `CURRENT_TIMESTAMP` when updating
     #[cfg(feature = "mysql")]
     #[builder(type = Type::Timestamp)]
     use_current_on_update: bool,
@@ -142,18 +155,18 @@ impl Column {
         }
 
         if let Some(after) = &self.after {
-            sql.push_str(&format!(" AFTER {after}"));
+            write!(sql, " AFTER {after}").unwrap();
         }
 
         if let Some(comment) = &self.comment {
-            sql.push_str(&format!(" COMMENT {comment}"));
+            write!(sql, " COMMENT {comment}").unwrap();
         }
 
         if let Some(default) = &self.default {
             if self.r#type == Type::Json {
-                sql.push_str(&format!(" DEFAULT '{}'", default.as_str().unwrap()));
+                write!(sql, " DEFAULT '{}'", default.as_str().unwrap()).unwrap();
             } else {
-                sql.push_str(&format!(" DEFAULT {}", default));
+                write!(sql, " DEFAULT {default}").unwrap();
             }
         }
 
@@ -176,11 +189,16 @@ impl Column {
         }
 
         if let Some(index) = &self.index {
-            sql.push_str(&format!(" INDEX {index}"));
+            write!(sql, " INDEX {index}").unwrap();
         }
 
         if self.primary {
             sql.push_str(" PRIMARY KEY");
+
+            // `SQLite` only accepts `AUTOINCREMENT` right after `PRIMARY KEY`
+            if self.auto_increment && connection::which_db().is_sqlite() {
+                sql.push_str(" AUTOINCREMENT");
+            }
         }
 
         if self.unique {
@@ -188,7 +206,7 @@ impl Column {
         }
 
         if self.use_current {
-            #[cfg(feature = "mysql")]
+            #[cfg(any(feature = "mysql", feature = "sqlite"))]
             sql.push_str(" DEFAULT CURRENT_TIMESTAMP");
 
             #[cfg(feature = "postgres")]
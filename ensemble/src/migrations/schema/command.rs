@@ -1,4 +1,7 @@
-use std::{fmt::Display, sync::mpsc};
+use std::{
+    fmt::{Display, Write},
+    sync::mpsc,
+};
 
 use ensemble_derive::Column;
 
@@ -40,8 +43,10 @@ impl ForeignIndex {
             .as_ref()
             .expect("failed to build index: foreign column must be specified");
 
+        // index names can't be qualified, and are created in the schema of their table
+        let table_name = self.origin_table.rsplit('.').next().unwrap_or_default();
         let index_name = self.name.as_ref().map_or_else(
-            || format!("{}_{}_foreign", self.origin_table, self.column),
+            || format!("{table_name}_{}_foreign", self.column),
             ToString::to_string,
         );
 
@@ -49,23 +54,23 @@ impl ForeignIndex {
             Database::MySQL => format!(
                 "KEY {index_name} ({}), CONSTRAINT {index_name} FOREIGN KEY ({}) REFERENCES {}({foreign_column})", self.column, self.column, self.table,
             ),
-            Database::PostgreSQL => format!(
+            Database::PostgreSQL | Database::SQLite => format!(
                 "FOREIGN KEY ({}) REFERENCES {}({foreign_column})",
                 self.column, self.table,
             )
         };
 
         if let Some(on_delete) = &self.on_delete {
-            sql.push_str(&format!(" ON DELETE {on_delete}"));
+            write!(sql, " ON DELETE {on_delete}").unwrap();
         }
 
         if let Some(on_update) = &self.on_update {
-            sql.push_str(&format!(" ON UPDATE {on_update}"));
+            write!(sql, " ON UPDATE {on_update}").unwrap();
         }
 
         match connection::which_db() {
             Database::MySQL => (sql, None),
-            Database::PostgreSQL => (
+            Database::PostgreSQL | Database::SQLite => (
                 sql,
                 Some(format!(
                     "CREATE INDEX {index_name} ON {}({});",
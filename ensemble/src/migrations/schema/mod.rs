@@ -1,6 +1,5 @@
 use inflector::Inflector;
 use itertools::{Either, Itertools};
-use rbs::Value;
 use std::{any::type_name, sync::mpsc};
 
 use self::{
@@ -8,7 +7,7 @@ use self::{
     command::{Command, ForeignIndex},
 };
 use super::{migrator::MIGRATE_CONN, Error};
-use crate::{connection, Model};
+use crate::{connection, naming, Model};
 
 mod column;
 mod command;
@@ -75,13 +74,11 @@ impl Schema {
         let mut conn_lock = MIGRATE_CONN.try_lock().map_err(|_| Error::Lock)?;
         let mut conn = conn_lock.take().ok_or(Error::Lock)?;
 
-        let (sql, bindings) = (
-            format!("DROP TABLE ?"),
-            vec![Value::String(table_name.to_string())],
-        );
+        // identifiers can't be bound as parameters, so the name is interpolated like in `create`
+        let sql = format!("DROP TABLE {table_name}");
 
-        tracing::debug!(sql = sql.as_str(), bindings = ?bindings, "Running DROP TABLE SQL query");
-        let query_result = conn.exec(&sql, bindings).await;
+        tracing::debug!(sql = sql.as_str(), "Running DROP TABLE SQL query");
+        let query_result = conn.exec(&sql, vec![]).await;
 
         conn_lock.replace(conn);
         drop(conn_lock);
@@ -135,67 +132,67 @@ pub struct Table {
 
 impl Table {
     /// Creates a primary key incrementing integer column called `id`.
-    pub fn id(&mut self) -> Column {
+    pub fn id(&self) -> Column {
         let column = Column::new("id".to_string(), Type::BigInteger, self.sender.clone())
             .primary(true)
             .increments(true);
 
         #[cfg(feature = "mysql")]
-        {
-            return column.unsigned(true);
-        }
+        let column = column.unsigned(true);
 
-        #[cfg(not(feature = "mysql"))]
-        {
-            column
-        }
+        column
     }
 
     /// Create a primary key UUID column called `id`.
-    pub fn uuid(&mut self) -> Column {
+    pub fn uuid(&self) -> Column {
         Column::new("id".to_string(), Type::Uuid, self.sender.clone())
             .uuid(true)
             .primary(true)
     }
 
+    /// Create a primary key ULID column called `id`. ULIDs are generated by the models, since databases can't.
+    pub fn ulid(&self) -> Column {
+        Column::new("id".to_string(), Type::String(26), self.sender.clone()).primary(true)
+    }
+
     /// Create a new big integer (8-byte) column on the table.
-    pub fn integer(&mut self, name: &str) -> Column {
+    pub fn integer(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::BigInteger, self.sender.clone())
     }
 
     /// Create a new json column on the table.
-    pub fn json(&mut self, name: &str) -> Column {
+    pub fn json(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::Json, self.sender.clone())
     }
 
     /// Create a new string column on the table.
-    pub fn string(&mut self, name: &str) -> Column {
+    pub fn string(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::String(255), self.sender.clone())
     }
 
     /// Create a new boolean column on the table.
-    pub fn boolean(&mut self, name: &str) -> Column {
+    pub fn boolean(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::Boolean, self.sender.clone())
     }
 
     /// Create a new text column on the table.
-    pub fn text(&mut self, name: &str) -> Column {
+    pub fn text(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::Text, self.sender.clone())
     }
 
     /// Create a new timestamp column on the table.
-    pub fn timestamp(&mut self, name: &str) -> Column {
+    pub fn timestamp(&self, name: &str) -> Column {
         Column::new(name.to_string(), Type::Timestamp, self.sender.clone())
     }
 
     /// Specify a foreign key for the table.
-    pub fn foreign(&mut self, column: &str) -> ForeignIndex {
+    pub fn foreign(&self, column: &str) -> ForeignIndex {
         ForeignIndex::new(column.to_string(), self.name.clone(), self.sender.clone())
     }
 
     #[cfg(feature = "mysql")]
     /// Create a new enum column on the table.
-    pub fn r#enum(&mut self, name: &str, values: &[&str]) -> Column {
+    pub fn r#enum(&self, name: &str, values: &[&str]) -> Column {
         Column::new(
             name.to_string(),
             Type::Enum(values.iter().map(ToString::to_string).collect()),
@@ -204,8 +201,8 @@ impl Table {
     }
 
     /// Create a foreign ID column for the given model.
-    pub fn foreign_id_for<M: Model>(&mut self) -> ForeignIndex {
-        let column = format!("{}_{}", M::NAME, M::PRIMARY_KEY).to_snake_case();
+    pub fn foreign_id_for<M: Model>(&self) -> ForeignIndex {
+        let column = naming::strategy().foreign_key(M::NAME, M::PRIMARY_KEY);
 
         if ["u64", "u32", "u16", "u8", "usize"].contains(&type_name::<M::PrimaryKey>()) {
             #[allow(unused_variables)]
@@ -220,11 +217,11 @@ impl Table {
         }
 
         let index = ForeignIndex::new(column, self.name.clone(), self.sender.clone());
-        index.on(M::TABLE_NAME).references(M::PRIMARY_KEY)
+        index.on(M::QUALIFIED_TABLE).references(M::PRIMARY_KEY)
     }
 
     /// Create a foreign ID column for the given model.
-    pub fn foreign_id(&mut self, name: &str) -> ForeignIndex {
+    pub fn foreign_id(&self, name: &str) -> ForeignIndex {
         #[allow(unused_variables)]
         let column = Column::new(name.to_string(), Type::BigInteger, self.sender.clone());
 
@@ -244,7 +241,7 @@ impl Table {
     }
 
     /// Create a foreign UUID column for the given model.
-    pub fn foreign_uuid(&mut self, name: &str) -> ForeignIndex {
+    pub fn foreign_uuid(&self, name: &str) -> ForeignIndex {
         Column::new(name.to_string(), Type::Uuid, self.sender.clone()).uuid(true);
         let index = ForeignIndex::new(name.to_string(), self.name.clone(), self.sender.clone());
 
@@ -256,8 +253,21 @@ impl Table {
         }
     }
 
+    /// Create a foreign ULID column for the given model.
+    pub fn foreign_ulid(&self, name: &str) -> ForeignIndex {
+        Column::new(name.to_string(), Type::String(26), self.sender.clone());
+        let index = ForeignIndex::new(name.to_string(), self.name.clone(), self.sender.clone());
+
+        // if the column name is of the form `resource_id`, we extract and set the table name and foreign column name
+        if let Some((resource, column)) = name.split_once('_') {
+            index.on(&resource.to_plural()).references(column)
+        } else {
+            index
+        }
+    }
+
     /// Add nullable creation and update timestamps to the table.
-    pub fn timestamps(&mut self) {
+    pub fn timestamps(&self) {
         self.timestamp("created_at")
             .nullable(true)
             .use_current(true);
@@ -0,0 +1,85 @@
+//! Naming conventions used to infer column and table names for relationships.
+//!
+//! Ensemble follows Laravel's conventions by default: a `User` model is referenced by a `user_id` foreign key,
+//! and a `User` <-> `Role` pivot table is named `role_user`. If your schema follows different conventions,
+//! implement [`NamingStrategy`] and register it with [`set_naming_strategy`] before using any of your models.
+//!
+//! Individual relationships can still override these conventions with the `foreign_key`, `local_key` and `pivot_table` attributes.
+
+use inflector::Inflector;
+use std::sync::OnceLock;
+
+static STRATEGY: OnceLock<Box<dyn NamingStrategy>> = OnceLock::new();
+
+/// The conventions used to infer the names of foreign keys and pivot tables.
+///
+/// Every method has a default implementation, so you only need to override the conventions your schema doesn't follow.
+///
+/// ## Example
+///
+/// ```rust
+/// use ensemble::naming::NamingStrategy;
+///
+/// struct Prefixed;
+///
+/// impl NamingStrategy for Prefixed {
+///     fn foreign_key(&self, model: &str, _primary_key: &str) -> String {
+///         format!("fk_{}", model.to_lowercase())
+///     }
+/// }
+///
+/// assert_eq!(Prefixed.foreign_key("User", "id"), "fk_user");
+/// assert_eq!(Prefixed.pivot_table("User", "Role"), "role_user");
+/// ```
+pub trait NamingStrategy: Send + Sync {
+    /// The name of the column referencing the given model, given the model's name and primary key (e.g., `user_id` for `User`).
+    fn foreign_key(&self, model: &str, primary_key: &str) -> String {
+        format!("{}_{}", model.to_snake_case(), primary_key).to_snake_case()
+    }
+
+    /// The name of the pivot table joining the two given models (e.g., `role_user` for `User` and `Role`).
+    fn pivot_table(&self, first: &str, second: &str) -> String {
+        let mut names = [first, second];
+        names.sort_unstable();
+
+        names.join("_").to_snake_case()
+    }
+
+    /// The names of the type and id columns of a polymorphic relation with the given name (e.g., `commentable_type` and `commentable_id`).
+    fn morph_columns(&self, name: &str) -> (String, String) {
+        let name = name.to_snake_case();
+
+        (format!("{name}_type"), format!("{name}_id"))
+    }
+
+    /// The name of the pivot table of a polymorphic many to many relation with the given name (e.g., `taggables` for `taggable`).
+    fn morph_pivot_table(&self, name: &str) -> String {
+        name.to_snake_case().to_plural()
+    }
+}
+
+/// The default naming conventions, matching Laravel's.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultNamingStrategy;
+
+impl NamingStrategy for DefaultNamingStrategy {}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The naming strategy has already been set, or was used before being set.")]
+pub struct AlreadySet;
+
+/// Sets the naming strategy used by every relationship that doesn't specify its keys explicitly.
+///
+/// # Errors
+///
+/// Returns an error if a naming strategy has already been set, or if the default strategy has already been used.
+pub fn set_naming_strategy<S: NamingStrategy + 'static>(strategy: S) -> Result<(), AlreadySet> {
+    STRATEGY.set(Box::new(strategy)).map_err(|_| AlreadySet)
+}
+
+/// The naming strategy currently in use.
+pub fn strategy() -> &'static dyn NamingStrategy {
+    STRATEGY
+        .get_or_init(|| Box::new(DefaultNamingStrategy))
+        .as_ref()
+}
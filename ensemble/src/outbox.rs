@@ -0,0 +1,225 @@
+//! A transactional outbox for model events.
+//!
+//! Models marked with `#[ensemble(outbox)]` record an [`Event`] every time they are created, saved or deleted,
+//! in the same transaction as the change itself. An event is stored if and only if the change is committed,
+//! so a separate process can [`drain`] the outbox and publish its events to a message queue without ever missing one or announcing a change that was rolled back.
+//!
+//! Add the outbox table to your migrations with [`CreateOutboxTable`]. It is called `outbox` unless configured otherwise with [`ConnectionConfig::outbox_table`](crate::ConnectionConfig::outbox_table).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::{Model, outbox};
+//! #[derive(Debug, Model)]
+//! #[ensemble(outbox)]
+//! struct Order {
+//!     id: u64,
+//!     total: u64,
+//! }
+//!
+//! # async fn publish(_: &outbox::Event) -> Result<(), std::io::Error> { Ok(()) }
+//! # async fn run() -> Result<(), outbox::DrainError<std::io::Error>> {
+//! // the Order is inserted, along with an `Order` `created` event
+//! Order { id: 0, total: 100 }.create().await?;
+//!
+//! // later, from a background job
+//! outbox::drain(100, |events| async move {
+//!     for event in &events {
+//!         publish(event).await?;
+//!     }
+//!
+//!     Ok(())
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use itertools::Itertools;
+use rbs::Value;
+use serde::Deserialize;
+use std::future::Future;
+
+use crate::{
+    connection::{self, Transaction},
+    query::Dialect,
+    types::{DateTime, Json},
+    value, Error, Model,
+};
+
+pub use crate::hooks::EventType;
+
+/// A change to a model, as recorded in the outbox.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Event {
+    /// The id of the outbox row. Events are drained in the order of their ids.
+    pub id: u64,
+    /// The name of the model that changed (e.g. `Order`).
+    pub model: String,
+    /// The primary key of the model that changed.
+    #[serde(rename = "model_key")]
+    pub key: String,
+    /// What happened to the model.
+    pub event: EventType,
+    /// The JSON representation of the model after the change (or before it, for deletions). Hidden fields are not included.
+    pub payload: Json,
+    /// When the change was made.
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DrainError<E> {
+    #[error(transparent)]
+    Database(#[from] Error),
+
+    #[error("The outbox handler failed: {0}")]
+    Handler(E),
+}
+
+/// Hand the oldest `batch_size` events in the outbox to `handler`, deleting them once it succeeds. Returns the number of events handled.
+///
+/// The events are locked (with `SELECT ... FOR UPDATE SKIP LOCKED`) for as long as the handler runs, and workers skip the events another one
+/// has locked rather than waiting for them, so several workers can drain the outbox at once. `SQLite` has no row locks, so only run one worker there.
+/// If the handler fails, nothing is deleted and the events will be handed out again. Delivery is at-least-once:
+/// if the handler succeeds but the deletion can't be committed, the same events will be handled again later.
+///
+/// # Errors
+///
+/// Returns a [`DrainError::Handler`] error if the handler fails, or a [`DrainError::Database`] error if the outbox can't be read or cleared.
+pub async fn drain<F, Fut, E>(batch_size: usize, handler: F) -> Result<usize, DrainError<E>>
+where
+    F: FnOnce(Vec<Event>) -> Fut + Send,
+    Fut: Future<Output = Result<(), E>> + Send,
+{
+    let table = connection::outbox_table();
+    let mut transaction = Transaction::begin(table).await?;
+
+    let events = transaction
+        .fetch(&batch_sql(table, batch_size, Dialect::default()), vec![])
+        .await?
+        .into_iter()
+        .map(value::from::<Event>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::from)?;
+
+    if events.is_empty() {
+        transaction.commit().await?;
+        return Ok(0);
+    }
+
+    let ids = events
+        .iter()
+        .map(|event| Value::U64(event.id))
+        .collect_vec();
+    let count = ids.len();
+
+    if let Err(error) = handler(events).await {
+        transaction.rollback().await.ok();
+        return Err(DrainError::Handler(error));
+    }
+
+    transaction
+        .exec(
+            &format!(
+                "DELETE FROM {table} WHERE id IN ({})",
+                ids.iter().map(|_| "?").join(", ")
+            ),
+            ids,
+        )
+        .await?;
+    transaction.commit().await?;
+
+    Ok(count)
+}
+
+/// The statement selecting (and locking) the next `batch_size` events that no other worker has locked.
+fn batch_sql(table: &str, batch_size: usize, dialect: Dialect) -> String {
+    let lock = match dialect {
+        Dialect::Mysql | Dialect::Postgres => " FOR UPDATE SKIP LOCKED",
+        Dialect::Sqlite => "",
+    };
+
+    format!("SELECT * FROM {table} ORDER BY id LIMIT {batch_size}{lock}")
+}
+
+/// The statement recording a change to a model in the outbox, to run in the same transaction as the change itself.
+pub(crate) fn record<M: Model>(
+    event: EventType,
+    key: String,
+    payload: serde_json::Value,
+) -> Result<(String, Vec<Value>), Error> {
+    Ok((
+        format!(
+            "INSERT INTO {} (model, model_key, event, payload, created_at) VALUES (?, ?, ?, ?, ?)",
+            connection::outbox_table()
+        ),
+        vec![
+            Value::String(M::NAME.to_string()),
+            Value::String(key),
+            Value::String(event.as_str().to_string()),
+            value::for_db(Json(payload))?,
+            value::for_db(DateTime::now())?,
+        ],
+    ))
+}
+
+pub(crate) fn payload<M: Model>(model: &M) -> serde_json::Value {
+    // models implement Serialize themselves, and never fail to serialize
+    serde_json::to_value(model).unwrap_or_default()
+}
+
+/// A migration creating the outbox table.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use ensemble::{migrate, outbox::CreateOutboxTable};
+/// # async fn run() -> Result<(), ensemble::migrations::Error> {
+/// migrate!(CreateOutboxTable).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[derive(Debug, Default)]
+pub struct CreateOutboxTable;
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[async_trait::async_trait]
+impl crate::migrations::Migration for CreateOutboxTable {
+    async fn up(&self) -> Result<(), crate::migrations::Error> {
+        crate::migrations::Schema::create(connection::outbox_table(), |table| {
+            table.id();
+            table.string("model");
+            table.string("model_key");
+            table.string("event");
+            table.json("payload");
+            table.timestamp("created_at").use_current(true);
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), crate::migrations::Error> {
+        crate::migrations::Schema::drop(connection::outbox_table()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_sql, Dialect};
+
+    #[test]
+    fn workers_skip_the_events_locked_by_others() {
+        assert_eq!(
+            batch_sql("outbox", 100, Dialect::Mysql),
+            "SELECT * FROM outbox ORDER BY id LIMIT 100 FOR UPDATE SKIP LOCKED"
+        );
+        assert_eq!(
+            batch_sql("outbox", 100, Dialect::Postgres),
+            "SELECT * FROM outbox ORDER BY id LIMIT 100 FOR UPDATE SKIP LOCKED"
+        );
+        assert_eq!(
+            batch_sql("outbox", 100, Dialect::Sqlite),
+            "SELECT * FROM outbox ORDER BY id LIMIT 100"
+        );
+    }
+}
@@ -1,38 +1,156 @@
 use itertools::Itertools;
 use rbs::Value;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    fmt::Display,
+    fmt::{Debug, Display, Write},
+    future::Future,
+    time::Duration,
 };
 
-use crate::{connection, value, Error, Model};
+use crate::{
+    connection::{self, QueryOptions, Transaction},
+    redaction, tenancy,
+    types::DateTime,
+    value::{self, ColumnDef},
+    ChunkedWriteError, Error, FromRow, Model,
+};
+
+mod clause;
+mod dialect;
+pub(crate) mod expr;
+mod json;
+mod paginator;
+mod params;
+mod scope;
+
+#[doc(hidden)]
+pub use crate::connection::operation;
+pub use clause::Clause;
+pub use dialect::Dialect;
+pub(crate) use dialect::{insert_many_statement, insert_sql, insert_statement};
+pub use expr::Expr;
+pub use paginator::{CursorPaginator, Paginator};
+pub use params::Params;
+pub use scope::GlobalScope;
+
+/// The column soft deleted records are marked with.
+const DELETED_AT: &str = "deleted_at";
 
 /// The Query Builder.
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Builder {
     table: String,
+    primary_key: &'static str,
+    columns: &'static [ColumnDef],
     join: Vec<Join>,
     order: Vec<Order>,
     limit: Option<usize>,
     offset: Option<usize>,
+    options: QueryOptions,
+    unconstrained: bool,
+    read_only: Option<&'static str>,
+    tenancy: Option<Tenancy>,
+    trashed: Option<Trashed>,
     r#where: Vec<WhereClause>,
     eager_load: HashSet<String>,
+    exists: Vec<Exists>,
+    distinct: Option<String>,
+    select: Vec<String>,
+    hints: Vec<String>,
+    index_hints: Vec<IndexHint>,
+    chunk_pause: Option<Duration>,
+    lock: bool,
+    lock_wait: LockWait,
+    on_primary: bool,
+    /// Whether a clause couldn't be added (like one with an invalid JSON path), so the query fails with an [`Error::InvalidQuery`] instead of running.
+    invalid: bool,
+}
+
+/// What a query locking its rows does when another transaction already locked some of them.
+#[derive(Debug, Clone, Copy)]
+enum LockWait {
+    Wait,
+    SkipLocked,
+    NoWait,
+}
+
+/// How a query on a model marked with `tenant_column` is scoped.
+#[derive(Debug)]
+enum Tenancy {
+    Scoped { column: &'static str, tenant: Value },
+    Missing(&'static str),
+    Bypassed,
+}
+
+/// Which records a query on a model marked with `soft_deletes` includes.
+#[derive(Debug, Clone, Copy)]
+enum Trashed {
+    Without,
+    With,
+    Only,
 }
 
 impl Builder {
     pub(crate) fn new(table: String) -> Self {
         Self {
             table,
+            primary_key: "id",
+            columns: &[],
             limit: None,
             offset: None,
+            options: QueryOptions::default(),
+            unconstrained: false,
+            read_only: None,
+            tenancy: None,
+            trashed: None,
             join: vec![],
             order: vec![],
             r#where: vec![],
             eager_load: HashSet::new(),
+            exists: vec![],
+            distinct: None,
+            select: vec![],
+            hints: vec![],
+            index_hints: vec![],
+            chunk_pause: None,
+            lock: false,
+            lock_wait: LockWait::Wait,
+            on_primary: false,
+            invalid: false,
         }
     }
 
+    /// Set the primary key of the table, which chunked writes are ordered by.
+    pub(crate) const fn keyed(mut self, primary_key: &'static str) -> Self {
+        self.primary_key = primary_key;
+        self
+    }
+
+    /// Reject any statement that would write to the table.
+    pub(crate) const fn read_only(mut self, table: &'static str) -> Self {
+        self.read_only = Some(table);
+        self
+    }
+
+    /// Scope the query to the current tenant, recording whether there is one.
+    pub(crate) fn tenant(mut self, table: &'static str, column: &'static str) -> Self {
+        self.tenancy = Some(
+            tenancy::current().map_or(Tenancy::Missing(table), |tenant| Tenancy::Scoped {
+                column,
+                tenant,
+            }),
+        );
+        self
+    }
+
+    /// Exclude soft deleted records from the query, unless it asks for them with [`with_trashed`](Self::with_trashed) or [`only_trashed`](Self::only_trashed).
+    pub(crate) const fn soft_deletes(mut self) -> Self {
+        self.trashed = Some(Trashed::Without);
+        self
+    }
+
     /// Execute a raw SQL query and return the results.
     ///
     /// # Safety
@@ -44,11 +162,96 @@ impl Builder {
     ///
     /// Returns an error if the query fails, or if a connection to the database cannot be established.
     pub async unsafe fn raw_sql(sql: &str, bindings: Vec<Value>) -> Result<Vec<Value>, Error> {
-        let mut conn = connection::get().await?;
+        connection::fetch("", sql, bindings, QueryOptions::ONCE).await
+    }
 
-        conn.get_values(sql, bindings)
-            .await
-            .map_err(|e| Error::Database(e.to_string()))
+    /// Execute a raw SQL query and hydrate the results into `T`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it allows for arbitrary SQL to be executed, which can lead to SQL injection.
+    /// It is recommended to build queries using the methods provided by the query builder instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a row cannot be hydrated into `T`, or if a connection to the database cannot be established.
+    pub async unsafe fn raw_sql_as<T: FromRow>(
+        sql: &str,
+        bindings: Vec<Value>,
+    ) -> Result<Vec<T>, Error> {
+        let rows = connection::fetch("", sql, bindings, QueryOptions::ONCE).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(value::from::<T>)
+            .collect::<Result<Vec<T>, rbs::Error>>()?)
+    }
+
+    /// Execute a raw SQL query with named parameters (e.g. `:email`) and return the results.
+    ///
+    /// The parameters can be created with the [`params!`](crate::params) macro, or be any `Serialize` struct, with its fields as the names.
+    /// A parameter can be used more than once, but every parameter must be used.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it allows for arbitrary SQL to be executed, which can lead to SQL injection.
+    /// It is recommended to build queries using the methods provided by the query builder instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query uses a parameter that isn't given ([`Error::MissingParameter`]), if a parameter is never used ([`Error::UnusedParameter`]),
+    /// if the query fails, or if a connection to the database cannot be established.
+    pub async unsafe fn raw_sql_named<P: Serialize + Send>(
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<Value>, Error> {
+        let (sql, bindings) = params::bind(sql, params)?;
+
+        connection::fetch("", &sql, bindings, QueryOptions::ONCE).await
+    }
+
+    /// Like [`raw_sql_named`](Self::raw_sql_named), but hydrating the results into `T`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it allows for arbitrary SQL to be executed, which can lead to SQL injection.
+    /// It is recommended to build queries using the methods provided by the query builder instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parameters don't match the ones used by the query, if the query fails, if a row cannot be hydrated into `T`,
+    /// or if a connection to the database cannot be established.
+    pub async unsafe fn raw_sql_named_as<T: FromRow, P: Serialize + Send>(
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<T>, Error> {
+        let (sql, bindings) = params::bind(sql, params)?;
+        let rows = connection::fetch("", &sql, bindings, QueryOptions::ONCE).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(value::from::<T>)
+            .collect::<Result<Vec<T>, rbs::Error>>()?)
+    }
+
+    /// Run `EXPLAIN` on a raw SQL query, returning the database's plan for it.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it allows for arbitrary SQL to be executed, which can lead to SQL injection.
+    /// Note that [`Explain::Analyze`] runs the query, so it should only be used with `SELECT` queries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async unsafe fn explain_raw(
+        sql: &str,
+        bindings: Vec<Value>,
+        kind: Explain,
+    ) -> Result<QueryPlan, Error> {
+        let rows = connection::fetch("", &kind.prefix(sql)?, bindings, QueryOptions::ONCE).await?;
+
+        Ok(QueryPlan::from(rows))
     }
 
     /// Set the table which the query is targeting.
@@ -58,6 +261,42 @@ impl Builder {
         self
     }
 
+    /// Run the query across every tenant, even if none is set. Does nothing for models that aren't scoped to a tenant.
+    ///
+    /// Every query run this way is logged as a warning.
+    #[must_use]
+    pub fn without_tenancy(mut self) -> Self {
+        if self.tenancy.is_some() {
+            self.tenancy = Some(Tenancy::Bypassed);
+        }
+        self
+    }
+
+    /// Apply a scope, a reusable function adding constraints to the query. Scopes are usually defined on the model they constrain:
+    ///
+    /// ```rust
+    /// # use ensemble::{query::Builder, Model};
+    /// # #[derive(Debug, Model)]
+    /// # struct User {
+    /// #     id: u64,
+    /// #     active: bool,
+    /// # }
+    /// impl User {
+    ///     fn active(query: Builder) -> Builder {
+    ///         query.r#where("active", "=", true)
+    ///     }
+    /// }
+    ///
+    /// # async fn example() -> Result<(), ensemble::Error> {
+    /// let users = User::query().scope(User::active).get::<User>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn scope(self, scope: impl FnOnce(Self) -> Self) -> Self {
+        scope(self)
+    }
+
     /// Apply the given callback to the builder if the provided condition is true.
     #[must_use]
     pub fn when(mut self, condition: bool, r#fn: impl FnOnce(Self) -> Self) -> Self {
@@ -89,11 +328,12 @@ impl Builder {
         Op: Into<Operator>,
         T: serde::Serialize,
     {
+        let value = self.bound(column, value::for_db(value).unwrap());
         self.r#where.push(WhereClause::Simple(Where {
             boolean: Boolean::And,
             operator: operator.into(),
             column: column.to_string(),
-            value: Some(value::for_db(value).unwrap()),
+            value: Some(value),
         }));
 
         self
@@ -113,6 +353,54 @@ impl Builder {
         self
     }
 
+    /// Retry the query up to `times` times if it fails with a transient error.
+    ///
+    /// Unlike `SELECT` queries, which are retried according to the configured [`QueryRetryPolicy`](crate::QueryRetryPolicy),
+    /// statements that modify rows are never retried unless explicitly marked with this method.
+    #[must_use]
+    pub const fn retry(mut self, times: u32) -> Self {
+        self.options.retries = Some(times);
+        self
+    }
+
+    /// Never retry the query, regardless of the configured [`QueryRetryPolicy`](crate::QueryRetryPolicy).
+    #[must_use]
+    pub const fn no_retry(self) -> Self {
+        self.retry(0)
+    }
+
+    /// Give up on the query if it hasn't finished after `timeout`, returning an [`Error::QueryTimeout`].
+    ///
+    /// The connection the query was running on is closed instead of being returned to the pool, and the query is never retried.
+    /// Inside a [`transaction`](crate::transaction), that's the transaction's connection, so the transaction is rolled back and every later query in it fails too.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Run the query on the primary database, even when [read replicas](crate::ConnectionConfig::read_replica) are configured,
+    /// like to read rows written just before, which the replicas may not have caught up with yet.
+    #[must_use]
+    pub const fn on_primary(mut self) -> Self {
+        self.on_primary = true;
+        self
+    }
+
+    /// Describe the columns of the table, so updates can reject the immutable ones.
+    pub(crate) const fn described_by(mut self, columns: &'static [ColumnDef]) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Wait for `pause` between the batches of [`delete_in_chunks`](Self::delete_in_chunks) and [`update_in_chunks`](Self::update_in_chunks),
+    /// giving replicas and other queries room to catch up.
+    #[must_use]
+    pub const fn pause_between_chunks(mut self, pause: Duration) -> Self {
+        self.chunk_pause = Some(pause);
+        self
+    }
+
     /// Set the relationships that should be eager loaded.
     #[must_use]
     pub fn with<T: Into<EagerLoad>>(mut self, relations: T) -> Self {
@@ -121,6 +409,133 @@ impl Builder {
         self
     }
 
+    /// Stop eager loading the given relationships, like those the model loads by default with `#[model(eager)]`.
+    #[must_use]
+    pub fn without<T: Into<EagerLoad>>(mut self, relations: T) -> Self {
+        for relation in relations.into().list() {
+            self.eager_load.remove(&relation);
+        }
+
+        self
+    }
+
+    /// Check whether each retrieved model has any models in the given relationship, without retrieving them.
+    /// The result is available through the relationship's [`exists`](crate::relationships::Relationship::exists) method (or the model's generated `{relation}_exists` method).
+    #[must_use]
+    pub fn with_exists(self, relation: &str) -> Self {
+        self.add_exists(relation, None)
+    }
+
+    /// Like [`with_exists`](Self::with_exists), but only counting the related models matching the constraints added by `constraint`.
+    #[must_use]
+    pub fn with_exists_where(
+        self,
+        relation: &str,
+        constraint: impl FnOnce(Self) -> Self + Send + Sync + 'static,
+    ) -> Self {
+        self.add_exists(relation, Some(Box::new(constraint)))
+    }
+
+    fn add_exists(mut self, relation: &str, constraint: Option<Constraint>) -> Self {
+        self.exists.push(Exists {
+            relation: relation.to_string(),
+            constraint,
+        });
+
+        self
+    }
+
+    /// Only select the distinct values of the given column.
+    pub(crate) fn distinct(mut self, column: &str) -> Self {
+        self.distinct = Some(column.to_string());
+        self
+    }
+
+    /// Select a column written by the crate itself, like `tags.*`. Unlike [`select_raw`](Self::select_raw), it never comes from user input.
+    pub(crate) fn select_column(mut self, column: String) -> Self {
+        self.select.push(column);
+        self
+    }
+
+    /// Suggest an index for `MySQL` to use when selecting from the table, rendering a `USE INDEX` hint after the table name.
+    /// Index hints are ignored by Postgres.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't a valid index name, which may only contain letters, digits, `_` and `$`.
+    #[must_use]
+    pub fn use_index(self, index: &str) -> Self {
+        self.index_hint(IndexHintType::Use, index)
+    }
+
+    /// Make `MySQL` use the given index when selecting from the table (unless it can't be used at all), rendering a `FORCE INDEX` hint.
+    /// Index hints are ignored by Postgres.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't a valid index name, which may only contain letters, digits, `_` and `$`.
+    #[must_use]
+    pub fn force_index(self, index: &str) -> Self {
+        self.index_hint(IndexHintType::Force, index)
+    }
+
+    /// Keep `MySQL` from using the given index when selecting from the table, rendering an `IGNORE INDEX` hint.
+    /// Index hints are ignored by Postgres.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't a valid index name, which may only contain letters, digits, `_` and `$`.
+    #[must_use]
+    pub fn ignore_index(self, index: &str) -> Self {
+        self.index_hint(IndexHintType::Ignore, index)
+    }
+
+    fn index_hint(mut self, r#type: IndexHintType, index: &str) -> Self {
+        assert!(
+            !index.is_empty()
+                && index
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$'),
+            "The index name `{index}` is invalid."
+        );
+
+        self.index_hints.push(IndexHint {
+            r#type,
+            index: index.to_string(),
+        });
+        self
+    }
+
+    /// Add an optimizer hint comment (e.g. `/*+ MAX_EXECUTION_TIME(1000) */`), rendered right after `SELECT`.
+    /// `MySQL` reads the hints from the comment, while Postgres ignores them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hint` isn't a single `/*+ ... */` comment.
+    #[must_use]
+    pub fn query_hint(mut self, hint: &str) -> Self {
+        let body = hint
+            .trim()
+            .strip_prefix("/*+")
+            .and_then(|hint| hint.strip_suffix("*/"));
+
+        assert!(
+            body.is_some_and(|body| !body.contains("*/")),
+            "The query hint `{hint}` must be a single `/*+ ... */` comment."
+        );
+
+        self.hints.push(hint.trim().to_string());
+        self
+    }
+
+    /// Make `MySQL` join the tables in the order they're listed in, rendering `SELECT STRAIGHT_JOIN`.
+    /// Ignored by Postgres.
+    #[must_use]
+    pub fn straight_join(mut self) -> Self {
+        self.hints.push("STRAIGHT_JOIN".to_string());
+        self
+    }
+
     /// Add an "or where" clause to the query.
     ///
     /// # Panics
@@ -137,343 +552,1765 @@ impl Builder {
             "Cannot use or_where without a where clause."
         );
 
+        let value = self.bound(column, value.into());
         self.r#where.push(WhereClause::Simple(Where {
             operator: op.into(),
             boolean: Boolean::Or,
-            value: Some(value.into()),
+            value: Some(value),
             column: column.to_string(),
         }));
 
         self
     }
 
-    /// Add a "where not null" clause to the query.
+    /// Add a "where null" clause to the query.
     #[must_use]
-    pub fn where_not_null(mut self, column: &str) -> Self {
+    pub fn where_null(mut self, column: &str) -> Self {
         self.r#where.push(WhereClause::Simple(Where {
             value: None,
             boolean: Boolean::And,
             column: column.to_string(),
-            operator: Operator::NotNull,
+            operator: Operator::IsNull,
         }));
 
         self
     }
 
-    /// Add an inner join to the query.
+    /// Add a "where not null" clause to the query.
     #[must_use]
-    pub fn join<Op: Into<Operator>>(
-        mut self,
-        column: &str,
-        first: &str,
-        op: Op,
-        second: &str,
-    ) -> Self {
-        self.join.push(Join {
-            operator: op.into(),
-            first: first.to_string(),
+    pub fn where_not_null(mut self, column: &str) -> Self {
+        self.r#where.push(WhereClause::Simple(Where {
+            value: None,
+            boolean: Boolean::And,
             column: column.to_string(),
-            r#type: JoinType::Inner,
-            second: second.to_string(),
-        });
+            operator: Operator::NotNull,
+        }));
 
         self
     }
 
-    /// Add an "order by" clause to the query.
+    /// Add a "where between" clause to the query, matching values from `start` to `end` (both inclusive).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided values cannot be serialized.
     #[must_use]
-    pub fn order_by<Dir: Into<Direction>>(mut self, column: &str, direction: Dir) -> Self {
-        self.order.push(Order {
-            column: column.to_string(),
-            direction: direction.into(),
-        });
+    pub fn where_between<T: serde::Serialize>(self, column: &str, start: T, end: T) -> Self {
+        self.where_range(column, Operator::Between, start, end)
+    }
 
-        self
+    /// Add a "where not between" clause to the query, matching values before `start` or after `end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided values cannot be serialized.
+    #[must_use]
+    pub fn where_not_between<T: serde::Serialize>(self, column: &str, start: T, end: T) -> Self {
+        self.where_range(column, Operator::NotBetween, start, end)
     }
 
-    /// Logically group a set of where clauses.
+    /// Add a where clause comparing only the date part of a column (e.g. `2023-10-01`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
     #[must_use]
-    pub fn where_group(mut self, r#fn: impl FnOnce(Self) -> Self) -> Self {
-        let builder = r#fn(Self::new(self.table.clone()));
+    pub fn where_date<T: serde::Serialize>(self, column: &str, date: T) -> Self {
+        self.where_part(DatePart::Date, column, date)
+    }
 
-        self.r#where
-            .push(WhereClause::Group(builder.r#where, Boolean::And));
+    /// Add a where clause comparing only the year of a date column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn where_year<T: serde::Serialize>(self, column: &str, year: T) -> Self {
+        self.where_part(DatePart::Year, column, year)
+    }
 
-        self
+    /// Add a where clause comparing only the month (from 1 to 12) of a date column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn where_month<T: serde::Serialize>(self, column: &str, month: T) -> Self {
+        self.where_part(DatePart::Month, column, month)
     }
 
-    /// Get the SQL representation of the query.
+    /// Add a where clause comparing only the day of the month of a date column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
     #[must_use]
-    pub fn to_sql(&self, r#type: Type) -> String {
-        let mut sql = match r#type {
-            Type::Update => String::new(), // handled in update()
-            Type::Delete => format!("DELETE FROM {}", self.table),
-            Type::Select => format!("SELECT * FROM {}", self.table),
-            Type::Count => format!("SELECT COUNT(*) FROM {}", self.table),
-        };
+    pub fn where_day<T: serde::Serialize>(self, column: &str, day: T) -> Self {
+        self.where_part(DatePart::Day, column, day)
+    }
 
-        if !self.join.is_empty() {
-            for join in &self.join {
-                sql.push_str(&format!(
-                    " {} {} ON {} {} {}",
-                    join.r#type, join.column, join.first, join.operator, join.second
-                ));
-            }
-        }
+    /// Add a where clause comparing only the time part of a column (e.g. `14:30:00`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn where_time<T: serde::Serialize>(self, column: &str, time: T) -> Self {
+        self.where_part(DatePart::Time, column, time)
+    }
 
-        if !self.r#where.is_empty() {
-            sql.push_str(" WHERE ");
+    fn where_range<T: serde::Serialize>(
+        mut self,
+        column: &str,
+        operator: Operator,
+        start: T,
+        end: T,
+    ) -> Self {
+        let value = Value::Array(vec![
+            value::for_db(start).unwrap(),
+            value::for_db(end).unwrap(),
+        ]);
+        self.r#where.push(WhereClause::Simple(Where {
+            operator,
+            boolean: Boolean::And,
+            column: column.to_string(),
+            value: Some(self.bound(column, value)),
+        }));
 
-            for (i, where_clause) in self.r#where.iter().enumerate() {
-                sql.push_str(&where_clause.to_sql(i != self.r#where.len() - 1));
-            }
+        self
+    }
+
+    /// Add a where clause comparing a value inside a JSON column, addressed by a path like `meta->author->name` (or `meta->tags->0` for the first element of an array).
+    /// Postgres extracts the value as text, so it should be compared with strings there.
+    ///
+    /// A key of the path that is empty or contains quotes makes the query fail with an [`Error::InvalidQuery`] when it's run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn where_json<T, Op>(mut self, path: &str, operator: Op, value: T) -> Self
+    where
+        Op: Into<Operator>,
+        T: serde::Serialize,
+    {
+        let Some(path) = json::JsonPath::parse(path) else {
+            self.invalid = true;
+            return self;
+        };
+        let value = self.bound(path.column(), value::for_db(value).unwrap());
+        self.r#where.push(WhereClause::Simple(Where {
+            boolean: Boolean::And,
+            operator: operator.into(),
+            column: path.extract(Dialect::default()),
+            value: Some(value),
+        }));
+
+        self
+    }
+
+    /// Add a where clause matching JSON columns whose array (or object) at `path` contains `value`, like `where_json_contains("meta->tags", "rust")`.
+    /// On `SQLite`, the value must be one of the array's elements, so looking for several values or a part of an object isn't supported.
+    ///
+    /// Like with [`where_json`](Self::where_json), an invalid path makes the query fail with an [`Error::InvalidQuery`] when it's run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn where_json_contains<T: serde::Serialize>(mut self, path: &str, value: T) -> Self {
+        let dialect = Dialect::default();
+        let Some(path) = json::JsonPath::parse(path) else {
+            self.invalid = true;
+            return self;
+        };
+
+        // SAFETY: the column is written as-is like in every other where clause, and the path's keys can't contain quotes.
+        let clause = unsafe { Clause::default().sql_unchecked(path.contains(dialect)) };
+        let clause = if dialect == Dialect::Sqlite {
+            clause.bind(value)
+        } else {
+            clause.bind(serde_json::to_string(&value).unwrap())
+        };
+
+        self.push_custom(clause, Boolean::And)
+    }
+
+    fn where_part<T: serde::Serialize>(mut self, part: DatePart, column: &str, value: T) -> Self {
+        let value = self.bound(column, value::for_db(value).unwrap());
+        self.r#where.push(WhereClause::Simple(Where {
+            boolean: Boolean::And,
+            operator: Operator::Equals,
+            column: part.wrap(column),
+            value: Some(value),
+        }));
+
+        self
+    }
+
+    /// Include soft deleted records, which queries on models marked with `soft_deletes` leave out by default.
+    #[must_use]
+    pub const fn with_trashed(mut self) -> Self {
+        if self.trashed.is_some() {
+            self.trashed = Some(Trashed::With);
+        }
+
+        self
+    }
+
+    /// Only include soft deleted records, whose `deleted_at` column is set.
+    #[must_use]
+    pub fn only_trashed(mut self) -> Self {
+        if self.trashed.is_none() {
+            return self.where_not_null(DELETED_AT);
+        }
+
+        self.trashed = Some(Trashed::Only);
+        self
+    }
+
+    /// Allow bulk operations like [`soft_delete`](Self::soft_delete) to run without any where clauses, affecting every row in the table.
+    #[must_use]
+    pub const fn unconstrained(mut self) -> Self {
+        self.unconstrained = true;
+        self
+    }
+
+    /// Lock the selected rows with `FOR UPDATE` until the transaction ends, so other transactions can't change (or lock) them in the meantime.
+    /// The query must run in a transaction, either with [`get_in`](Self::get_in) or inside a [`transaction`](crate::transaction) closure. Not supported on `SQLite`.
+    #[must_use]
+    pub const fn lock_for_update(mut self) -> Self {
+        self.lock = true;
+        self
+    }
+
+    /// Skip the rows another transaction already locked instead of waiting for them, like when several workers pick jobs from a queue.
+    /// Only applies to rows locked with [`lock_for_update`](Self::lock_for_update). Requires `MySQL` 8 or Postgres.
+    #[must_use]
+    pub const fn skip_locked(mut self) -> Self {
+        self.lock_wait = LockWait::SkipLocked;
+        self
+    }
+
+    /// Fail with [`Error::LockNotAvailable`] instead of waiting when another transaction already locked one of the rows.
+    /// Only applies to rows locked with [`lock_for_update`](Self::lock_for_update). Requires `MySQL` 8 or Postgres.
+    #[must_use]
+    pub const fn nowait(mut self) -> Self {
+        self.lock_wait = LockWait::NoWait;
+        self
+    }
+
+    /// Add an inner join to the query.
+    #[must_use]
+    pub fn join<Op: Into<Operator>>(
+        mut self,
+        column: &str,
+        first: &str,
+        op: Op,
+        second: &str,
+    ) -> Self {
+        self.join.push(Join {
+            operator: op.into(),
+            first: first.to_string(),
+            column: column.to_string(),
+            r#type: JoinType::Inner,
+            second: second.to_string(),
+        });
+
+        self
+    }
+
+    /// Add an "order by" clause to the query.
+    #[must_use]
+    pub fn order_by<Dir: Into<Direction>>(mut self, column: &str, direction: Dir) -> Self {
+        self.order.push(Order {
+            column: column.to_string(),
+            direction: direction.into(),
+        });
+
+        self
+    }
+
+    /// Add a raw "order by" clause to the query, like `FIELD(status, 'draft', 'published')`.
+    #[must_use]
+    pub fn order_by_raw<Dir: Into<Direction>>(mut self, expr: Expr, direction: Dir) -> Self {
+        self.order.push(Order {
+            column: expr.into_sql(),
+            direction: direction.into(),
+        });
+
+        self
+    }
+
+    /// Select a raw expression, like `COUNT(*) AS total`. Once an expression is selected, only the selected expressions are returned instead of every column.
+    #[must_use]
+    pub fn select_raw(mut self, expr: Expr) -> Self {
+        self.select.push(expr.into_sql());
+        self
+    }
+
+    /// Add a where clause written by hand, for operators the builder doesn't support (like Postgres' `&&` or `MySQL`'s `SOUNDS LIKE`).
+    /// The clause is combined with the others like any other where clause, and its values are bound in place of its `?` placeholders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clause doesn't bind exactly one value for each of its placeholders.
+    #[must_use]
+    pub fn where_custom(self, r#fn: impl FnOnce(Clause) -> Clause) -> Self {
+        self.push_custom(r#fn(Clause::default()), Boolean::And)
+    }
+
+    /// Add an "or where" clause written by hand. See [`where_custom`](Self::where_custom).
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is the first where clause, or if the clause doesn't bind exactly one value for each of its placeholders.
+    #[must_use]
+    pub fn or_where_custom(self, r#fn: impl FnOnce(Clause) -> Clause) -> Self {
+        assert!(
+            !self.r#where.is_empty(),
+            "Cannot use or_where_custom without a where clause."
+        );
+
+        self.push_custom(r#fn(Clause::default()), Boolean::Or)
+    }
+
+    fn push_custom(mut self, clause: Clause, boolean: Boolean) -> Self {
+        let (placeholders, bindings) = clause.arity();
+        assert_eq!(
+            placeholders, bindings,
+            "The custom where clause has {placeholders} placeholders, but {bindings} values were bound."
+        );
+
+        self.r#where.push(WhereClause::Custom(clause, boolean));
+        self
+    }
+
+    /// Logically group a set of where clauses.
+    #[must_use]
+    pub fn where_group(mut self, r#fn: impl FnOnce(Self) -> Self) -> Self {
+        let builder = r#fn(Self::new(self.table.clone()).described_by(self.columns));
+
+        self.invalid |= builder.invalid;
+        self.r#where
+            .push(WhereClause::Group(builder.r#where, Boolean::And));
+
+        self
+    }
+
+    /// Get the SQL representation of the query.
+    #[must_use]
+    pub fn to_sql(&self, r#type: Type) -> String {
+        let sql = match r#type {
+            Type::Update => String::new(), // handled in update()
+            Type::Delete => format!("DELETE FROM {}", self.table),
+            Type::Select => format!(
+                "SELECT {}{} FROM {}",
+                self.hints_sql(),
+                self.columns_sql(),
+                self.table_sql()
+            ),
+            Type::Count => format!(
+                "SELECT {}COUNT(*) FROM {}",
+                self.hints_sql(),
+                self.table_sql()
+            ),
+        };
+
+        let lock = match r#type {
+            Type::Select => self.lock_sql(),
+            _ => String::new(),
+        };
+
+        sql + &self.clauses_sql() + &lock
+    }
+
+    /// The row lock taken by the query, if it has one.
+    fn lock_sql(&self) -> String {
+        if !self.lock {
+            return String::new();
+        }
+
+        match self.lock_wait {
+            LockWait::Wait => " FOR UPDATE".to_string(),
+            LockWait::SkipLocked => " FOR UPDATE SKIP LOCKED".to_string(),
+            LockWait::NoWait => " FOR UPDATE NOWAIT".to_string(),
+        }
+    }
+
+    /// The columns (and expressions) selected, or `*` for every column.
+    fn columns_sql(&self) -> String {
+        let columns = self
+            .distinct
+            .iter()
+            .map(|column| format!("DISTINCT {column}"))
+            .chain(self.select.iter().cloned())
+            .join(", ");
+
+        if columns.is_empty() {
+            "*".to_string()
+        } else {
+            columns
+        }
+    }
+
+    /// The hints rendered after `SELECT`, followed by a space. Only `MySQL` supports them.
+    fn hints_sql(&self) -> String {
+        if Dialect::default() != Dialect::Mysql {
+            return String::new();
+        }
+
+        self.hints.iter().map(|hint| format!("{hint} ")).join("")
+    }
+
+    /// The table selected from, along with its index hints.
+    fn table_sql(&self) -> String {
+        if Dialect::default() != Dialect::Mysql || self.index_hints.is_empty() {
+            return self.table.clone();
+        }
+
+        format!("{} {}", self.table, self.index_hints.iter().join(" "))
+    }
+
+    fn clauses_sql(&self) -> String {
+        self.joins_sql() + &self.filters_sql()
+    }
+
+    fn joins_sql(&self) -> String {
+        self.join
+            .iter()
+            .map(|join| {
+                format!(
+                    " {} {} ON {} {} {}",
+                    join.r#type, join.column, join.first, join.operator, join.second
+                )
+            })
+            .join("")
+    }
+
+    /// The where, order, limit and offset clauses, which follow the joins.
+    fn filters_sql(&self) -> String {
+        let mut sql = String::new();
+
+        let mut r#where = String::new();
+        for (i, where_clause) in self.r#where.iter().enumerate() {
+            r#where.push_str(&where_clause.to_sql(i != self.r#where.len() - 1));
+        }
+
+        let mut scopes = vec![];
+        if let Some(Tenancy::Scoped { column, .. }) = &self.tenancy {
+            scopes.push(format!("{}.{column} = ?", self.table));
+        }
+        match self.trashed {
+            Some(Trashed::Without) => scopes.push(format!("{}.{DELETED_AT} IS NULL", self.table)),
+            Some(Trashed::Only) => scopes.push(format!("{}.{DELETED_AT} IS NOT NULL", self.table)),
+            Some(Trashed::With) | None => {}
+        }
+
+        if !scopes.is_empty() {
+            // the user's clauses are grouped, so an `or_where` can't escape the scopes
+            write!(sql, " WHERE {}", scopes.join(" AND ")).unwrap();
+
+            if !r#where.is_empty() {
+                write!(sql, " AND ({where})").unwrap();
+            }
+        } else if !r#where.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&r#where);
+        }
+
+        if !self.order.is_empty() {
+            sql.push_str(" ORDER BY ");
+
+            sql.push_str(
+                &self
+                    .order
+                    .iter()
+                    .map(|order| format!("{} {}", order.column, order.direction))
+                    .join(", "),
+            );
+        }
+
+        if let Some(take) = self.limit {
+            write!(sql, " LIMIT {take}").unwrap();
+        }
+
+        if let Some(skip) = self.offset {
+            write!(sql, " OFFSET {skip}").unwrap();
+        }
+
+        sql
+    }
+
+    /// Get the current query value bindings.
+    #[must_use]
+    pub fn get_bindings(&self) -> Vec<Value> {
+        self.bindings()
+            .into_iter()
+            .map(redaction::unmark)
+            .collect()
+    }
+
+    /// The query's bindings, with the values of sensitive columns still marked for [redaction](crate::redaction).
+    fn bindings(&self) -> Vec<Value> {
+        let tenant = match &self.tenancy {
+            Some(Tenancy::Scoped { tenant, .. }) => Some(tenant.clone()),
+            _ => None,
+        };
+
+        tenant
+            .into_iter()
+            .chain(self.r#where.iter().flat_map(WhereClause::get_bindings))
+            .collect()
+    }
+
+    /// Retrieve the number of records that match the query constraints.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn count(self) -> Result<u64, Error> {
+        self.count_rows().await
+    }
+
+    async fn count_rows(&self) -> Result<u64, Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+
+        let values = self
+            .read(&self.to_sql(Type::Count), self.bindings())
+            .await?;
+
+        values
+            .into_iter()
+            .next()
+            .and_then(scalar)
+            .and_then(|count| count.as_u64())
+            .ok_or_else(|| {
+                Error::Serialization(rbs::value::ext::Error::Syntax(
+                    "Failed to parse count value".to_string(),
+                ))
+            })
+    }
+
+    /// Retrieve the sum of the values of a given column, as a `T` (like `u64` for integer columns, or `f64` for decimals).
+    /// Returns `T::default()` (zero) if no records match the query constraints.
+    ///
+    /// Sums the database returns as strings (like `MySQL`'s `DECIMAL` or Postgres' `NUMERIC`) are parsed, so use `String` as `T` to keep every digit of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the sum cannot be deserialized into `T`.
+    pub async fn sum<T: DeserializeOwned + Default>(self, column: &str) -> Result<T, Error> {
+        let sum = self.aggregate("SUM", column).await?;
+
+        Ok(sum.map(number).transpose()?.unwrap_or_default())
+    }
+
+    /// Retrieve the average of the values of a given column. Returns `None` if no records match the query constraints.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the average isn't a number.
+    pub async fn avg(self, column: &str) -> Result<Option<f64>, Error> {
+        let avg = self.aggregate("AVG", column).await?;
+
+        avg.map(number).transpose()
+    }
+
+    /// Retrieve the minimum value of a given column. Returns `None` if no records match the query constraints.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the value cannot be deserialized into `T`.
+    pub async fn min<T: DeserializeOwned>(self, column: &str) -> Result<Option<T>, Error> {
+        let min = self.aggregate("MIN", column).await?;
+
+        Ok(min.map(rbs::from_value).transpose()?)
+    }
+
+    /// Retrieve the maximum value of a given column. Returns `None` if no records match the query constraints.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a connection to the database cannot be established, or if the value cannot be deserialized into `T`.
+    pub async fn max<T: DeserializeOwned>(self, column: &str) -> Result<Option<T>, Error> {
+        let max = self.aggregate("MAX", column).await?;
+
+        Ok(max.map(rbs::from_value).transpose()?)
+    }
+
+    /// Execute the query and return the first result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn first<M: Model>(mut self) -> Result<Option<M>, Error> {
+        self.limit = Some(1);
+        let table = self.table.clone();
+        let values = operation("first", &table, self.fetch::<M>()).await?;
+
+        Ok(values.into_iter().next())
+    }
+
+    /// Execute the query and return its only result.
+    ///
+    /// At most two rows are retrieved, which is enough to tell whether more than one record matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if no records match the query, [`Error::MultipleRecordsFound`] if more than one does,
+    /// or an error if the query fails or a connection to the database cannot be established.
+    pub async fn sole<M: Model>(mut self) -> Result<M, Error> {
+        self.limit = Some(2);
+
+        sole(self.get::<M>().await?)
+    }
+
+    /// Execute the query and return the results.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownRelation`] if a relationship to eager load isn't one of the model's, or an error if the query fails or a connection to the database cannot be established.
+    pub async fn get<M: Model>(self) -> Result<Vec<M>, Error> {
+        let table = self.table.clone();
+
+        operation("get", &table, self.fetch()).await
+    }
+
+    async fn fetch<M: Model>(self) -> Result<Vec<M>, Error> {
+        self.ensure_relations::<M>()?;
+        let rows = self.run_select().await?;
+
+        self.hydrate(rows).await
+    }
+
+    /// Retrieve the `page`th page of `per_page` results (starting at page 1), along with the total number of matching records, counted by a separate query.
+    /// Any limit or offset the query has is replaced by the page's.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuery`] if `per_page` is 0, or any of the errors [`get`](Self::get) returns.
+    pub async fn paginate<M: Model>(
+        mut self,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Paginator<M>, Error> {
+        if per_page == 0 {
+            return Err(Error::InvalidQuery);
+        }
+        let page = page.max(1);
+
+        // the count can't be ordered on postgres, and shouldn't be limited anywhere
+        let order = std::mem::take(&mut self.order);
+        self.limit = None;
+        self.offset = None;
+        let total = self.count_rows().await?;
+        self.order = order;
+
+        let skip = (page - 1).saturating_mul(per_page);
+        let data = if u64::try_from(skip).is_ok_and(|skip| skip < total) {
+            self.limit(per_page).offset(skip).get().await?
+        } else {
+            vec![]
+        };
+
+        Ok(Paginator::new(data, total, per_page, page))
+    }
+
+    /// Retrieve up to `per_page` models ordered by primary key, starting after the one whose key is `cursor` (or at the first one without a cursor).
+    /// The page holds the cursor to pass back for the next one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidQuery`] if `per_page` is 0, or if the query is already ordered, limited or offset.
+    /// Returns any of the errors [`get`](Self::get) returns.
+    pub async fn cursor_paginate<M: Model>(
+        self,
+        per_page: usize,
+        cursor: Option<M::PrimaryKey>,
+    ) -> Result<CursorPaginator<M>, Error> {
+        let paged = !self.order.is_empty() || self.limit.is_some() || self.offset.is_some();
+        if per_page == 0 || paged {
+            return Err(Error::InvalidQuery);
+        }
+
+        let data = self
+            .when_some(cursor, |query, key| query.r#where(M::PRIMARY_KEY, ">", key))
+            .order_by(M::PRIMARY_KEY, "asc")
+            .limit(per_page.saturating_add(1))
+            .get()
+            .await?;
+
+        Ok(CursorPaginator::new(data, per_page))
+    }
+
+    /// Execute the query in `transaction` and return the results. Rows locked with [`lock_for_update`](Self::lock_for_update) stay locked until the transaction ends.
+    ///
+    /// Relationships are still eager loaded outside of the transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LockNotAvailable`] if the query uses [`nowait`](Self::nowait) and one of the rows is already locked,
+    /// or any of the errors [`get`](Self::get) returns.
+    pub async fn get_in<M: Model>(self, transaction: &mut Transaction) -> Result<Vec<M>, Error> {
+        self.ensure_relations::<M>()?;
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        self.ensure_lock(true)?;
+        let (sql, bindings) = (self.to_sql(Type::Select), self.bindings());
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), "Executing SELECT SQL query in a transaction");
+
+        let rows = transaction.fetch(&sql, bindings).await?;
+
+        self.hydrate(rows).await
+    }
+
+    fn ensure_relations<M: Model>(&self) -> Result<(), Error> {
+        M::ensure_relations(
+            self.eager_load
+                .iter()
+                .chain(self.exists.iter().map(|exists| &exists.relation))
+                .map(String::as_str),
+        )
+    }
+
+    /// Deserialize the models from their rows, and load the relationships the query asked for.
+    pub(crate) async fn hydrate<M: Model>(self, rows: Vec<Value>) -> Result<Vec<M>, Error> {
+        let mut models = rows
+            .into_iter()
+            .map(value::from::<M>)
+            .collect::<Result<Vec<M>, rbs::Error>>()?;
+
+        if models.is_empty() || (self.eager_load.is_empty() && self.exists.is_empty()) {
+            return Ok(models);
+        }
+
+        let model = M::default();
+        for Exists {
+            relation,
+            constraint,
+        } in self.exists
+        {
+            let query =
+                model.exists_query(&relation, models.iter().collect::<Vec<&M>>().as_slice());
+            let query = match constraint {
+                Some(constraint) => constraint(query),
+                None => query,
+            };
+
+            let keys = query
+                .get_rows()
+                .await?
+                .into_iter()
+                .filter_map(|row| row.into_values().next())
+                .collect::<Vec<_>>();
+
+            for model in &mut models {
+                model.fill_exists(&relation, &keys)?;
+            }
+        }
+
+        for relation in self.eager_load {
+            tracing::trace!(
+                "Eager loading {relation} relation for {} models",
+                models.len()
+            );
+
+            let rows = model
+                .eager_load(&relation, models.iter().collect::<Vec<&M>>().as_slice())
+                .get_rows()
+                .await?;
+
+            for model in &mut models {
+                model.fill_relation(&relation, &rows)?;
+            }
+        }
+
+        Ok(models)
+    }
+
+    /// Execute the query and hydrate the first result into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if the row cannot be hydrated into `T`, or if a connection to the database cannot be established.
+    pub async fn first_as<T: FromRow>(mut self) -> Result<Option<T>, Error> {
+        self.limit = Some(1);
+        let rows = self.get_as::<T>().await?;
+
+        Ok(rows.into_iter().next())
+    }
+
+    /// Execute the query and hydrate the results into `T`, which doesn't need to be a model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, if a row cannot be hydrated into `T`, or if a connection to the database cannot be established.
+    pub async fn get_as<T: FromRow>(self) -> Result<Vec<T>, Error> {
+        Ok(self
+            .run_select()
+            .await?
+            .into_iter()
+            .map(value::from::<T>)
+            .collect::<Result<Vec<T>, rbs::Error>>()?)
+    }
+
+    /// Execute the query and return the results as a vector of rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub(crate) async fn get_rows(&self) -> Result<Vec<HashMap<String, Value>>, Error> {
+        let values = self
+            .run_select()
+            .await?
+            .into_iter()
+            .map(|v| {
+                let Value::Map(map) = v else { unreachable!() };
+
+                map.into_iter()
+                    .map(|(k, v)| (k.into_string().unwrap_or_else(|| unreachable!()), v))
+                    .collect()
+            })
+            .collect();
+
+        Ok(values)
+    }
+
+    /// Ask the database how it would execute the query, using the same bindings it would run with.
+    ///
+    /// Note that [`Explain::Analyze`] actually runs the query to collect its timings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unsupported`] for plans `SQLite` can't produce (it only has [`Explain::Plan`]),
+    /// or an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn explain(self, kind: Explain) -> Result<QueryPlan, Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        let (sql, bindings) = (kind.prefix(&self.to_sql(Type::Select))?, self.bindings());
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing EXPLAIN SQL query");
+
+        let rows = self.read(&sql, bindings).await?;
+
+        Ok(QueryPlan::from(rows))
+    }
+
+    /// Insert a new record into the database. Returns the ID of the inserted record, if applicable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn insert<Id: for<'de> serde::Deserialize<'de>, T: Into<Columns> + Send>(
+        &self,
+        columns: T,
+    ) -> Result<Id, Error> {
+        self.ensure_writable()?;
+
+        let (sql, bindings) = self.insert_sql(columns)?;
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing INSERT SQL query");
+
+        let result = connection::exec(&self.table, &sql, bindings, self.options).await?;
+
+        Ok(rbs::from_value(result.last_insert_id)?)
+    }
+
+    /// Update records in the database. Returns the number of affected rows.
+    /// On `MySQL`, the query may be ordered and limited (like `ORDER BY created_at LIMIT 100`), or join other tables, but not both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unsupported`] if the query is ordered, limited or joined on other databases, or has an offset.
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn update<T: Into<Columns> + Send>(self, values: T) -> Result<u64, Error> {
+        self.ensure_writable()?;
+
+        let (sql, bindings) = self.update_sql(values)?;
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing UPDATE SQL query");
+
+        crate::identity_map::forget_table(&self.table);
+
+        connection::exec(&self.table, &sql, bindings, self.options)
+            .await
+            .map(|r| r.rows_affected)
+    }
+
+    /// Delete records from the database. Returns the number of affected rows.
+    /// On `MySQL`, the query may be ordered and limited (like `ORDER BY created_at LIMIT 100`), or join other tables, but not both.
+    ///
+    /// Records of models marked with `soft_deletes` are soft deleted instead, by setting their `deleted_at` column. Use [`force_delete`](Self::force_delete) to remove them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unsupported`] if the query is ordered, limited or joined on other databases, or has an offset.
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn delete(self) -> Result<u64, Error> {
+        if self.trashed.is_some() {
+            return self.update(vec![(DELETED_AT, DateTime::now())]).await;
+        }
+
+        self.force_delete().await
+    }
+
+    /// Delete records from the database, even on models marked with `soft_deletes`. Returns the number of affected rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unsupported`] if the query is ordered, limited or joined on other databases, or has an offset.
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn force_delete(self) -> Result<u64, Error> {
+        self.ensure_writable()?;
+
+        let (sql, bindings) = self.delete_sql()?;
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing DELETE SQL query");
+
+        crate::identity_map::forget_table(&self.table);
+
+        connection::exec(&self.table, &sql, bindings, self.options)
+            .await
+            .map(|r| r.rows_affected)
+    }
+
+    /// Delete the matching records, along with the rows of the joined `tables` they were matched with, in a single statement.
+    /// Returns the number of affected rows, across every table.
+    ///
+    /// This is `MySQL`'s multi-table form (`DELETE orders, order_items FROM orders INNER JOIN order_items ON ...`), useful to purge child rows alongside their parents.
+    /// It can't soft delete, so it isn't available for models marked with `soft_deletes`: remove their records with [`force_delete`](Self::force_delete) instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidQuery`] if one of the `tables` isn't joined, an [`Error::Unsupported`] for models marked with `soft_deletes`, on other databases
+    /// or if the query is ordered or limited, or an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn delete_with_join(self, tables: &[&str]) -> Result<u64, Error> {
+        self.ensure_writable()?;
+        if self.trashed.is_some() {
+            return Err(Error::Unsupported(
+                "multi-table deletes of soft deleting models (use `force_delete`)",
+            ));
+        }
+
+        let (sql, bindings) = self.delete_sql_for(Dialect::default(), tables)?;
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing DELETE SQL query");
+
+        crate::identity_map::forget_table(&self.table);
+
+        connection::exec(&self.table, &sql, bindings, self.options)
+            .await
+            .map(|r| r.rows_affected)
+    }
+
+    /// Delete records from the database, returning the deleted models.
+    ///
+    /// On Postgres and `SQLite`, this is a single `DELETE ... RETURNING *`. `MySQL` doesn't support `RETURNING` (and `MariaDB` can't be told apart from it),
+    /// so the matching rows are selected and locked with `FOR UPDATE` first, then deleted by primary key in the same transaction.
+    ///
+    /// Records of models marked with `soft_deletes` are soft deleted instead (see [`update_returning`](Self::update_returning)), so the returned models have their `deleted_at` set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn delete_returning<M: Model>(self) -> Result<Vec<M>, Error> {
+        self.ensure_writable()?;
+        if self.trashed.is_some() {
+            return self
+                .update_returning(vec![(DELETED_AT, DateTime::now())])
+                .await;
+        }
+
+        crate::identity_map::forget_table(&self.table);
+        let rows = if Dialect::default().supports_returning() {
+            let (sql, bindings) = self.delete_sql()?;
+            self.fetch_returning(&sql, bindings).await?
+        } else {
+            self.returning_in_transaction(Self::delete_sql, false)
+                .await?
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(value::from::<M>)
+            .collect::<Result<Vec<M>, rbs::Error>>()?)
+    }
+
+    /// Update records in the database, returning the updated models.
+    ///
+    /// On Postgres and `SQLite`, this is a single `UPDATE ... RETURNING *`. On `MySQL`, the matching rows are selected and locked with `FOR UPDATE` first,
+    /// then updated by primary key and selected again, all in the same transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn update_returning<M: Model, T: Into<Columns> + Send>(
+        self,
+        values: T,
+    ) -> Result<Vec<M>, Error> {
+        self.ensure_writable()?;
+
+        crate::identity_map::forget_table(&self.table);
+        let rows = if Dialect::default().supports_returning() {
+            let (sql, bindings) = self.update_sql(values)?;
+            self.fetch_returning(&sql, bindings).await?
+        } else {
+            self.returning_in_transaction(move |by_key| by_key.update_sql(values), true)
+                .await?
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(value::from::<M>)
+            .collect::<Result<Vec<M>, rbs::Error>>()?)
+    }
+
+    /// Insert the records matched by `source` into this query's table, with a single `INSERT ... SELECT`. Returns the number of inserted rows.
+    ///
+    /// Columns are matched by name: the columns both models have are copied, or every column of whichever side is a model.
+    /// If neither is, or if `source` selects its own columns, the selected columns are inserted in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidQuery`] if this query has any clauses, or if it's scoped to a tenant and `source` isn't scoped to the same one,
+    /// or an error if the query fails or a connection to the database cannot be established.
+    pub async fn insert_from_select(&self, source: Self) -> Result<u64, Error> {
+        self.ensure_writable()?;
+
+        let (sql, bindings) = self.insert_select_sql(source)?;
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing INSERT SELECT SQL query");
+
+        connection::exec(&self.table, &sql, bindings, self.options)
+            .await
+            .map(|r| r.rows_affected)
+    }
+
+    /// Delete records in batches of `batch_size`, ordered by primary key, until no matching records are left. Returns the total number of affected rows.
+    ///
+    /// Each batch is its own statement, so locks are only held for a batch at a time, and a huge delete doesn't end up as a single transaction.
+    ///
+    /// Records of models marked with `soft_deletes` are soft deleted instead, leaving out the ones that already are.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkedWriteError`] holding the number of rows deleted by the previous batches if the query has joins, a limit or an offset,
+    /// if a batch fails, or if a connection to the database cannot be established.
+    pub async fn delete_in_chunks(self, batch_size: usize) -> Result<u64, ChunkedWriteError> {
+        self.delete_in_chunks_with_progress(batch_size, |_, _| {})
+            .await
+    }
+
+    /// Like [`delete_in_chunks`](Self::delete_in_chunks), calling `progress` after every batch with the number of rows it deleted, and the total so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkedWriteError`] holding the number of rows deleted by the previous batches if the query has joins, a limit or an offset,
+    /// if a batch fails, or if a connection to the database cannot be established.
+    pub async fn delete_in_chunks_with_progress<P: FnMut(u64, u64) + Send>(
+        mut self,
+        batch_size: usize,
+        progress: P,
+    ) -> Result<u64, ChunkedWriteError> {
+        let (sql, bindings) = if self.trashed.is_some() {
+            // records that are already trashed would keep matching every batch
+            self.trashed = Some(Trashed::Without);
+            self.chunked_update_sql(vec![(DELETED_AT, DateTime::now())], batch_size)?
+        } else {
+            self.chunked_delete_sql(batch_size)?
+        };
+
+        self.write_in_chunks(&sql, bindings, progress).await
+    }
+
+    /// Update records in batches of `batch_size`, ordered by primary key, until no matching records are left. Returns the total number of affected rows.
+    ///
+    /// Every batch runs the same statement, so `values` must make the records stop matching the query (like setting the `status` it filters on),
+    /// otherwise the same records are updated again and again.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkedWriteError`] holding the number of rows updated by the previous batches if the query has joins, a limit or an offset,
+    /// if a batch fails, or if a connection to the database cannot be established.
+    pub async fn update_in_chunks<T: Into<Columns> + Send>(
+        self,
+        values: T,
+        batch_size: usize,
+    ) -> Result<u64, ChunkedWriteError> {
+        self.update_in_chunks_with_progress(values, batch_size, |_, _| {})
+            .await
+    }
+
+    /// Like [`update_in_chunks`](Self::update_in_chunks), calling `progress` after every batch with the number of rows it updated, and the total so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChunkedWriteError`] holding the number of rows updated by the previous batches if the query has joins, a limit or an offset,
+    /// if a batch fails, or if a connection to the database cannot be established.
+    pub async fn update_in_chunks_with_progress<
+        T: Into<Columns> + Send,
+        P: FnMut(u64, u64) + Send,
+    >(
+        mut self,
+        values: T,
+        batch_size: usize,
+        progress: P,
+    ) -> Result<u64, ChunkedWriteError> {
+        let (sql, bindings) = self.chunked_update_sql(values, batch_size)?;
+
+        self.write_in_chunks(&sql, bindings, progress).await
+    }
+
+    /// Soft delete every matching record in a single `UPDATE`, setting its `deleted_at` column to the current time. Returns the number of affected rows.
+    ///
+    /// Like other mass updates, the models are never retrieved, so nothing that runs when saving a single model (like timestamps or validation) is applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unconstrained`] error if the query has no where clauses and wasn't marked as [`unconstrained`](Self::unconstrained),
+    /// or an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn soft_delete(self) -> Result<u64, Error> {
+        self.ensure_constrained()?;
+
+        self.update(vec![(DELETED_AT, DateTime::now())]).await
+    }
+
+    /// Restore every matching soft deleted record in a single `UPDATE`, clearing its `deleted_at` column. Returns the number of affected rows.
+    ///
+    /// Like other mass updates, the models are never retrieved, so nothing that runs when saving a single model (like timestamps or validation) is applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Unconstrained`] error if the query has no where clauses and wasn't marked as [`unconstrained`](Self::unconstrained),
+    /// or an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn restore(mut self) -> Result<u64, Error> {
+        self.ensure_constrained()?;
+
+        if self.trashed.is_some() {
+            self.trashed = Some(Trashed::Only);
+        }
+
+        self.update(vec![(DELETED_AT, Value::Null)]).await
+    }
+
+    /// Run a truncate statement on the table. Returns the number of affected rows.
+    ///
+    /// Since a truncate can't be limited to a single tenant, it is rejected on models scoped to one unless tenancy is bypassed with [`without_tenancy`](Self::without_tenancy).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if a connection to the database cannot be established.
+    pub async fn truncate(self) -> Result<u64, Error> {
+        self.ensure_writable()?;
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+
+        if matches!(self.tenancy, Some(Tenancy::Scoped { .. })) {
+            return Err(Error::InvalidQuery);
+        }
+
+        let sql = format!("TRUNCATE TABLE {}", self.table);
+
+        tracing::debug!(sql = sql.as_str(), timeout = ?self.options.timeout, "Executing TRUNCATE SQL query");
+
+        crate::identity_map::forget_table(&self.table);
+
+        connection::exec(&self.table, &sql, vec![], self.options)
+            .await
+            .map(|r| r.rows_affected)
+    }
+}
+
+impl Builder {
+    pub(crate) fn insert_sql<T: Into<Columns>>(
+        &self,
+        columns: T,
+    ) -> Result<(String, Vec<Value>), Error> {
+        let values = self.insert_columns(columns)?;
+
+        Ok((
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                self.table,
+                values.iter().map(|(column, _)| column).join(", "),
+                values
+                    .iter()
+                    .map(|(_, value)| expr::placeholder(value))
+                    .join(", ")
+            ),
+            values
+                .into_iter()
+                .map(|(_, value)| value)
+                .filter(expr::is_bound)
+                .collect(),
+        ))
+    }
+
+    /// Mark `value` for [redaction](crate::redaction) if it's bound to one of the model's sensitive columns.
+    fn bound(&self, column: &str, value: Value) -> Value {
+        let name = column.rsplit('.').next().unwrap_or(column);
+
+        if self
+            .columns
+            .iter()
+            .any(|def| def.sensitive && def.name == name)
+        {
+            redaction::mark(value)
+        } else {
+            value
+        }
+    }
+
+    /// The columns to insert, along with the tenant's column if the query is scoped to one.
+    fn insert_columns<T: Into<Columns>>(&self, columns: T) -> Result<Vec<(String, Value)>, Error> {
+        if self.limit.is_some()
+            || !self.join.is_empty()
+            || !self.order.is_empty()
+            || !self.r#where.is_empty()
+        {
+            return Err(Error::InvalidQuery);
+        }
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+
+        // an incrementing key that hasn't been set is left for the database to generate, since `SQLite` (and Postgres) would store the `0` as it is
+        let increments = self
+            .columns
+            .iter()
+            .find(|def| def.primary_key && def.increments)
+            .map(|def| def.name);
+
+        let mut values: Vec<(String, Value)> = columns
+            .into()
+            .0
+            .into_iter()
+            .filter(|(column, value)| {
+                Some(column.as_str()) != increments || value.as_u64() != Some(0)
+            })
+            .map(|(column, value)| {
+                let value = self.bound(&column, value);
+                (column, value)
+            })
+            .collect();
+
+        if let Some(Tenancy::Scoped { column, tenant }) = &self.tenancy {
+            match values.iter_mut().find(|(name, _)| name == column) {
+                Some((_, value)) => *value = tenant.clone(),
+                None => values.push(((*column).to_string(), tenant.clone())),
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// An insert that updates the `update` columns of the stored row instead when one with the same `unique` columns already exists.
+    pub(crate) fn upsert_sql<T: Into<Columns>>(
+        &self,
+        columns: T,
+        unique: &[&str],
+        update: &[&str],
+    ) -> Result<(String, Vec<Value>), Error> {
+        if unique.is_empty() {
+            return Err(Error::InvalidQuery);
         }
+        let (sql, bindings) = self.insert_sql(columns)?;
 
-        if !self.order.is_empty() {
-            sql.push_str(" ORDER BY ");
+        // an empty update would skip the conflicting row, so it couldn't be returned
+        let update = if update.is_empty() {
+            &unique[..1]
+        } else {
+            update
+        };
 
-            sql.push_str(
-                &self
-                    .order
+        let sql = if Dialect::default() == Dialect::Mysql {
+            format!(
+                "{sql} ON DUPLICATE KEY UPDATE {}",
+                update
                     .iter()
-                    .map(|order| format!("{} {}", order.column, order.direction))
-                    .join(", "),
-            );
-        }
+                    .map(|column| format!("{column} = VALUES({column})"))
+                    .join(", ")
+            )
+        } else {
+            format!(
+                "{sql} ON CONFLICT ({}) DO UPDATE SET {}",
+                unique.join(", "),
+                update
+                    .iter()
+                    .map(|column| format!("{column} = EXCLUDED.{column}"))
+                    .join(", ")
+            )
+        };
 
-        if let Some(take) = self.limit {
-            sql.push_str(&format!(" LIMIT {take}"));
-        }
+        Ok((sql, bindings))
+    }
 
-        if let Some(skip) = self.offset {
-            sql.push_str(&format!(" OFFSET {skip}"));
+    pub(crate) fn insert_select_sql(
+        &self,
+        mut source: Self,
+    ) -> Result<(String, Vec<Value>), Error> {
+        if self.limit.is_some()
+            || !self.join.is_empty()
+            || !self.order.is_empty()
+            || !self.r#where.is_empty()
+        {
+            return Err(Error::InvalidQuery);
+        }
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+
+        // rows copied into a tenant's table must all belong to that tenant
+        if let Some(Tenancy::Scoped { tenant, .. }) = &self.tenancy {
+            if !matches!(&source.tenancy, Some(Tenancy::Scoped { tenant: source_tenant, .. }) if source_tenant == tenant)
+            {
+                return Err(Error::InvalidQuery);
+            }
         }
 
-        sql
-    }
+        let columns = if source.select.is_empty() && source.distinct.is_none() {
+            match (self.columns, source.columns) {
+                (target, []) => target.iter().map(|column| column.name).collect(),
+                ([], selected) => selected.iter().map(|column| column.name).collect(),
+                (target, selected) => target
+                    .iter()
+                    .map(|column| column.name)
+                    .filter(|name| selected.iter().any(|column| column.name == *name))
+                    .collect(),
+            }
+        } else {
+            vec![]
+        };
 
-    /// Get the current query value bindings.
-    #[must_use]
-    pub fn get_bindings(&self) -> Vec<Value> {
-        self.r#where
+        if columns.is_empty() {
+            let (select, bindings) = source.select_sql()?;
+            return Ok((format!("INSERT INTO {} {select}", self.table), bindings));
+        }
+
+        source.select = columns
             .iter()
-            .flat_map(WhereClause::get_bindings)
-            .collect()
+            .map(|column| format!("{}.{column}", source.table))
+            .collect();
+        let (select, bindings) = source.select_sql()?;
+
+        Ok((
+            format!(
+                "INSERT INTO {} ({}) {select}",
+                self.table,
+                columns.join(", ")
+            ),
+            bindings,
+        ))
     }
 
-    /// Retrieve the number of records that match the query constraints.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn count(self) -> Result<u64, Error> {
-        let mut conn = connection::get().await?;
+    pub(crate) fn select_sql(&self) -> Result<(String, Vec<Value>), Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
 
-        let values = conn
-            .get_values(&self.to_sql(Type::Count), self.get_bindings())
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok((self.to_sql(Type::Select), self.bindings()))
+    }
 
-        values.first().and_then(Value::as_u64).ok_or_else(|| {
-            Error::Serialization(rbs::value::ext::Error::Syntax(
-                "Failed to parse count value".to_string(),
-            ))
-        })
+    pub(crate) fn delete_sql(&self) -> Result<(String, Vec<Value>), Error> {
+        self.delete_sql_for(Dialect::default(), &[])
     }
 
-    /// Execute the query and return the first result.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn first<M: Model>(mut self) -> Result<Option<M>, Error> {
-        self.limit = Some(1);
-        let values = self.get::<M>().await?;
+    /// The statement soft deleting the matching records, by setting their `deleted_at` column.
+    pub(crate) fn soft_delete_sql(&self) -> Result<(String, Vec<Value>), Error> {
+        self.update_sql(vec![(DELETED_AT, DateTime::now())])
+    }
 
-        Ok(values.into_iter().next())
+    /// The statement restoring the matching soft deleted records, by clearing their `deleted_at` column.
+    pub(crate) fn restore_sql(&self) -> Result<(String, Vec<Value>), Error> {
+        self.update_sql(vec![(DELETED_AT, Value::Null)])
     }
 
-    /// Execute the query and return the results.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn get<M: Model>(self) -> Result<Vec<M>, Error> {
-        let mut models = self
-            ._get()
-            .await?
-            .into_iter()
-            .map(value::from::<M>)
-            .collect::<Result<Vec<M>, rbs::Error>>()?;
+    pub(crate) fn update_sql<T: Into<Columns>>(
+        &self,
+        values: T,
+    ) -> Result<(String, Vec<Value>), Error> {
+        self.update_sql_for(Dialect::default(), values)
+    }
 
-        if models.is_empty() || self.eager_load.is_empty() {
-            return Ok(models);
+    /// The delete statement for `dialect`, also deleting the matching rows of the joined tables listed in `also`.
+    fn delete_sql_for(
+        &self,
+        dialect: Dialect,
+        also: &[&str],
+    ) -> Result<(String, Vec<Value>), Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+
+        if also
+            .iter()
+            .any(|table| !self.join.iter().any(|join| join.column == *table))
+        {
+            return Err(Error::InvalidQuery);
         }
+        self.ensure_write_clauses(dialect)?;
 
-        let model = M::default();
-        for relation in self.eager_load {
-            tracing::trace!(
-                "Eager loading {relation} relation for {} models",
-                models.len()
-            );
+        // `MySQL` names the tables to delete from when joining others
+        let sql = if self.join.is_empty() {
+            format!("DELETE FROM {}", self.table)
+        } else {
+            format!(
+                "DELETE {} FROM {}",
+                std::iter::once(self.table.as_str())
+                    .chain(also.iter().copied())
+                    .join(", "),
+                self.table
+            )
+        };
 
-            let rows = model
-                .eager_load(&relation, models.iter().collect::<Vec<&M>>().as_slice())
-                .get_rows()
-                .await?;
+        Ok((sql + &self.clauses_sql(), self.bindings()))
+    }
 
-            for model in &mut models {
-                model.fill_relation(&relation, &rows)?;
+    fn update_sql_for<T: Into<Columns>>(
+        &self,
+        dialect: Dialect,
+        values: T,
+    ) -> Result<(String, Vec<Value>), Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        self.ensure_write_clauses(dialect)?;
+
+        self.update_statement(values, &self.joins_sql(), &self.filters_sql())
+    }
+
+    /// Reject the clauses `dialect` can't render on a delete or an update.
+    /// `MySQL` can order and limit single-table statements, or join other tables, while Postgres and `SQLite` can do neither.
+    const fn ensure_write_clauses(&self, dialect: Dialect) -> Result<(), Error> {
+        if self.offset.is_some() {
+            return Err(Error::Unsupported("`OFFSET` on a delete or an update"));
+        }
+
+        let limited = self.limit.is_some() || !self.order.is_empty();
+        let joined = !self.join.is_empty();
+
+        if matches!(dialect, Dialect::Mysql) {
+            if limited && joined {
+                return Err(Error::Unsupported(
+                    "`ORDER BY` or `LIMIT` on a delete or an update joining other tables",
+                ));
             }
+        } else if limited {
+            return Err(Error::Unsupported(
+                "`ORDER BY` or `LIMIT` on a delete or an update",
+            ));
+        } else if joined {
+            return Err(Error::Unsupported("joins on a delete or an update"));
         }
 
-        Ok(models)
+        Ok(())
     }
 
-    /// Execute the query and return the results as a vector of rows.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub(crate) async fn get_rows(&self) -> Result<Vec<HashMap<String, Value>>, Error> {
-        let values = self
-            ._get()
-            .await?
+    fn update_statement<T: Into<Columns>>(
+        &self,
+        values: T,
+        joins: &str,
+        clauses: &str,
+    ) -> Result<(String, Vec<Value>), Error> {
+        let values: Vec<(String, Value)> = values
+            .into()
+            .0
             .into_iter()
-            .map(|v| {
-                let Value::Map(map) = v else { unreachable!() };
-
-                map.into_iter()
-                    .map(|(k, v)| (k.into_string().unwrap_or_else(|| unreachable!()), v))
-                    .collect()
+            .map(|(column, value)| {
+                let value = self.bound(&column, value);
+                (column, value)
             })
             .collect();
 
-        Ok(values)
+        if let Some(column) = self
+            .columns
+            .iter()
+            .find(|column| column.immutable && values.iter().any(|(name, _)| name == column.name))
+        {
+            return Err(Error::ImmutableColumn(column.name));
+        }
+
+        Ok((
+            format!(
+                "UPDATE {}{joins} SET {} {clauses}",
+                self.table,
+                values
+                    .iter()
+                    .map(|(column, value)| format!("{column} = {}", expr::placeholder(value)))
+                    .join(", "),
+            ),
+            values
+                .into_iter()
+                .map(|(_, value)| value)
+                .filter(expr::is_bound)
+                .chain(self.bindings())
+                .collect(),
+        ))
     }
 
-    /// Insert a new record into the database. Returns the ID of the inserted record, if applicable.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn insert<Id: for<'de> serde::Deserialize<'de>, T: Into<Columns> + Send>(
+    fn chunked_delete_sql(&mut self, batch_size: usize) -> Result<(String, Vec<Value>), Error> {
+        self.ensure_writable()?;
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        let clauses = self.chunk_sql(batch_size)?;
+
+        Ok((
+            format!("DELETE FROM {}{clauses}", self.table),
+            self.bindings(),
+        ))
+    }
+
+    fn chunked_update_sql<T: Into<Columns>>(
+        &mut self,
+        values: T,
+        batch_size: usize,
+    ) -> Result<(String, Vec<Value>), Error> {
+        self.ensure_writable()?;
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        let clauses = self.chunk_sql(batch_size)?;
+
+        self.update_statement(values, "", &clauses)
+    }
+
+    /// The clauses limiting a statement to the next `batch_size` matching rows, by primary key.
+    /// `MySQL` limits the statement itself, while Postgres (which can't) limits a subquery selecting their keys.
+    fn chunk_sql(&mut self, batch_size: usize) -> Result<String, Error> {
+        if !self.join.is_empty() || self.limit.is_some() || self.offset.is_some() {
+            return Err(Error::InvalidQuery);
+        }
+
+        let key = self.primary_key;
+        self.order = vec![Order {
+            column: key.to_string(),
+            direction: Direction::Ascending,
+        }];
+        self.limit = Some(batch_size);
+        let clauses = self.clauses_sql();
+
+        if Dialect::default() != Dialect::Mysql {
+            return Ok(format!(
+                " WHERE {key} IN (SELECT {key} FROM {}{clauses})",
+                self.table
+            ));
+        }
+
+        Ok(clauses)
+    }
+
+    /// Run a chunked write until a batch affects no rows, pausing between batches.
+    async fn write_in_chunks<P: FnMut(u64, u64) + Send>(
         &self,
-        columns: T,
-    ) -> Result<Id, Error> {
-        if self.limit.is_some()
-            || !self.join.is_empty()
-            || !self.order.is_empty()
-            || !self.r#where.is_empty()
-        {
+        sql: &str,
+        bindings: Vec<Value>,
+        mut progress: P,
+    ) -> Result<u64, ChunkedWriteError> {
+        crate::identity_map::forget_table(&self.table);
+        let mut affected = 0;
+
+        loop {
+            tracing::debug!(sql = sql, bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing chunked SQL query");
+
+            let batch =
+                match connection::exec(&self.table, sql, bindings.clone(), self.options).await {
+                    Ok(result) => result.rows_affected,
+                    Err(error) => return Err(ChunkedWriteError { error, affected }),
+                };
+
+            if batch == 0 {
+                return Ok(affected);
+            }
+
+            affected += batch;
+            progress(batch, affected);
+
+            if let Some(pause) = self.chunk_pause {
+                tokio::time::sleep(pause).await;
+            }
+        }
+    }
+
+    /// Run a statement ending in `RETURNING *`, which is still a write, so it's only retried when explicitly requested.
+    async fn fetch_returning(&self, sql: &str, bindings: Vec<Value>) -> Result<Vec<Value>, Error> {
+        let sql = format!("{sql} RETURNING *");
+        let options = QueryOptions {
+            retries: Some(self.options.retries.unwrap_or_default()),
+            ..self.options
+        };
+
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing RETURNING SQL query");
+
+        connection::fetch(&self.table, &sql, bindings, options).await
+    }
+
+    /// Emulate `RETURNING` for databases that don't support it: lock the matching rows, run `statement` on them by primary key,
+    /// and return them as they were before (or, with `refetch`, as they are after) in a single transaction.
+    async fn returning_in_transaction<F>(
+        &self,
+        statement: F,
+        refetch: bool,
+    ) -> Result<Vec<Value>, Error>
+    where
+        F: FnOnce(&Self) -> Result<(String, Vec<Value>), Error> + Send,
+    {
+        let (mut sql, bindings) = self.select_sql()?;
+        // rows locked with `lock_for_update` already are, along with its wait option
+        if !self.lock {
+            sql.push_str(" FOR UPDATE");
+        }
+        let mut transaction = Transaction::begin(&self.table).await?;
+
+        let rows = transaction.fetch(&sql, bindings).await?;
+        if rows.is_empty() {
+            transaction.commit().await?;
+            return Ok(rows);
+        }
+
+        let keys = rows
+            .iter()
+            .map(|row| match &row[self.primary_key] {
+                Value::Null => Err(Error::InvalidQuery),
+                key => Ok(key.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let by_key = Self::new(self.table.clone())
+            .keyed(self.primary_key)
+            .described_by(self.columns)
+            .r#where(self.primary_key, "IN", keys);
+
+        let (sql, bindings) = statement(&by_key)?;
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing SQL query for emulated RETURNING");
+        transaction.exec(&sql, bindings).await?;
+
+        let rows = if refetch {
+            let (sql, bindings) = by_key.select_sql()?;
+            transaction.fetch(&sql, bindings).await?
+        } else {
+            rows
+        };
+
+        transaction.commit().await?;
+        Ok(rows)
+    }
+
+    const fn ensure_writable(&self) -> Result<(), Error> {
+        match self.read_only {
+            Some(table) => Err(Error::ReadOnly(table)),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject queries with a clause that couldn't be added, like one with an invalid JSON path.
+    const fn ensure_valid(&self) -> Result<(), Error> {
+        if self.invalid {
             return Err(Error::InvalidQuery);
         }
 
-        let mut conn = connection::get().await?;
-        let values: Vec<(String, Value)> = columns.into().0;
+        Ok(())
+    }
 
-        let (sql, bindings) = (
-            format!(
-                "INSERT INTO {} ({}) VALUES ({})",
-                self.table,
-                values.iter().map(|(column, _)| column).join(", "),
-                values.iter().map(|_| "?").join(", ")
-            ),
-            values.into_iter().map(|(_, value)| value).collect(),
-        );
+    fn ensure_tenant(&self) -> Result<(), Error> {
+        match self.tenancy {
+            Some(Tenancy::Missing(table)) => Err(Error::NoTenant(table)),
+            Some(Tenancy::Bypassed) => {
+                tracing::warn!(
+                    table = self.table.as_str(),
+                    "Running a query without tenant scoping"
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
 
-        tracing::debug!(sql = sql.as_str(), bindings = ?bindings, "Executing INSERT SQL query");
+    /// Reject row locks that wouldn't do anything: wait options without a lock, a lock that would be released as soon as the query ends,
+    /// or any lock on `SQLite`.
+    const fn ensure_lock(&self, in_transaction: bool) -> Result<(), Error> {
+        if !self.lock && !matches!(self.lock_wait, LockWait::Wait) {
+            return Err(Error::InvalidLock(
+                "`skip_locked` and `nowait` only apply to rows locked with `lock_for_update`",
+            ));
+        }
 
-        let result = conn
-            .exec(&sql, bindings)
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?;
+        // SQLite has no row locks, since a write locks the whole database until the transaction ends
+        if self.lock && matches!(Dialect::current(), Dialect::Sqlite) {
+            return Err(Error::Unsupported("row locks"));
+        }
 
-        Ok(rbs::from_value(result.last_insert_id)?)
+        if self.lock && !in_transaction {
+            return Err(Error::InvalidLock(
+                "rows locked outside of a transaction are released as soon as the query ends, so run it with `get_in` or in a `transaction`",
+            ));
+        }
+
+        Ok(())
     }
 
-    /// Update records in the database. Returns the number of affected rows.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn update<T: Into<Columns> + Send>(self, values: T) -> Result<u64, Error> {
-        let mut conn = connection::get().await?;
-        let values: Vec<(String, Value)> = values.into().0;
+    const fn ensure_constrained(&self) -> Result<(), Error> {
+        if self.r#where.is_empty() && !self.unconstrained {
+            return Err(Error::Unconstrained);
+        }
 
+        Ok(())
+    }
+
+    async fn aggregate(&self, function: &str, column: &str) -> Result<Option<Value>, Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
         let (sql, bindings) = (
             format!(
-                "UPDATE {} SET {} {}",
-                self.table,
-                values
-                    .iter()
-                    .map(|(column, _)| format!("{} = ?", column))
-                    .join(", "),
-                self.to_sql(Type::Update)
+                "SELECT {}{function}({column}) AS aggregate FROM {}{}",
+                self.hints_sql(),
+                self.table_sql(),
+                self.clauses_sql()
             ),
-            values
-                .iter()
-                .map(|(_, value)| value.clone())
-                .chain(self.get_bindings())
-                .collect(),
+            self.bindings(),
         );
 
-        tracing::debug!(sql = sql.as_str(), bindings = ?bindings, "Executing UPDATE SQL query");
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing aggregate SQL query");
 
-        conn.exec(&sql, bindings)
-            .await
-            .map_err(|e| Error::Database(e.to_string()))
-            .map(|r| r.rows_affected)
+        let rows = self.read(&sql, bindings).await?;
+
+        Ok(rows
+            .into_iter()
+            .next()
+            .and_then(scalar)
+            .filter(|value| !value.is_null()))
     }
 
-    /// Delete records from the database. Returns the number of affected rows.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn delete(self) -> Result<u64, Error> {
-        let mut conn = connection::get().await?;
-        let (sql, bindings) = (self.to_sql(Type::Delete), self.get_bindings());
+    async fn run_select(&self) -> Result<Vec<Value>, Error> {
+        self.ensure_valid()?;
+        self.ensure_tenant()?;
+        self.ensure_lock(connection::in_transaction())?;
+        let (sql, bindings) = (self.to_sql(Type::Select), self.bindings());
 
-        tracing::debug!(sql = sql.as_str(), bindings = ?bindings, "Executing DELETE SQL query");
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), timeout = ?self.options.timeout, "Executing SELECT SQL query");
 
-        conn.exec(&sql, bindings)
-            .await
-            .map_err(|e| Error::Database(e.to_string()))
-            .map(|r| r.rows_affected)
+        self.read(&sql, bindings).await
     }
 
-    /// Run a truncate statement on the table. Returns the number of affected rows.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the query fails, or if a connection to the database cannot be established.
-    pub async fn truncate(self) -> Result<u64, Error> {
-        let mut conn = connection::get().await?;
-        let sql = format!("TRUNCATE TABLE {}", self.table);
-
-        tracing::debug!(sql = sql.as_str(), "Executing TRUNCATE SQL query");
-
-        conn.exec(&sql, vec![])
-            .await
-            .map_err(|e| Error::Database(e.to_string()))
-            .map(|r| r.rows_affected)
+    /// Run a `SELECT` statement built from the query, on a read replica unless the query asked for the [primary](Self::on_primary).
+    async fn read(&self, sql: &str, bindings: Vec<Value>) -> Result<Vec<Value>, Error> {
+        if self.on_primary {
+            connection::fetch(&self.table, sql, bindings, self.options).await
+        } else {
+            connection::read(&self.table, sql, bindings, self.options).await
+        }
     }
 }
 
-impl Builder {
-    async fn _get(&self) -> Result<Vec<Value>, Error> {
-        let mut conn = connection::get().await?;
-        let (sql, bindings) = (self.to_sql(Type::Select), self.get_bindings());
-
-        tracing::debug!(sql = sql.as_str(), bindings = ?bindings, "Executing SELECT SQL query");
+type Constraint = Box<dyn FnOnce(Builder) -> Builder + Send + Sync>;
 
-        let values = conn
-            .get_values(&sql, bindings)
-            .await
-            .map_err(|s| Error::Database(s.to_string()))?;
+/// A relationship to check for existence, added with [`Builder::with_exists`].
+struct Exists {
+    relation: String,
+    constraint: Option<Constraint>,
+}
 
-        Ok(values)
+impl Debug for Exists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Exists")
+            .field("relation", &self.relation)
+            .field("constrained", &self.constraint.is_some())
+            .finish()
     }
 }
 
@@ -498,6 +2335,12 @@ impl From<&str> for EagerLoad {
     }
 }
 
+impl From<&[&str]> for EagerLoad {
+    fn from(value: &[&str]) -> Self {
+        Self::Multiple(value.iter().map(ToString::to_string).collect())
+    }
+}
+
 impl From<Vec<&str>> for EagerLoad {
     fn from(value: Vec<&str>) -> Self {
         Self::Multiple(value.iter().map(ToString::to_string).collect())
@@ -530,6 +2373,12 @@ impl<T: Serialize> From<Vec<(&str, T)>> for Columns {
         )
     }
 }
+impl<T: Serialize, const N: usize> From<[(&str, T); N]> for Columns {
+    fn from(values: [(&str, T); N]) -> Self {
+        Self::from(values.as_slice())
+    }
+}
+
 impl<T: Serialize> From<&[(&str, T)]> for Columns {
     fn from(values: &[(&str, T)]) -> Self {
         Self(
@@ -541,6 +2390,191 @@ impl<T: Serialize> From<&[(&str, T)]> for Columns {
     }
 }
 
+/// The kinds of plans [`Builder::explain`] can return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Explain {
+    /// The estimated plan (`EXPLAIN`).
+    #[default]
+    Plan,
+    /// The plan with actual timings, which requires running the query (`EXPLAIN ANALYZE`).
+    Analyze,
+    /// The estimated plan, formatted as JSON.
+    Json,
+}
+
+impl Explain {
+    fn prefix(self, sql: &str) -> Result<String, Error> {
+        let keyword = match (Dialect::default(), self) {
+            (Dialect::Sqlite, Self::Plan) => "EXPLAIN QUERY PLAN",
+            (Dialect::Sqlite, Self::Analyze) => {
+                return Err(Error::Unsupported("`EXPLAIN ANALYZE`"))
+            }
+            (Dialect::Sqlite, Self::Json) => return Err(Error::Unsupported("JSON query plans")),
+            (_, Self::Plan) => "EXPLAIN",
+            (_, Self::Analyze) => "EXPLAIN ANALYZE",
+            (Dialect::Postgres, Self::Json) => "EXPLAIN (FORMAT JSON)",
+            (Dialect::Mysql, Self::Json) => "EXPLAIN FORMAT=JSON",
+        };
+
+        Ok(format!("{keyword} {sql}"))
+    }
+}
+
+/// A row returned by the database, with its columns in the order they were selected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row(Vec<(String, Value)>);
+
+impl Row {
+    /// Get the value of the given column.
+    #[must_use]
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, value)| value)
+    }
+
+    /// Get the value of the given column, converted to `T` the same way query results are hydrated into model fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::UnknownColumn`] if the row doesn't have the column, or an error if its value can't be converted to `T`.
+    pub fn try_get<T: DeserializeOwned>(&self, column: &str) -> Result<T, Error> {
+        let value = self
+            .get(column)
+            .ok_or_else(|| Error::UnknownColumn(column.to_string()))?;
+
+        Ok(value::from::<T>(value.clone())?)
+    }
+
+    /// The names of the columns in the row.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Iterate over the columns and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+impl From<Value> for Row {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Map(map) => Self(
+                map.into_iter()
+                    .map(|(column, value)| {
+                        let column = match column {
+                            Value::String(column) => column,
+                            column => column.to_string(),
+                        };
+
+                        (column, value)
+                    })
+                    .collect(),
+            ),
+            value => Self(vec![(String::new(), value)]),
+        }
+    }
+}
+
+impl FromIterator<(String, Value)> for Row {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// The plan returned by [`Builder::explain`]. Displaying it prints the rows as an aligned table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    pub rows: Vec<Row>,
+}
+
+impl From<Vec<Value>> for QueryPlan {
+    fn from(rows: Vec<Value>) -> Self {
+        Self {
+            rows: rows.into_iter().map(Row::from).collect(),
+        }
+    }
+}
+
+impl Display for QueryPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns = self
+            .rows
+            .iter()
+            .flat_map(Row::columns)
+            .unique()
+            .collect::<Vec<_>>();
+        let cells = self
+            .rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|column| match row.get(column) {
+                        None => String::new(),
+                        Some(Value::Null) => "NULL".to_string(),
+                        Some(Value::String(value)) => value.clone(),
+                        Some(value) => value.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let widths = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                cells
+                    .iter()
+                    .flat_map(|row| row[i].lines())
+                    .chain([*column])
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let line = |f: &mut std::fmt::Formatter<'_>, cells: &[&str]| {
+            let cells = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .join(" | ");
+
+            writeln!(f, "| {cells} |")
+        };
+
+        line(f, &columns)?;
+        writeln!(
+            f,
+            "|{}|",
+            widths.iter().map(|width| "-".repeat(width + 2)).join("|")
+        )?;
+
+        for row in &cells {
+            // multi-line values (like Postgres' text plans) get one line per row
+            let lines = row
+                .iter()
+                .map(|cell| cell.lines().count())
+                .max()
+                .unwrap_or_default();
+
+            for i in 0..lines.max(1) {
+                line(
+                    f,
+                    &row.iter()
+                        .map(|cell| cell.lines().nth(i).unwrap_or_default())
+                        .collect::<Vec<_>>(),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Available sort directions.
 #[derive(Debug)]
 pub enum Direction {
@@ -597,6 +2631,32 @@ impl Display for JoinType {
     }
 }
 
+/// An index hint for `MySQL`.
+#[derive(Debug)]
+struct IndexHint {
+    r#type: IndexHintType,
+    index: String,
+}
+
+#[derive(Debug)]
+enum IndexHintType {
+    Use,
+    Force,
+    Ignore,
+}
+
+impl Display for IndexHint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyword = match self.r#type {
+            IndexHintType::Use => "USE",
+            IndexHintType::Force => "FORCE",
+            IndexHintType::Ignore => "IGNORE",
+        };
+
+        write!(f, "{keyword} INDEX ({})", self.index)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Type {
     Count,
@@ -618,18 +2678,26 @@ struct Join {
 #[derive(Debug)]
 enum WhereClause {
     Simple(Where),
-    Group(Vec<WhereClause>, Boolean),
+    Group(Vec<Self>, Boolean),
+    Custom(Clause, Boolean),
 }
 
 impl WhereClause {
     fn to_sql(&self, add_boolean: bool) -> String {
         match self {
             Self::Simple(where_clause) => where_clause.to_sql(add_boolean),
+            Self::Custom(clause, boolean) => {
+                if add_boolean {
+                    format!("{} {boolean} ", clause.to_sql())
+                } else {
+                    clause.to_sql()
+                }
+            }
             Self::Group(where_clauses, boolean) => {
                 let mut sql = String::new();
 
                 for (i, where_clause) in where_clauses.iter().enumerate() {
-                    sql.push_str(&format!("({})", where_clause.to_sql(false)));
+                    write!(sql, "({})", where_clause.to_sql(false)).unwrap();
 
                     if i != where_clauses.len() - 1 {
                         sql.push_str(" AND ");
@@ -655,16 +2723,17 @@ impl WhereClause {
                     Value::Array(array) => array,
                     _ => vec![v],
                 })
+                .filter(expr::is_bound)
                 .collect(),
             Self::Group(where_clauses, _) => {
                 where_clauses.iter().flat_map(Self::get_bindings).collect()
             }
+            Self::Custom(clause, _) => clause.bindings().collect(),
         }
     }
 }
 
 /// A where clause.
-#[derive(Debug)]
 struct Where {
     column: String,
     boolean: Boolean,
@@ -672,6 +2741,17 @@ struct Where {
     value: Option<Value>,
 }
 
+impl Debug for Where {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Where")
+            .field("column", &self.column)
+            .field("boolean", &self.boolean)
+            .field("operator", &self.operator)
+            .field("value", &self.value.as_ref().map(redaction::Shown))
+            .finish()
+    }
+}
+
 impl Where {
     fn to_sql(&self, add_boolean: bool) -> String {
         let sql = format!(
@@ -679,17 +2759,57 @@ impl Where {
             self.column,
             self.operator,
             self.value.as_ref().map_or_else(String::new, |value| {
-                value.as_array().map_or_else(
-                    || "?".to_string(),
-                    |value| format!("({})", value.iter().map(|_| "?").join(", ")),
-                )
+                match (&self.operator, value.as_array()) {
+                    (Operator::Between | Operator::NotBetween, Some(values)) => {
+                        values.iter().map(expr::placeholder).join(" AND ")
+                    }
+                    (_, Some(values)) => {
+                        format!("({})", values.iter().map(expr::placeholder).join(", "))
+                    }
+                    (_, None) => expr::placeholder(value).to_string(),
+                }
             })
         );
+        let sql = sql.trim_end();
 
         if add_boolean {
             format!("{sql} {} ", self.boolean)
         } else {
-            sql
+            sql.to_string()
+        }
+    }
+}
+
+/// The part of a date column compared by [`Builder::where_date`] and friends.
+#[derive(Debug, Clone, Copy)]
+enum DatePart {
+    Date,
+    Year,
+    Month,
+    Day,
+    Time,
+}
+
+impl DatePart {
+    /// The expression extracting this part from `column`.
+    fn wrap(self, column: &str) -> String {
+        match (Dialect::default(), self) {
+            (Dialect::Postgres, Self::Date) => format!("{column}::date"),
+            (Dialect::Postgres, Self::Time) => format!("{column}::time"),
+            (Dialect::Postgres, Self::Year) => format!("EXTRACT(YEAR FROM {column})"),
+            (Dialect::Postgres, Self::Month) => format!("EXTRACT(MONTH FROM {column})"),
+            (Dialect::Postgres, Self::Day) => format!("EXTRACT(DAY FROM {column})"),
+            // SQLite stores dates as text, and `strftime` returns their parts as text too
+            (Dialect::Sqlite, Self::Date) => format!("date({column})"),
+            (Dialect::Sqlite, Self::Time) => format!("time({column})"),
+            (Dialect::Sqlite, Self::Year) => format!("CAST(strftime('%Y', {column}) AS INTEGER)"),
+            (Dialect::Sqlite, Self::Month) => format!("CAST(strftime('%m', {column}) AS INTEGER)"),
+            (Dialect::Sqlite, Self::Day) => format!("CAST(strftime('%d', {column}) AS INTEGER)"),
+            (Dialect::Mysql, Self::Date) => format!("DATE({column})"),
+            (Dialect::Mysql, Self::Time) => format!("TIME({column})"),
+            (Dialect::Mysql, Self::Year) => format!("YEAR({column})"),
+            (Dialect::Mysql, Self::Month) => format!("MONTH({column})"),
+            (Dialect::Mysql, Self::Day) => format!("DAY({column})"),
         }
     }
 }
@@ -805,3 +2925,735 @@ impl AsRef<Self> for Builder {
         self
     }
 }
+
+/// Extract the single value from a row returned by an aggregate query.
+pub(crate) fn scalar(row: Value) -> Option<Value> {
+    match row {
+        Value::Map(map) => map.into_iter().next().map(|(_, value)| value),
+        value => Some(value),
+    }
+}
+
+/// Deserialize the result of a numeric aggregate into `T`.
+/// Drivers return some numeric types as strings (like `MySQL`'s `DECIMAL`), which are parsed when `T` can't hold the string itself.
+fn number<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    let value = match value {
+        Value::Ext(_, value) => *value,
+        value => value,
+    };
+
+    let error = match rbs::from_value(value.clone()) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+
+    let Value::String(text) = &value else {
+        return Err(error.into());
+    };
+    let parsed = text
+        .parse()
+        .map(Value::U64)
+        .or_else(|_| text.parse().map(Value::I64))
+        .or_else(|_| text.parse().map(Value::F64))
+        .map_err(|_| error)?;
+
+    Ok(rbs::from_value(parsed)?)
+}
+
+/// The only one of the results, which were retrieved with a limit of two.
+fn sole<T>(results: Vec<T>) -> Result<T, Error> {
+    if results.len() > 1 {
+        return Err(Error::MultipleRecordsFound {
+            count_hint: results.len(),
+        });
+    }
+
+    results.into_iter().next().ok_or(Error::NotFound)
+}
+
+/// Execute a raw SQL query with positional `?` bindings, returning its rows.
+///
+/// Unlike [`Builder::raw_sql`], the query must be a string literal, so values can only make it into the query through its bindings.
+/// The query isn't constrained by tenancy, soft deletes or global scopes. Use [`Model::from_raw`] to hydrate the rows into models instead.
+///
+/// # Errors
+///
+/// Returns an error if the query fails, or if a connection to the database cannot be established.
+pub async fn raw(sql: &'static str, bindings: Vec<Value>) -> Result<Vec<Row>, Error> {
+    let rows = connection::fetch("", sql, bindings, QueryOptions::ONCE).await?;
+
+    Ok(rows.into_iter().map(Row::from).collect())
+}
+
+/// Insert `model`, or update the stored model with the same `unique` columns, returning the model as it's stored. Used internally by the `Model` derive.
+///
+/// Every column is updated except the primary key, the `unique` columns, immutable and database generated columns, and the ones in `keep` (like `created_at`).
+/// Postgres and `SQLite` return the row from the statement itself, while on `MySQL` it's selected by its `unique` columns afterwards, in the same transaction.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be written or read back, or if a connection to the database cannot be established.
+#[doc(hidden)]
+pub async fn upsert<M: Model>(model: &M, unique: &[&str], keep: &[&str]) -> Result<M, Error> {
+    let Value::Map(mut columns) = value::for_db(model)? else {
+        return Err(Error::InvalidQuery);
+    };
+
+    // a new model gets its key from the database, and an existing one keeps the key it has
+    let increments = M::columns()
+        .iter()
+        .any(|column| column.primary_key && column.increments);
+    if increments && model.primary_key() == &M::PrimaryKey::default() {
+        columns.rm(M::PRIMARY_KEY);
+    }
+
+    let update = M::columns()
+        .iter()
+        .filter(|column| {
+            !column.primary_key
+                && !column.immutable
+                && !column.db_generated
+                && !unique.contains(&column.name)
+                && !keep.contains(&column.name)
+        })
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    let query = M::query();
+    query.ensure_writable()?;
+    let (sql, bindings) = query.upsert_sql(Value::Map(columns.clone()), unique, &update)?;
+
+    let rows = if Dialect::default().supports_returning() {
+        query.fetch_returning(&sql, bindings).await?
+    } else {
+        let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+        transaction.exec(&sql, bindings).await?;
+
+        let (sql, bindings) = unique
+            .iter()
+            .fold(M::query(), |query, column| {
+                query.r#where(column, "=", columns[*column].clone())
+            })
+            .select_sql()?;
+        let rows = transaction.fetch(&sql, bindings).await?;
+        transaction.commit().await?;
+
+        rows
+    };
+
+    let model = value::from::<M>(rows.into_iter().next().ok_or(Error::NotFound)?)?;
+    crate::identity_map::forget::<M>(model.primary_key())?;
+
+    Ok(model)
+}
+
+/// Insert `model`, returning it with its key and `columns` (the ones populated by the database) as stored by the database.
+///
+/// Postgres and `SQLite` return them from the insert itself, with `INSERT ... RETURNING`. On `MySQL`, the model is selected by primary key after the insert.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be inserted or read back, or if a connection to the database cannot be established.
+#[doc(hidden)]
+pub async fn insert_returning<M: Model>(model: &M, columns: &[&str]) -> Result<M, Error> {
+    let inserted = value::for_db(model)?;
+    let (sql, bindings) = M::query().insert_sql(inserted.clone())?;
+
+    if !Dialect::default().supports_returning() {
+        tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), "Executing INSERT SQL query");
+        let result =
+            connection::exec(M::QUALIFIED_TABLE, &sql, bindings, QueryOptions::default()).await?;
+
+        let key = if model.primary_key() == &M::PrimaryKey::default() {
+            result.last_insert_id
+        } else {
+            value::for_db(model.primary_key())?
+        };
+
+        // the model may not match its global scopes, so it's found without them,
+        // and on the primary database, since a replica may not have the write yet
+        return M::without_global_scopes()
+            .on_primary()
+            .r#where(M::PRIMARY_KEY, "=", key)
+            .first::<M>()
+            .await?
+            .ok_or(Error::NotFound);
+    }
+
+    let sql = format!(
+        "{sql} RETURNING {}",
+        std::iter::once(M::PRIMARY_KEY)
+            .chain(columns.iter().copied())
+            .join(", ")
+    );
+    tracing::debug!(sql = sql.as_str(), bindings = ?redaction::Logged(&bindings), "Executing INSERT SQL query");
+
+    // it's still an insert, so it isn't retried like other queries returning rows
+    let row = connection::fetch(M::QUALIFIED_TABLE, &sql, bindings, QueryOptions::ONCE)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(Error::NotFound)?;
+
+    // every other column holds the value that was just inserted
+    let (Value::Map(mut stored), Value::Map(returned)) = (inserted, row) else {
+        return Err(Error::InvalidQuery);
+    };
+    for (column, value) in returned {
+        stored.insert(column, value);
+    }
+
+    Ok(value::from(Value::Map(stored))?)
+}
+
+/// Insert every one of `models` with a single multi-row `INSERT` statement, returning the number of inserted rows.
+///
+/// # Errors
+///
+/// Returns an error if the models cannot be inserted, or if a connection to the database cannot be established.
+pub(crate) async fn insert_many<M: Model>(models: &[M]) -> Result<u64, Error> {
+    if models.is_empty() {
+        return Ok(0);
+    }
+
+    let (sql, bindings) = insert_many_statement(models, Dialect::default())?;
+
+    tracing::debug!(
+        sql = sql.as_str(),
+        rows = models.len(),
+        "Executing INSERT SQL query"
+    );
+
+    connection::exec(M::QUALIFIED_TABLE, &sql, bindings, QueryOptions::default())
+        .await
+        .map(|r| r.rows_affected)
+}
+
+/// Find a model in `table` with `find`, or create it with `create` if there isn't one.
+///
+/// If the insert fails because a concurrent request created the same model after it was looked up, the lookup is retried.
+/// The insert's statement (or transaction) is over by then, so the retry sees the other request's row whatever the isolation level.
+///
+/// Inside a [`transaction`](crate::transaction), the insert runs in a savepoint that's rolled back when it fails,
+/// since Postgres refuses to run any other statement in a transaction once one of them failed.
+pub(crate) async fn first_or_create<M, F, FFut, C, CFut>(
+    table: &str,
+    find: F,
+    create: C,
+) -> Result<M, Error>
+where
+    F: Fn() -> FFut + Send,
+    FFut: Future<Output = Result<Option<M>, Error>> + Send,
+    C: FnOnce() -> CFut + Send,
+    CFut: Future<Output = Result<M, Error>> + Send,
+{
+    if let Some(model) = find().await? {
+        return Ok(model);
+    }
+
+    let savepoint = if connection::in_transaction() {
+        Some(Transaction::begin(table).await?)
+    } else {
+        None
+    };
+
+    let created = create().await;
+    if let Some(savepoint) = savepoint {
+        match &created {
+            Ok(_) => savepoint.commit().await?,
+            Err(_) => savepoint.rollback().await?,
+        }
+    }
+
+    match created {
+        Err(Error::UniqueViolation) => find().await?.ok_or(Error::UniqueViolation),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sole_requires_exactly_one_result() {
+        assert_eq!(sole(vec![1]).unwrap(), 1);
+        assert!(matches!(sole::<u64>(vec![]), Err(Error::NotFound)));
+        assert!(matches!(
+            sole(vec![1, 2]),
+            Err(Error::MultipleRecordsFound { count_hint: 2 })
+        ));
+    }
+
+    #[test]
+    fn racing_creates_find_the_winning_row() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let table = std::sync::Mutex::new(Vec::<&str>::new());
+
+        let find = || async { Ok(table.lock().unwrap().first().copied()) };
+        let created = runtime.block_on(first_or_create("users", find, || async {
+            // another request inserts the row between the lookup and the insert
+            table.lock().unwrap().push("theirs");
+
+            Err(Error::UniqueViolation)
+        }));
+        assert_eq!(created.unwrap(), "theirs");
+
+        // other errors aren't retried
+        let error = runtime.block_on(first_or_create(
+            "users",
+            || async { Ok(None::<&str>) },
+            || async { Err(Error::InvalidQuery) },
+        ));
+        assert!(matches!(error, Err(Error::InvalidQuery)));
+    }
+
+    #[test]
+    fn expressions_are_written_into_statements_without_shifting_bindings() {
+        let query = Builder::new("counters".to_string()).r#where("name", "=", "visits");
+        let (sql, bindings) = query
+            .update_sql(vec![
+                ("value", Value::from(Expr::raw("value + 1"))),
+                ("label", Value::from("Visits")),
+                ("updated_at", Value::from(Expr::now())),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            sql,
+            "UPDATE counters SET value = value + 1, label = ?, updated_at = CURRENT_TIMESTAMP  WHERE name = ?"
+        );
+        assert_eq!(bindings, vec![Value::from("Visits"), Value::from("visits")]);
+
+        let (sql, bindings) = Builder::new("counters".to_string())
+            .insert_sql(vec![
+                ("created_at", Value::from(Expr::now())),
+                ("value", Value::from(1)),
+            ])
+            .unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO counters (created_at, value) VALUES (CURRENT_TIMESTAMP, ?)"
+        );
+        assert_eq!(bindings, vec![Value::from(1)]);
+    }
+
+    #[test]
+    fn expressions_can_be_selected_ordered_and_compared() {
+        let query = Builder::new("posts".to_string())
+            .select_raw(Expr::raw("COUNT(*) AS total"))
+            .r#where("published_at", "<", Expr::now())
+            .where_between("votes", Value::from(1), Value::from(Expr::raw("max_votes")))
+            .order_by_raw(Expr::raw("FIELD(status, 'draft', 'published')"), "desc");
+
+        assert_eq!(
+            query.to_sql(Type::Select),
+            "SELECT COUNT(*) AS total FROM posts WHERE published_at < CURRENT_TIMESTAMP AND votes BETWEEN ? AND max_votes ORDER BY FIELD(status, 'draft', 'published') DESC"
+        );
+        assert_eq!(query.get_bindings(), vec![Value::from(1)]);
+    }
+
+    #[test]
+    fn soft_deleted_records_are_left_out_unless_asked_for() {
+        let query = || {
+            Builder::new("posts".to_string())
+                .soft_deletes()
+                .r#where("votes", ">", 10)
+                .where_not_null("published_at")
+        };
+
+        assert_eq!(
+            query().to_sql(Type::Select),
+            "SELECT * FROM posts WHERE posts.deleted_at IS NULL AND (votes > ? AND published_at IS NOT NULL)"
+        );
+        assert_eq!(
+            query().only_trashed().to_sql(Type::Select),
+            "SELECT * FROM posts WHERE posts.deleted_at IS NOT NULL AND (votes > ? AND published_at IS NOT NULL)"
+        );
+        assert_eq!(
+            query().with_trashed().to_sql(Type::Select),
+            "SELECT * FROM posts WHERE votes > ? AND published_at IS NOT NULL"
+        );
+    }
+
+    #[test]
+    fn custom_clauses_bind_their_values_in_order() {
+        let query = Builder::new("stores".to_string())
+            .r#where("active", "=", true)
+            .where_custom(|clause| {
+                clause
+                    .sql("ST_DWithin(location, ?, ?)")
+                    .bind(Expr::raw("ST_MakePoint(0, 0)"))
+                    .bind(500)
+            })
+            .where_custom(|clause| clause.sql("name SOUNDS LIKE ?").bind("Ensemble"))
+            .r#where("rating", ">", 4);
+
+        assert_eq!(
+            query.to_sql(Type::Select),
+            "SELECT * FROM stores WHERE active = ? AND ST_DWithin(location, ST_MakePoint(0, 0), ?) AND name SOUNDS LIKE ? AND rating > ?"
+        );
+        assert_eq!(
+            query.get_bindings(),
+            vec![
+                Value::from(true),
+                Value::I32(500),
+                Value::from("Ensemble"),
+                Value::I32(4)
+            ]
+        );
+
+        let grouped = Builder::new("stores".to_string()).where_group(|query| {
+            query.where_custom(|clause| clause.sql("tags && ?").bind(vec!["vegan"]))
+        });
+        assert_eq!(
+            grouped.to_sql(Type::Select),
+            "SELECT * FROM stores WHERE (tags && ?)"
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The custom where clause has 2 placeholders, but 1 values were bound."
+    )]
+    fn custom_clauses_must_bind_every_placeholder() {
+        let _ = Builder::new("stores".to_string())
+            .where_custom(|clause| clause.sql("ST_DWithin(location, ?, ?)").bind(500));
+    }
+
+    #[test]
+    fn chunked_writes_are_limited_by_primary_key() {
+        let (sql, bindings) = Builder::new("sessions".to_string())
+            .keyed("token")
+            .r#where("expires_at", "<", 100)
+            .order_by("created_at", "desc")
+            .chunked_delete_sql(1000)
+            .unwrap();
+        // only MySQL can limit a write itself, so other databases limit the keys it touches
+        if Dialect::default() == Dialect::Mysql {
+            assert_eq!(
+                sql,
+                "DELETE FROM sessions WHERE expires_at < ? ORDER BY token ASC LIMIT 1000"
+            );
+        } else {
+            assert_eq!(
+                sql,
+                "DELETE FROM sessions WHERE token IN (SELECT token FROM sessions WHERE expires_at < ? ORDER BY token ASC LIMIT 1000)"
+            );
+        }
+        assert_eq!(bindings, vec![Value::I32(100)]);
+
+        let (sql, bindings) = Builder::new("users".to_string())
+            .r#where("status", "=", "pending")
+            .chunked_update_sql(vec![("status", "active")], 500)
+            .unwrap();
+        if Dialect::default() == Dialect::Mysql {
+            assert_eq!(
+                sql,
+                "UPDATE users SET status = ?  WHERE status = ? ORDER BY id ASC LIMIT 500"
+            );
+        } else {
+            assert_eq!(
+                sql,
+                "UPDATE users SET status = ?  WHERE id IN (SELECT id FROM users WHERE status = ? ORDER BY id ASC LIMIT 500)"
+            );
+        }
+        assert_eq!(
+            bindings,
+            vec![Value::from("active"), Value::from("pending")]
+        );
+    }
+
+    #[test]
+    fn inserts_from_selects_copy_the_columns_both_tables_have() {
+        #[derive(Debug, ensemble::Model)]
+        #[ensemble(table = "orders")]
+        struct Order {
+            id: u64,
+            total: u64,
+            notes: String,
+        }
+
+        #[derive(Debug, ensemble::Model)]
+        #[ensemble(table = "archive_orders")]
+        struct ArchivedOrder {
+            id: u64,
+            total: u64,
+            archived_reason: Option<String>,
+        }
+
+        let (sql, bindings) = ArchivedOrder::query()
+            .insert_select_sql(Order::query().r#where("total", "<", 100))
+            .unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO archive_orders (id, total) SELECT orders.id, orders.total FROM orders WHERE total < ?"
+        );
+        assert_eq!(bindings, vec![Value::I32(100)]);
+
+        // without any columns to match, the selected columns are inserted in order
+        let (sql, _) = Builder::new("archive_orders".to_string())
+            .insert_select_sql(Builder::new("orders".to_string()).r#where("total", "<", 100))
+            .unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO archive_orders SELECT * FROM orders WHERE total < ?"
+        );
+
+        let filtered = ArchivedOrder::query()
+            .r#where("id", "=", 1)
+            .insert_select_sql(Order::query());
+        assert!(matches!(filtered, Err(Error::InvalidQuery)));
+    }
+
+    #[test]
+    fn upserts_update_the_row_they_conflict_with() {
+        let users = || Builder::new("users".to_string());
+        let columns = || vec![("email", "taylor@example.com"), ("name", "Taylor")];
+
+        let (sql, bindings) = users()
+            .upsert_sql(columns(), &["email"], &["name"])
+            .unwrap();
+        if Dialect::default() == Dialect::Mysql {
+            assert_eq!(
+                sql,
+                "INSERT INTO users (email, name) VALUES (?, ?) ON DUPLICATE KEY UPDATE name = VALUES(name)"
+            );
+        } else {
+            assert_eq!(
+                sql,
+                "INSERT INTO users (email, name) VALUES (?, ?) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name"
+            );
+        }
+        assert_eq!(
+            bindings,
+            vec![Value::from("taylor@example.com"), Value::from("Taylor")]
+        );
+
+        // the conflicting row is still touched, so it can be read back
+        let (sql, _) = users().upsert_sql(columns(), &["email"], &[]).unwrap();
+        if Dialect::default() == Dialect::Mysql {
+            assert!(sql.ends_with("ON DUPLICATE KEY UPDATE email = VALUES(email)"));
+        } else {
+            assert!(sql.ends_with("ON CONFLICT (email) DO UPDATE SET email = EXCLUDED.email"));
+        }
+
+        assert!(matches!(
+            users().upsert_sql(columns(), &[], &["name"]),
+            Err(Error::InvalidQuery)
+        ));
+    }
+
+    #[test]
+    fn locked_rows_can_skip_or_refuse_to_wait_for_other_locks() {
+        let jobs = || {
+            Builder::new("jobs".to_string())
+                .r#where("status", "=", "pending")
+                .order_by("id", "asc")
+                .limit(10)
+                .lock_for_update()
+        };
+
+        assert_eq!(
+            jobs().to_sql(Type::Select),
+            "SELECT * FROM jobs WHERE status = ? ORDER BY id ASC LIMIT 10 FOR UPDATE"
+        );
+        assert_eq!(
+            jobs().skip_locked().to_sql(Type::Select),
+            "SELECT * FROM jobs WHERE status = ? ORDER BY id ASC LIMIT 10 FOR UPDATE SKIP LOCKED"
+        );
+        assert_eq!(
+            jobs().nowait().to_sql(Type::Count),
+            "SELECT COUNT(*) FROM jobs WHERE status = ? ORDER BY id ASC LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn row_locks_that_would_do_nothing_are_rejected() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let unlocked = runtime.block_on(Builder::new("jobs".to_string()).skip_locked().get_rows());
+        assert!(matches!(unlocked, Err(Error::InvalidLock(_))));
+
+        // the lock would be released as soon as the query ends, and SQLite has no row locks at all
+        let outside = runtime.block_on(
+            Builder::new("jobs".to_string())
+                .lock_for_update()
+                .get_rows(),
+        );
+        if Dialect::default() == Dialect::Sqlite {
+            assert!(matches!(outside, Err(Error::Unsupported(_))));
+        } else {
+            assert!(matches!(outside, Err(Error::InvalidLock(_))));
+        }
+    }
+
+    #[test]
+    fn mysql_writes_can_be_ordered_and_limited() {
+        let sessions = || {
+            Builder::new("sessions".to_string())
+                .r#where("expires_at", "<", 100)
+                .order_by("created_at", "asc")
+                .limit(1000)
+        };
+
+        let (sql, bindings) = sessions().delete_sql_for(Dialect::Mysql, &[]).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM sessions WHERE expires_at < ? ORDER BY created_at ASC LIMIT 1000"
+        );
+        assert_eq!(bindings, vec![Value::I32(100)]);
+
+        let (sql, bindings) = sessions()
+            .update_sql_for(Dialect::Mysql, vec![("revoked", true)])
+            .unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE sessions SET revoked = ?  WHERE expires_at < ? ORDER BY created_at ASC LIMIT 1000"
+        );
+        assert_eq!(bindings, vec![Value::Bool(true), Value::I32(100)]);
+
+        for dialect in [Dialect::Postgres, Dialect::Sqlite] {
+            assert!(matches!(
+                sessions().delete_sql_for(dialect, &[]),
+                Err(Error::Unsupported(_))
+            ));
+            assert!(matches!(
+                sessions().update_sql_for(dialect, vec![("revoked", true)]),
+                Err(Error::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn mysql_writes_can_join_other_tables() {
+        let orders = || {
+            Builder::new("orders".to_string())
+                .join("order_items", "order_items.order_id", "=", "orders.id")
+                .r#where("orders.status", "=", "cancelled")
+        };
+
+        let (sql, bindings) = orders().delete_sql_for(Dialect::Mysql, &[]).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE orders FROM orders INNER JOIN order_items ON order_items.order_id = orders.id WHERE orders.status = ?"
+        );
+        assert_eq!(bindings, vec![Value::from("cancelled")]);
+
+        let (sql, _) = orders()
+            .delete_sql_for(Dialect::Mysql, &["order_items"])
+            .unwrap();
+        assert_eq!(
+            sql,
+            "DELETE orders, order_items FROM orders INNER JOIN order_items ON order_items.order_id = orders.id WHERE orders.status = ?"
+        );
+
+        let (sql, bindings) = orders()
+            .update_sql_for(Dialect::Mysql, vec![("order_items.refunded", true)])
+            .unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE orders INNER JOIN order_items ON order_items.order_id = orders.id SET order_items.refunded = ?  WHERE orders.status = ?"
+        );
+        assert_eq!(bindings, vec![Value::Bool(true), Value::from("cancelled")]);
+
+        // only the joined tables can be deleted from
+        assert!(matches!(
+            orders().delete_sql_for(Dialect::Mysql, &["refunds"]),
+            Err(Error::InvalidQuery)
+        ));
+
+        // multi-table statements can't be ordered or limited, even on `MySQL`
+        assert!(matches!(
+            orders().limit(10).delete_sql_for(Dialect::Mysql, &[]),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            orders()
+                .order_by("orders.id", "asc")
+                .update_sql_for(Dialect::Mysql, vec![("orders.status", "archived")]),
+            Err(Error::Unsupported(_))
+        ));
+
+        for dialect in [Dialect::Postgres, Dialect::Sqlite] {
+            assert!(matches!(
+                orders().delete_sql_for(dialect, &["order_items"]),
+                Err(Error::Unsupported(_))
+            ));
+            assert!(matches!(
+                orders().update_sql_for(dialect, vec![("orders.status", "archived")]),
+                Err(Error::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn plain_writes_are_the_same_in_every_dialect() {
+        for dialect in [Dialect::Mysql, Dialect::Postgres, Dialect::Sqlite] {
+            let sessions = || Builder::new("sessions".to_string()).r#where("expires_at", "<", 100);
+
+            let (sql, _) = sessions().delete_sql_for(dialect, &[]).unwrap();
+            assert_eq!(sql, "DELETE FROM sessions WHERE expires_at < ?");
+
+            let (sql, _) = sessions()
+                .update_sql_for(dialect, vec![("revoked", true)])
+                .unwrap();
+            assert_eq!(sql, "UPDATE sessions SET revoked = ?  WHERE expires_at < ?");
+
+            // no database can skip rows in a write
+            assert!(matches!(
+                sessions().offset(10).delete_sql_for(dialect, &[]),
+                Err(Error::Unsupported(_))
+            ));
+            assert!(matches!(
+                sessions()
+                    .offset(10)
+                    .update_sql_for(dialect, vec![("revoked", true)]),
+                Err(Error::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn chunked_writes_reject_limits_and_joins() {
+        let limited = Builder::new("sessions".to_string())
+            .limit(10)
+            .chunked_delete_sql(1000);
+        assert!(matches!(limited, Err(Error::InvalidQuery)));
+
+        let joined = Builder::new("sessions".to_string())
+            .join("users", "users.id", "=", "sessions.user_id")
+            .chunked_delete_sql(1000);
+        assert!(matches!(joined, Err(Error::InvalidQuery)));
+    }
+
+    #[test]
+    fn chunked_write_errors_report_the_affected_rows() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        // no database is set up, so the first batch fails
+        let error = runtime
+            .block_on(
+                Builder::new("sessions".to_string())
+                    .r#where("expires_at", "<", 100)
+                    .delete_in_chunks(1000),
+            )
+            .unwrap_err();
+        assert_eq!(error.affected, 0);
+
+        let error = runtime
+            .block_on(
+                Builder::new("sessions".to_string())
+                    .read_only("sessions")
+                    .update_in_chunks(vec![("expires_at", 0)], 1000),
+            )
+            .unwrap_err();
+        assert!(matches!(error.error, Error::ReadOnly("sessions")));
+    }
+}
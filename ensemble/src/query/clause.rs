@@ -0,0 +1,184 @@
+//! Custom where clauses, for operators the builder doesn't support natively.
+
+use rbs::Value;
+use serde::Serialize;
+use std::fmt::Debug;
+
+use super::expr;
+use crate::{redaction, value};
+
+/// A where clause written by hand, like `ST_DWithin(location, ?, ?)`, created with [`where_custom`](super::Builder::where_custom).
+///
+/// Each `?` in the SQL is a placeholder for the next value passed to [`bind`](Self::bind), so the bindings of the clause always line up with the rest of the query's.
+/// Question marks inside string literals, quoted identifiers and comments aren't placeholders.
+/// Like with [`Expr`](super::Expr), the SQL can only be built from string literals, unless you use the `unsafe` [`sql_unchecked`](Self::sql_unchecked) method.
+///
+/// ```rust
+/// # use ensemble::Model;
+/// # #[derive(Debug, Model)]
+/// # struct Store {
+/// #     id: u64,
+/// #     name: String,
+/// # }
+/// # async fn run() -> Result<(), ensemble::Error> {
+/// let stores = Store::query()
+///     .where_custom(|clause| clause.sql("name SOUNDS LIKE ?").bind("Ensemble"))
+///     .get::<Store>()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Clause {
+    sql: String,
+    bindings: Vec<Value>,
+}
+
+impl Clause {
+    /// Append SQL to the clause.
+    #[must_use]
+    pub fn sql(mut self, sql: &'static str) -> Self {
+        self.sql.push_str(sql);
+        self
+    }
+
+    /// Append SQL built at runtime to the clause.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because the SQL is written into the statement verbatim, which can lead to SQL injection.
+    /// Make sure it never contains user input.
+    #[must_use]
+    pub unsafe fn sql_unchecked(mut self, sql: impl AsRef<str>) -> Self {
+        self.sql.push_str(sql.as_ref());
+        self
+    }
+
+    /// Bind a value to the next placeholder of the clause. An [`Expr`](super::Expr) is written in place of the placeholder instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided value cannot be serialized.
+    #[must_use]
+    pub fn bind<T: Serialize>(mut self, value: T) -> Self {
+        self.bindings.push(value::for_db(value).unwrap());
+        self
+    }
+
+    /// The number of placeholders in the clause's SQL, and the number of values bound to them.
+    pub(super) fn arity(&self) -> (usize, usize) {
+        (placeholders(&self.sql).len(), self.bindings.len())
+    }
+
+    /// The SQL of the clause, with the placeholders of expressions replaced by their SQL.
+    pub(super) fn to_sql(&self) -> String {
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut last = 0;
+
+        for (index, value) in placeholders(&self.sql).into_iter().zip(&self.bindings) {
+            sql.push_str(&self.sql[last..index]);
+            sql.push_str(expr::placeholder(value));
+            last = index + 1;
+        }
+
+        sql.push_str(&self.sql[last..]);
+        sql
+    }
+
+    /// The values bound to the clause's placeholders, in order.
+    pub(super) fn bindings(&self) -> impl Iterator<Item = Value> + '_ {
+        self.bindings
+            .iter()
+            .filter(|value| expr::is_bound(value))
+            .cloned()
+    }
+}
+
+impl Debug for Clause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clause")
+            .field("sql", &self.sql)
+            .field(
+                "bindings",
+                &self
+                    .bindings
+                    .iter()
+                    .map(redaction::Shown)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// The byte offsets of the placeholders in `sql`, skipping string literals, quoted identifiers and comments.
+fn placeholders(sql: &str) -> Vec<usize> {
+    let bytes = sql.as_bytes();
+    let mut placeholders = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let end: Option<&[u8]> = match (bytes[i], bytes.get(i + 1)) {
+            (b'\'', _) => Some(b"'"),
+            (b'"', _) => Some(b"\""),
+            (b'`', _) => Some(b"`"),
+            (b'-', Some(b'-')) => Some(b"\n"),
+            (b'/', Some(b'*')) => Some(b"*/"),
+            (b'?', _) => {
+                placeholders.push(i);
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(end) = end {
+            i += if matches!(bytes[i], b'-' | b'/') {
+                2
+            } else {
+                1
+            };
+            while i < bytes.len() && !bytes[i..].starts_with(end) {
+                i += 1;
+            }
+            i = (i + end.len()).min(bytes.len());
+            continue;
+        }
+
+        i += 1;
+    }
+
+    placeholders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Expr;
+
+    #[test]
+    fn placeholders_in_quotes_and_comments_are_skipped() {
+        let clause = Clause::default()
+            .sql("name = '?' AND `what?` = ? /* ? */ AND note = ? -- ?\n")
+            .bind(1)
+            .bind(2);
+
+        assert_eq!(clause.arity(), (2, 2));
+        assert_eq!(
+            clause.bindings().collect::<Vec<_>>(),
+            vec![Value::I32(1), Value::I32(2)]
+        );
+    }
+
+    #[test]
+    fn expressions_replace_their_placeholder() {
+        let clause = Clause::default()
+            .sql("ST_DWithin(location, ?, ?)")
+            .bind(Expr::raw("ST_MakePoint(0, 0)"))
+            .bind(500);
+
+        assert_eq!(
+            clause.to_sql(),
+            "ST_DWithin(location, ST_MakePoint(0, 0), ?)"
+        );
+        assert_eq!(clause.bindings().collect::<Vec<_>>(), vec![Value::I32(500)]);
+    }
+}
@@ -0,0 +1,510 @@
+//! Rendering inserts as SQL text, for seed files and data migrations that run without Ensemble.
+
+use itertools::Itertools;
+use rbs::Value;
+use std::time::Duration;
+
+use super::{expr, Builder};
+use crate::{value, Error, Model};
+
+/// The database a statement is rendered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Mysql,
+    Postgres,
+    Sqlite,
+}
+
+impl Default for Dialect {
+    /// The dialect of the database Ensemble was built for.
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl Dialect {
+    /// The dialect of the database Ensemble was built for, in const contexts.
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(feature = "postgres") {
+            Self::Postgres
+        } else if cfg!(feature = "sqlite") {
+            Self::Sqlite
+        } else {
+            Self::Mysql
+        }
+    }
+
+    /// Whether writes can return the rows they touched with `RETURNING`, instead of selecting them again.
+    pub(crate) const fn supports_returning(self) -> bool {
+        matches!(self, Self::Postgres | Self::Sqlite)
+    }
+
+    /// The statement setting a server-side `timeout` for every statement run in the session, or `None` if the database has no such setting.
+    ///
+    /// `MySQL`'s `max_execution_time` only applies to `SELECT` queries.
+    pub(crate) fn statement_timeout(self, timeout: Duration) -> Option<String> {
+        match self {
+            Self::Mysql => Some(format!(
+                "SET SESSION max_execution_time = {}",
+                timeout.as_millis()
+            )),
+            Self::Postgres => Some(format!("SET statement_timeout = {}", timeout.as_millis())),
+            Self::Sqlite => None,
+        }
+    }
+
+    /// Quote a table or column name. Each part of a qualified name (e.g. `public.users`) is quoted separately,
+    /// and parts that are already quoted (like the schema of a [qualified table](Model::QUALIFIED_TABLE)) are quoted again for this dialect.
+    #[must_use]
+    pub fn quote_identifier(self, name: &str) -> String {
+        let quote = match self {
+            Self::Mysql => '`',
+            Self::Postgres | Self::Sqlite => '"',
+        };
+
+        identifier_parts(name)
+            .iter()
+            .map(|part| {
+                let part = part.replace(quote, &format!("{quote}{quote}"));
+                format!("{quote}{part}{quote}")
+            })
+            .join(".")
+    }
+
+    /// Render a value as a SQL literal.
+    ///
+    /// Strings are escaped for the dialect's default settings, so `MySQL` servers running with `NO_BACKSLASH_ESCAPES` will read backslashes twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value can't be represented in the dialect, like a `NaN` outside of Postgres.
+    pub fn literal(self, value: &Value) -> Result<String, Error> {
+        if let Some(sql) = expr::sql(value) {
+            return Ok(sql.to_string());
+        }
+
+        Ok(match value {
+            Value::Null => "NULL".to_string(),
+            Value::Bool(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+            Value::I32(value) => value.to_string(),
+            Value::I64(value) => value.to_string(),
+            Value::U32(value) => value.to_string(),
+            Value::U64(unsigned) => {
+                self.bind(value.clone())?;
+                unsigned.to_string()
+            }
+            Value::F32(value) => self.float(f64::from(*value))?,
+            Value::F64(value) => self.float(*value)?,
+            Value::String(value) => self.string(value),
+            Value::Binary(bytes) => {
+                let hex = bytes.iter().map(|byte| format!("{byte:02x}")).join("");
+
+                match self {
+                    Self::Postgres => format!("'\\x{hex}'"),
+                    Self::Mysql | Self::Sqlite => format!("X'{hex}'"),
+                }
+            }
+            Value::Array(_) | Value::Map(_) => self.string(
+                &serde_json::to_string(value)
+                    .map_err(|error| rbs::Error::Syntax(error.to_string()))?,
+            ),
+            Value::Ext(_, value) => self.literal(value)?,
+        })
+    }
+
+    /// Convert a value to the closest type the dialect's driver can bind, so it's stored (and read back) without wrapping around.
+    ///
+    /// Postgres and `SQLite` don't have unsigned integers, so they're bound as the smallest signed integer they fit in,
+    /// and `SQLite` doesn't have booleans, so they're bound as `0` or `1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::OutOfRange`] if an unsigned integer is too large for the dialect's largest integer type.
+    pub(crate) fn bind(self, value: Value) -> Result<Value, Error> {
+        Ok(match (self, value) {
+            (Self::Mysql, value) => value,
+            (_, Value::Array(values)) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| self.bind(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Self::Postgres, Value::U32(value)) => {
+                i32::try_from(value).map_or_else(|_| Value::I64(value.into()), Value::I32)
+            }
+            (Self::Sqlite, Value::U32(value)) => Value::I64(value.into()),
+            (Self::Sqlite, Value::Bool(value)) => Value::I64(value.into()),
+            (_, Value::U64(value)) => Value::I64(i64::try_from(value).map_err(|_| {
+                Error::OutOfRange {
+                    value: value.to_string(),
+                    target: match self {
+                        Self::Sqlite => "INTEGER",
+                        Self::Mysql | Self::Postgres => "BIGINT",
+                    }
+                    .to_string(),
+                }
+            })?),
+            (_, value) => value,
+        })
+    }
+
+    fn string(self, value: &str) -> String {
+        let value = value.replace('\'', "''");
+
+        match self {
+            Self::Mysql => format!("'{}'", value.replace('\\', "\\\\").replace('\0', "\\0")),
+            Self::Postgres | Self::Sqlite => format!("'{value}'"),
+        }
+    }
+
+    fn float(self, value: f64) -> Result<String, Error> {
+        if value.is_finite() {
+            return Ok(value.to_string());
+        }
+
+        match self {
+            Self::Postgres if value.is_nan() => Ok("'NaN'".to_string()),
+            Self::Postgres if value.is_sign_positive() => Ok("'Infinity'".to_string()),
+            Self::Postgres => Ok("'-Infinity'".to_string()),
+            Self::Mysql | Self::Sqlite => Err(Error::Serialization(rbs::Error::Syntax(format!(
+                "{value} cannot be represented in {self:?}."
+            )))),
+        }
+    }
+
+    /// The value of a column left out of one of the rows of a multi-row insert.
+    const fn missing(self) -> &'static str {
+        match self {
+            // SQLite doesn't support `DEFAULT` in `VALUES`, but fills in `NULL` keys itself
+            Self::Sqlite => "NULL",
+            Self::Mysql | Self::Postgres => "DEFAULT",
+        }
+    }
+}
+
+/// The rows inserting some models, with `None` for the columns left out of a row.
+struct Rows {
+    query: Builder,
+    columns: Vec<String>,
+    values: Vec<Vec<Option<Value>>>,
+}
+
+/// The rows inserting `models`, as `create` would.
+/// Incrementing keys that haven't been set are left for the database to generate.
+fn rows<M: Model>(models: &[M]) -> Result<Rows, Error> {
+    let query = M::query();
+    query.ensure_writable()?;
+
+    let increments = M::columns()
+        .iter()
+        .find(|column| column.primary_key && column.increments)
+        .map(|column| column.name);
+    let unset_key = value::for_db(M::PrimaryKey::default())?;
+
+    let rows = models
+        .iter()
+        .map(|model| {
+            let mut columns = query.insert_columns(value::for_db(model)?)?;
+            columns.retain(|(column, value)| {
+                Some(column.as_str()) != increments || *value != unset_key
+            });
+
+            Ok(columns)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let columns = rows
+        .iter()
+        .flatten()
+        .map(|(column, _)| column.clone())
+        .unique()
+        .collect::<Vec<_>>();
+
+    let values = rows
+        .into_iter()
+        .map(|mut row| {
+            columns
+                .iter()
+                .map(|column| {
+                    row.iter()
+                        .position(|(name, _)| name == column)
+                        .map(|index| row.swap_remove(index).1)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(Rows {
+        query,
+        columns,
+        values,
+    })
+}
+
+/// Split a (possibly qualified) name into its parts, unquoting the ones quoted with backticks or double quotes.
+fn identifier_parts(name: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut quote = None;
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            // a doubled quote is an escaped one
+            Some(q) if c == q && chars.peek() == Some(&q) => {
+                chars.next();
+                part.push(c);
+            }
+            Some(q) if c == q => quote = None,
+            None if part.is_empty() && (c == '`' || c == '"') => quote = Some(c),
+            None if c == '.' => parts.push(std::mem::take(&mut part)),
+            _ => part.push(c),
+        }
+    }
+    parts.push(part);
+
+    parts
+}
+
+fn header(dialect: Dialect, Rows { query, columns, .. }: &Rows) -> String {
+    format!(
+        "INSERT INTO {} ({}) VALUES ",
+        dialect.quote_identifier(&query.table),
+        columns
+            .iter()
+            .map(|column| dialect.quote_identifier(column))
+            .join(", ")
+    )
+}
+
+/// A single `INSERT` statement for `models`, with its values inlined. Returns an empty string if there are no models.
+pub fn insert_sql<M: Model>(models: &[M], dialect: Dialect) -> Result<String, Error> {
+    if models.is_empty() {
+        return Ok(String::new());
+    }
+
+    let rows = rows(models)?;
+
+    let values = rows
+        .values
+        .iter()
+        .map(|row| {
+            let row = row
+                .iter()
+                .map(|value| {
+                    value.as_ref().map_or_else(
+                        || Ok(dialect.missing().to_string()),
+                        |value| dialect.literal(value),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Ok(format!("({})", row.join(", ")))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(format!("{}{};", header(dialect, &rows), values.join(", ")))
+}
+
+/// An `INSERT` statement for `model` with placeholders for its values, along with the values to bind to them.
+pub fn insert_statement<M: Model>(
+    model: &M,
+    dialect: Dialect,
+) -> Result<(String, Vec<Value>), Error> {
+    insert_many_statement(std::slice::from_ref(model), dialect)
+}
+
+/// A single `INSERT` statement for `models` with placeholders for their values, along with the values to bind to them.
+pub fn insert_many_statement<M: Model>(
+    models: &[M],
+    dialect: Dialect,
+) -> Result<(String, Vec<Value>), Error> {
+    let rows = rows(models)?;
+
+    let mut bindings = vec![];
+    let values = rows
+        .values
+        .iter()
+        .map(|row| {
+            let placeholders = row
+                .iter()
+                .map(|value| {
+                    let Some(value) = value else {
+                        return Ok(dialect.missing().to_string());
+                    };
+
+                    if let Some(sql) = expr::sql(value) {
+                        return Ok(sql.to_string());
+                    }
+
+                    bindings.push(dialect.bind(value.clone())?);
+                    Ok(match dialect {
+                        Dialect::Postgres => format!("${}", bindings.len()),
+                        Dialect::Mysql | Dialect::Sqlite => "?".to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Ok(format!("({})", placeholders.join(", ")))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok((
+        format!("{}{}", header(dialect, &rows), values.join(", ")),
+        bindings,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_quoted() {
+        assert_eq!(Dialect::Mysql.quote_identifier("users"), "`users`");
+        assert_eq!(
+            Dialect::Postgres.quote_identifier("public.users"),
+            "\"public\".\"users\""
+        );
+        assert_eq!(Dialect::Sqlite.quote_identifier("we\"ird"), "\"we\"\"ird\"");
+    }
+
+    #[test]
+    fn quoted_parts_are_quoted_for_the_dialect() {
+        assert_eq!(
+            Dialect::Postgres.quote_identifier("`Billing`.invoices"),
+            "\"Billing\".\"invoices\""
+        );
+        assert_eq!(
+            Dialect::Mysql.quote_identifier("\"Billing\".invoices"),
+            "`Billing`.`invoices`"
+        );
+        assert_eq!(
+            Dialect::Postgres.quote_identifier("\"odd.\"\"name\".users"),
+            "\"odd.\"\"name\".\"users\""
+        );
+    }
+
+    #[test]
+    fn statement_timeouts_are_set_per_session() {
+        let timeout = Duration::from_secs(30);
+
+        assert_eq!(
+            Dialect::Mysql.statement_timeout(timeout).as_deref(),
+            Some("SET SESSION max_execution_time = 30000")
+        );
+        assert_eq!(
+            Dialect::Postgres.statement_timeout(timeout).as_deref(),
+            Some("SET statement_timeout = 30000")
+        );
+        assert_eq!(Dialect::Sqlite.statement_timeout(timeout), None);
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let value = Value::from("it's a \\ test");
+
+        assert_eq!(
+            Dialect::Mysql.literal(&value).unwrap(),
+            "'it''s a \\\\ test'"
+        );
+        assert_eq!(
+            Dialect::Postgres.literal(&value).unwrap(),
+            "'it''s a \\ test'"
+        );
+        assert_eq!(
+            Dialect::Sqlite
+                .literal(&Value::Binary(vec![0xde, 0xad]))
+                .unwrap(),
+            "X'dead'"
+        );
+    }
+
+    #[test]
+    fn non_finite_floats_are_only_supported_by_postgres() {
+        assert_eq!(
+            Dialect::Postgres.literal(&Value::F64(f64::NAN)).unwrap(),
+            "'NaN'"
+        );
+        assert!(Dialect::Mysql.literal(&Value::F64(f64::INFINITY)).is_err());
+    }
+
+    const DIALECTS: [Dialect; 3] = [Dialect::Mysql, Dialect::Postgres, Dialect::Sqlite];
+
+    /// The value the dialect's driver returns for a bound value, once it's stored in the closest column type.
+    fn read_back(dialect: Dialect, bound: Value) -> Value {
+        match (dialect, bound) {
+            // `TINYINT(1)`, and `BIGINT UNSIGNED` values above `i64::MAX` come back wrapped around
+            (Dialect::Mysql, Value::Bool(value)) => Value::U64(value.into()),
+            (Dialect::Mysql, Value::U32(value)) => Value::I64(value.into()),
+            #[allow(clippy::cast_possible_wrap)]
+            (Dialect::Mysql, Value::U64(value)) => Value::I64(value as i64),
+            (_, value) => value,
+        }
+    }
+
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(
+        dialect: Dialect,
+        value: T,
+    ) -> Result<T, Error> {
+        let bound = dialect.bind(value::for_db(value)?)?;
+
+        Ok(value::from(read_back(dialect, bound))?)
+    }
+
+    #[test]
+    fn booleans_and_unsigned_integers_round_trip() {
+        for dialect in DIALECTS {
+            assert!(round_trip(dialect, true).unwrap(), "{dialect:?}");
+            assert!(!round_trip(dialect, false).unwrap(), "{dialect:?}");
+            assert_eq!(round_trip(dialect, u8::MAX).unwrap(), u8::MAX);
+            assert_eq!(round_trip(dialect, u16::MAX).unwrap(), u16::MAX);
+            assert_eq!(round_trip(dialect, u32::MAX).unwrap(), u32::MAX);
+            assert_eq!(
+                round_trip(dialect, i64::MAX as u64).unwrap(),
+                i64::MAX as u64
+            );
+
+            assert!(
+                matches!(round_trip(dialect, u64::MAX), Err(Error::OutOfRange { .. })),
+                "{dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn integers_are_range_checked_when_read() {
+        let error = value::from::<u8>(Value::I64(300)).map_err(Error::from);
+        assert!(
+            matches!(error, Err(Error::OutOfRange { value, target }) if value == "300" && target == "u8")
+        );
+
+        let error = value::from::<u64>(Value::I64(-1)).map_err(Error::from);
+        assert!(matches!(error, Err(Error::OutOfRange { .. })));
+
+        assert!(value::from::<bool>(Value::I32(2)).is_err());
+        assert!(value::from::<bool>(Value::from("t")).unwrap());
+        assert!(value::from::<bool>(Value::Binary(vec![1])).unwrap());
+        assert_eq!(
+            value::from::<u64>(Value::Ext(
+                "Decimal",
+                Box::new(Value::from(u64::MAX.to_string()))
+            ))
+            .unwrap(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn unsigned_literals_are_range_checked() {
+        assert_eq!(
+            Dialect::Mysql.literal(&Value::U64(u64::MAX)).unwrap(),
+            u64::MAX.to_string()
+        );
+        assert!(matches!(
+            Dialect::Sqlite.literal(&Value::U64(u64::MAX)),
+            Err(Error::OutOfRange { .. })
+        ));
+    }
+}
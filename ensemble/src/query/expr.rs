@@ -0,0 +1,121 @@
+//! Raw SQL expressions, rendered verbatim instead of being bound as values.
+
+use rbs::Value;
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// The name of the extension values expressions are serialized as.
+const EXPR: &str = "ensemble::Expr";
+
+/// A raw SQL expression, like `value + 1` or `NOW()`, written into a statement in place of a value.
+///
+/// Expressions can be used in updates, inserts and where clauses, or with [`select_raw`](super::Builder::select_raw) and [`order_by_raw`](super::Builder::order_by_raw).
+/// The expression is written into the statement as-is, without being escaped or bound, so it must never contain user input.
+/// To make that hard to do by accident, expressions can only be created from string literals (or other `&'static str`),
+/// unless you use the `unsafe` [`raw_unchecked`](Self::raw_unchecked) constructor.
+///
+/// Expressions can be mixed with regular values by converting both to an [`rbs::Value`]:
+///
+/// ```rust
+/// # use ensemble::{query::Expr, rbs::Value, Model};
+/// # #[derive(Debug, Model)]
+/// # struct Counter {
+/// #     id: u64,
+/// #     value: u64,
+/// # }
+/// # async fn run() -> Result<(), ensemble::Error> {
+/// Counter::query()
+///     .r#where("id", "=", 1)
+///     .update(vec![
+///         ("value", Value::from(Expr::raw("value + 1"))),
+///         ("updated_at", Value::from(Expr::now())),
+///         ("name", Value::from("visits")),
+///     ])
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr(Cow<'static, str>);
+
+impl Expr {
+    /// Create an expression from a string literal.
+    #[must_use]
+    pub const fn raw(sql: &'static str) -> Self {
+        Self(Cow::Borrowed(sql))
+    }
+
+    /// Create an expression from a string built at runtime.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because the expression is written into the statement verbatim, which can lead to SQL injection.
+    /// Make sure it never contains user input.
+    pub unsafe fn raw_unchecked(sql: impl Into<String>) -> Self {
+        Self(Cow::Owned(sql.into()))
+    }
+
+    /// The current date and time, as set by the database.
+    #[must_use]
+    pub const fn now() -> Self {
+        Self::raw("CURRENT_TIMESTAMP")
+    }
+
+    /// The SQL of the expression.
+    #[must_use]
+    pub fn as_sql(&self) -> &str {
+        &self.0
+    }
+
+    /// Take the SQL of the expression.
+    #[must_use]
+    pub fn into_sql(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(EXPR, self.as_sql())
+    }
+}
+
+impl From<Expr> for Value {
+    fn from(expr: Expr) -> Self {
+        Self::Ext(EXPR, Box::new(Self::String(expr.into_sql())))
+    }
+}
+
+/// The SQL of `value`, if it holds an expression.
+pub fn sql(value: &Value) -> Option<&str> {
+    match value {
+        Value::Ext(EXPR, sql) => sql.as_str(),
+        _ => None,
+    }
+}
+
+/// What `value` is written as in a statement: its SQL if it's an expression, or a placeholder it's bound to otherwise.
+pub fn placeholder(value: &Value) -> &str {
+    sql(value).unwrap_or("?")
+}
+
+/// Whether `value` is bound to a placeholder, rather than written into the statement.
+pub fn is_bound(value: &Value) -> bool {
+    sql(value).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value;
+
+    #[test]
+    fn expressions_serialize_for_the_database() {
+        let value = value::for_db(Expr::raw("value + 1")).unwrap();
+
+        assert_eq!(sql(&value), Some("value + 1"));
+        assert_eq!(value, Value::from(Expr::raw("value + 1")));
+        assert!(is_bound(&Value::from("value + 1")));
+        assert_eq!(placeholder(&Value::from(1)), "?");
+    }
+}
@@ -0,0 +1,182 @@
+//! Paths into JSON columns, like `meta->author->name`, used by [`where_json`](super::Builder::where_json) and [`where_json_contains`](super::Builder::where_json_contains).
+
+use std::fmt::Write;
+
+use super::Dialect;
+
+/// A step of a path into a JSON document.
+#[derive(Debug, PartialEq, Eq)]
+enum Key<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+/// A column holding JSON, and the keys leading to a value inside it.
+#[derive(Debug)]
+pub(super) struct JsonPath<'a> {
+    column: &'a str,
+    keys: Vec<Key<'a>>,
+}
+
+impl<'a> JsonPath<'a> {
+    /// Parse a path like `meta->tags->0`, whose numeric keys index into arrays.
+    /// Returns `None` if a key of the path is empty or contains quotes, since keys are written into the statement.
+    pub(super) fn parse(path: &'a str) -> Option<Self> {
+        let mut parts = path.split("->").map(str::trim);
+        let column = parts.next().unwrap_or_default();
+
+        let keys = parts
+            .map(|key| {
+                if key.is_empty() || key.contains(['\'', '"', '\\']) {
+                    return None;
+                }
+
+                Some(key.parse().map_or(Key::Field(key), Key::Index))
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self { column, keys })
+    }
+
+    /// The column holding the JSON document.
+    pub(super) const fn column(&self) -> &'a str {
+        self.column
+    }
+
+    /// The expression extracting the value at the path, as a plain (unquoted) SQL value.
+    pub(super) fn extract(&self, dialect: Dialect) -> String {
+        if self.keys.is_empty() {
+            return self.column.to_string();
+        }
+
+        match dialect {
+            Dialect::Mysql => format!(
+                "JSON_UNQUOTE(JSON_EXTRACT({}, '{}'))",
+                self.column,
+                self.json_path()
+            ),
+            Dialect::Sqlite => format!("json_extract({}, '{}')", self.column, self.json_path()),
+            Dialect::Postgres => {
+                let (last, keys) = self.keys.split_last().unwrap();
+                format!(
+                    "{}{}->>{}",
+                    self.column,
+                    postgres_keys(keys),
+                    postgres_key(last)
+                )
+            }
+        }
+    }
+
+    /// A clause matching documents whose value at the path contains the bound value.
+    /// `MySQL` and Postgres bind the value as JSON, while `SQLite` looks for it among the elements of the array, so it binds it as is.
+    pub(super) fn contains(&self, dialect: Dialect) -> String {
+        match (dialect, self.keys.is_empty()) {
+            (Dialect::Mysql, true) => format!("JSON_CONTAINS({}, ?)", self.column),
+            (Dialect::Mysql, false) => {
+                format!("JSON_CONTAINS({}, ?, '{}')", self.column, self.json_path())
+            }
+            (Dialect::Postgres, _) => format!(
+                "({}{})::jsonb @> ?::jsonb",
+                self.column,
+                postgres_keys(&self.keys)
+            ),
+            (Dialect::Sqlite, true) => format!(
+                "EXISTS (SELECT 1 FROM json_each({}) WHERE json_each.value = ?)",
+                self.column
+            ),
+            (Dialect::Sqlite, false) => format!(
+                "EXISTS (SELECT 1 FROM json_each({}, '{}') WHERE json_each.value = ?)",
+                self.column,
+                self.json_path()
+            ),
+        }
+    }
+
+    /// The path as used by `MySQL` and `SQLite`'s JSON functions, like `$."tags"[0]`.
+    fn json_path(&self) -> String {
+        self.keys.iter().fold(String::from("$"), |mut path, key| {
+            match key {
+                Key::Field(field) => write!(path, ".\"{field}\""),
+                Key::Index(index) => write!(path, "[{index}]"),
+            }
+            .unwrap();
+
+            path
+        })
+    }
+}
+
+fn postgres_keys(keys: &[Key]) -> String {
+    keys.iter().fold(String::new(), |mut sql, key| {
+        write!(sql, "->{}", postgres_key(key)).unwrap();
+        sql
+    })
+}
+
+fn postgres_key(key: &Key) -> String {
+    match key {
+        Key::Field(field) => format!("'{field}'"),
+        Key::Index(index) => index.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dialect, JsonPath, Key};
+
+    #[test]
+    fn paths_are_split_into_keys() {
+        let path = JsonPath::parse("meta->tags->0").unwrap();
+
+        assert_eq!(path.column(), "meta");
+        assert_eq!(path.keys, [Key::Field("tags"), Key::Index(0)]);
+    }
+
+    #[test]
+    fn keys_cannot_be_empty_or_contain_quotes() {
+        assert!(JsonPath::parse("meta->tags') OR ('1").is_none());
+        assert!(JsonPath::parse("meta->->name").is_none());
+    }
+
+    #[test]
+    fn values_are_extracted_with_each_dialect() {
+        let path = JsonPath::parse("meta->author->name").unwrap();
+
+        assert_eq!(
+            path.extract(Dialect::Mysql),
+            r#"JSON_UNQUOTE(JSON_EXTRACT(meta, '$."author"."name"'))"#
+        );
+        assert_eq!(
+            path.extract(Dialect::Sqlite),
+            r#"json_extract(meta, '$."author"."name"')"#
+        );
+        assert_eq!(path.extract(Dialect::Postgres), "meta->'author'->>'name'");
+        assert_eq!(
+            JsonPath::parse("meta").unwrap().extract(Dialect::Postgres),
+            "meta"
+        );
+    }
+
+    #[test]
+    fn containment_is_checked_with_each_dialect() {
+        let path = JsonPath::parse("meta->tags").unwrap();
+
+        assert_eq!(
+            path.contains(Dialect::Mysql),
+            r#"JSON_CONTAINS(meta, ?, '$."tags"')"#
+        );
+        assert_eq!(
+            path.contains(Dialect::Postgres),
+            "(meta->'tags')::jsonb @> ?::jsonb"
+        );
+        assert_eq!(
+            path.contains(Dialect::Sqlite),
+            r#"EXISTS (SELECT 1 FROM json_each(meta, '$."tags"') WHERE json_each.value = ?)"#
+        );
+        assert_eq!(
+            JsonPath::parse("tags").unwrap().contains(Dialect::Mysql),
+            "JSON_CONTAINS(tags, ?)"
+        );
+    }
+}
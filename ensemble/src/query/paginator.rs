@@ -0,0 +1,102 @@
+//! Pages of query results, returned by [`paginate`](super::Builder::paginate) and [`cursor_paginate`](super::Builder::cursor_paginate).
+
+use serde::Serialize;
+
+use crate::Model;
+
+/// A page of results, along with the total number of records matching the query.
+///
+/// It serializes with the page's details next to its `data`, so it can be returned from a handler as it is:
+///
+/// ```json
+/// { "data": [...], "total": 42, "per_page": 15, "current_page": 2, "last_page": 3 }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct Paginator<T> {
+    /// The records on the page.
+    pub data: Vec<T>,
+    /// The number of records matching the query, across every page.
+    pub total: u64,
+    /// The maximum number of records on a page.
+    pub per_page: usize,
+    /// The number of the page, starting at 1.
+    pub current_page: usize,
+    /// The number of the last page, which is 1 when no records match.
+    pub last_page: usize,
+}
+
+impl<T> Paginator<T> {
+    pub(super) fn new(data: Vec<T>, total: u64, per_page: usize, current_page: usize) -> Self {
+        let total_pages = usize::try_from(total)
+            .unwrap_or(usize::MAX)
+            .div_ceil(per_page);
+
+        Self {
+            data,
+            total,
+            per_page,
+            current_page,
+            last_page: total_pages.max(1),
+        }
+    }
+
+    /// Whether there are pages after this one.
+    #[must_use]
+    pub const fn has_more_pages(&self) -> bool {
+        self.current_page < self.last_page
+    }
+}
+
+/// A page of results ordered by primary key, along with the key to retrieve the next page with.
+///
+/// Unlike [`Paginator`], it doesn't count the matching records or skip over the previous pages, so retrieving a page deep into a large table stays fast.
+#[derive(Debug, Serialize)]
+pub struct CursorPaginator<M: Model> {
+    /// The models on the page.
+    pub data: Vec<M>,
+    /// The maximum number of models on a page.
+    pub per_page: usize,
+    /// The key of the last model on the page, to pass to [`cursor_paginate`](super::Builder::cursor_paginate) for the next page.
+    /// It's `None` on the last page.
+    pub next_cursor: Option<M::PrimaryKey>,
+}
+
+impl<M: Model> CursorPaginator<M> {
+    pub(super) fn new(mut data: Vec<M>, per_page: usize) -> Self {
+        // one more model than fits is retrieved, to tell whether there's a next page
+        let next_cursor = if data.len() > per_page {
+            data.truncate(per_page);
+            data.last().map(|model| model.primary_key().clone())
+        } else {
+            None
+        };
+
+        Self {
+            data,
+            per_page,
+            next_cursor,
+        }
+    }
+
+    /// Whether there are pages after this one.
+    #[must_use]
+    pub const fn has_more_pages(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Paginator;
+
+    #[test]
+    fn the_last_page_holds_the_remaining_records() {
+        let page = Paginator::<()>::new(vec![], 31, 15, 2);
+        assert_eq!(page.last_page, 3);
+        assert!(page.has_more_pages());
+
+        let page = Paginator::<()>::new(vec![], 0, 15, 1);
+        assert_eq!(page.last_page, 1);
+        assert!(!page.has_more_pages());
+    }
+}
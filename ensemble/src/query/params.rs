@@ -0,0 +1,226 @@
+//! Named parameters for raw SQL queries.
+
+use rbs::Value;
+use serde::{ser::SerializeMap, Serialize};
+
+use crate::{value, Error};
+
+/// A set of named parameters for [`Builder::raw_sql_named`](super::Builder::raw_sql_named), usually created with the [`params!`](crate::params) macro.
+///
+/// Any other `Serialize` struct (or map) can be used as the parameters instead, with its fields as the names.
+#[derive(Debug, Default)]
+pub struct Params(Vec<(String, Result<Value, String>)>);
+
+impl Params {
+    /// Create an empty set of parameters.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Set the value of the parameter with the given name, replacing any previous value.
+    /// If the value can't be serialized, the query using the parameters returns the error instead.
+    #[must_use]
+    pub fn set<T: Serialize>(mut self, name: &str, value: T) -> Self {
+        let value = value::for_db(value).map_err(|error| error.to_string());
+
+        self.0.retain(|(existing, _)| existing != name);
+        self.0.push((name.to_string(), value));
+        self
+    }
+}
+
+impl Serialize for Params {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            let value = value.as_ref().map_err(serde::ser::Error::custom)?;
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Create a set of named [`Params`] for a raw SQL query.
+///
+/// ```rust
+/// # let email = "taylor@example.com";
+/// let params = ensemble::params! { "email" => email, "active" => true };
+/// ```
+#[macro_export]
+macro_rules! params {
+    ($($name:expr => $value:expr),* $(,)?) => {
+        $crate::query::Params::new()$(.set($name, $value))*
+    };
+}
+
+/// Replace the `:name` parameters in `sql` with positional placeholders, returning the bindings in order.
+///
+/// Parameters inside string literals, quoted identifiers and comments are left alone, as are Postgres casts (`::type`) and `MySQL` assignments (`:=`).
+pub fn bind<P: Serialize>(sql: &str, params: P) -> Result<(String, Vec<Value>), Error> {
+    let Value::Map(params) = value::for_db(params)? else {
+        return Err(Error::Serialization(rbs::Error::Syntax(
+            "The parameters of a raw query must be a struct or a map.".to_string(),
+        )));
+    };
+
+    let params = params
+        .into_iter()
+        .map(|(name, value)| (name.into_string().unwrap_or_default(), value))
+        .collect::<Vec<_>>();
+
+    let mut used = vec![false; params.len()];
+    let mut bindings = vec![];
+    let mut query = String::with_capacity(sql.len());
+
+    let chars = sql.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        let skip_until: Option<&[char]> = match (c, chars.get(i + 1)) {
+            ('\'', _) => Some(&['\'']),
+            ('"', _) => Some(&['"']),
+            ('`', _) => Some(&['`']),
+            ('-', Some('-')) => Some(&['\n']),
+            ('/', Some('*')) => Some(&['*', '/']),
+            _ => None,
+        };
+
+        if let Some(end) = skip_until {
+            let start = i;
+            i += if matches!(c, '-' | '/') { 2 } else { 1 };
+            while i < chars.len() && !chars[i..].starts_with(end) {
+                i += 1;
+            }
+            i = (i + end.len()).min(chars.len());
+
+            query.extend(&chars[start..i]);
+            continue;
+        }
+
+        if c == ':' {
+            match chars.get(i + 1) {
+                Some(':' | '=') => {
+                    query.extend(&chars[i..i + 2]);
+                    i += 2;
+                    continue;
+                }
+                Some(next) if next.is_ascii_alphabetic() || *next == '_' => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while end < chars.len()
+                        && (chars[end].is_ascii_alphanumeric() || chars[end] == '_')
+                    {
+                        end += 1;
+                    }
+
+                    let name = chars[start..end].iter().collect::<String>();
+                    let Some(index) = params.iter().position(|(param, _)| *param == name) else {
+                        return Err(Error::MissingParameter(name));
+                    };
+
+                    used[index] = true;
+                    bindings.push(params[index].1.clone());
+                    query.push('?');
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        query.push(c);
+        i += 1;
+    }
+
+    if let Some(index) = used.iter().position(|used| !used) {
+        return Err(Error::UnusedParameter(params[index].0.clone()));
+    }
+
+    Ok((query, bindings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_replaced_with_placeholders() {
+        let (sql, bindings) = bind(
+            "SELECT * FROM users WHERE email = :email AND active = :active",
+            params! { "active" => true, "email" => "taylor@example.com" },
+        )
+        .unwrap();
+
+        assert_eq!(sql, "SELECT * FROM users WHERE email = ? AND active = ?");
+        assert_eq!(
+            bindings,
+            vec![Value::from("taylor@example.com"), Value::from(true)]
+        );
+    }
+
+    #[test]
+    fn names_can_be_used_more_than_once() {
+        let (sql, bindings) = bind(
+            "SELECT * FROM posts WHERE author_id = :user OR editor_id = :user OR reviewer_id = :user_id",
+            params! { "user" => 1, "user_id" => 2 },
+        )
+        .unwrap();
+
+        assert_eq!(
+            sql,
+            "SELECT * FROM posts WHERE author_id = ? OR editor_id = ? OR reviewer_id = ?"
+        );
+        assert_eq!(bindings, vec![Value::I32(1), Value::I32(1), Value::I32(2)]);
+    }
+
+    #[test]
+    fn structs_can_be_used_as_parameters() {
+        #[derive(Serialize)]
+        struct Search {
+            name: String,
+            limit: u64,
+        }
+
+        let (sql, bindings) = bind(
+            "SELECT * FROM users WHERE name = :name LIMIT :limit",
+            Search {
+                name: "Taylor".to_string(),
+                limit: 10,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(sql, "SELECT * FROM users WHERE name = ? LIMIT ?");
+        assert_eq!(bindings, vec![Value::from("Taylor"), Value::from(10u64)]);
+    }
+
+    #[test]
+    fn quotes_comments_and_casts_are_left_alone() {
+        let (sql, bindings) = bind(
+            "SELECT ':skipped', \"a:b\", created_at::date, @n := 1 FROM t -- :comment\nWHERE id = :id /* :also */",
+            params! { "id" => 1 },
+        )
+        .unwrap();
+
+        assert_eq!(
+            sql,
+            "SELECT ':skipped', \"a:b\", created_at::date, @n := 1 FROM t -- :comment\nWHERE id = ? /* :also */"
+        );
+        assert_eq!(bindings, vec![Value::I32(1)]);
+    }
+
+    #[test]
+    fn missing_and_unused_names_are_errors() {
+        let error = bind("SELECT * FROM users WHERE id = :id", Params::new()).unwrap_err();
+        assert!(matches!(error, Error::MissingParameter(name) if name == "id"));
+
+        let error = bind(
+            "SELECT * FROM users WHERE id = :id",
+            params! { "id" => 1, "email" => "taylor@example.com" },
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::UnusedParameter(name) if name == "email"));
+    }
+}
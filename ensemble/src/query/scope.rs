@@ -0,0 +1,33 @@
+//! Constraints applied to every query for a model, set with `#[ensemble(global_scope = "...")]`.
+
+use super::Builder;
+
+/// A global scope of a model, adding its constraints to every query for the model, including the ones loading it as a relationship.
+///
+/// Scopes are functions taking a query and returning it constrained, the same kind of function [`Builder::scope`] applies to a single query:
+///
+/// ```rust
+/// # use ensemble::{query::{Builder, Type}, Model};
+/// #[derive(Debug, Model)]
+/// #[ensemble(global_scope = "Post::published")]
+/// struct Post {
+///     id: u64,
+///     published: bool,
+/// }
+///
+/// impl Post {
+///     fn published(query: Builder) -> Builder {
+///         query.r#where("published", "=", true)
+///     }
+/// }
+///
+/// assert_eq!(Post::query().to_sql(Type::Select), "SELECT * FROM posts WHERE published = ?");
+/// assert_eq!(Post::without_global_scope("Post::published").to_sql(Type::Select), "SELECT * FROM posts");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalScope {
+    /// The name of the scope, which is the path it was set with. Used to leave the scope out of a query with [`without_global_scope`](crate::Model::without_global_scope).
+    pub name: &'static str,
+    /// Adds the scope's constraints to a query.
+    pub apply: fn(Builder) -> Builder,
+}
@@ -0,0 +1,296 @@
+//! Keeping the values bound to queries out of logs and error messages.
+//!
+//! Values bound to columns marked with `#[model(sensitive)]` (like passwords or API tokens) are always shown as `[REDACTED]`:
+//! in the `db.bindings` field of query spans, in the events the query builder logs before running a query, in the `Debug` output of the query builder, and in the errors returned by the database (like the value of a unique violation).
+//! Every other value is shown according to the policy set with [`set_binding_logging`], which shows them in full by default.
+//!
+//! Values are redacted wherever they're logged, and again right before they are sent to the database, so statements built by hand don't skip the policy.
+//! Only values bound through the query builder know the column they belong to, though: values bound to [raw queries](crate::query::Builder::raw_sql)
+//! are never marked as sensitive, so use [`BindingLogging::Redacted`] if those can hold secrets too.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use ensemble::{redaction::BindingLogging, Model};
+//! #[derive(Debug, Model)]
+//! struct User {
+//!     id: u64,
+//!     email: String,
+//!     #[model(sensitive)]
+//!     password: String,
+//! }
+//!
+//! let query = User::query().r#where("password", "=", "hunter2");
+//! assert!(!format!("{query:?}").contains("hunter2"));
+//!
+//! // hide the email addresses too
+//! ensemble::set_binding_logging(BindingLogging::Redacted);
+//!
+//! let query = User::query().r#where("email", "=", "taylor@example.com");
+//! assert!(!format!("{query:?}").contains("taylor@example.com"));
+//! ```
+
+use itertools::Itertools;
+use rbs::Value;
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::Error;
+
+/// What sensitive values are replaced with.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// The name of the extension sensitive values are wrapped in, until they are bound to a statement.
+const SENSITIVE: &str = "ensemble::Sensitive";
+
+/// Shorter values are not scrubbed from error messages, since they would also match parts of the message itself.
+const MIN_SECRET_LEN: usize = 3;
+
+static POLICY: AtomicU8 = AtomicU8::new(BindingLogging::Full as u8);
+
+/// How the values bound to queries are shown in logs and error messages. Values of sensitive columns are always redacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum BindingLogging {
+    /// Leave the values out entirely.
+    None,
+    /// Replace every value with `[REDACTED]`, keeping only how many there are.
+    Redacted,
+    /// Show every value that isn't sensitive.
+    #[default]
+    Full,
+}
+
+/// Set how the values bound to queries are shown in logs and error messages, for the whole process.
+pub fn set_binding_logging(policy: BindingLogging) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// How the values bound to queries are currently shown.
+#[must_use]
+pub fn binding_logging() -> BindingLogging {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => BindingLogging::None,
+        1 => BindingLogging::Redacted,
+        _ => BindingLogging::Full,
+    }
+}
+
+/// Mark `value` as sensitive. The values of arrays (like those of `IN` clauses) are marked one by one, and expressions are left alone.
+pub(crate) fn mark(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(mark).collect()),
+        value if crate::query::expr::sql(&value).is_some() || is_sensitive(&value) => value,
+        value => Value::Ext(SENSITIVE, Box::new(value)),
+    }
+}
+
+/// Remove the sensitive marker from `value`, if it has one.
+pub(crate) fn unmark(value: Value) -> Value {
+    match value {
+        Value::Ext(SENSITIVE, value) => *value,
+        Value::Array(values) => Value::Array(values.into_iter().map(unmark).collect()),
+        value => value,
+    }
+}
+
+fn is_sensitive(value: &Value) -> bool {
+    matches!(value, Value::Ext(SENSITIVE, _))
+}
+
+/// The values bound to a statement, as they can be shown in logs and error messages.
+///
+/// These are taken from the bindings as they're sent to the database, and only ever hold the values the policy allows showing.
+pub(crate) struct Bindings {
+    policy: BindingLogging,
+    values: Vec<Option<Value>>,
+    /// The values to scrub from error messages, longest first so overlapping values are scrubbed whole.
+    secrets: Vec<String>,
+}
+
+impl Bindings {
+    /// Split `bindings` into the values sent to the database and the ones that can be shown, following `policy`.
+    pub(crate) fn split(bindings: Vec<Value>, policy: BindingLogging) -> (Vec<Value>, Self) {
+        let mut secrets = vec![];
+
+        let (bindings, values): (Vec<_>, Vec<_>) = bindings
+            .into_iter()
+            .map(|value| {
+                let shown = !is_sensitive(&value) && policy == BindingLogging::Full;
+                let value = unmark(value);
+
+                if shown {
+                    (value.clone(), Some(value))
+                } else {
+                    secrets.extend(text(&value));
+                    (value, None)
+                }
+            })
+            .unzip();
+
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+
+        (
+            bindings,
+            Self {
+                policy,
+                values,
+                secrets,
+            },
+        )
+    }
+
+    /// Whether the values can be shown at all.
+    #[must_use]
+    pub(crate) const fn is_shown(&self) -> bool {
+        !matches!(self.policy, BindingLogging::None)
+    }
+
+    /// Replace the values that can't be shown in an error message with `[REDACTED]`.
+    #[must_use]
+    pub(crate) fn scrub(&self, message: &str) -> String {
+        self.secrets
+            .iter()
+            .fold(message.to_string(), |message, secret| {
+                message.replace(secret.as_str(), REDACTED)
+            })
+    }
+
+    /// Scrub the values that can't be shown from `error`.
+    pub(crate) fn scrub_error(&self, error: Error) -> Error {
+        match error {
+            Error::Database(message) => Error::Database(self.scrub(&message)),
+            Error::OutOfRange { value, target } => Error::OutOfRange {
+                value: self.scrub(&value),
+                target,
+            },
+            error => error,
+        }
+    }
+}
+
+impl Debug for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.is_shown() {
+            return f.write_str(REDACTED);
+        }
+
+        let values = self.values.iter().map(|value| {
+            value
+                .as_ref()
+                .map_or_else(|| REDACTED.to_string(), |value| format!("{value:?}"))
+        });
+
+        write!(f, "[{}]", values.format(", "))
+    }
+}
+
+/// A value as it can be shown in the `Debug` output of a query, following the current policy.
+pub(crate) struct Shown<'a>(pub &'a Value);
+
+impl Debug for Shown<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Value::Array(values) => f
+                .debug_list()
+                .entries(values.iter().map(Shown))
+                .finish(),
+            value if is_sensitive(value) || binding_logging() != BindingLogging::Full => {
+                f.write_str(REDACTED)
+            }
+            value => Debug::fmt(value, f),
+        }
+    }
+}
+
+/// The values bound to a statement as they can be shown in the `Debug` output of a log event, following the current policy.
+///
+/// Use this instead of the bindings themselves anywhere they're logged, since sensitive values are only unwrapped right before they're sent to the database.
+pub(crate) struct Logged<'a>(pub &'a [Value]);
+
+impl Debug for Logged<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if binding_logging() == BindingLogging::None {
+            return f.write_str(REDACTED);
+        }
+
+        f.debug_list().entries(self.0.iter().map(Shown)).finish()
+    }
+}
+
+/// The text `value` would appear as in an error message, if it's long enough to be told apart from the rest of the message.
+fn text(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(values) => values.iter().flat_map(text).collect(),
+        Value::Ext(_, value) => text(value),
+        Value::String(value) => vec![value.clone()],
+        Value::Binary(value) => String::from_utf8(value.clone()).into_iter().collect(),
+        Value::I32(_) | Value::I64(_) | Value::U32(_) | Value::U64(_) | Value::F32(_) | Value::F64(_) => {
+            vec![value.to_string()]
+        }
+        Value::Null | Value::Bool(_) | Value::Map(_) => vec![],
+    }
+    .into_iter()
+    .filter(|text| text.len() >= MIN_SECRET_LEN)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(policy: BindingLogging) -> Bindings {
+        let (_, bindings) = Bindings::split(
+            vec![
+                Value::from("taylor@example.com"),
+                mark(Value::from("hunter2")),
+            ],
+            policy,
+        );
+
+        bindings
+    }
+
+    #[test]
+    fn sensitive_values_are_always_redacted() {
+        let logged = format!("{:?}", bindings(BindingLogging::Full));
+
+        assert!(!logged.contains("hunter2"));
+        assert_eq!(logged, r#"[String("taylor@example.com"), [REDACTED]]"#);
+    }
+
+    #[test]
+    fn the_policy_redacts_every_other_value() {
+        let redacted = format!("{:?}", bindings(BindingLogging::Redacted));
+        assert_eq!(redacted, "[[REDACTED], [REDACTED]]");
+
+        let none = bindings(BindingLogging::None);
+        assert!(!none.is_shown());
+        assert_eq!(format!("{none:?}"), "[REDACTED]");
+    }
+
+    #[test]
+    fn markers_are_removed_before_binding() {
+        let (values, _) = Bindings::split(
+            vec![mark(Value::Array(vec![Value::from("a"), Value::from(1)]))],
+            BindingLogging::Full,
+        );
+
+        assert_eq!(values, vec![Value::Array(vec![Value::from("a"), Value::from(1)])]);
+    }
+
+    #[test]
+    fn secrets_are_scrubbed_from_errors() {
+        let error = Error::Database("Duplicate entry 'hunter2' for key 'users.password'".to_string());
+        let message = bindings(BindingLogging::Full).scrub_error(error).to_string();
+
+        assert!(!message.contains("hunter2"));
+        assert_eq!(message, "Duplicate entry '[REDACTED]' for key 'users.password'");
+
+        let error = Error::Database("Duplicate entry 'taylor@example.com' for key 'users.email'".to_string());
+        let message = bindings(BindingLogging::Redacted).scrub_error(error).to_string();
+        assert!(!message.contains("taylor@example.com"));
+    }
+}
@@ -3,8 +3,11 @@ use rbs::Value;
 use serde::Serialize;
 use std::{collections::HashMap, fmt::Debug};
 
-use super::{find_related, Relationship, Status};
-use crate::{query::Builder, value::serializing_for_db, Error, Model};
+use super::{
+    cache_one, exists_in, find_related, restore_one, RelationDef, RelationKind, Relationship,
+    Status,
+};
+use crate::{query::Builder, strict, value::serializing_for_db, Error, Model};
 
 /// ## A Belongs To relationship.
 /// A belongs to relationship is used to define relationships where a model is the child to a single models. For example, a website may belong to a user.
@@ -36,7 +39,9 @@ use crate::{query::Builder, value::serializing_for_db, Error, Model};
 #[derive(Clone, Default)]
 pub struct BelongsTo<Local: Model, Related: Model> {
     local_key: String,
+    name: &'static str,
     relation: Status<Related>,
+    exists: Option<bool>,
     _local: std::marker::PhantomData<Local>,
     /// The value of the local model's related key.
     pub value: Related::PrimaryKey,
@@ -54,15 +59,31 @@ impl<Local: Model, Related: Model> Relationship for BelongsTo<Local, Related> {
         Self {
             value,
             local_key,
+            name: Related::TABLE_NAME,
             relation: Status::initial(),
+            exists: None,
             _local: std::marker::PhantomData,
         }
     }
 
+    fn definition(&self) -> RelationDef {
+        RelationDef {
+            name: self.name,
+            kind: RelationKind::BelongsTo,
+            related: Related::NAME,
+            related_table: Related::QUALIFIED_TABLE,
+            // the foreign key is a column of the local model, which fills it in
+            foreign_key: String::new(),
+            local_key: self.local_key.clone(),
+            pivot_table: None,
+            morph_type: None,
+        }
+    }
+
     fn query(&self) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.local_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.local_key),
                 "=",
                 self.value.clone(),
             )
@@ -70,8 +91,12 @@ impl<Local: Model, Related: Model> Relationship for BelongsTo<Local, Related> {
     }
 
     /// Get the related model.
-    async fn get(&mut self) -> Result<&mut Self::Value, Error> {
+    async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error> {
         if self.relation.is_none() {
+            if !self.relation.is_loaded() {
+                strict::ensure_lazy_allowed(Local::NAME, self.name)?;
+            }
+
             let relation = self.query().first().await?.ok_or(Error::NotFound)?;
 
             self.relation = Status::Fetched(Some(relation));
@@ -87,13 +112,42 @@ impl<Local: Model, Related: Model> Relationship for BelongsTo<Local, Related> {
     fn eager_query(&self, related: Vec<Self::Key>) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.local_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.local_key),
                 "in",
                 related,
             )
             .limit(1)
     }
 
+    fn exists_query(&self, related: Vec<Self::Key>) -> Builder {
+        Related::query()
+            .r#where(
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.local_key),
+                "in",
+                related,
+            )
+            .distinct(&format!("{}.{}", Related::QUALIFIED_TABLE, self.local_key))
+    }
+
+    fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error> {
+        self.exists = Some(exists_in(keys, &self.value)?);
+
+        Ok(())
+    }
+
+    fn exists(&self) -> Option<bool> {
+        self.exists
+    }
+
+    fn cached(&self) -> Result<Option<Value>, Error> {
+        cache_one(&self.relation)
+    }
+
+    fn restore(&mut self, value: Value) -> Result<(), Error> {
+        self.relation = restore_one(value)?;
+        Ok(())
+    }
+
     fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error> {
         let related = find_related(related, &self.local_key, &self.value, true)?;
 
@@ -103,6 +157,16 @@ impl<Local: Model, Related: Model> Relationship for BelongsTo<Local, Related> {
     }
 }
 
+impl<Local: Model, Related: Model> BelongsTo<Local, Related> {
+    #[doc(hidden)]
+    /// Set the name of the field holding the relationship, used in errors. Not intended to be used directly.
+    #[must_use]
+    pub const fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+}
+
 impl<Local: Model, Related: Model> Debug for BelongsTo<Local, Related> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.relation.fmt(f)
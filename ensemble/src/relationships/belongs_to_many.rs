@@ -1,10 +1,19 @@
-use inflector::Inflector;
 use rbs::Value;
 use serde::Serialize;
 use std::{collections::HashMap, fmt::Debug};
 
-use super::{find_related, Relationship, Status};
-use crate::{query::Builder, value::serializing_for_db, Error, Model};
+use super::{
+    cache_many, ensure_persisted, exists_in, qualified, restore_many, RelationDef, RelationKind,
+    Relationship, Status,
+};
+use crate::{
+    naming,
+    query::{Builder, Row},
+    strict,
+    types::DateTime,
+    value::{self, serializing_for_db},
+    Error, FromRow, Model,
+};
 
 /// ## A Many to Many relationship.
 /// A many to many relationship is used to define relationships where a model is the parent of one or more child models, but can also be a child to multiple parent models.
@@ -37,12 +46,35 @@ pub struct BelongsToMany<Local: Model, Related: Model> {
     local_key: String,
     foreign_key: String,
     pivot_table: String,
+    timestamps: bool,
+    morph: Option<Morph>,
+    name: &'static str,
+    pivot_columns: Vec<&'static str>,
     relation: Status<Vec<Related>>,
+    /// The selected pivot columns of the loaded models, by primary key.
+    pivots: Vec<(String, Row)>,
+    exists: Option<bool>,
     _local: std::marker::PhantomData<Local>,
     /// The value of the local model's primary key.
     pub value: Related::PrimaryKey,
 }
 
+/// The type column of a polymorphic pivot table, and the type the relationship's rows are marked with.
+#[derive(Debug, Clone)]
+struct Morph {
+    column: String,
+    r#type: &'static str,
+}
+
+/// The changes made by [`BelongsToMany::sync`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Synced<K> {
+    /// The keys of the models that were attached.
+    pub attached: Vec<K>,
+    /// The keys of the models that were detached.
+    pub detached: Vec<K>,
+}
+
 #[async_trait::async_trait]
 impl<Local: Model, Related: Model> Relationship for BelongsToMany<Local, Related> {
     type Value = Vec<Related>;
@@ -50,51 +82,69 @@ impl<Local: Model, Related: Model> Relationship for BelongsToMany<Local, Related
     type RelatedKey = (Option<String>, Option<String>, Option<String>);
 
     fn build(value: Self::Key, (pivot_table, foreign_key, local_key): Self::RelatedKey) -> Self {
+        // pivot tables are kept in the schema of the model defining the relationship
         let pivot_table = pivot_table.unwrap_or_else(|| {
-            let mut names = [Local::NAME.to_string(), Related::NAME.to_string()];
-            names.sort();
-            names.join("_").to_snake_case()
+            qualified(
+                Local::SCHEMA,
+                naming::strategy().pivot_table(Local::NAME, Related::NAME),
+            )
         });
 
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_{}", Related::NAME.to_snake_case(), Related::PRIMARY_KEY).to_snake_case()
-        });
+        let foreign_key = foreign_key
+            .unwrap_or_else(|| naming::strategy().foreign_key(Related::NAME, Related::PRIMARY_KEY));
 
-        let local_key = local_key.unwrap_or_else(|| {
-            format!("{}_{}", Local::NAME.to_snake_case(), Local::PRIMARY_KEY).to_snake_case()
-        });
+        let local_key = local_key
+            .unwrap_or_else(|| naming::strategy().foreign_key(Local::NAME, Local::PRIMARY_KEY));
 
         Self {
             value,
             local_key,
             foreign_key,
             pivot_table,
+            timestamps: false,
+            morph: None,
+            name: Related::TABLE_NAME,
+            pivot_columns: vec![],
             relation: Status::initial(),
+            pivots: vec![],
+            exists: None,
             _local: std::marker::PhantomData,
         }
     }
 
+    fn definition(&self) -> RelationDef {
+        RelationDef {
+            name: self.name,
+            kind: RelationKind::BelongsToMany,
+            related: Related::NAME,
+            related_table: Related::QUALIFIED_TABLE,
+            foreign_key: self.foreign_key.clone(),
+            local_key: self.local_key.clone(),
+            pivot_table: Some(self.pivot_table.clone()),
+            morph_type: self
+                .morph
+                .as_ref()
+                .map(|morph| (morph.column.clone(), morph.r#type)),
+        }
+    }
+
     fn query(&self) -> Builder {
-        Related::query()
-            .from(Related::TABLE_NAME)
-            .join(
-                &self.pivot_table,
-                &format!("{}.{}", Related::TABLE_NAME, Related::PRIMARY_KEY),
-                "=",
-                &format!("{}.{}", self.pivot_table, self.foreign_key),
-            )
-            .r#where(
-                &format!("{}.{}", self.pivot_table, self.local_key),
-                "=",
-                self.value.clone(),
-            )
+        self.with_pivot_columns(self.joined(self.join_pivot().r#where(
+            &format!("{}.{}", self.pivot_table, self.local_key),
+            "=",
+            self.value.clone(),
+        )))
     }
 
-    async fn get(&mut self) -> Result<&mut Self::Value, Error> {
+    async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error> {
         if self.relation.is_none() {
-            let relation = self.query().get().await?;
+            if !self.relation.is_loaded() {
+                strict::ensure_lazy_allowed(Local::NAME, self.name)?;
+            }
 
-            self.relation = Status::Fetched(Some(relation));
+            let rows = self.query().get_rows().await?;
+
+            self.hydrate(rows.iter())?;
         }
 
         Ok(self.relation.as_mut().unwrap())
@@ -105,30 +155,669 @@ impl<Local: Model, Related: Model> Relationship for BelongsToMany<Local, Related
     }
 
     fn eager_query(&self, related: Vec<Self::Key>) -> Builder {
-        Related::query()
-            .from(Related::TABLE_NAME)
-            .join(
-                &self.pivot_table,
-                &format!("{}.{}", Related::TABLE_NAME, Related::PRIMARY_KEY),
-                "=",
-                &format!("{}.{}", self.pivot_table, self.foreign_key),
-            )
-            .r#where(
-                &format!("{}.{}", self.pivot_table, self.local_key),
-                "in",
-                related,
-            )
+        self.with_pivot_columns(self.joined(self.join_pivot().r#where(
+            &format!("{}.{}", self.pivot_table, self.local_key),
+            "in",
+            related,
+        )))
+    }
+
+    fn exists_query(&self, related: Vec<Self::Key>) -> Builder {
+        let local_key = format!("{}.{}", self.pivot_table, self.local_key);
+
+        self.joined(self.join_pivot().r#where(&local_key, "in", related))
+            .distinct(&local_key)
+    }
+
+    fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error> {
+        self.exists = Some(exists_in(keys, &self.value)?);
+
+        Ok(())
+    }
+
+    fn exists(&self) -> Option<bool> {
+        self.exists
+    }
+
+    fn cached(&self) -> Result<Option<Value>, Error> {
+        cache_many(&self.relation)
+    }
+
+    fn restore(&mut self, value: Value) -> Result<(), Error> {
+        self.relation = restore_many(value)?;
+        self.pivots.clear();
+        Ok(())
     }
 
     fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error> {
-        let related = find_related(related, &self.foreign_key, &self.value, false)?;
+        let value = value::for_db(&self.value)?.to_string();
+        let local_key = self.local_key.clone();
+
+        self.hydrate(related.iter().filter(|row| {
+            row.get(&local_key)
+                .is_some_and(|key| key.to_string() == value)
+        }))
+    }
+}
+
+impl<Local: Model, Related: Model> BelongsToMany<Local, Related> {
+    #[doc(hidden)]
+    /// Set the name of the field holding the relationship, used in errors. Not intended to be used directly.
+    #[must_use]
+    pub const fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Fill the `created_at` and `updated_at` columns of the pivot rows inserted through this relationship.
+    /// Enabled with the `#[model(pivot_timestamps)]` attribute on the relationship field.
+    #[must_use]
+    pub const fn with_timestamps(mut self) -> Self {
+        self.timestamps = true;
+        self
+    }
+
+    /// Select the given columns of the pivot table along with the related models, to be read with [`pivot`](Self::pivot) once the relationship is loaded.
+    /// Enabled with the `#[model(with_pivot = ["column"])]` attribute on the relationship field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the columns isn't a plain column name, made of letters, digits and `_`.
+    #[must_use]
+    pub fn with_pivot(mut self, columns: impl IntoIterator<Item = &'static str>) -> Self {
+        for column in columns {
+            assert!(
+                !column.is_empty()
+                    && column
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "The pivot column `{column}` is invalid."
+            );
 
-        if !related.is_empty() {
-            self.relation = Status::Fetched(Some(related));
+            self.pivot_columns.push(column);
         }
 
+        self
+    }
+
+    /// Only join the pivot rows whose `column` holds `type`, and fill it in for the rows inserted through this relationship.
+    pub(super) fn morph(mut self, column: String, r#type: &'static str) -> Self {
+        self.morph = Some(Morph { column, r#type });
+        self
+    }
+
+    /// The columns of the pivot row attaching the loaded `Related` model with the given primary key to the parent.
+    /// Only the columns passed to [`with_pivot`](Self::with_pivot) are available, and `None` is returned until the relationship is loaded or if the model isn't part of it.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// # }
+    /// #[derive(Debug, Model, Clone)]
+    /// struct User {
+    ///   id: u64,
+    ///   #[model(with_pivot = ["expires_at"])]
+    ///   roles: BelongsToMany<User, Role>
+    /// }
+    ///
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    /// user.roles().await?;
+    ///
+    /// let expires_at = user.roles.pivot(&2).and_then(|pivot| pivot.get("expires_at"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn pivot(&self, related: &Related::PrimaryKey) -> Option<&Row> {
+        let key = value::for_db(related).ok()?.to_string();
+
+        self.pivots
+            .iter()
+            .find(|(pivot, _)| *pivot == key)
+            .map(|(_, row)| row)
+    }
+
+    /// A query on the related models, joined with the pivot table.
+    fn join_pivot(&self) -> Builder {
+        Related::query().from(Related::QUALIFIED_TABLE).join(
+            &self.pivot_table,
+            &format!("{}.{}", Related::QUALIFIED_TABLE, Related::PRIMARY_KEY),
+            "=",
+            &format!("{}.{}", self.pivot_table, self.foreign_key),
+        )
+    }
+
+    /// Constrain a query joining the pivot table to the relationship's type, if it's polymorphic.
+    fn joined(&self, query: Builder) -> Builder {
+        let Some(morph) = &self.morph else {
+            return query;
+        };
+
+        query.r#where(
+            &format!("{}.{}", self.pivot_table, morph.column),
+            "=",
+            morph.r#type,
+        )
+    }
+
+    /// Select the requested pivot columns next to the related model's, prefixed with `pivot_` so they can't collide with them.
+    fn with_pivot_columns(&self, query: Builder) -> Builder {
+        if self.pivot_columns.is_empty() {
+            return query;
+        }
+
+        let query = query
+            .select_column(format!("{}.*", Related::QUALIFIED_TABLE))
+            .select_column(format!("{}.{}", self.pivot_table, self.local_key));
+
+        self.pivot_columns.iter().fold(query, |query, column| {
+            query.select_column(format!("{}.{column} AS pivot_{column}", self.pivot_table))
+        })
+    }
+
+    /// Load the related models from rows joined with the pivot table, keeping their pivot columns aside.
+    fn hydrate<'a>(
+        &mut self,
+        rows: impl Iterator<Item = &'a HashMap<String, Value>>,
+    ) -> Result<(), Error> {
+        let mut related = vec![];
+        let mut pivots = vec![];
+
+        for row in rows {
+            let model: Related = without_pivot(row)?;
+
+            if !self.pivot_columns.is_empty() {
+                let pivot = self
+                    .pivot_columns
+                    .iter()
+                    .filter_map(|column| {
+                        row.get(&format!("pivot_{column}"))
+                            .map(|value| ((*column).to_string(), value.clone()))
+                    })
+                    .collect();
+
+                pivots.push((value::for_db(model.primary_key())?.to_string(), pivot));
+            }
+
+            related.push(model);
+        }
+
+        self.relation = Status::Fetched(Some(related));
+        self.pivots = pivots;
+
         Ok(())
     }
+
+    /// Forget the loaded models, once the pivot table has changed.
+    fn forget(&mut self) {
+        self.relation = Status::initial();
+        self.pivots.clear();
+    }
+
+    /// A query on the pivot rows of the parent.
+    fn pivot_query(&self) -> Builder {
+        let query = Builder::new(self.pivot_table.clone()).r#where(
+            &self.local_key,
+            "=",
+            self.value.clone(),
+        );
+
+        match &self.morph {
+            Some(morph) => query.r#where(&morph.column, "=", morph.r#type),
+            None => query,
+        }
+    }
+
+    /// Retrieve the rows of the pivot table attaching models to the parent, hydrated into `T`.
+    /// This gives access to the extra columns of the pivot table, like the ones filled by [`attach_with`](Self::attach_with).
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the query fails, if a row cannot be hydrated into `T`, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{FromRow, Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  roles: BelongsToMany<User, Role>
+    /// # }
+    /// #[derive(Debug, FromRow)]
+    /// struct Assignment {
+    ///     role_id: u64,
+    ///     assigned_by: u64,
+    /// }
+    ///
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let user = User::find(1).await?;
+    ///
+    /// let assignments: Vec<Assignment> = user.roles.pivots().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pivots<T: FromRow>(&self) -> Result<Vec<T>, Error> {
+        self.pivot_query().get_as().await
+    }
+
+    /// Attach the `Related` model with the given primary key to the parent, by inserting a row into the pivot table.
+    /// If the model is already attached, no row is inserted. Returns whether the model was attached.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot row cannot be inserted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  roles: BelongsToMany<User, Role>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    ///
+    /// user.roles.attach(2).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn attach(&mut self, related: Related::PrimaryKey) -> Result<bool, Error> {
+        self.attach_with(related, Vec::<(&str, Value)>::new()).await
+    }
+
+    /// Like [`attach`](Self::attach), but also filling the given columns of the pivot row.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot data cannot be serialized, if the pivot row cannot be inserted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  roles: BelongsToMany<User, Role>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    ///
+    /// user.roles.attach_with(2, [("assigned_by", 1)]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn attach_with<'a, T: Serialize>(
+        &mut self,
+        related: Related::PrimaryKey,
+        pivot: impl IntoIterator<Item = (&'a str, T)> + Send,
+    ) -> Result<bool, Error> {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let pivot = pivot
+            .into_iter()
+            .map(|(column, value)| Ok((column.to_string(), value::for_db(value)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let attached = self.attach_missing(vec![related], &pivot).await?;
+
+        Ok(!attached.is_empty())
+    }
+
+    /// Attach every `Related` model with one of the given primary keys that isn't attached to the parent yet.
+    /// Unlike a full sync, models missing from `related` are never detached. Returns the keys of the models that were attached.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot rows cannot be inserted, or if a connection to the database cannot be established.
+    /// The pivot rows are inserted one after another, so the models attached before a failure stay attached.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Tag {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Post {
+    /// #  id: u64,
+    /// #  tags: BelongsToMany<Post, Tag>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut post = Post::find(1).await?;
+    ///
+    /// // tags 1 and 3 are added, even if the post already had tag 2
+    /// let attached = post.tags.sync_without_detaching(vec![1, 2, 3]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sync_without_detaching(
+        &mut self,
+        related: Vec<Related::PrimaryKey>,
+    ) -> Result<Vec<Related::PrimaryKey>, Error> {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        self.attach_missing(related, &[]).await
+    }
+
+    /// Detach the `Related` models with the given primary keys from the parent, by deleting their rows from the pivot table.
+    /// Returns the number of pivot rows deleted. When `related` is empty, nothing is detached and no query is run.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot rows cannot be deleted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  roles: BelongsToMany<User, Role>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    ///
+    /// user.roles.detach(vec![2, 3]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn detach(&mut self, related: Vec<Related::PrimaryKey>) -> Result<u64, Error> {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        if related.is_empty() {
+            return Ok(0);
+        }
+
+        let detached = self
+            .pivot_query()
+            .r#where(&self.foreign_key, "in", related)
+            .delete()
+            .await?;
+
+        if detached > 0 {
+            self.forget();
+        }
+
+        Ok(detached)
+    }
+
+    /// Detach every `Related` model from the parent. Returns the number of pivot rows deleted.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot rows cannot be deleted, or if a connection to the database cannot be established.
+    pub async fn detach_all(&mut self) -> Result<u64, Error> {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let detached = self.pivot_query().delete().await?;
+        self.forget();
+
+        Ok(detached)
+    }
+
+    /// Make the `Related` models with the given primary keys the only ones attached to the parent,
+    /// detaching the ones missing from `related` and attaching the ones that aren't attached yet.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the pivot rows cannot be read, deleted or inserted, or if a connection to the database cannot be established.
+    /// Models are detached before any are attached, and the changes aren't made in a transaction.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Tag {
+    /// #   id: u64,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Post {
+    /// #  id: u64,
+    /// #  tags: BelongsToMany<Post, Tag>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut post = Post::find(1).await?;
+    ///
+    /// let changes = post.tags.sync(vec![1, 2]).await?;
+    /// println!("attached {:?}, detached {:?}", changes.attached, changes.detached);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sync(
+        &mut self,
+        related: Vec<Related::PrimaryKey>,
+    ) -> Result<Synced<Related::PrimaryKey>, Error> {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let mut detached = vec![];
+        for row in self
+            .pivot_query()
+            .distinct(&self.foreign_key)
+            .get_rows()
+            .await?
+        {
+            let Some(key) = row.into_values().next() else {
+                continue;
+            };
+
+            let key: Related::PrimaryKey = rbs::from_value(key)?;
+            if !related.contains(&key) {
+                detached.push(key);
+            }
+        }
+
+        if !detached.is_empty() {
+            self.pivot_query()
+                .r#where(&self.foreign_key, "in", detached.clone())
+                .delete()
+                .await?;
+
+            self.forget();
+        }
+
+        let attached = self.attach_missing(related, &[]).await?;
+
+        Ok(Synced { attached, detached })
+    }
+
+    async fn attach_missing(
+        &mut self,
+        related: Vec<Related::PrimaryKey>,
+        pivot: &[(String, Value)],
+    ) -> Result<Vec<Related::PrimaryKey>, Error> {
+        let mut missing: Vec<Related::PrimaryKey> = Vec::with_capacity(related.len());
+        for key in related {
+            if !missing.contains(&key) {
+                missing.push(key);
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+
+        let attached = self
+            .pivot_query()
+            .r#where(&self.foreign_key, "in", missing.clone())
+            .distinct(&self.foreign_key)
+            .get_rows()
+            .await?
+            .into_iter()
+            .filter_map(|row| row.into_values().next())
+            .collect::<Vec<_>>();
+
+        let mut inserted = Vec::with_capacity(missing.len());
+        for key in missing {
+            if exists_in(&attached, &key)? {
+                continue;
+            }
+
+            self.insert_pivot(&key, pivot).await?;
+            inserted.push(key);
+        }
+
+        if !inserted.is_empty() {
+            // the loaded models no longer match the pivot table
+            self.forget();
+        }
+
+        Ok(inserted)
+    }
+
+    async fn insert_pivot<K: Serialize + Sync>(
+        &self,
+        related: &K,
+        pivot: &[(String, Value)],
+    ) -> Result<(), Error> {
+        let mut columns = vec![
+            (self.local_key.as_str(), value::for_db(&self.value)?),
+            (self.foreign_key.as_str(), value::for_db(related)?),
+        ];
+
+        if let Some(morph) = &self.morph {
+            columns.push((morph.column.as_str(), value::for_db(morph.r#type)?));
+        }
+
+        columns.extend(
+            pivot
+                .iter()
+                .map(|(column, value)| (column.as_str(), value.clone())),
+        );
+
+        if self.timestamps {
+            let now = value::for_db(DateTime::now())?;
+
+            columns.push(("created_at", now.clone()));
+            columns.push(("updated_at", now));
+        }
+
+        Builder::new(self.pivot_table.clone())
+            .insert::<Value, _>(columns)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new `Related` model, and attach it to the parent by inserting a row into the pivot table.
+    /// Both inserts run in a transaction, so the model isn't left behind unattached if the pivot row can't be inserted.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the model or the pivot row cannot be inserted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::BelongsToMany};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Role {
+    /// #   id: u64,
+    /// #   name: String,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  roles: BelongsToMany<User, Role>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    ///
+    /// let role = user.roles.create(Role {
+    ///  name: "Editor".to_string(),
+    ///  ..Default::default()
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    pub async fn create(&mut self, related: Related) -> Result<Related, Error>
+    where
+        Related: Clone,
+    {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let result = crate::transaction(|| self.create_attached(related)).await?;
+
+        if let Status::Fetched(Some(relation)) = &mut self.relation {
+            relation.push(result.clone());
+        }
+
+        Ok(result)
+    }
+
+    /// Create multiple `Related` models, attaching each of them to the parent.
+    /// The models are inserted one after another in a single transaction, so the first failure rolls back every model and pivot row inserted before it.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if any of the models or pivot rows cannot be inserted, or if a connection to the database cannot be established.
+    pub async fn create_many(&mut self, related: Vec<Related>) -> Result<Vec<Related>, Error>
+    where
+        Related: Clone,
+    {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let created = crate::transaction(|| async {
+            let mut created = Vec::with_capacity(related.len());
+            for model in related {
+                created.push(self.create_attached(model).await?);
+            }
+
+            Ok::<_, Error>(created)
+        })
+        .await?;
+
+        if let Status::Fetched(Some(relation)) = &mut self.relation {
+            relation.extend(created.iter().cloned());
+        }
+
+        Ok(created)
+    }
+
+    /// Insert `related` and its pivot row, without updating the loaded models.
+    async fn create_attached(&self, related: Related) -> Result<Related, Error> {
+        let related = Related::create(related).await?;
+        self.insert_pivot(related.primary_key(), &[]).await?;
+
+        Ok(related)
+    }
+}
+
+/// Deserialize a related model from a row joined with the pivot table, leaving out the pivot's columns.
+fn without_pivot<M: Model>(row: &HashMap<String, Value>) -> Result<M, Error> {
+    let columns = row
+        .iter()
+        .filter(|(column, _)| M::columns().iter().any(|def| def.name == column.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    Ok(value::from::<M>(value::for_db(columns)?)?)
 }
 
 impl<Local: Model, Related: Model> Debug for BelongsToMany<Local, Related> {
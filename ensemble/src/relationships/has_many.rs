@@ -1,14 +1,12 @@
-use inflector::Inflector;
 use rbs::Value;
 use serde::Serialize;
 use std::{collections::HashMap, fmt::Debug};
 
-use super::{find_related, Relationship, Status};
-use crate::{
-    query::Builder,
-    value::{self, serializing_for_db},
-    Error, Model,
+use super::{
+    cache_many, ensure_persisted, exists_in, find_related, restore_many, with_foreign_key,
+    RelationDef, RelationKind, Relationship, Status,
 };
+use crate::{naming, query::Builder, strict, value::serializing_for_db, Error, Model};
 
 /// ## A One to Many relationship.
 /// A one-to-many relationship is used to define relationships where a single model is the parent to one or more child models.
@@ -42,7 +40,9 @@ use crate::{
 #[derive(Clone, Default)]
 pub struct HasMany<Local: Model, Related: Model> {
     foreign_key: String,
+    name: &'static str,
     relation: Status<Vec<Related>>,
+    exists: Option<bool>,
     /// The value of the local model's primary key.
     pub value: Local::PrimaryKey,
 }
@@ -54,25 +54,43 @@ impl<Local: Model, Related: Model> Relationship for HasMany<Local, Related> {
     type RelatedKey = Option<String>;
 
     fn build(value: Self::Key, foreign_key: Self::RelatedKey) -> Self {
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_{}", Local::NAME.to_snake_case(), Local::PRIMARY_KEY).to_snake_case()
-        });
+        let foreign_key = foreign_key
+            .unwrap_or_else(|| naming::strategy().foreign_key(Local::NAME, Local::PRIMARY_KEY));
 
         Self {
             value,
             foreign_key,
+            name: Related::TABLE_NAME,
             relation: Status::initial(),
+            exists: None,
+        }
+    }
+
+    fn definition(&self) -> RelationDef {
+        RelationDef {
+            name: self.name,
+            kind: RelationKind::HasMany,
+            related: Related::NAME,
+            related_table: Related::QUALIFIED_TABLE,
+            foreign_key: self.foreign_key.clone(),
+            local_key: Local::PRIMARY_KEY.to_string(),
+            pivot_table: None,
+            morph_type: None,
         }
     }
 
     fn query(&self) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.foreign_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.foreign_key),
                 "=",
                 self.value.clone(),
             )
-            .where_not_null(&format!("{}.{}", Related::TABLE_NAME, self.foreign_key))
+            .where_not_null(&format!(
+                "{}.{}",
+                Related::QUALIFIED_TABLE,
+                self.foreign_key
+            ))
     }
 
     /// Get the related models.
@@ -80,8 +98,12 @@ impl<Local: Model, Related: Model> Relationship for HasMany<Local, Related> {
     /// # Errors
     ///
     /// Returns an error if the model cannot be retrieved, or if a connection to the database cannot be established.
-    async fn get(&mut self) -> Result<&mut Self::Value, Error> {
+    async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error> {
         if self.relation.is_none() {
+            if !self.relation.is_loaded() {
+                strict::ensure_lazy_allowed(Local::NAME, self.name)?;
+            }
+
             let relation = self.query().get().await?;
 
             self.relation = Status::Fetched(Some(relation));
@@ -97,30 +119,67 @@ impl<Local: Model, Related: Model> Relationship for HasMany<Local, Related> {
     fn eager_query(&self, related: Vec<Self::Key>) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.foreign_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.foreign_key),
                 "in",
                 related,
             )
-            .where_not_null(&format!("{}.{}", Related::TABLE_NAME, self.foreign_key))
+            .where_not_null(&format!(
+                "{}.{}",
+                Related::QUALIFIED_TABLE,
+                self.foreign_key
+            ))
+    }
+
+    fn exists_query(&self, related: Vec<Self::Key>) -> Builder {
+        self.eager_query(related).distinct(&format!(
+            "{}.{}",
+            Related::QUALIFIED_TABLE,
+            self.foreign_key
+        ))
+    }
+
+    fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error> {
+        self.exists = Some(exists_in(keys, &self.value)?);
+
+        Ok(())
+    }
+
+    fn exists(&self) -> Option<bool> {
+        self.exists
+    }
+
+    fn cached(&self) -> Result<Option<Value>, Error> {
+        cache_many(&self.relation)
+    }
+
+    fn restore(&mut self, value: Value) -> Result<(), Error> {
+        self.relation = restore_many(value)?;
+        Ok(())
     }
 
     fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error> {
         let related = find_related(related, &self.foreign_key, &self.value, false)?;
 
-        if !related.is_empty() {
-            self.relation = Status::Fetched(Some(related));
-        }
+        self.relation = Status::Fetched(Some(related));
 
         Ok(())
     }
 }
 
 impl<Local: Model, Related: Model> HasMany<Local, Related> {
-    /// Create a new `Related` model.
+    #[doc(hidden)]
+    /// Set the name of the field holding the relationship, used in errors. Not intended to be used directly.
+    #[must_use]
+    pub const fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Create a new `Related` model, setting its foreign key to the parent's primary key.
     ///
     /// ## Errors
     ///
-    /// Returns an error if the model cannot be inserted, or if a connection to the database cannot be established.
+    /// Returns an error if the parent model hasn't been saved yet, if the model cannot be inserted, or if a connection to the database cannot be established.
     ///
     /// ## Example
     ///
@@ -149,18 +208,10 @@ impl<Local: Model, Related: Model> HasMany<Local, Related> {
     where
         Related: Clone,
     {
-        let Value::Map(mut value) = rbs::to_value(related)? else {
-            return Err(Error::Serialization(rbs::Error::Syntax(
-                "Expected a map".to_string(),
-            )));
-        };
+        ensure_persisted::<Local, _>(&self.value)?;
 
-        value.insert(
-            Value::String(self.foreign_key.clone()),
-            value::for_db(&self.value)?,
-        );
-
-        let result = Related::create(rbs::from_value(Value::Map(value))?).await?;
+        let result =
+            Related::create(with_foreign_key(related, &self.foreign_key, &self.value)?).await?;
 
         if let Status::Fetched(Some(relation)) = &mut self.relation {
             relation.push(result.clone());
@@ -168,6 +219,26 @@ impl<Local: Model, Related: Model> HasMany<Local, Related> {
 
         Ok(result)
     }
+
+    /// Create multiple `Related` models, setting their foreign key to the parent's primary key.
+    /// The models are inserted one after another, and the first failure stops the remaining inserts.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if any of the models cannot be inserted, or if a connection to the database cannot be established.
+    pub async fn create_many(&mut self, related: Vec<Related>) -> Result<Vec<Related>, Error>
+    where
+        Related: Clone,
+    {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let mut created = Vec::with_capacity(related.len());
+        for model in related {
+            created.push(self.create(model).await?);
+        }
+
+        Ok(created)
+    }
 }
 
 impl<Local: Model, Related: Model> Debug for HasMany<Local, Related> {
@@ -1,10 +1,12 @@
-use inflector::Inflector;
 use rbs::Value;
 use serde::Serialize;
 use std::{collections::HashMap, fmt::Debug};
 
-use super::{find_related, Relationship, Status};
-use crate::{query::Builder, value::serializing_for_db, Error, Model};
+use super::{
+    cache_one, ensure_persisted, exists_in, find_related, restore_one, with_foreign_key,
+    RelationDef, RelationKind, Relationship, Status,
+};
+use crate::{naming, query::Builder, strict, value::serializing_for_db, Error, Model};
 
 /// ## A One to One relationship.
 /// A one-to-one relationship is a very basic type of database relationship. For example, a User model might be associated with one Phone model.
@@ -36,7 +38,9 @@ use crate::{query::Builder, value::serializing_for_db, Error, Model};
 #[derive(Clone, Default)]
 pub struct HasOne<Local: Model, Related: Model> {
     foreign_key: String,
+    name: &'static str,
     relation: Status<Related>,
+    exists: Option<bool>,
     /// The value of the local model's primary key.
     pub value: Local::PrimaryKey,
 }
@@ -48,30 +52,52 @@ impl<Local: Model, Related: Model> Relationship for HasOne<Local, Related> {
     type RelatedKey = Option<String>;
 
     fn build(value: Self::Key, foreign_key: Self::RelatedKey) -> Self {
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_{}", Local::NAME.to_snake_case(), Local::PRIMARY_KEY).to_snake_case()
-        });
+        let foreign_key = foreign_key
+            .unwrap_or_else(|| naming::strategy().foreign_key(Local::NAME, Local::PRIMARY_KEY));
 
         Self {
             value,
             foreign_key,
+            name: Related::TABLE_NAME,
             relation: Status::initial(),
+            exists: None,
+        }
+    }
+
+    fn definition(&self) -> RelationDef {
+        RelationDef {
+            name: self.name,
+            kind: RelationKind::HasOne,
+            related: Related::NAME,
+            related_table: Related::QUALIFIED_TABLE,
+            foreign_key: self.foreign_key.clone(),
+            local_key: Local::PRIMARY_KEY.to_string(),
+            pivot_table: None,
+            morph_type: None,
         }
     }
 
     fn query(&self) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.foreign_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.foreign_key),
                 "=",
                 self.value.clone(),
             )
-            .where_not_null(&format!("{}.{}", Related::TABLE_NAME, self.foreign_key))
+            .where_not_null(&format!(
+                "{}.{}",
+                Related::QUALIFIED_TABLE,
+                self.foreign_key
+            ))
             .limit(1)
     }
 
-    async fn get(&mut self) -> Result<&mut Self::Value, Error> {
+    async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error> {
         if self.relation.is_none() {
+            if !self.relation.is_loaded() {
+                strict::ensure_lazy_allowed(Local::NAME, self.name)?;
+            }
+
             let relation = self.query().first().await?.ok_or(Error::NotFound)?;
 
             self.relation = Status::Fetched(Some(relation));
@@ -87,14 +113,51 @@ impl<Local: Model, Related: Model> Relationship for HasOne<Local, Related> {
     fn eager_query(&self, related: Vec<Self::Key>) -> Builder {
         Related::query()
             .r#where(
-                &format!("{}.{}", Related::TABLE_NAME, self.foreign_key),
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.foreign_key),
                 "in",
                 related,
             )
-            .where_not_null(&format!("{}.{}", Related::TABLE_NAME, self.foreign_key))
+            .where_not_null(&format!(
+                "{}.{}",
+                Related::QUALIFIED_TABLE,
+                self.foreign_key
+            ))
             .limit(1)
     }
 
+    fn exists_query(&self, related: Vec<Self::Key>) -> Builder {
+        Related::query()
+            .r#where(
+                &format!("{}.{}", Related::QUALIFIED_TABLE, self.foreign_key),
+                "in",
+                related,
+            )
+            .distinct(&format!(
+                "{}.{}",
+                Related::QUALIFIED_TABLE,
+                self.foreign_key
+            ))
+    }
+
+    fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error> {
+        self.exists = Some(exists_in(keys, &self.value)?);
+
+        Ok(())
+    }
+
+    fn exists(&self) -> Option<bool> {
+        self.exists
+    }
+
+    fn cached(&self) -> Result<Option<Value>, Error> {
+        cache_one(&self.relation)
+    }
+
+    fn restore(&mut self, value: Value) -> Result<(), Error> {
+        self.relation = restore_one(value)?;
+        Ok(())
+    }
+
     fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error> {
         let related = find_related(related, &self.foreign_key, &self.value, true)?;
 
@@ -104,6 +167,59 @@ impl<Local: Model, Related: Model> Relationship for HasOne<Local, Related> {
     }
 }
 
+impl<Local: Model, Related: Model> HasOne<Local, Related> {
+    #[doc(hidden)]
+    /// Set the name of the field holding the relationship, used in errors. Not intended to be used directly.
+    #[must_use]
+    pub const fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Create a new `Related` model, setting its foreign key to the parent's primary key.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the parent model hasn't been saved yet, if the model cannot be inserted, or if a connection to the database cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use ensemble::{Model, relationships::HasOne};
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct Phone {
+    /// #   id: u64,
+    /// #   number: String,
+    /// # }
+    /// # #[derive(Debug, Model, Clone)]
+    /// # struct User {
+    /// #  id: u64,
+    /// #  phone: HasOne<User, Phone>
+    /// # }
+    /// # async fn call() -> Result<(), ensemble::Error> {
+    /// let mut user = User::find(1).await?;
+    ///
+    /// let phone = user.phone.create(Phone {
+    ///  number: "555-0100".to_string(),
+    ///  ..Default::default()
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    pub async fn create(&mut self, related: Related) -> Result<Related, Error>
+    where
+        Related: Clone,
+    {
+        ensure_persisted::<Local, _>(&self.value)?;
+
+        let result =
+            Related::create(with_foreign_key(related, &self.foreign_key, &self.value)?).await?;
+
+        self.relation = Status::Fetched(Some(result.clone()));
+
+        Ok(result)
+    }
+}
+
 impl<Local: Model, Related: Model> Debug for HasOne<Local, Related> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.relation.fmt(f)
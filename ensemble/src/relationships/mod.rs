@@ -5,17 +5,25 @@ mod belongs_to;
 mod belongs_to_many;
 mod has_many;
 mod has_one;
+mod morph_to_many;
+mod registry;
 
 use std::ops::Deref;
 use std::{collections::HashMap, ops::DerefMut};
 
-use crate::{query::Builder, value, Error, Model};
+use crate::{
+    cache,
+    query::{Builder, Dialect},
+    value, Error, Model,
+};
 
 pub use belongs_to::BelongsTo;
-pub use belongs_to_many::BelongsToMany;
+pub use belongs_to_many::{BelongsToMany, Synced};
 pub use has_many::HasMany;
 pub use has_one::HasOne;
+pub use morph_to_many::{MorphToMany, MorphedByMany};
 use rbs::Value;
+pub use registry::{register, registered, registered_tables};
 
 /// A relationship between two models.
 #[async_trait::async_trait]
@@ -34,7 +42,7 @@ pub trait Relationship {
     /// # Errors
     ///
     /// Returns an error if the model cannot be retrieved, or if a connection to the database cannot be established.
-    async fn get(&mut self) -> Result<&mut Self::Value, Error>;
+    async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error>;
 
     /// Whether the relationship has been loaded.
     fn is_loaded(&self) -> bool;
@@ -54,11 +62,67 @@ pub trait Relationship {
     /// Match the eagerly loaded results to their parents. Not intended to be used directly.
     fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error>;
 
+    #[doc(hidden)]
+    /// Get the query retrieving which of the given parents have related models, for [`with_exists`](Builder::with_exists). Not intended to be used directly.
+    fn exists_query(&self, related: Vec<Self::Key>) -> Builder;
+
+    #[doc(hidden)]
+    /// Record whether the parent is among the keys returned by the existence query. Not intended to be used directly.
+    fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error>;
+
+    /// Whether any related models exist, if the parent was retrieved with [`with_exists`](Builder::with_exists) for this relationship.
+    fn exists(&self) -> Option<bool>;
+
+    #[doc(hidden)]
+    /// Get the loaded related models, encoded for [`Model::to_cached_bytes_with_relations`]. Not intended to be used directly.
+    fn cached(&self) -> Result<Option<Value>, Error>;
+
+    #[doc(hidden)]
+    /// Load the related models from their cached value. Not intended to be used directly.
+    fn restore(&mut self, value: Value) -> Result<(), Error>;
+
+    /// Describe the relationship, as listed by [`Model::relations`].
+    fn definition(&self) -> RelationDef;
+
     #[doc(hidden)]
     /// Create an instance of the relationship. Not intended to be used directly.
     fn build(value: Self::Key, related_key: Self::RelatedKey) -> Self;
 }
 
+/// The kind of a relationship, named after the type of its field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationKind {
+    HasOne,
+    HasMany,
+    BelongsTo,
+    BelongsToMany,
+    MorphToMany,
+    MorphedByMany,
+}
+
+/// A relationship declared on a model, as returned by [`Model::relations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationDef {
+    /// The name of the field holding the relationship.
+    pub name: &'static str,
+    /// The kind of the relationship.
+    pub kind: RelationKind,
+    /// The [name](Model::NAME) of the related model.
+    pub related: &'static str,
+    /// The [table](Model::QUALIFIED_TABLE) of the related model.
+    pub related_table: &'static str,
+    /// The column holding the reference between the models: on the related table for `HasOne` and `HasMany`, on the model's own table for `BelongsTo`,
+    /// and on the pivot table (pointing at the related model) for many to many relationships.
+    pub foreign_key: String,
+    /// The column the reference points at: the model's primary key for `HasOne` and `HasMany`, and the related model's primary key for `BelongsTo`.
+    /// For many to many relationships, this is the column of the pivot table pointing at the model itself.
+    pub local_key: String,
+    /// The pivot table of many to many relationships.
+    pub pivot_table: Option<String>,
+    /// The type column of a polymorphic pivot table, and the type the relationship's rows are marked with.
+    pub morph_type: Option<(String, &'static str)>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Status<T> {
     Initial(Option<T>),
@@ -111,6 +175,89 @@ impl<T: serde::Serialize> serde::Serialize for Status<T> {
     }
 }
 
+/// The cached value of a relationship to a single model, which is `Null` if there is no related model.
+fn cache_one<M: Model>(status: &Status<M>) -> Result<Option<Value>, Error> {
+    match status {
+        Status::Initial(_) => Ok(None),
+        Status::Fetched(None) => Ok(Some(Value::Null)),
+        Status::Fetched(Some(model)) => cache::to_value(model, true).map(Some),
+    }
+}
+
+/// The cached value of a relationship to many models.
+fn cache_many<M: Model>(status: &Status<Vec<M>>) -> Result<Option<Value>, Error> {
+    let Status::Fetched(models) = status else {
+        return Ok(None);
+    };
+
+    let models = models
+        .iter()
+        .flatten()
+        .map(|model| cache::to_value(model, true))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Some(Value::Array(models)))
+}
+
+fn restore_one<M: Model>(value: Value) -> Result<Status<M>, Error> {
+    if value.is_null() {
+        return Ok(Status::Fetched(None));
+    }
+
+    Ok(Status::Fetched(Some(cache::from_value(value)?)))
+}
+
+fn restore_many<M: Model>(value: Value) -> Result<Status<Vec<M>>, Error> {
+    let Value::Array(values) = value else {
+        return Err(Error::InvalidCache(
+            "a relationship to many models is not a list".to_string(),
+        ));
+    };
+
+    let models = values
+        .into_iter()
+        .map(cache::from_value)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Status::Fetched(Some(models)))
+}
+
+/// Make sure the parent model has been saved, so its children aren't created with a default foreign key.
+fn ensure_persisted<M: Model, T: Default + PartialEq>(value: &T) -> Result<(), Error> {
+    if *value == T::default() {
+        return Err(Error::ParentUnpersisted(M::NAME));
+    }
+
+    Ok(())
+}
+
+/// Set the foreign key on the given model, returning it ready to be created.
+pub(crate) fn with_foreign_key<M: Model, T: serde::Serialize>(
+    model: M,
+    foreign_key: &str,
+    value: T,
+) -> Result<M, Error> {
+    let Value::Map(mut map) = rbs::to_value(model)? else {
+        return Err(Error::Serialization(rbs::Error::Syntax(
+            "Expected a map".to_string(),
+        )));
+    };
+
+    map.insert(
+        Value::String(foreign_key.to_string()),
+        value::for_db(value)?,
+    );
+
+    Ok(rbs::from_value(Value::Map(map))?)
+}
+
+/// Whether `value` is one of the keys returned by an existence query.
+fn exists_in<T: serde::Serialize>(keys: &[Value], value: T) -> Result<bool, Error> {
+    let value = value::for_db(value)?.to_string();
+
+    Ok(keys.iter().any(|key| key.to_string() == value))
+}
+
 fn find_related<M: Model, T: serde::Serialize>(
     related: &[HashMap<String, Value>],
     foreign_key: &str,
@@ -132,3 +279,11 @@ fn find_related<M: Model, T: serde::Serialize>(
 
     Ok(related)
 }
+
+/// Qualify `table` with `schema`, if there is one. The schema is quoted, like in a model's [qualified table](Model::QUALIFIED_TABLE).
+fn qualified(schema: Option<&str>, table: String) -> String {
+    match schema {
+        Some(schema) => format!("{}.{table}", Dialect::default().quote_identifier(schema)),
+        None => table,
+    }
+}
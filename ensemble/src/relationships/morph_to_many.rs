@@ -0,0 +1,257 @@
+use rbs::Value;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+};
+
+use super::{qualified, BelongsToMany, RelationDef, RelationKind, Relationship};
+use crate::{naming, query::Builder, Error, Model};
+
+/// ## A polymorphic Many to Many relationship.
+/// A polymorphic many to many relationship lets models of different types share the same related models through a single pivot table.
+/// For example, posts and videos may both have tags, with every attachment stored in a `taggables` table holding the tag's key,
+/// the key of the tagged model (`taggable_id`) and its type (`taggable_type`).
+///
+/// The relationship needs a name, which the pivot table and its columns are named after. The pivot table defaults to the plural of the name,
+/// and can be overridden with the `pivot_table` attribute. The type column holds the [name](Model::NAME) of the parent model.
+///
+/// Every method of [`BelongsToMany`] (like `attach`, `detach` and `sync`) is available, and fills in the type column.
+///
+/// ## Example
+///
+/// ```rust
+/// # use ensemble::{Model, relationships::MorphToMany};
+/// # #[derive(Debug, Model)]
+/// # struct Tag {
+/// #   id: u64,
+/// # }
+/// #[derive(Debug, Model)]
+/// struct Post {
+///   id: u64,
+///   #[model(morph_name = "taggable")]
+///   tags: MorphToMany<Post, Tag>
+/// }
+///
+/// # async fn call() -> Result<(), ensemble::Error> {
+/// let mut post = Post::find(1).await?;
+///
+/// let tags: &Vec<Tag> = post.tags().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MorphToMany<Local: Model, Related: Model>(BelongsToMany<Local, Related>);
+
+/// ## The inverse of a polymorphic Many to Many relationship.
+/// Retrieves the models of a single type attached through a [`MorphToMany`] relationship. For example, the posts a tag is attached to.
+/// Models of other types attached to the same tag are left out, so a `Tag` would have a `MorphedByMany` relationship for each type it can be attached to.
+///
+/// ## Example
+///
+/// ```rust
+/// # use ensemble::{Model, relationships::MorphedByMany};
+/// # #[derive(Debug, Model)]
+/// # struct Post {
+/// #   id: u64,
+/// # }
+/// # #[derive(Debug, Model)]
+/// # struct Video {
+/// #   id: u64,
+/// # }
+/// #[derive(Debug, Model)]
+/// struct Tag {
+///   id: u64,
+///   #[model(morph_name = "taggable")]
+///   posts: MorphedByMany<Tag, Post>,
+///   #[model(morph_name = "taggable")]
+///   videos: MorphedByMany<Tag, Video>,
+/// }
+///
+/// # async fn call() -> Result<(), ensemble::Error> {
+/// let mut tag = Tag::find(1).await?;
+///
+/// let posts: &Vec<Post> = tag.posts().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MorphedByMany<Local: Model, Related: Model>(BelongsToMany<Local, Related>);
+
+/// The name of the relationship, followed by the pivot table, foreign key and local key overrides.
+type MorphKey = (String, Option<String>, Option<String>, Option<String>);
+
+impl<Local: Model, Related: Model> MorphToMany<Local, Related> {
+    fn new(
+        value: Related::PrimaryKey,
+        (name, pivot_table, foreign_key, local_key): MorphKey,
+    ) -> Self {
+        let (type_column, id_column) = naming::strategy().morph_columns(&name);
+        let pivot_table = pivot_table.unwrap_or_else(|| {
+            qualified(Related::SCHEMA, naming::strategy().morph_pivot_table(&name))
+        });
+
+        let relation = BelongsToMany::build(
+            value,
+            (
+                Some(pivot_table),
+                foreign_key,
+                Some(local_key.unwrap_or(id_column)),
+            ),
+        );
+
+        Self(relation.morph(type_column, Local::NAME))
+    }
+}
+
+impl<Local: Model, Related: Model> MorphedByMany<Local, Related> {
+    fn new(
+        value: Related::PrimaryKey,
+        (name, pivot_table, foreign_key, local_key): MorphKey,
+    ) -> Self {
+        let (type_column, id_column) = naming::strategy().morph_columns(&name);
+        let pivot_table = pivot_table.unwrap_or_else(|| {
+            qualified(Local::SCHEMA, naming::strategy().morph_pivot_table(&name))
+        });
+
+        let relation = BelongsToMany::build(
+            value,
+            (
+                Some(pivot_table),
+                Some(foreign_key.unwrap_or(id_column)),
+                local_key,
+            ),
+        );
+
+        Self(relation.morph(type_column, Related::NAME))
+    }
+}
+
+/// Both relationships are a [`BelongsToMany`] over a pivot table constrained to a type, so everything else is forwarded to it.
+macro_rules! forward_to_pivot {
+    ($relationship:ident) => {
+        #[async_trait::async_trait]
+        impl<Local: Model, Related: Model> Relationship for $relationship<Local, Related> {
+            type Value = Vec<Related>;
+            type Key = Related::PrimaryKey;
+            type RelatedKey = MorphKey;
+
+            fn build(value: Self::Key, related_key: Self::RelatedKey) -> Self {
+                Self::new(value, related_key)
+            }
+
+            fn query(&self) -> Builder {
+                self.0.query()
+            }
+
+            fn definition(&self) -> RelationDef {
+                RelationDef {
+                    kind: RelationKind::$relationship,
+                    ..self.0.definition()
+                }
+            }
+
+            async fn get<'a>(&'a mut self) -> Result<&'a mut Self::Value, Error> {
+                self.0.get().await
+            }
+
+            fn is_loaded(&self) -> bool {
+                self.0.is_loaded()
+            }
+
+            fn eager_query(&self, related: Vec<Self::Key>) -> Builder {
+                self.0.eager_query(related)
+            }
+
+            fn r#match(&mut self, related: &[HashMap<String, Value>]) -> Result<(), Error> {
+                self.0.r#match(related)
+            }
+
+            fn exists_query(&self, related: Vec<Self::Key>) -> Builder {
+                self.0.exists_query(related)
+            }
+
+            fn match_exists(&mut self, keys: &[Value]) -> Result<(), Error> {
+                self.0.match_exists(keys)
+            }
+
+            fn exists(&self) -> Option<bool> {
+                self.0.exists()
+            }
+
+            fn cached(&self) -> Result<Option<Value>, Error> {
+                self.0.cached()
+            }
+
+            fn restore(&mut self, value: Value) -> Result<(), Error> {
+                self.0.restore(value)
+            }
+        }
+
+        impl<Local: Model, Related: Model> $relationship<Local, Related> {
+            #[doc(hidden)]
+            /// Set the name of the field holding the relationship, used in errors. Not intended to be used directly.
+            #[must_use]
+            pub fn named(self, name: &'static str) -> Self {
+                Self(self.0.named(name))
+            }
+
+            /// Fill the `created_at` and `updated_at` columns of the pivot rows inserted through this relationship.
+            /// Enabled with the `#[model(pivot_timestamps)]` attribute on the relationship field.
+            #[must_use]
+            pub fn with_timestamps(self) -> Self {
+                Self(self.0.with_timestamps())
+            }
+
+            /// Select the given columns of the pivot table along with the related models, to be read with [`pivot`](BelongsToMany::pivot).
+            /// Enabled with the `#[model(with_pivot = ["column"])]` attribute on the relationship field.
+            #[must_use]
+            pub fn with_pivot(self, columns: impl IntoIterator<Item = &'static str>) -> Self {
+                Self(self.0.with_pivot(columns))
+            }
+        }
+
+        impl<Local: Model, Related: Model> Deref for $relationship<Local, Related> {
+            type Target = BelongsToMany<Local, Related>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<Local: Model, Related: Model> DerefMut for $relationship<Local, Related> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<Local: Model, Related: Model> Debug for $relationship<Local, Related> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<Local: Model, Related: Model> Serialize for $relationship<Local, Related> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+
+        #[cfg(feature = "schema")]
+        impl<Local: Model, Related: Model + schemars::JsonSchema> schemars::JsonSchema
+            for $relationship<Local, Related>
+        {
+            fn schema_name() -> String {
+                <BelongsToMany<Local, Related>>::schema_name()
+            }
+
+            fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                <BelongsToMany<Local, Related>>::json_schema(gen)
+            }
+        }
+    };
+}
+
+forward_to_pivot!(MorphToMany);
+forward_to_pivot!(MorphedByMany);
@@ -0,0 +1,38 @@
+//! A registry of the relationships of every model, keyed by table, for tools that work with models they don't know the type of.
+
+use std::{collections::BTreeMap, sync::RwLock};
+
+use super::RelationDef;
+use crate::Model;
+
+static REGISTRY: RwLock<BTreeMap<&'static str, &'static [RelationDef]>> =
+    RwLock::new(BTreeMap::new());
+
+/// Add the relationships of `M` to the registry, under its [qualified table](Model::QUALIFIED_TABLE). Registering a model again has no effect.
+pub fn register<M: Model>() {
+    REGISTRY
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(M::QUALIFIED_TABLE, M::relations());
+}
+
+/// The relationships of the model registered for `table`, if there is one.
+#[must_use]
+pub fn registered(table: &str) -> Option<&'static [RelationDef]> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(table)
+        .copied()
+}
+
+/// The tables of every registered model, in alphabetical order.
+#[must_use]
+pub fn registered_tables() -> Vec<&'static str> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .keys()
+        .copied()
+        .collect()
+}
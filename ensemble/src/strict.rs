@@ -0,0 +1,72 @@
+//! Catching lazy loading, to surface N+1 queries during development.
+//!
+//! Once strict mode is enabled with [`strict_mode`], accessing a relationship that hasn't been loaded yet returns an
+//! [`Error::LazyLoadViolation`] instead of querying the database. Relationships loaded with [`with`](crate::Model::with)
+//! or [`load`](crate::Model::load) are unaffected, and [`allow_lazy`] lets a block of code lazy load anyway.
+//!
+//! Strict mode is off by default, and meant for development and tests.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::{Model, relationships::HasMany};
+//! # #[derive(Debug, Model)]
+//! # struct Post {
+//! #     id: u64,
+//! # }
+//! #[derive(Debug, Model)]
+//! struct User {
+//!     id: u64,
+//!     posts: HasMany<User, Post>,
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! ensemble::strict_mode(true);
+//!
+//! let mut user = User::find(1).await?;
+//! assert!(user.posts().await.is_err());
+//!
+//! let posts = ensemble::allow_lazy(user.posts()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    future::Future,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::Error;
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    static ALLOW_LAZY: ();
+}
+
+/// Enable or disable strict mode for the whole process.
+pub fn strict_mode(enabled: bool) {
+    STRICT.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether strict mode is enabled.
+#[must_use]
+pub fn is_strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+/// Run `future`, allowing it to lazy load relationships even in strict mode.
+///
+/// The override is only visible to the task running `future`; tasks spawned from it must be wrapped in their own `allow_lazy` call.
+pub async fn allow_lazy<F: Future>(future: F) -> F::Output {
+    ALLOW_LAZY.scope((), future).await
+}
+
+/// Make sure the `relation` relationship of a `model` model may be lazy loaded.
+pub fn ensure_lazy_allowed(model: &'static str, relation: &'static str) -> Result<(), Error> {
+    if is_strict() && ALLOW_LAZY.try_with(|()| ()).is_err() {
+        return Err(Error::LazyLoadViolation { model, relation });
+    }
+
+    Ok(())
+}
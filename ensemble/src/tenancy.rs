@@ -0,0 +1,52 @@
+//! Scoping models to the current tenant.
+//!
+//! Models marked with `#[ensemble(tenant_column = "tenant_id")]` can only be queried inside [`with_tenant`]:
+//! every query on them is constrained to the current tenant, and every model they create belongs to it.
+//! Querying them anywhere else returns an [`Error::NoTenant`](crate::Error::NoTenant) error instead of silently reading (or writing) every tenant's rows.
+//!
+//! Jobs that legitimately work across tenants can opt out of the scope for a single query with [`Builder::without_tenancy`](crate::query::Builder::without_tenancy).
+//! Every such query is logged as a warning, so they are easy to audit.
+//!
+//! Raw SQL queries are never scoped.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use ensemble::{Model, tenancy};
+//! #[derive(Debug, Model)]
+//! #[ensemble(tenant_column = "team_id")]
+//! struct Project {
+//!     id: u64,
+//!     team_id: u64,
+//!     name: String,
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! let projects = tenancy::with_tenant(42_u64, async {
+//!     // SELECT * FROM projects WHERE projects.team_id = 42
+//!     Project::all().await
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use rbs::Value;
+use std::future::Future;
+
+tokio::task_local! {
+    static TENANT: Value;
+}
+
+/// Run `future` with `tenant` as the current tenant.
+///
+/// The tenant is only visible to the task running `future`; tasks spawned from it must be wrapped in their own `with_tenant` call.
+pub async fn with_tenant<T: Into<Value>, F: Future>(tenant: T, future: F) -> F::Output {
+    TENANT.scope(tenant.into(), future).await
+}
+
+/// The current tenant, if there is one.
+#[must_use]
+pub fn current() -> Option<Value> {
+    TENANT.try_with(Clone::clone).ok()
+}
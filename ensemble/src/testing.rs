@@ -0,0 +1,485 @@
+//! Utilities for testing code that uses Ensemble. Requires the `testing` feature.
+//!
+//! - [`fake_connection`] records the statements your code would run without running them, so query builders can be unit tested without a database.
+//! - [`assert_query_count`] checks how many statements a block of code runs, like to catch N+1 queries before they reach production.
+//! - [`setup_test_db`] creates a temporary database for integration tests, runs your migrations on it, and drops it once you're done.
+//!
+//! Statements are recorded for the task running the future, so tests running in parallel don't see each other's queries.
+//! Tasks spawned from it aren't recorded, unless they're wrapped in their own scope.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use ensemble::{testing, Model};
+//! #[derive(Debug, Model)]
+//! struct Flight {
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! # async fn run() -> Result<(), ensemble::Error> {
+//! let fake = testing::fake_connection();
+//!
+//! // nothing is sent to the database, and the query returns no rows
+//! let flights = fake
+//!     .run(Flight::query().r#where("name", "=", "BA117").get::<Flight>())
+//!     .await?;
+//!
+//! assert_eq!(fake.statements()[0].sql, "SELECT * FROM flights WHERE name = ?");
+//! # Ok(())
+//! # }
+//! ```
+
+use itertools::Itertools;
+use rbatis::rbdc::db::ExecResult;
+use rbs::Value;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+use crate::{connection, migrations, SetupError};
+use crate::{redaction, Error};
+
+tokio::task_local! {
+    static SCOPE: Scope;
+}
+
+type Log = Arc<Mutex<Vec<Statement>>>;
+
+/// The recorders the current task's statements are sent to, and whether they're faked.
+#[derive(Clone, Default)]
+struct Scope {
+    logs: Vec<Log>,
+    fake: Option<Arc<Mutex<Responses>>>,
+}
+
+/// The results queued for the statements run on a fake connection.
+#[derive(Debug, Default)]
+struct Responses {
+    rows: VecDeque<Vec<Value>>,
+    affected: VecDeque<Result<u64, Error>>,
+}
+
+/// A statement run by Ensemble, with the values bound to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub sql: String,
+    pub bindings: Vec<Value>,
+    /// Whether the statement is sent to a [read replica](crate::ConnectionConfig::read_replica) when any are configured, instead of the primary database.
+    pub replica: bool,
+    /// Which [retry](crate::QueryRetryPolicy) of the statement this is, or 0 for its first attempt. Every retry is recorded as a statement of its own.
+    pub retry: u32,
+}
+
+/// Run `future` in a scope nested in the current one, changed by `nest`.
+async fn nested<F: Future>(nest: impl FnOnce(&mut Scope), future: F) -> F::Output {
+    let mut scope = SCOPE.try_with(Clone::clone).unwrap_or_default();
+    nest(&mut scope);
+
+    SCOPE.scope(scope, future).await
+}
+
+/// Run `future`, returning its output along with every statement it ran, in order.
+pub async fn record_queries<F: Future>(future: F) -> (F::Output, Vec<Statement>) {
+    let log = Log::default();
+    let output = nested(|scope| scope.logs.push(Arc::clone(&log)), future).await;
+
+    let statements = std::mem::take(&mut *log.lock().unwrap_or_else(PoisonError::into_inner));
+    (output, statements)
+}
+
+/// Run `future`, checking that it ran exactly `expected` statements (including those that failed, and their retries), and return its output.
+///
+/// # Panics
+///
+/// Panics if `future` ran a different number of statements, listing the ones it ran.
+pub async fn assert_query_count<F: Future>(expected: usize, future: F) -> F::Output {
+    let (output, statements) = record_queries(future).await;
+
+    assert!(
+        statements.len() == expected,
+        "Expected {expected} queries to run, but {} did:\n{}",
+        statements.len(),
+        statements
+            .iter()
+            .map(|statement| format!("  {}", statement.sql))
+            .join("\n")
+    );
+
+    output
+}
+
+/// Create a [`FakeConnection`].
+#[must_use]
+pub fn fake_connection() -> FakeConnection {
+    FakeConnection::default()
+}
+
+/// A connection that records statements instead of running them, created with [`fake_connection`].
+///
+/// Queries return no rows and statements affect no rows, unless you queue other results with [`returning`](Self::returning), [`affecting`](Self::affecting) and [`failing`](Self::failing).
+/// Transactions begun on it are faked too.
+#[derive(Debug, Clone, Default)]
+pub struct FakeConnection {
+    log: Log,
+    responses: Arc<Mutex<Responses>>,
+}
+
+impl FakeConnection {
+    /// Queue the rows returned by the next query. Each row is a map of column names to values.
+    #[must_use]
+    pub fn returning(self, rows: Vec<Value>) -> Self {
+        self.responses().rows.push_back(rows);
+        self
+    }
+
+    /// Queue the number of rows affected by the next statement that modifies rows.
+    #[must_use]
+    pub fn affecting(self, rows: u64) -> Self {
+        self.responses().affected.push_back(Ok(rows));
+        self
+    }
+
+    /// Queue an error for the next statement that modifies rows to fail with, in place of the number of rows it affects.
+    /// Like a [`Error::UniqueViolation`], to test how your code handles a row inserted by a concurrent request.
+    #[must_use]
+    pub fn failing(self, error: Error) -> Self {
+        self.responses().affected.push_back(Err(error));
+        self
+    }
+
+    /// Run `future`, recording the statements it runs instead of sending them to the database.
+    pub async fn run<F: Future>(&self, future: F) -> F::Output {
+        nested(
+            |scope| {
+                scope.logs.push(Arc::clone(&self.log));
+                scope.fake = Some(Arc::clone(&self.responses));
+            },
+            future,
+        )
+        .await
+    }
+
+    /// The statements recorded so far, in order.
+    #[must_use]
+    pub fn statements(&self) -> Vec<Statement> {
+        self.log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn responses(&self) -> std::sync::MutexGuard<'_, Responses> {
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Record a statement in the current task's scopes, returning the faked scope's responses if it shouldn't be run.
+fn record(sql: &str, bindings: &[Value], replica: bool) -> Option<Arc<Mutex<Responses>>> {
+    SCOPE
+        .try_with(|scope| {
+            for log in &scope.logs {
+                log.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(Statement {
+                        sql: sql.to_string(),
+                        bindings: bindings.iter().cloned().map(redaction::unmark).collect(),
+                        replica,
+                        retry: 0,
+                    });
+            }
+
+            scope.fake.clone()
+        })
+        .ok()
+        .flatten()
+}
+
+/// Record another attempt at the statement `sql` the current task ran last, after it failed with a transient error.
+pub(crate) fn retry(sql: &str, retry: u32) {
+    let _ = SCOPE.try_with(|scope| {
+        for log in &scope.logs {
+            let mut log = log.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(statement) = log.iter().rev().find(|statement| statement.sql == sql) {
+                let statement = Statement {
+                    retry,
+                    ..statement.clone()
+                };
+                log.push(statement);
+            }
+        }
+    });
+}
+
+/// Record a query, returning the rows it fetches if it's faked.
+pub(crate) fn fetch(sql: &str, bindings: &[Value], replica: bool) -> Option<Vec<Value>> {
+    let responses = record(sql, bindings, replica)?;
+    let rows = responses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .rows
+        .pop_front()
+        .unwrap_or_default();
+
+    Some(rows)
+}
+
+/// Record a statement, returning its result if it's faked.
+pub(crate) fn exec(sql: &str, bindings: &[Value]) -> Option<Result<ExecResult, Error>> {
+    let responses = record(sql, bindings, false)?;
+    let rows_affected = responses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .affected
+        .pop_front()
+        .unwrap_or(Ok(0));
+
+    Some(rows_affected.map(|rows_affected| ExecResult {
+        rows_affected,
+        last_insert_id: Value::Null,
+    }))
+}
+
+/// Whether the current task is running on a fake connection.
+pub(crate) fn is_faked() -> bool {
+    SCOPE
+        .try_with(|scope| scope.fake.is_some())
+        .unwrap_or_default()
+}
+
+/// The environment variable holding the URL of the database server [`setup_test_db`] creates test databases on. Not needed for `SQLite`.
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+pub const DATABASE_URL_VAR: &str = "ENSEMBLE_TEST_DATABASE_URL";
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[derive(Debug, thiserror::Error)]
+pub enum TestDatabaseError {
+    #[error("Set the {} environment variable to the URL of a database server where test databases can be created.", DATABASE_URL_VAR)]
+    MissingUrl,
+
+    #[error("The test database server returned an error.")]
+    Server(#[from] rbatis::Error),
+
+    #[error(transparent)]
+    Setup(#[from] SetupError),
+
+    #[error(transparent)]
+    Migration(#[from] migrations::Error),
+}
+
+/// A temporary database created by [`setup_test_db`], dropped along with the guard.
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+#[derive(Debug)]
+pub struct TestDatabase {
+    name: String,
+    /// The server the database was created on, or `None` for an in-memory `SQLite` database, which is gone once the process exits.
+    server_url: Option<String>,
+}
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+impl TestDatabase {
+    /// The name of the database.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let Some(url) = self.server_url.clone() else {
+            return;
+        };
+
+        // dropping the test database can't wait for the pool's connections to close, so they're terminated
+        let sql = if connection::which_db().is_postgres() {
+            format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name)
+        } else {
+            format!("DROP DATABASE IF EXISTS {}", self.name)
+        };
+
+        // the guard may be dropped inside a runtime, which can't be blocked on
+        let dropped = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|error| error.to_string())?
+                .block_on(exec_on_server(&url, &sql))
+                .map_err(|error| error.to_string())
+        })
+        .join();
+
+        if let Ok(Err(error)) = dropped {
+            tracing::warn!(
+                database = self.name,
+                error,
+                "Failed to drop the test database"
+            );
+        }
+    }
+}
+
+/// Create a uniquely-named database on the server at [`DATABASE_URL_VAR`], and run `migrations` on it.
+///
+/// The database is set up as Ensemble's database, and dropped along with the returned guard, which should be kept alive until every test using it is done.
+/// Since Ensemble has a single connection pool, a process can only set up one test database.
+/// On `SQLite`, the test database is an in-memory one instead, so there's no server to set up.
+///
+/// ```rust,no_run
+/// # use ensemble::{migrations::{Error, Migration, Schema}, testing};
+/// # #[derive(Debug, Default)]
+/// # struct CreateUsersTable;
+/// # #[ensemble::async_trait]
+/// # impl Migration for CreateUsersTable {
+/// #     async fn up(&self) -> Result<(), Error> { Ok(()) }
+/// #     async fn down(&self) -> Result<(), Error> { Ok(()) }
+/// # }
+/// # async fn run() -> Result<(), testing::TestDatabaseError> {
+/// let database = testing::setup_test_db(ensemble::migrate!(CreateUsersTable)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the environment variable isn't set, if the database can't be created, if Ensemble's database was already set up, or if a migration fails.
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+pub async fn setup_test_db<F>(migrations: F) -> Result<TestDatabase, TestDatabaseError>
+where
+    F: Future<Output = Result<(), migrations::Error>> + Send,
+{
+    if connection::which_db().is_sqlite() {
+        connection::setup("sqlite::memory:").await?;
+        migrations.await?;
+
+        return Ok(TestDatabase {
+            name: ":memory:".to_string(),
+            server_url: None,
+        });
+    }
+
+    let server_url = std::env::var(DATABASE_URL_VAR).map_err(|_| TestDatabaseError::MissingUrl)?;
+
+    let name = format!(
+        "ensemble_test_{}_{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    );
+    exec_on_server(&server_url, &format!("CREATE DATABASE {name}")).await?;
+
+    // creating the database validated the URL
+    let mut url = url::Url::parse(&server_url).unwrap_or_else(|_| unreachable!());
+    url.set_path(&name);
+
+    // from here on, the guard drops the database if anything fails
+    let database = TestDatabase {
+        name,
+        server_url: Some(server_url),
+    };
+
+    connection::setup(url.as_str()).await?;
+    migrations.await?;
+
+    Ok(database)
+}
+
+/// Run a statement on its own connection to the server, outside of Ensemble's pool.
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+async fn exec_on_server(url: &str, sql: &str) -> Result<(), TestDatabaseError> {
+    #[cfg(feature = "mysql")]
+    let driver = rbdc_mysql::driver::MysqlDriver {};
+    #[cfg(feature = "postgres")]
+    let driver = rbdc_pg::driver::PgDriver {};
+    #[cfg(feature = "sqlite")]
+    let driver = rbdc_sqlite::driver::SqliteDriver {};
+
+    let rb = rbatis::RBatis::new();
+    rb.init(
+        driver,
+        &connection::driver_url(url).map_err(SetupError::from)?,
+    )?;
+    rb.exec(sql, vec![]).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_scopes_record_for_every_scope() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let fake = fake_connection().returning(vec![Value::Null]);
+
+        let (rows, inner) = runtime.block_on(fake.run(async {
+            fetch("SELECT 1", &[], false);
+            record_queries(async { fetch("SELECT 2", &[Value::I32(2)], false) }).await
+        }));
+
+        // the queued rows went to the first query, but the inner scope is still faked
+        assert_eq!(rows, Some(vec![]));
+        assert_eq!(
+            inner,
+            vec![Statement {
+                sql: "SELECT 2".to_string(),
+                bindings: vec![Value::I32(2)],
+                replica: false,
+                retry: 0,
+            }]
+        );
+        assert_eq!(
+            fake.statements()
+                .into_iter()
+                .map(|statement| statement.sql)
+                .collect::<Vec<_>>(),
+            vec!["SELECT 1", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn retries_are_recorded_as_statements_of_their_own() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let ((), statements) = runtime.block_on(record_queries(async {
+            fetch("SELECT 1", &[Value::I32(1)], true);
+            fetch("SELECT 2", &[], false);
+            retry("SELECT 1", 1);
+            retry("SELECT 1", 2);
+        }));
+
+        assert_eq!(
+            statements
+                .iter()
+                .map(|statement| (statement.sql.as_str(), statement.retry))
+                .collect::<Vec<_>>(),
+            vec![
+                ("SELECT 1", 0),
+                ("SELECT 2", 0),
+                ("SELECT 1", 1),
+                ("SELECT 1", 2)
+            ]
+        );
+        assert_eq!(statements[3].bindings, [Value::I32(1)]);
+        assert!(statements[3].replica);
+    }
+
+    #[test]
+    fn statements_outside_a_scope_are_not_recorded() {
+        assert!(fetch("SELECT 1", &[], false).is_none());
+        assert!(!is_faked());
+    }
+}
@@ -2,6 +2,8 @@ mod datetime;
 mod hashed;
 #[cfg(feature = "json")]
 mod json;
+#[cfg(feature = "ulid")]
+mod ulid;
 #[cfg(feature = "uuid")]
 mod uuid;
 
@@ -10,5 +12,7 @@ pub use hashed::Hashed;
 /// A JSON value, used for storing arbitrary data in the database.
 pub use json::{Json, ToJson};
 
+#[cfg(feature = "ulid")]
+pub use ulid::Ulid;
 #[cfg(feature = "uuid")]
 pub use uuid::Uuid;
@@ -0,0 +1,102 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use rbs::Value;
+use serde::Deserializer;
+
+/// A ULID, a 26 characters long identifier which, unlike a UUID, sorts by the time it was generated at.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+#[repr(transparent)]
+pub struct Ulid(ulid::Ulid);
+
+// serialized as a plain string, since the database drivers don't know of any `Ulid` extension type
+impl serde::Serialize for Ulid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Ulid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Self(ulid::Ulid::deserialize(deserializer)?))
+    }
+}
+
+impl Display for Ulid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Debug for Ulid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ulid({})", self.0)
+    }
+}
+
+impl From<Ulid> for Value {
+    fn from(ulid: Ulid) -> Self {
+        Self::String(ulid.0.to_string())
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = ulid::DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl Ulid {
+    #[must_use]
+    pub fn new() -> Self {
+        Self(ulid::Ulid::new())
+    }
+
+    #[must_use]
+    pub const fn nil() -> Self {
+        Self(ulid::Ulid::nil())
+    }
+}
+
+impl Deref for Ulid {
+    type Target = ulid::Ulid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Ulid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Ulid {
+    fn schema_name() -> String {
+        "Ulid".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            format: Some("ulid".to_string()),
+            string: Some(Box::new(schemars::schema::StringValidation {
+                min_length: Some(26),
+                max_length: Some(26),
+                pattern: None,
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
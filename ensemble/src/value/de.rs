@@ -14,6 +14,73 @@ pub fn deserialize_value<'de, T: Deserialize<'de>>(val: rbs::Value) -> Result<T,
     Deserialize::deserialize(ValueDeserializer(val))
 }
 
+/// Separates the value from the target type in the errors of integers that don't fit in their field.
+const OUT_OF_RANGE: &str = " is out of range for ";
+
+fn out_of_range_error(value: i128, target: &str) -> rbs::Error {
+    rbs::Error::Syntax(format!("integer {value}{OUT_OF_RANGE}{target}"))
+}
+
+/// The value and target type of an error returned when an integer doesn't fit in its field.
+pub fn out_of_range(error: &rbs::Error) -> Option<(String, String)> {
+    let rbs::Error::Syntax(message) = error;
+    let (value, target) = message.strip_prefix("integer ")?.split_once(OUT_OF_RANGE)?;
+
+    Some((value.to_string(), target.to_string()))
+}
+
+/// The value of an integer column, however the driver returned it. Postgres returns `NUMERIC` columns (the closest type to a `u64`) as decimals.
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::I32(value) => Some(i128::from(*value)),
+        Value::I64(value) => Some(i128::from(*value)),
+        Value::U32(value) => Some(i128::from(*value)),
+        Value::U64(value) => Some(i128::from(*value)),
+        Value::Ext("Decimal", value) => match value.as_ref() {
+            Value::String(value) => value.parse().ok(),
+            value => integer(value),
+        },
+        Value::Ext(_, value) => integer(value),
+        _ => None,
+    }
+}
+
+/// The value of a boolean column. Postgres has a `BOOLEAN` type, while `MySQL` stores booleans as `TINYINT(1)` (or `BIT(1)`) and `SQLite` as integers.
+fn boolean(value: &Value) -> Option<Result<bool, rbs::Error>> {
+    let integer = match value {
+        Value::Bool(value) => return Some(Ok(*value)),
+        Value::Binary(bytes) if bytes.len() == 1 => i128::from(bytes[0]),
+        Value::String(value) => {
+            return match value.to_ascii_lowercase().as_str() {
+                "1" | "t" | "true" => Some(Ok(true)),
+                "0" | "f" | "false" => Some(Ok(false)),
+                _ => None,
+            }
+        }
+        value => integer(value)?,
+    };
+
+    Some(match integer {
+        0 => Ok(false),
+        1 => Ok(true),
+        integer => Err(out_of_range_error(integer, "bool")),
+    })
+}
+
+/// Deserialize integers with their range checked, so they're widened (and narrowed) from whichever integer type the driver returned without wrapping around.
+macro_rules! deserialize_integers {
+    ($($method:ident => $ty:ty, $visit:ident;)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let Some(value) = integer(&self.0) else {
+                return self.deserialize_any(visitor);
+            };
+
+            let value = <$ty>::try_from(value).map_err(|_| out_of_range_error(value, stringify!($ty)))?;
+            visitor.$visit(value)
+        }
+    )*};
+}
+
 #[repr(transparent)]
 struct ValueDeserializer(rbs::Value);
 
@@ -23,6 +90,7 @@ trait ValueBase<'de>: Deserializer<'de, Error = rbs::Error> {
     type Iter: ExactSizeIterator<Item = Self::Item>;
     type MapIter: Iterator<Item = (Self::Item, Self::Item)>;
 
+    #[allow(dead_code)]
     fn is_null(&self) -> bool;
     fn unexpected(&self) -> Unexpected<'_>;
 
@@ -30,7 +98,7 @@ trait ValueBase<'de>: Deserializer<'de, Error = rbs::Error> {
     fn into_map_iter(self) -> Result<Self::MapIter, Self::Item>;
 }
 
-impl<'de> ValueBase<'de> for Value {
+impl ValueBase<'_> for Value {
     type Item = ValueDeserializer;
     type Iter = IntoIter<Self::Item>;
     type MapIter = IntoIter<(Self::Item, Self::Item)>;
@@ -85,7 +153,7 @@ impl<'de> ValueBase<'de> for Value {
     }
 }
 
-impl<'de> ValueBase<'de> for ValueDeserializer {
+impl ValueBase<'_> for ValueDeserializer {
     type Item = Self;
     type Iter = IntoIter<Self::Item>;
     type MapIter = IntoIter<(Self::Item, Self::Item)>;
@@ -425,8 +493,29 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
         }
     }
 
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match boolean(&self.0) {
+            Some(value) => visitor.visit_bool(value?),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_integers! {
+        deserialize_u8 => u8, visit_u8;
+        deserialize_u16 => u16, visit_u16;
+        deserialize_u32 => u32, visit_u32;
+        deserialize_u64 => u64, visit_u64;
+        deserialize_i8 => i8, visit_i8;
+        deserialize_i16 => i16, visit_i16;
+        deserialize_i32 => i32, visit_i32;
+        deserialize_i64 => i64, visit_i64;
+    }
+
     forward_to_deserialize_any! {
-        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
+        f32 f64 char str string unit seq
         bytes byte_buf map tuple_struct struct
         identifier tuple ignored_any
     }
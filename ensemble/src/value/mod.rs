@@ -1,11 +1,16 @@
-use serde::Serialize;
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, Error},
+    Deserialize, Deserializer, Serialize,
+};
+use std::fmt::Display;
 
 use self::{de::deserialize_value, ser::fast_serialize};
-use crate::Model;
 
 mod de;
 mod ser;
 
+pub(crate) use de::out_of_range;
+
 /// Serialize a model for the database.
 ///
 /// # Errors
@@ -15,15 +20,382 @@ pub fn for_db<T: Serialize>(value: T) -> Result<rbs::Value, rbs::Error> {
     fast_serialize(value)
 }
 
-/// Deserialize a model from the database.
+/// Serialize a model for an `UPDATE`, leaving out its immutable columns.
+/// If the model has a [`Tracker`], the columns that didn't change since it was loaded or saved are left out too.
+///
+/// # Errors
+///
+/// Returns an [`Error::ImmutableColumn`](crate::Error::ImmutableColumn) if the model's [`Tracker`] saw one of them change since it was loaded,
+/// or an error if serialization fails.
+pub fn for_update<M: crate::Model>(model: &M) -> Result<rbs::Value, crate::Error> {
+    let mut columns = for_db(model)?;
+    let original = model
+        .tracker()
+        .and_then(|tracker| tracker.original.as_ref());
+
+    if let rbs::Value::Map(map) = &mut columns {
+        for column in M::columns().iter().filter(|column| column.immutable) {
+            if original.is_some_and(|original| original[column.name] != map[column.name]) {
+                return Err(crate::Error::ImmutableColumn(column.name));
+            }
+
+            map.rm(column.name);
+        }
+
+        if let Some(rbs::Value::Map(original)) = original {
+            for (column, value) in original {
+                if let Some(column) = column.as_str() {
+                    if &map[column] == value {
+                        map.rm(column);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Whether the model has a [`Tracker`] and none of its columns changed since it was loaded or saved, so saving it would write nothing.
+pub(crate) fn unchanged<M: crate::Model>(model: &M) -> bool {
+    model
+        .tracker()
+        .is_some_and(|tracker| for_db(model).is_ok_and(|columns| tracker.is_clean(&columns)))
+}
+
+/// Deserialize a model (or any other [`FromRow`](crate::FromRow) type) from the database.
 ///
 /// # Errors
 ///
 /// Returns an error if deserialization fails.
-pub(crate) fn from<M: Model>(value: rbs::Value) -> Result<M, rbs::Error> {
-    deserialize_value::<M>(value)
+pub(crate) fn from<T: DeserializeOwned>(value: rbs::Value) -> Result<T, rbs::Error> {
+    deserialize_value::<T>(value)
+}
+
+/// A column whose value differs between two instances of a model, as returned by [`Model::diff`](crate::Model::diff).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    /// The name of the column.
+    pub column: String,
+    /// The column's value in the original model.
+    pub old: rbs::Value,
+    /// The column's value in the updated model.
+    pub new: rbs::Value,
+}
+
+/// A column persisted by a model, as returned by [`Model::columns`](crate::Model::columns).
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnDef {
+    /// The name of the column in the database.
+    pub name: &'static str,
+    /// The name of the struct field holding the column.
+    pub field: &'static str,
+    /// Whether the column is the model's primary key.
+    pub primary_key: bool,
+    /// Whether the field is an `Option`, and the column can hold `NULL`.
+    pub nullable: bool,
+    /// Whether the column gets a value when the model is created without one (e.g. `#[model(default = ...)]`, `uuid`, `ulid` or timestamps).
+    pub has_default: bool,
+    /// Whether the column is an auto-incrementing key, assigned by the database on insert.
+    pub increments: bool,
+    /// Whether the column is populated by the database itself, and never written by the model.
+    pub db_generated: bool,
+    /// Whether the column is only written when the model is created, and left out of its updates.
+    pub immutable: bool,
+    /// Whether the column holds a unique value for each model, like an email address, as marked with `#[model(unique)]`.
+    pub unique: bool,
+    /// Whether the column holds a secret, like a password, whose values are [redacted](crate::redaction) from logs and errors. Marked with `#[model(sensitive)]`.
+    pub sensitive: bool,
+    /// The kind of value the column holds.
+    pub r#type: ColumnType,
+}
+
+/// The kind of value held by a [`ColumnDef`], based on the type of its field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Bool,
+    Integer,
+    UnsignedInteger,
+    Float,
+    String,
+    DateTime,
+    Uuid,
+    Ulid,
+    Json,
+    Bytes,
+    /// Any other type, including the foreign keys of `BelongsTo` relationships.
+    Other,
+}
+
+/// Compare two models serialized with [`for_db`], column by column.
+pub(crate) fn diff(old: rbs::Value, new: rbs::Value) -> Vec<Change> {
+    let (rbs::Value::Map(old), rbs::Value::Map(new)) = (old, new) else {
+        return vec![];
+    };
+
+    // both models have the same type, so their columns are always serialized in the same order
+    old.into_iter()
+        .zip(new)
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((column, old), (_, new))| Change {
+            column: column.into_string().unwrap_or_default(),
+            old,
+            new,
+        })
+        .collect()
+}
+
+/// The JSON Schema of a type, such as a model deriving its schema with `#[ensemble(json_schema)]`.
+///
+/// # Panics
+///
+/// Panics if the schema cannot be converted to JSON, which never happens for schemas generated by `schemars`.
+#[cfg(feature = "schema")]
+#[must_use]
+pub fn json_schema<T: schemars::JsonSchema>() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap()
+}
+
+/// Tracks the changes made to a model by the last time it was saved.
+///
+/// Add a field of this type to a model to enable its generated `is_clean`, `is_dirty`, `get_dirty`, `was_changed`, `was_changed_column` and `changes` methods.
+/// The field is not a column: it's never read from or written to the database, nor serialized.
+///
+/// Saving a model with a tracker only updates the columns that changed since it was loaded, and doesn't run a query at all if none did.
+///
+/// ```rust
+/// # use ensemble::{Model, value::Tracker};
+/// #[derive(Debug, Model)]
+/// struct Post {
+///     id: u64,
+///     title: String,
+///     tracker: Tracker,
+/// }
+///
+/// # async fn run() -> Result<(), ensemble::Error> {
+/// let mut post = Post::find(1).await?;
+/// post.title = "Hello, world!".to_string();
+/// post.save().await?;
+///
+/// if post.was_changed_column("title") {
+///     // re-index the post
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tracker {
+    /// The columns of the model, as they were last loaded from or written to the database.
+    original: Option<rbs::Value>,
+    /// The columns written by the last save.
+    changes: Vec<Change>,
+}
+
+impl Tracker {
+    /// Whether the last save changed any column.
+    #[must_use]
+    pub const fn was_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Whether the last save changed the given column.
+    #[must_use]
+    pub fn was_changed_column(&self, column: &str) -> bool {
+        self.changes.iter().any(|change| change.column == column)
+    }
+
+    /// The columns changed by the last save, with their values before and after it.
+    #[must_use]
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether `columns` (the model serialized with [`for_db`]) match the columns as they were last loaded or saved.
+    /// Models that were never loaded or saved are never clean.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn is_clean(&self, columns: &rbs::Value) -> bool {
+        self.original.as_ref() == Some(columns)
+    }
+
+    /// The columns of `columns` (the model serialized with [`for_db`]) that differ from the columns as they were last loaded or saved.
+    /// Every column of a model that was never loaded or saved is dirty.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn dirty(&self, columns: &rbs::Value) -> Vec<Change> {
+        let original = self
+            .original
+            .clone()
+            .unwrap_or_else(|| null_columns(columns));
+
+        diff(original, columns.clone())
+    }
+
+    /// Record that the model was loaded from (or created in) the database with the given columns, forgetting the last save's changes.
+    #[doc(hidden)]
+    pub fn sync(&mut self, columns: rbs::Value) {
+        self.original = Some(columns);
+        self.changes.clear();
+    }
+
+    /// Record that the model was saved with the given columns.
+    /// Every column of a model that was never loaded counts as changed, since the values it replaced are unknown.
+    #[doc(hidden)]
+    pub fn persist(&mut self, columns: rbs::Value) {
+        let original = self
+            .original
+            .take()
+            .unwrap_or_else(|| null_columns(&columns));
+
+        self.changes = diff(original, columns.clone());
+        self.original = Some(columns);
+    }
+}
+
+/// The same columns as `columns`, all set to `NULL`.
+fn null_columns(columns: &rbs::Value) -> rbs::Value {
+    let rbs::Value::Map(columns) = columns else {
+        return rbs::Value::Null;
+    };
+
+    rbs::Value::Map(rbs::value::map::ValueMap(
+        columns
+            .into_iter()
+            .map(|(column, _)| (column.clone(), rbs::Value::Null))
+            .collect(),
+    ))
+}
+
+/// Overrides of which fields are included when serializing a model, as set by [`Model::make_visible`](crate::Model::make_visible) and [`Model::make_hidden`](crate::Model::make_hidden).
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Visibility<'a> {
+    visible: Vec<&'a str>,
+    hidden: Vec<&'a str>,
+    without_relations: bool,
+}
+
+#[cfg(feature = "json")]
+impl Visibility<'_> {
+    /// Whether the given column should be serialized, given whether it is hidden by default.
+    /// Columns explicitly hidden take precedence over those made visible.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn shows(&self, column: &str, hidden: bool) -> bool {
+        if self.hidden.contains(&column) {
+            return false;
+        }
+
+        !hidden || self.visible.contains(&column)
+    }
+
+    /// Whether the model's loaded relationships should be serialized.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn shows_relations(&self) -> bool {
+        !self.without_relations
+    }
+}
+
+/// A model that serializes with its own [`Visibility`], without changing the model itself.
+///
+/// Overrides only live as long as the wrapper: the model (and any clone of it) keeps serializing as usual.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct Visible<'a, M> {
+    model: &'a M,
+    visibility: Visibility<'a>,
+}
+
+#[cfg(feature = "json")]
+impl<'a, M: crate::Model> Visible<'a, M> {
+    pub(crate) fn new(model: &'a M) -> Self {
+        Self {
+            model,
+            visibility: Visibility::default(),
+        }
+    }
+
+    /// Include the given fields, even if they are hidden by default.
+    #[must_use]
+    pub fn make_visible(mut self, columns: &[&'a str]) -> Self {
+        self.visibility.visible.extend_from_slice(columns);
+        self
+    }
+
+    /// Leave the given fields out.
+    #[must_use]
+    pub fn make_hidden(mut self, columns: &[&'a str]) -> Self {
+        self.visibility.hidden.extend_from_slice(columns);
+        self
+    }
+
+    /// Leave the model's relationships out, even if they are loaded.
+    #[must_use]
+    pub const fn without_relations(mut self) -> Self {
+        self.visibility.without_relations = true;
+        self
+    }
+
+    /// Convert the model to a JSON value, applying the overrides.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model cannot be converted to JSON. Since Ensemble manually implement Serialize, this should never happen.
+    #[must_use]
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<M: crate::Model> Serialize for Visible<'_, M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // visibility never applies to the columns written to the database
+        if serializing_for_db::<S>() {
+            return self.model.serialize(serializer);
+        }
+
+        self.model.serialize_visible(serializer, &self.visibility)
+    }
 }
 
 pub(crate) fn serializing_for_db<S: serde::Serializer>() -> bool {
     std::any::type_name::<S::Error>() == std::any::type_name::<rbs::Error>()
 }
+
+/// Hydrates a field through its `#[model(get)]` accessor. Used internally by the `Model` derive.
+#[doc(hidden)]
+pub struct Accessor<S, T, E> {
+    column: &'static str,
+    get: fn(S) -> Result<T, E>,
+}
+
+impl<S, T, E> Accessor<S, T, E> {
+    pub const fn new(column: &'static str, get: fn(S) -> Result<T, E>) -> Self {
+        Self { column, get }
+    }
+}
+
+impl<'de, S: Deserialize<'de>, T, E: Display> DeserializeSeed<'de> for Accessor<S, T, E> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        (self.get)(S::deserialize(deserializer)?).map_err(|e| {
+            D::Error::custom(format!("failed to hydrate the {} column: {e}", self.column))
+        })
+    }
+}
+
+/// Deserializes a field that is present as `Some`, even if its value is `null`. Used internally by the `Model` derive, for the nullable fields of patches.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be deserialized.
+#[doc(hidden)]
+pub fn provided<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
@@ -240,6 +240,7 @@ pub struct DefaultSerializeMap {
     next_key: Option<Value>,
 }
 
+#[allow(dead_code)]
 pub struct SerializeStructVariant {
     idx: u32,
     vec: Vec<Value>,
@@ -509,6 +510,17 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "ulid")]
+    fn serializes_ulids_as_strings() {
+        let ulid = crate::types::Ulid::new();
+
+        assert_eq!(
+            fast_serialize(ulid).unwrap(),
+            Value::String(ulid.to_string())
+        );
+    }
+
     #[test]
     fn properly_serializes_hashed() {
         let hashed = Hashed::new("hello-world");
@@ -0,0 +1,295 @@
+//! Writes that take more than a single statement: writes to models that record their own changes, using `#[ensemble(outbox)]` or `#[ensemble(auditable)]`,
+//! and inserts that retrieve the inserted row.
+//! Every write runs in a transaction, along with the statements recording it, so the records only exist if the write is committed.
+//!
+//! These functions are used internally by the `Model` derive, and should not be called directly.
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    audit,
+    connection::Transaction,
+    hooks,
+    hooks::EventType,
+    outbox,
+    query::{Builder, Dialect},
+    value, Error, Model,
+};
+
+/// Insert a model, returning the id of the inserted row.
+///
+/// # Errors
+///
+/// Returns an error if the model or its records cannot be inserted, or if a connection to the database cannot be established.
+pub async fn insert<M: Model, Id: DeserializeOwned>(model: &M) -> Result<Id, Error> {
+    let (sql, bindings) = M::query().insert_sql(value::for_db(model)?)?;
+
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+    let result = transaction.exec(&sql, bindings).await?;
+
+    let key = if model.primary_key() == &M::PrimaryKey::default() {
+        rbs::from_value(result.last_insert_id.clone())?
+    } else {
+        model.primary_key().clone()
+    };
+
+    record(
+        &mut transaction,
+        &key,
+        EventType::Created,
+        None,
+        Some(model),
+    )
+    .await?;
+    hooks::defer(&mut transaction, model, &key, EventType::Created)?;
+    transaction.commit().await?;
+
+    Ok(rbs::from_value(result.last_insert_id)?)
+}
+
+/// Insert a model, returning the row as stored in the database, including the key and any columns filled in by the database.
+///
+/// Postgres and `SQLite` return the row from the insert itself. On `MySQL`, it is selected by primary key after the insert, in the same transaction.
+///
+/// # Errors
+///
+/// Returns an error if the model or its records cannot be inserted, or if a connection to the database cannot be established.
+pub async fn insert_and_fetch<M: Model>(model: &M) -> Result<M, Error> {
+    let (sql, bindings) = M::query().insert_sql(value::for_db(model)?)?;
+
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+    let row = if Dialect::default().supports_returning() {
+        transaction
+            .fetch(&format!("{sql} RETURNING *"), bindings)
+            .await?
+    } else {
+        let result = transaction.exec(&sql, bindings).await?;
+
+        let key = if model.primary_key() == &M::PrimaryKey::default() {
+            result.last_insert_id
+        } else {
+            value::for_db(model.primary_key())?
+        };
+
+        let (sql, bindings) = M::without_global_scopes()
+            .r#where(M::PRIMARY_KEY, "=", key)
+            .select_sql()?;
+        transaction.fetch(&sql, bindings).await?
+    };
+
+    let model = value::from::<M>(row.into_iter().next().ok_or(Error::NotFound)?)?;
+
+    record(
+        &mut transaction,
+        model.primary_key(),
+        EventType::Created,
+        None,
+        Some(&model),
+    )
+    .await?;
+    hooks::defer(&mut transaction, &model, model.primary_key(), EventType::Created)?;
+    transaction.commit().await?;
+
+    Ok(model)
+}
+
+/// Update a model, returning the number of affected rows.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be updated or its changes cannot be recorded, or if a connection to the database cannot be established.
+pub async fn update<M: Model>(model: &M) -> Result<u64, Error> {
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+    let rows_affected = update_in(&mut transaction, model).await?;
+
+    // nothing changed, so there's nothing to record
+    if rows_affected == 0 {
+        transaction.rollback().await?;
+    } else {
+        transaction.commit().await?;
+    }
+
+    Ok(rows_affected)
+}
+
+/// Update a set of models in a single transaction, returning the number of affected rows.
+///
+/// # Errors
+///
+/// Returns an error if any of the models cannot be updated or their changes cannot be recorded, or if a connection to the database cannot be established.
+pub async fn update_many<M: Model>(models: &[&mut M]) -> Result<u64, Error> {
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+
+    let mut rows_affected = 0;
+    for model in models {
+        rows_affected += update_in(&mut transaction, &**model).await?;
+    }
+
+    transaction.commit().await?;
+    Ok(rows_affected)
+}
+
+/// Delete a model, returning the number of affected rows. Models marked with `soft_deletes` are removed too, even if they're already soft deleted.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be deleted or its deletion cannot be recorded, or if a connection to the database cannot be established.
+pub async fn delete<M: Model>(model: &M) -> Result<u64, Error> {
+    let query = by_key(model)?.with_trashed();
+    let statement = query.delete_sql()?;
+
+    remove(model, &query, statement).await
+}
+
+/// Soft delete a model marked with `soft_deletes`, by setting its `deleted_at` column. Returns the number of affected rows.
+/// It's recorded as a deletion, like with [`delete`].
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be soft deleted or its deletion cannot be recorded, or if a connection to the database cannot be established.
+pub async fn soft_delete<M: Model>(model: &M) -> Result<u64, Error> {
+    let query = by_key(model)?;
+    let statement = query.soft_delete_sql()?;
+
+    remove(model, &query, statement).await
+}
+
+/// Restore a soft deleted model, clearing its `deleted_at` column. Returns the number of affected rows.
+/// It's recorded as an update, so `model` should be the model as it is once restored.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be restored or its restoration cannot be recorded, or if a connection to the database cannot be established.
+pub async fn restore<M: Model>(model: &M) -> Result<u64, Error> {
+    let query = by_key(model)?.only_trashed();
+    let (sql, bindings) = query.restore_sql()?;
+
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+    let old = locked::<M>(&mut transaction, &query).await?;
+    let rows_affected = transaction.exec(&sql, bindings).await?.rows_affected;
+
+    if rows_affected == 0 {
+        transaction.rollback().await?;
+        return Ok(0);
+    }
+
+    record(
+        &mut transaction,
+        model.primary_key(),
+        EventType::Updated,
+        old.as_ref(),
+        Some(model),
+    )
+    .await?;
+    transaction.commit().await?;
+
+    Ok(rows_affected)
+}
+
+/// Run the statement deleting (or soft deleting) a model matched by `query`, and record its deletion.
+async fn remove<M: Model>(
+    model: &M,
+    query: &Builder,
+    (sql, bindings): (String, Vec<rbs::Value>),
+) -> Result<u64, Error> {
+    let mut transaction = Transaction::begin(M::QUALIFIED_TABLE).await?;
+    let old = locked::<M>(&mut transaction, query).await?;
+    let rows_affected = transaction.exec(&sql, bindings).await?.rows_affected;
+
+    if rows_affected == 0 {
+        transaction.rollback().await?;
+        return Ok(0);
+    }
+
+    let old = old.as_ref().unwrap_or(model);
+    record(
+        &mut transaction,
+        model.primary_key(),
+        EventType::Deleted,
+        Some(old),
+        None,
+    )
+    .await?;
+    hooks::defer(&mut transaction, old, model.primary_key(), EventType::Deleted)?;
+    transaction.commit().await?;
+
+    Ok(rows_affected)
+}
+
+async fn update_in<M: Model>(transaction: &mut Transaction, model: &M) -> Result<u64, Error> {
+    let query = by_key(model)?;
+    let (sql, bindings) = query.update_sql(value::for_update(model)?)?;
+
+    let old = locked::<M>(transaction, &query).await?;
+    let rows_affected = transaction.exec(&sql, bindings).await?.rows_affected;
+
+    if rows_affected != 0 {
+        record(
+            transaction,
+            model.primary_key(),
+            EventType::Updated,
+            old.as_ref(),
+            Some(model),
+        )
+        .await?;
+        hooks::defer(transaction, model, model.primary_key(), EventType::Updated)?;
+    }
+
+    Ok(rows_affected)
+}
+
+fn by_key<M: Model>(model: &M) -> Result<Builder, Error> {
+    let key = value::for_db(model.primary_key())?;
+
+    Ok(M::without_global_scopes().r#where(M::PRIMARY_KEY, "=", key))
+}
+
+/// The model as it is currently stored, locked until the transaction ends. Only auditable models need it.
+async fn locked<M: Model>(
+    transaction: &mut Transaction,
+    query: &Builder,
+) -> Result<Option<M>, Error> {
+    if !M::AUDITABLE {
+        return Ok(None);
+    }
+
+    let (sql, bindings) = query.select_sql()?;
+    let row = transaction
+        .fetch(&format!("{sql} FOR UPDATE"), bindings)
+        .await?
+        .into_iter()
+        .next();
+
+    Ok(row.map(value::from::<M>).transpose()?)
+}
+
+async fn record<M: Model>(
+    transaction: &mut Transaction,
+    key: &M::PrimaryKey,
+    event: EventType,
+    old: Option<&M>,
+    new: Option<&M>,
+) -> Result<(), Error> {
+    if M::OUTBOX {
+        let mut payload = outbox::payload(new.or(old).unwrap_or_else(|| unreachable!()));
+
+        // created models might not have their generated key yet
+        if let Some(payload) = payload.as_object_mut() {
+            payload.insert(
+                M::PRIMARY_KEY.to_string(),
+                serde_json::to_value(key).unwrap_or_default(),
+            );
+        }
+
+        let (sql, bindings) = outbox::record::<M>(event, key.to_string(), payload)?;
+        transaction.exec(&sql, bindings).await?;
+    }
+
+    if M::AUDITABLE {
+        if let Some((sql, bindings)) = audit::record::<M>(key, event, old, new)? {
+            transaction.exec(&sql, bindings).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -102,15 +102,16 @@ fn impl_set(fields: &Fields) -> syn::Result<TokenStream> {
                 .attr
                 .rename
                 .as_ref()
-                .map_or(iden.clone(), |s| Ident::new(s, iden.span()));
+                .map_or_else(|| iden.clone(), |s| Ident::new(s, iden.span()));
 
             let types_constraint = if only_types.is_empty() {
                 TokenStream::new()
             } else {
                 quote_spanned! {
-                    f.span()=> if !matches!(self.r#type, #(#only_types)|*) {
-                        panic!("{} is not a valid option for {} columns.", stringify!(#iden), self.r#type);
-                    }
+                    f.span()=> assert!(
+                        matches!(self.r#type, #(#only_types)|*),
+                        "{} is not a valid option for {} columns.", stringify!(#iden), self.r#type
+                    );
                 }
             };
 
@@ -168,17 +169,15 @@ fn build_needs(field: &Field) -> syn::Result<TokenStream> {
         let ident = &segment;
 
         if i == 0 {
-            tokens.extend(quote_spanned! {segment.span()=> !self.#ident });
+            tokens.extend(quote_spanned! {segment.span()=> self.#ident });
         } else {
-            tokens.extend(quote_spanned! {segment.span()=> && !self.#ident });
+            tokens.extend(quote_spanned! {segment.span()=> || self.#ident });
         }
     }
 
     let iden = &field.ident;
     Ok(quote_spanned! {field.span()=>
-        if #tokens {
-            panic!("{} requires one of {} to be set.", stringify!(#iden), stringify!(#needs));
-        }
+        assert!(#tokens, "{} requires one of {} to be set.", stringify!(#iden), stringify!(#needs));
     })
 }
 
@@ -0,0 +1,38 @@
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::model::{field::Fields, serde};
+
+pub fn r#impl(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let syn::Data::Struct(r#struct) = &ast.data else {
+        return Err(syn::Error::new_spanned(
+            ast,
+            "FromRow derive only supports structs",
+        ));
+    };
+
+    let syn::Fields::Named(struct_fields) = &r#struct.fields else {
+        return Err(syn::Error::new_spanned(
+            ast,
+            "FromRow derive only supports named fields",
+        ));
+    };
+
+    let fields = Fields::plain(struct_fields.clone());
+    if let Some(field) = fields.relationships().first() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "Relationships are only supported on models. Use `#[derive(Model)]` instead.",
+        ));
+    }
+
+    let name = &ast.ident;
+    let deserialize_impl = serde::impl_deserialize(name, &fields)?;
+
+    Ok(quote! {
+        #deserialize_impl
+
+        #[automatically_derived]
+        impl ::ensemble::FromRow for #name {}
+    })
+}
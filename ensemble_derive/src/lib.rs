@@ -4,6 +4,7 @@ use std::fmt::Display;
 use syn::{parse_macro_input, DeriveInput};
 
 mod column;
+mod from_row;
 mod model;
 
 #[proc_macro_derive(Model, attributes(ensemble, model, validate))]
@@ -11,10 +12,27 @@ pub fn derive_model(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut ast = parse_macro_input!(input as DeriveInput);
     let opts = match deluxe::extract_attributes(&mut ast) {
         Ok(opts) => opts,
-        Err(e) => return e.into_compile_error().into(),
+        Err(e) => return model_error(&ast, e).into(),
     };
 
     model::r#impl(&ast, opts)
+        .unwrap_or_else(|e| model_error(&ast, e))
+        .into()
+}
+
+/// Report an error in a model's definition, along with a stub implementation of `Model` so that the uses of the model don't report errors of their own.
+fn model_error(ast: &DeriveInput, error: syn::Error) -> proc_macro2::TokenStream {
+    let mut tokens = error.into_compile_error();
+    tokens.extend(model::stub(ast));
+
+    tokens
+}
+
+#[proc_macro_derive(FromRow, attributes(model))]
+pub fn derive_from_row(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    from_row::r#impl(&ast)
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
@@ -34,6 +52,33 @@ pub(crate) enum Relationship {
     HasMany,
     BelongsTo,
     BelongsToMany,
+    MorphToMany,
+    MorphedByMany,
+}
+
+impl Relationship {
+    /// The names of the relationship types, as they appear in a field's type.
+    pub(crate) const NAMES: [&'static str; 6] = [
+        "HasOne",
+        "HasMany",
+        "BelongsTo",
+        "BelongsToMany",
+        "MorphToMany",
+        "MorphedByMany",
+    ];
+
+    /// Whether the relationship is polymorphic, so it needs a `morph_name`.
+    pub(crate) const fn is_morph(self) -> bool {
+        matches!(self, Self::MorphToMany | Self::MorphedByMany)
+    }
+
+    /// Whether the relationship goes through a pivot table.
+    pub(crate) const fn has_pivot(self) -> bool {
+        matches!(
+            self,
+            Self::BelongsToMany | Self::MorphToMany | Self::MorphedByMany
+        )
+    }
 }
 
 impl Display for Relationship {
@@ -46,6 +91,8 @@ impl Display for Relationship {
                 Self::HasMany => "HasMany",
                 Self::BelongsTo => "BelongsTo",
                 Self::BelongsToMany => "BelongsToMany",
+                Self::MorphToMany => "MorphToMany",
+                Self::MorphedByMany => "MorphedByMany",
             }
         )
     }
@@ -59,6 +106,8 @@ impl From<String> for Relationship {
             "HasMany" => Self::HasMany,
             "BelongsTo" => Self::BelongsTo,
             "BelongsToMany" => Self::BelongsToMany,
+            "MorphToMany" => Self::MorphToMany,
+            "MorphedByMany" => Self::MorphedByMany,
             _ => panic!("Unknown relationship found."),
         }
     }
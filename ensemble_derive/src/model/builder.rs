@@ -0,0 +1,147 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::Visibility;
+
+use super::field::{Field, Fields};
+
+#[allow(clippy::too_many_lines)]
+pub fn r#impl(name: &Ident, vis: &Visibility, fields: &Fields) -> syn::Result<TokenStream> {
+    let primary_key = fields.primary_key()?;
+    let builder = format_ident!("{name}Builder");
+
+    let mut required: Vec<&Field> = vec![];
+    let mut optional: Vec<&Field> = vec![];
+    for field in &fields.fields {
+        if field.has_relationship() {
+            continue;
+        }
+
+        if field.default(name, primary_key)?.is_none() && !field.attr.db_generated {
+            required.push(field);
+        } else {
+            optional.push(field);
+        }
+    }
+
+    let generics = (0..required.len())
+        .map(|i| format_ident!("T{i}"))
+        .collect::<Vec<_>>();
+    let set = required.iter().map(|f| &f.ty).collect::<Vec<_>>();
+
+    let builder_fields = required
+        .iter()
+        .zip(&generics)
+        .map(|(field, generic)| {
+            let ident = &field.ident;
+            quote_spanned! {field.span()=> #ident: #generic }
+        })
+        .chain(optional.iter().map(|field| {
+            let (ident, ty) = (&field.ident, &field.ty);
+            quote_spanned! {field.span()=> #ident: ::std::option::Option<#ty> }
+        }));
+
+    let initial_fields = required
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            quote_spanned! {field.span()=> #ident: () }
+        })
+        .chain(optional.iter().map(|field| {
+            let ident = &field.ident;
+            quote_spanned! {field.span()=> #ident: ::std::option::Option::None }
+        }));
+
+    let required_setters = required.iter().enumerate().map(|(i, field)| {
+        let (ident, ty) = (&field.ident, &field.ty);
+        let others = generics
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, generic)| generic);
+        let before = generics.iter().take(i);
+        let after = generics.iter().skip(i + 1);
+        let (before_set, after_set) = (before.clone(), after.clone());
+        let moved = required
+            .iter()
+            .filter(|f| f.ident != field.ident)
+            .map(|f| &f.ident)
+            .chain(optional.iter().map(|f| &f.ident));
+
+        quote_spanned! {field.span()=>
+            impl<#(#others),*> #builder<#(#before,)* () #(, #after)*> {
+                pub fn #ident(self, #ident: #ty) -> #builder<#(#before_set,)* #ty #(, #after_set)*> {
+                    #builder {
+                        #ident,
+                        #(#moved: self.#moved,)*
+                    }
+                }
+            }
+        }
+    });
+
+    let optional_setters = optional.iter().map(|field| {
+        let (ident, ty) = (&field.ident, &field.ty);
+
+        quote_spanned! {field.span()=>
+            pub fn #ident(mut self, #ident: #ty) -> Self {
+                self.#ident = ::std::option::Option::Some(#ident);
+                self
+            }
+        }
+    });
+
+    let defaults = if required.len() < fields.fields.len() {
+        quote! { let defaults = <#name as ::std::default::Default>::default(); }
+    } else {
+        TokenStream::new()
+    };
+
+    let build_fields = fields.fields.iter().map(|field| {
+        let ident = &field.ident;
+
+        if required.iter().any(|f| f.ident == field.ident) {
+            quote_spanned! {field.span()=> #ident: self.#ident }
+        } else if optional.iter().any(|f| f.ident == field.ident) {
+            quote_spanned! {field.span()=> #ident: self.#ident.unwrap_or(defaults.#ident) }
+        } else {
+            quote_spanned! {field.span()=> #ident: defaults.#ident }
+        }
+    });
+
+    let tracker_init = fields.tracker_init();
+
+    Ok(quote! {
+        #[doc = concat!("A builder for [`", stringify!(#name), "`], created with [`", stringify!(#name), "::builder`].")]
+        #[must_use]
+        #vis struct #builder<#(#generics = ()),*> {
+            #(#builder_fields,)*
+        }
+
+        impl #name {
+            /// Create a builder for the model. Every field without a default value must be set before the model can be built.
+            #vis fn builder() -> #builder {
+                #builder {
+                    #(#initial_fields,)*
+                }
+            }
+        }
+
+        #(#required_setters)*
+
+        impl<#(#generics),*> #builder<#(#generics),*> {
+            #(#optional_setters)*
+        }
+
+        impl #builder<#(#set),*> {
+            /// Build the model, using the default value of every field that wasn't set.
+            pub fn build(self) -> #name {
+                #defaults
+
+                #name {
+                    #(#build_fields,)*
+                    #tracker_init
+                }
+            }
+        }
+    })
+}
@@ -24,8 +24,10 @@ impl ParseMetaItem for Value {
 
 #[derive(Debug, ParseMetaItem, Default)]
 #[deluxe(default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     pub uuid: bool,
+    pub ulid: bool,
     pub created_at: bool,
     pub updated_at: bool,
     pub incrementing: Option<bool>,
@@ -46,12 +48,15 @@ pub fn r#impl(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
         defaults.push(quote_spanned! { field.span() => #ident: #default });
     }
 
+    let tracker_init = fields.tracker_init();
+
     Ok(quote! {
         #[automatically_derived]
         impl core::default::Default for #name {
             fn default() -> Self {
                 Self {
                     #(#defaults,)*
+                    #tracker_init
                 }
             }
         }
@@ -1,6 +1,7 @@
 use std::{collections::HashMap, rc::Rc};
 
 use deluxe::ExtractAttributes;
+use inflector::Inflector;
 use proc_macro2::{Ident, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
 use syn::{spanned::Spanned, FieldsNamed, GenericArgument, PathArguments, Type};
@@ -9,9 +10,12 @@ use crate::Relationship;
 
 use super::default::{self, Value};
 
+#[allow(clippy::struct_field_names)]
 pub struct Fields {
     ast: FieldsNamed,
     pub fields: Vec<Field>,
+    /// The field holding the model's `Tracker`, which isn't a column.
+    pub tracker: Option<Field>,
 }
 
 pub struct Field {
@@ -42,6 +46,17 @@ pub struct Attr {
     pub local_key: Option<String>,
     pub foreign_key: Option<String>,
     pub pivot_table: Option<String>,
+    pub pivot_timestamps: bool,
+    pub with_pivot: Vec<String>,
+    pub morph_name: Option<String>,
+    pub get: Option<String>,
+    pub set: Option<String>,
+    pub db_generated: bool,
+    pub immutable: bool,
+    pub unique: bool,
+    pub sensitive: bool,
+    pub eager: bool,
+    pub flag: Option<String>,
 
     #[deluxe(flatten)]
     pub default: default::Options,
@@ -71,10 +86,75 @@ impl Field {
         }
     }
 
+    /// Whether the field holds the model's `Tracker`, instead of a column.
+    pub fn is_tracker(&self) -> bool {
+        matches!(&self.ty, Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == "Tracker"))
+    }
+
     pub fn span(&self) -> proc_macro2::Span {
         self.ast.span()
     }
 
+    pub fn getter(&self) -> syn::Result<Option<syn::Path>> {
+        self.attr
+            .get
+            .as_deref()
+            .map(|path| syn::parse_str(path).map_err(|e| syn::Error::new(self.span(), e)))
+            .transpose()
+    }
+
+    pub fn setter(&self) -> syn::Result<Option<syn::Path>> {
+        self.attr
+            .set
+            .as_deref()
+            .map(|path| syn::parse_str(path).map_err(|e| syn::Error::new(self.span(), e)))
+            .transpose()
+    }
+
+    /// The type of a field marked with `#[model(uuid)]` or `#[model(ulid)]`, whose values are generated when models are created.
+    pub fn generated_key(&self) -> syn::Result<Option<&syn::TypePath>> {
+        let attrs = &self.attr.default;
+        let expected = match (attrs.uuid, attrs.ulid) {
+            (false, false) => return Ok(None),
+            (true, true) => {
+                return Err(syn::Error::new_spanned(
+                    self,
+                    "A field can't be both a UUID and a ULID",
+                ))
+            }
+            (true, false) => "Uuid",
+            (false, true) => "Ulid",
+        };
+
+        match &self.ty {
+            Type::Path(ty) if ty.path.segments.last().unwrap().ident == expected => Ok(Some(ty)),
+            ty => Err(syn::Error::new_spanned(
+                ty,
+                format!("Field must be of type ensemble::types::{expected}"),
+            )),
+        }
+    }
+
+    /// String literals are used as-is for `String` fields, and parsed as expressions (e.g. `"Uuid::new_v4()"`) for every other type.
+    fn default_expr(&self, expr: &syn::Expr) -> syn::Result<syn::Expr> {
+        let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) = expr
+        else {
+            return Ok(expr.clone());
+        };
+
+        let is_string = matches!(&self.ty, Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == "String"));
+        if is_string {
+            return Ok(
+                syn::parse_quote_spanned! { lit.span() => ::std::string::String::from(#lit) },
+            );
+        }
+
+        lit.parse()
+    }
+
     pub fn default(&self, name: &Ident, primary_key: &Self) -> syn::Result<Option<TokenStream>> {
         let attrs = &self.attr.default;
         let is_primary = primary_key.ident == self.ident;
@@ -82,24 +162,16 @@ impl Field {
 
         Ok(if let Some(default) = &attrs.value {
             match default {
-                Value::Expr(expr) => Some(quote_spanned! { self.span() => #expr }),
-                Value::Default => Some(quote_spanned! { self.span() => Default::default() }),
-            }
-        } else if attrs.uuid {
-            let Type::Path(ty) = &self.ty else {
-                return Err(syn::Error::new_spanned(
-                    self,
-                    "Field must be of type ensemble::types::Uuid",
-                ));
-            };
+                Value::Expr(expr) => {
+                    let ty = &self.ty;
+                    let expr = self.default_expr(expr)?;
 
-            if ty.path.segments.last().unwrap().ident != "Uuid" {
-                return Err(syn::Error::new_spanned(
-                    ty,
-                    "Field must be of type ensemble::types::Uuid",
-                ));
+                    // spanned to the expression, so type errors point at it instead of the whole field
+                    Some(quote_spanned! { expr.span() => ::core::convert::identity::<#ty>(#expr) })
+                }
+                Value::Default => Some(quote_spanned! { self.span() => Default::default() }),
             }
-
+        } else if let Some(ty) = self.generated_key()? {
             Some(quote_spanned! { self.span() => <#ty>::new() })
         } else if attrs.incrementing.unwrap_or(is_primary && is_u64) {
             Some(quote_spanned! { self.span() => 0 })
@@ -123,8 +195,10 @@ impl Field {
                 ));
             }
 
+            let options = self.relationship_options(relationship_type)?;
+
             Some(
-                quote_spanned! { self.span() => <#relationship_ident<#name, #related>>::build(Default::default(), #foreign_key) },
+                quote_spanned! { self.span() => <#relationship_ident<#name, #related>>::build(Default::default(), #foreign_key) #options },
             )
         } else if self.ty.to_token_stream().to_string().starts_with("Option") {
             Some(quote_spanned! { self.span() => None })
@@ -133,6 +207,47 @@ impl Field {
         })
     }
 
+    /// The builder methods configuring the relationship, called after it's built.
+    pub(crate) fn relationship_options(
+        &self,
+        relationship_type: Relationship,
+    ) -> syn::Result<TokenStream> {
+        let name = self.ident.to_string();
+        let mut options = quote_spanned! {self.span()=> .named(#name) };
+
+        if relationship_type.is_morph() && self.attr.morph_name.is_none() {
+            return Err(syn::Error::new_spanned(
+                self,
+                format!("{relationship_type} relationships need a name, e.g. `#[model(morph_name = \"taggable\")]`."),
+            ));
+        }
+
+        if self.attr.pivot_timestamps {
+            if !relationship_type.has_pivot() {
+                return Err(syn::Error::new_spanned(
+                    self,
+                    "Only relationships with a pivot table have pivot timestamps.",
+                ));
+            }
+
+            options.extend(quote_spanned! {self.span()=> .with_timestamps() });
+        }
+
+        if !self.attr.with_pivot.is_empty() {
+            if !relationship_type.has_pivot() {
+                return Err(syn::Error::new_spanned(
+                    self,
+                    "Only relationships with a pivot table have pivot columns.",
+                ));
+            }
+
+            let columns = &self.attr.with_pivot;
+            options.extend(quote_spanned! {self.span()=> .with_pivot([#(#columns),*]) });
+        }
+
+        Ok(options)
+    }
+
     pub(crate) fn foreign_key(
         &self,
         relationship_type: Relationship,
@@ -146,6 +261,15 @@ impl Field {
 
                 quote_spanned! {self.span()=> (#pivot_table, #foreign_key, #local_key) }
             }
+            Relationship::MorphToMany | Relationship::MorphedByMany => {
+                // a missing name is reported by `relationship_options`
+                let morph_name = self.attr.morph_name.clone().unwrap_or_default();
+                let local_key = wrap_option(self.attr.local_key.clone());
+                let pivot_table = wrap_option(self.attr.pivot_table.clone());
+                let foreign_key = wrap_option(self.attr.foreign_key.clone());
+
+                quote_spanned! {self.span()=> (#morph_name.to_string(), #pivot_table, #foreign_key, #local_key) }
+            }
             Relationship::BelongsTo => {
                 quote_spanned! {self.span()=> Some(#related::PRIMARY_KEY.to_string()) }
             }
@@ -153,6 +277,36 @@ impl Field {
         }
     }
 
+    /// The variant of `ensemble::value::ColumnType` matching the field's type, looking through `Option`.
+    pub fn column_type(&self) -> TokenStream {
+        let mut ty = &self.ty;
+        if let Some(inner) = option_inner(ty) {
+            ty = inner;
+        }
+
+        let variant = match ty.to_token_stream().to_string().as_str() {
+            "bool" => "Bool",
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "Integer",
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "UnsignedInteger",
+            "f32" | "f64" => "Float",
+            "String" | "char" => "String",
+            "Vec < u8 >" => "Bytes",
+            _ => match ty {
+                Type::Path(path) => match path.path.segments.last() {
+                    Some(segment) if segment.ident == "DateTime" => "DateTime",
+                    Some(segment) if segment.ident == "Uuid" => "Uuid",
+                    Some(segment) if segment.ident == "Ulid" => "Ulid",
+                    Some(segment) if segment.ident == "Json" => "Json",
+                    _ => "Other",
+                },
+                _ => "Other",
+            },
+        };
+
+        let variant = Ident::new(variant, self.span());
+        quote_spanned! {self.span()=> ::ensemble::value::ColumnType::#variant }
+    }
+
     pub fn has_relationship(&self) -> bool {
         let Type::Path(ty) = &self.ty else {
             return false;
@@ -162,7 +316,7 @@ impl Field {
             return false;
         };
 
-        ["HasOne", "HasMany", "BelongsTo", "BelongsToMany"].contains(&ty.ident.to_string().as_str())
+        Relationship::NAMES.contains(&ty.ident.to_string().as_str())
     }
 
     pub(crate) fn relationship(
@@ -173,14 +327,10 @@ impl Field {
             return None;
         };
 
-        let Some(ty) = ty.path.segments.first() else {
-            return None;
-        };
+        let ty = ty.path.segments.first()?;
 
         let relationship_type = ty.ident.to_string();
-        if !["HasOne", "HasMany", "BelongsTo", "BelongsToMany"]
-            .contains(&relationship_type.as_str())
-        {
+        if !Relationship::NAMES.contains(&relationship_type.as_str()) {
             return None;
         }
         let relationship_type: Relationship = relationship_type.into();
@@ -195,14 +345,18 @@ impl Field {
         let related = &ty.path.segments.first().unwrap().ident;
 
         let value_key = match relationship_type {
-            Relationship::BelongsToMany | Relationship::HasOne | Relationship::HasMany => (
+            Relationship::BelongsToMany
+            | Relationship::MorphToMany
+            | Relationship::MorphedByMany
+            | Relationship::HasOne
+            | Relationship::HasMany => (
                 primary_key.ident.to_string(),
                 primary_key.ident.to_token_stream(),
             ),
             Relationship::BelongsTo => (
                 self.ident.to_string(),
                 self.attr.foreign_key.as_ref().map_or_else(
-                    || quote_spanned! {self.span() => format!("{}_{}", #related::NAME.to_snake_case(), #related::PRIMARY_KEY).to_snake_case() },
+                    || quote_spanned! {self.span() => ::ensemble::naming::strategy().foreign_key(#related::NAME, #related::PRIMARY_KEY) },
                     |foreign_key| quote_spanned! {self.span() => #foreign_key.to_string() }
                 ),
             ),
@@ -219,6 +373,27 @@ impl ToTokens for Field {
 }
 
 impl Fields {
+    /// Parse the fields of a struct that isn't a model, so it has no primary key or relationships.
+    pub fn plain(ast: FieldsNamed) -> Self {
+        let fields = ast.named.iter().map(|f| Field::new(f.clone())).collect();
+
+        Self {
+            ast,
+            fields,
+            tracker: None,
+        }
+    }
+
+    /// Initialize the tracker field (if any) in a struct expression, after the other fields.
+    pub fn tracker_init(&self) -> TokenStream {
+        self.tracker
+            .as_ref()
+            .map_or_else(TokenStream::new, |field| {
+                let ident = &field.ident;
+                quote_spanned! {field.span()=> #ident: ::std::default::Default::default(), }
+            })
+    }
+
     pub fn should_validate(&self) -> bool {
         self.fields.iter().any(|f| f.has_validation)
     }
@@ -250,6 +425,28 @@ impl Fields {
         })
     }
 
+    /// Name the columns of the fields without a `column` attribute with the given case (like serde's `rename_all`).
+    /// Relationships aren't columns, so they keep their names.
+    pub fn rename_all(&mut self, rule: &str) -> Result<(), String> {
+        let rename: fn(&str) -> String = match rule {
+            "camelCase" => Inflector::to_camel_case,
+            "PascalCase" => Inflector::to_pascal_case,
+            "snake_case" => Inflector::to_snake_case,
+            "SCREAMING_SNAKE_CASE" => Inflector::to_screaming_snake_case,
+            _ => return Err(format!(
+                "Unknown `rename_all` rule `{rule}`. Expected one of `camelCase`, `PascalCase`, `snake_case` or `SCREAMING_SNAKE_CASE`."
+            )),
+        };
+
+        for field in &mut self.fields {
+            if field.attr.column.is_none() && !field.has_relationship() {
+                field.attr.column = Some(rename(&field.ident.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn relationships(&self) -> Vec<&Field> {
         self.fields
             .iter()
@@ -286,9 +483,11 @@ impl ToTokens for Fields {
 impl TryFrom<FieldsNamed> for Fields {
     type Error = syn::Error;
     fn try_from(ast: FieldsNamed) -> Result<Self, Self::Error> {
-        let fields = ast.named.iter().map(|f| Field::new(f.clone())).collect();
+        let mut fields = Self::plain(ast);
 
-        let mut fields = Self { ast, fields };
+        if let Some(index) = fields.fields.iter().position(Field::is_tracker) {
+            fields.tracker = Some(fields.fields.remove(index));
+        }
 
         fields.mark_relationship_keys()?;
 
@@ -296,6 +495,26 @@ impl TryFrom<FieldsNamed> for Fields {
     }
 }
 
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+
+    let segment = ty.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
 fn wrap_option<T: quote::ToTokens>(option: Option<T>) -> TokenStream {
     option.map_or_else(
         || quote! { None },
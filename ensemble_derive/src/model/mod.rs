@@ -9,15 +9,36 @@ use crate::Relationship;
 
 use self::field::{Field, Fields};
 
+mod builder;
 mod default;
-mod field;
-mod serde;
+pub mod field;
+mod patch;
+#[cfg(all(feature = "schema", feature = "json"))]
+mod schema;
+pub mod serde;
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(ExtractAttributes, Default)]
 #[deluxe(attributes(ensemble), default)]
 pub struct Opts {
     #[deluxe(rename = table)]
     table_name: Option<String>,
+    inflection: Option<String>,
+    schema: Option<String>,
+    read_only: bool,
+    builder: bool,
+    patch: bool,
+    upsert: bool,
+    soft_deletes: bool,
+    outbox: bool,
+    auditable: bool,
+    tenant_column: Option<String>,
+    json_schema: bool,
+    rename_all: Option<String>,
+    after_commit: Option<String>,
+    after_rollback: Option<String>,
+    #[deluxe(append, rename = global_scope)]
+    global_scopes: Vec<String>,
 }
 
 pub fn r#impl(ast: &DeriveInput, opts: Opts) -> syn::Result<proc_macro2::TokenStream> {
@@ -35,20 +56,57 @@ pub fn r#impl(ast: &DeriveInput, opts: Opts) -> syn::Result<proc_macro2::TokenSt
         ));
     };
 
-    let fields = Fields::try_from(struct_fields.clone())?;
+    #[cfg(not(feature = "json"))]
+    if opts.outbox || opts.auditable {
+        return Err(syn::Error::new_spanned(
+            ast,
+            "The outbox and audit trail require the `json` feature",
+        ));
+    }
+
+    let mut fields = Fields::try_from(struct_fields.clone())?;
+    if let Some(rule) = &opts.rename_all {
+        fields
+            .rename_all(rule)
+            .map_err(|message| syn::Error::new_spanned(ast, message))?;
+    }
     let primary_key = fields.primary_key()?;
 
     let find_impl = impl_find(primary_key);
     let fresh_impl = impl_fresh(primary_key);
     let eager_load_impl = impl_eager_load(&fields);
-    let save_impl = impl_save(&fields, primary_key);
+    let save_impl = impl_save(&fields, primary_key, &opts);
     let primary_key_impl = impl_primary_key(primary_key);
+    let columns_impl = impl_columns(&fields, primary_key);
+    #[cfg(feature = "json")]
+    let serialize_visible_impl = serde::impl_serialize_visible(&ast.ident, &fields);
+    #[cfg(not(feature = "json"))]
+    let serialize_visible_impl = TokenStream::new();
     let fill_relation_impl = impl_fill_relation(&fields);
+    let exists_impl = impl_exists(&fields);
+    let relations_impl = impl_relations(&ast.ident, &fields)?;
+    let cache_impl = impl_cache(&fields);
     let serde_impl = serde::r#impl(&ast.ident, &fields)?;
     let default_impl = default::r#impl(&ast.ident, &fields)?;
-    let create_impl = impl_create(&ast.ident, &fields, primary_key);
+    let create_impl = impl_create(&ast.ident, &fields, primary_key, &opts);
     let relationships_impl = impl_relationships(&ast.ident, &fields)?;
-    let table_name_impl = impl_table_name(&ast.ident.to_string(), opts.table_name);
+    let flags_impl = impl_flags(&ast.ident, &fields)?;
+    let find_by_impl = impl_find_by(ast, &fields, &opts)?;
+    let (tracker_impl, tracker_methods_impl) = impl_tracker(&ast.ident, &fields);
+    let schema_impl = impl_schema(ast, &fields, &opts)?;
+    let builder_impl = if opts.builder {
+        builder::r#impl(&ast.ident, &ast.vis, &fields)?
+    } else {
+        TokenStream::new()
+    };
+    let patch_impl = if opts.patch {
+        patch::r#impl(&ast.ident, &ast.vis, &fields, &opts)?
+    } else {
+        TokenStream::new()
+    };
+    let constants_impl = impl_constants(&fields, &opts)?;
+    let table_name_impl =
+        impl_table_name(&ast.ident, opts.table_name, opts.schema, opts.inflection)?;
 
     let name = &ast.ident;
     let primary_key_type = &primary_key.ty;
@@ -60,6 +118,7 @@ pub fn r#impl(ast: &DeriveInput, opts: Opts) -> syn::Result<proc_macro2::TokenSt
             impl Model for #name {
                 type PrimaryKey = #primary_key_type;
                 const NAME: &'static str = stringify!(#name);
+                #constants_impl
 
                 #save_impl
                 #find_impl
@@ -68,17 +127,525 @@ pub fn r#impl(ast: &DeriveInput, opts: Opts) -> syn::Result<proc_macro2::TokenSt
                 #table_name_impl
                 #eager_load_impl
                 #primary_key_impl
+                #columns_impl
+                #serialize_visible_impl
                 #fill_relation_impl
+                #exists_impl
+                #relations_impl
+                #cache_impl
+                #tracker_impl
             }
             #serde_impl
             #default_impl
             #relationships_impl
+            #flags_impl
+            #find_by_impl
+            #tracker_methods_impl
+            #schema_impl
         };
+        #builder_impl
+        #patch_impl
     };
 
     Ok(gen)
 }
 
+/// A placeholder implementation of `Model` (and the traits it requires) for a model whose attributes are invalid,
+/// so the error is reported on its own instead of along with every use of the model as one.
+/// The crate can't compile with the error, so none of it ever runs.
+pub fn stub(ast: &DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+
+    #[cfg(feature = "json")]
+    let serialize_visible_impl = quote! {
+        fn serialize_visible<S: ::ensemble::serde::Serializer>(
+            &self,
+            _: S,
+            _: &::ensemble::value::Visibility<'_>,
+        ) -> Result<S::Ok, S::Error> {
+            unreachable!()
+        }
+    };
+    #[cfg(not(feature = "json"))]
+    let serialize_visible_impl = TokenStream::new();
+
+    quote! {
+        const _: () = {
+            #[automatically_derived]
+            #[ensemble::async_trait]
+            impl ::ensemble::Model for #name {
+                type PrimaryKey = u64;
+                const NAME: &'static str = stringify!(#name);
+                const TABLE_NAME: &'static str = "";
+                const PRIMARY_KEY: &'static str = "";
+
+                fn primary_key(&self) -> &u64 {
+                    unreachable!()
+                }
+
+                fn columns() -> &'static [::ensemble::value::ColumnDef] {
+                    unreachable!()
+                }
+
+                fn keys() -> &'static [&'static str] {
+                    unreachable!()
+                }
+
+                async fn find(_: u64) -> Result<Self, ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                async fn create(self) -> Result<Self, ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                async fn save(&mut self) -> Result<(), ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                async fn fresh(&self) -> Result<Self, ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                #serialize_visible_impl
+
+                fn prepare_create(&mut self) -> Result<(), ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                fn prepare_save(&mut self) -> Result<(), ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                fn eager_load(&self, _: &str, _: &[&Self]) -> ::ensemble::query::Builder {
+                    unreachable!()
+                }
+
+                fn fill_relation(
+                    &mut self,
+                    _: &str,
+                    _: &[::std::collections::HashMap<String, ::ensemble::rbs::Value>],
+                ) -> Result<(), ::ensemble::Error> {
+                    unreachable!()
+                }
+
+                fn exists_query(&self, _: &str, _: &[&Self]) -> ::ensemble::query::Builder {
+                    unreachable!()
+                }
+
+                fn fill_exists(&mut self, _: &str, _: &[::ensemble::rbs::Value]) -> Result<(), ::ensemble::Error> {
+                    unreachable!()
+                }
+            }
+
+            #[automatically_derived]
+            impl ::ensemble::serde::Serialize for #name {
+                fn serialize<S: ::ensemble::serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                    unreachable!()
+                }
+            }
+
+            #[automatically_derived]
+            impl<'de> ::ensemble::serde::Deserialize<'de> for #name {
+                fn deserialize<D: ::ensemble::serde::Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+                    unreachable!()
+                }
+            }
+
+            #[automatically_derived]
+            impl ::std::default::Default for #name {
+                fn default() -> Self {
+                    unreachable!()
+                }
+            }
+        };
+    }
+}
+
+fn impl_constants(fields: &Fields, opts: &Opts) -> syn::Result<TokenStream> {
+    let read_only = opts.read_only;
+    let outbox = opts.outbox;
+    let auditable = opts.auditable;
+    let soft_deletes = opts.soft_deletes;
+    let timestamps = fields
+        .fields
+        .iter()
+        .filter(|f| f.attr.default.created_at || f.attr.default.updated_at)
+        .map(|f| f.attr.column.clone().unwrap_or_else(|| f.ident.to_string()));
+    let tenant_column_impl = opts.tenant_column.as_ref().map(|column| {
+        quote! { const TENANT_COLUMN: Option<&'static str> = Some(#column); }
+    });
+
+    let hooks_impl = impl_hooks(opts)?;
+    let global_scopes_impl = impl_global_scopes(opts)?;
+
+    let mut eager_load = vec![];
+    for field in fields.fields.iter().filter(|f| f.attr.eager) {
+        if !field.has_relationship() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Only relationships can be eager loaded.",
+            ));
+        }
+
+        eager_load.push(field.ident.to_string());
+    }
+
+    Ok(quote! {
+        const READ_ONLY: bool = #read_only;
+        const EAGER_LOAD: &'static [&'static str] = &[#(#eager_load),*];
+        const OUTBOX: bool = #outbox;
+        const AUDITABLE: bool = #auditable;
+        const SOFT_DELETES: bool = #soft_deletes;
+        const TIMESTAMPS: &'static [&'static str] = &[#(#timestamps),*];
+        #tenant_column_impl
+        #hooks_impl
+        #global_scopes_impl
+    })
+}
+
+fn impl_global_scopes(opts: &Opts) -> syn::Result<TokenStream> {
+    if opts.global_scopes.is_empty() {
+        return Ok(TokenStream::new());
+    }
+
+    let scopes = opts
+        .global_scopes
+        .iter()
+        .map(|name| {
+            let path = syn::parse_str::<syn::Path>(name).map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    format!("A global scope must be a path to a function: {e}"),
+                )
+            })?;
+
+            Ok(quote! {
+                ::ensemble::query::GlobalScope { name: #name, apply: #path }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        const GLOBAL_SCOPES: &'static [::ensemble::query::GlobalScope] = &[#(#scopes),*];
+    })
+}
+
+fn impl_hooks(opts: &Opts) -> syn::Result<TokenStream> {
+    if opts.after_commit.is_none() && opts.after_rollback.is_none() {
+        return Ok(TokenStream::new());
+    }
+
+    let hook = |name: &str, path: Option<&String>| {
+        let Some(path) = path else {
+            return Ok(TokenStream::new());
+        };
+
+        let method = Ident::new(name, proc_macro2::Span::call_site());
+        let path = syn::parse_str::<syn::Path>(path).map_err(|e| {
+            syn::Error::new(
+                e.span(),
+                format!("The {name} hook must be a path to a function: {e}"),
+            )
+        })?;
+
+        Ok::<_, syn::Error>(quote! {
+            fn #method(&self, event: ::ensemble::hooks::EventType) {
+                #path(self, event);
+            }
+        })
+    };
+
+    let after_commit = hook("after_commit", opts.after_commit.as_ref())?;
+    let after_rollback = hook("after_rollback", opts.after_rollback.as_ref())?;
+
+    Ok(quote! {
+        const HOOKS: bool = true;
+        #after_commit
+        #after_rollback
+    })
+}
+
+fn impl_find_by(ast: &DeriveInput, fields: &Fields, opts: &Opts) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let primary_key = fields.primary_key()?;
+    let mut impls = vec![
+        impl_upsert(ast, fields, opts)?,
+        impl_soft_deletes(ast, fields, opts)?,
+    ];
+
+    for field in fields.fields.iter().filter(|field| field.attr.unique) {
+        if field.relationship(primary_key).is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Only columns can be marked as unique.",
+            ));
+        }
+
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let column = field
+            .attr
+            .column
+            .clone()
+            .unwrap_or_else(|| ident.to_string());
+        let method = Ident::new(&format!("find_by_{ident}"), ident.span());
+        let doc = format!("Find the model with the given `{column}`, if there is one.");
+
+        impls.push(quote_spanned! {field.span()=>
+            #[doc = #doc]
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the query fails, or if a connection to the database cannot be established.
+            #[allow(dead_code)]
+            pub async fn #method(#ident: impl Into<#ty> + Send) -> Result<Option<Self>, ::ensemble::Error> {
+                <Self as ::ensemble::Model>::find_by(#column, #ident.into()).await
+            }
+        });
+    }
+
+    if impls.iter().all(TokenStream::is_empty) {
+        return Ok(TokenStream::new());
+    }
+
+    Ok(quote! {
+        impl #name {
+            #(#impls)*
+        }
+    })
+}
+
+fn impl_upsert(ast: &DeriveInput, fields: &Fields, opts: &Opts) -> syn::Result<TokenStream> {
+    if !opts.upsert {
+        return Ok(TokenStream::new());
+    }
+
+    let column = |field: &Field| {
+        field
+            .attr
+            .column
+            .clone()
+            .unwrap_or_else(|| field.ident.to_string())
+    };
+    let unique = fields
+        .fields
+        .iter()
+        .filter(|field| field.attr.unique)
+        .map(column)
+        .collect::<Vec<_>>();
+    let keep = fields
+        .fields
+        .iter()
+        .filter(|field| field.attr.default.created_at)
+        .map(column);
+
+    if unique.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "`upsert` needs at least one column marked with `#[model(unique)]`, to tell which stored model to update.",
+        ));
+    }
+    if opts.outbox || opts.auditable {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "`upsert` can't record the changes it makes, so it isn't available on outbox or auditable models.",
+        ));
+    }
+
+    let doc = format!(
+        "Insert the model or, if one with the same {} is already stored, update it with this model's other columns.",
+        unique.iter().map(|column| format!("`{column}`")).collect::<Vec<_>>().join(", ")
+    );
+
+    Ok(quote! {
+        #[doc = #doc]
+        /// Returns the model as it's stored, with the stored model's primary key if it was updated.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the model cannot be written or read back, or if a connection to the database cannot be established.
+        #[allow(dead_code)]
+        pub async fn upsert(mut self) -> Result<Self, ::ensemble::Error> {
+            <Self as ::ensemble::Model>::prepare_create(&mut self)?;
+
+            ::ensemble::query::upsert(&self, &[#(#unique),*], &[#(#keep),*]).await
+        }
+    })
+}
+
+fn impl_soft_deletes(ast: &DeriveInput, fields: &Fields, opts: &Opts) -> syn::Result<TokenStream> {
+    if !opts.soft_deletes {
+        return Ok(TokenStream::new());
+    }
+
+    let Some(field) = fields.fields.iter().find(|field| {
+        field.attr.column.as_ref().map_or_else(
+            || field.ident == "deleted_at",
+            |column| column == "deleted_at",
+        )
+    }) else {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "`soft_deletes` needs a `deleted_at: Option<ensemble::types::DateTime>` field, to mark deleted models with.",
+        ));
+    };
+    let ty = field.ty.to_token_stream().to_string().replace(' ', "");
+    if !ty.starts_with("Option<") || !ty.ends_with("DateTime>") {
+        return Err(syn::Error::new_spanned(
+            &field.ty,
+            "`deleted_at` must be of type Option<ensemble::types::DateTime>.",
+        ));
+    }
+
+    let ident = &field.ident;
+
+    // outbox and auditable models record the restore as an update of the restored model
+    let restore = if opts.outbox || opts.auditable {
+        quote! {
+            let deleted_at = self.#ident.take();
+            let rows_affected = ::ensemble::write::restore(&*self).await;
+            if !matches!(rows_affected, Ok(1)) {
+                self.#ident = deleted_at;
+                rows_affected?;
+                return Err(::ensemble::Error::NotFound);
+            }
+        }
+    } else {
+        quote! {
+            let rows_affected = <Self as ::ensemble::Model>::query()
+                .r#where(Self::PRIMARY_KEY, "=", ::ensemble::value::for_db(::ensemble::Model::primary_key(self))?)
+                .restore()
+                .await?;
+            if rows_affected != 1 {
+                return Err(::ensemble::Error::NotFound);
+            }
+
+            self.#ident = None;
+        }
+    };
+
+    Ok(quote! {
+        /// Whether the model was soft deleted.
+        #[allow(dead_code)]
+        #[must_use]
+        pub const fn trashed(&self) -> bool {
+            self.#ident.is_some()
+        }
+
+        /// Restore the soft deleted model, clearing its `deleted_at` column.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::NotFound`](::ensemble::Error::NotFound) if the model isn't soft deleted, or an error if the query fails or a connection to the database cannot be established.
+        #[allow(dead_code)]
+        pub async fn restore(&mut self) -> Result<(), ::ensemble::Error> {
+            #restore
+            Ok(())
+        }
+
+        /// Query every model, including soft deleted ones.
+        #[allow(dead_code)]
+        #[must_use]
+        pub fn with_trashed() -> ::ensemble::query::Builder {
+            <Self as ::ensemble::Model>::query().with_trashed()
+        }
+
+        /// Query the soft deleted models.
+        #[allow(dead_code)]
+        #[must_use]
+        pub fn only_trashed() -> ::ensemble::query::Builder {
+            <Self as ::ensemble::Model>::query().only_trashed()
+        }
+    })
+}
+
+fn impl_flags(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
+    let mut impls = vec![];
+
+    for field in &fields.fields {
+        let Some(verb) = &field.attr.flag else {
+            continue;
+        };
+
+        let ident = &field.ident;
+        let ty = field.ty.to_token_stream().to_string().replace(' ', "");
+        let Some(state) = ident.to_string().strip_suffix("_at").map(str::to_string) else {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "Flags must be timestamps named after their state, like `published_at`.",
+            ));
+        };
+        if !ty.starts_with("Option<") || !ty.ends_with("DateTime>") {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "Flags must be of type Option<ensemble::types::DateTime>.",
+            ));
+        }
+
+        let column = field
+            .attr
+            .column
+            .clone()
+            .unwrap_or_else(|| ident.to_string());
+        let is_state = Ident::new(&format!("is_{state}"), ident.span());
+        let set = Ident::new(verb, ident.span());
+        let unset = Ident::new(&format!("un{verb}"), ident.span());
+        let scope = Ident::new(&state, ident.span());
+        let unset_scope = Ident::new(&format!("un{state}"), ident.span());
+        let set_doc = format!("Set `{ident}` to the current time.");
+        let unset_doc = format!("Clear `{ident}`.");
+        let is_state_doc = format!("Whether `{ident}` is set.");
+        let scope_doc = format!("Query the models whose `{column}` is set.");
+        let unset_scope_doc = format!("Query the models whose `{column}` isn't set.");
+
+        impls.push(quote_spanned! {field.span()=>
+            #[doc = #is_state_doc]
+            #[allow(dead_code)]
+            #[must_use]
+            pub const fn #is_state(&self) -> bool {
+                self.#ident.is_some()
+            }
+
+            #[doc = #set_doc]
+            #[allow(dead_code)]
+            pub fn #set(&mut self) {
+                self.#ident = Some(::ensemble::types::DateTime::now());
+            }
+
+            #[doc = #unset_doc]
+            #[allow(dead_code)]
+            pub fn #unset(&mut self) {
+                self.#ident = None;
+            }
+
+            #[doc = #scope_doc]
+            #[allow(dead_code)]
+            #[must_use]
+            pub fn #scope() -> ::ensemble::query::Builder {
+                <Self as ::ensemble::Model>::query().where_not_null(#column)
+            }
+
+            #[doc = #unset_scope_doc]
+            #[allow(dead_code)]
+            #[must_use]
+            pub fn #unset_scope() -> ::ensemble::query::Builder {
+                <Self as ::ensemble::Model>::query().where_null(#column)
+            }
+        });
+    }
+
+    if impls.is_empty() {
+        return Ok(TokenStream::new());
+    }
+
+    Ok(quote! {
+        impl #name {
+            #(#impls)*
+        }
+    })
+}
+
 fn impl_fill_relation(fields: &Fields) -> TokenStream {
     let relationships = fields.relationships();
 
@@ -120,12 +687,175 @@ fn impl_eager_load(fields: &Fields) -> TokenStream {
     }
 }
 
+fn impl_exists(fields: &Fields) -> TokenStream {
+    let relationships = fields.relationships();
+
+    let exists_queries = relationships.iter().map(|field| {
+        let ident = &field.ident;
+
+        quote_spanned! {field.span() =>
+            stringify!(#ident) => self.#ident.exists_query(related.iter().map(|model| &model.#ident.value).cloned().collect()),
+        }
+    });
+
+    let fill_exists = relationships.iter().map(|field| {
+        let ident = &field.ident;
+
+        quote_spanned! {field.span() =>
+            stringify!(#ident) => self.#ident.match_exists(keys),
+        }
+    });
+
+    quote! {
+        fn exists_query(&self, relation: &str, related: &[&Self]) -> ::ensemble::query::Builder {
+            match relation {
+                #(#exists_queries)*
+                _ => panic!("Model does not have a {relation} relation"),
+            }
+        }
+
+        fn fill_exists(&mut self, relation: &str, keys: &[::ensemble::rbs::Value]) -> Result<(), ::ensemble::Error> {
+            match relation {
+                #(#fill_exists)*
+                _ => panic!("Model does not have a {relation} relation"),
+            }
+        }
+    }
+}
+
+/// The `relations` method, describing every relationship with the same instance the model builds by default,
+/// so the definitions always match the queries the relationships run.
+fn impl_relations(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
+    let primary_key = fields.primary_key()?;
+    let relationships = fields.relationships();
+
+    if relationships.is_empty() {
+        return Ok(TokenStream::new());
+    }
+
+    let definitions = relationships
+        .iter()
+        .map(|field| {
+            let Some(relationship) = field.default(name, primary_key)? else {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    "Relationships can't have a default value.",
+                ));
+            };
+            let definition = quote_spanned! {field.span()=> ::ensemble::relationships::Relationship::definition(&#relationship) };
+
+            Ok(match field.relationship(primary_key) {
+                Some((Relationship::BelongsTo, _, (_, foreign_key))) => {
+                    quote_spanned! {field.span()=>
+                        ::ensemble::relationships::RelationDef {
+                            foreign_key: #foreign_key,
+                            ..#definition
+                        }
+                    }
+                }
+                _ => definition,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        fn relations() -> &'static [::ensemble::relationships::RelationDef] {
+            // keys are named at runtime, using the configured naming strategy
+            static RELATIONS: ::std::sync::OnceLock<::std::vec::Vec<::ensemble::relationships::RelationDef>> = ::std::sync::OnceLock::new();
+
+            RELATIONS.get_or_init(|| ::std::vec![#(#definitions),*])
+        }
+    })
+}
+
+/// The columns and relationships that `to_cached_bytes` needs, besides the ones serialized for the database.
+fn impl_cache(fields: &Fields) -> TokenStream {
+    let generated = fields
+        .fields
+        .iter()
+        .filter(|field| field.attr.db_generated)
+        .map(|field| {
+            let ident = &field.ident;
+            let column = field
+                .attr
+                .column
+                .clone()
+                .unwrap_or_else(|| ident.to_string());
+
+            quote_spanned! {field.span() =>
+                (#column, ::ensemble::value::for_db(&self.#ident)?),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let generated_columns = (!generated.is_empty()).then(|| {
+        quote! {
+            #[allow(clippy::needless_borrows_for_generic_args)]
+            fn generated_columns(&self) -> Result<Vec<(&'static str, ::ensemble::rbs::Value)>, ::ensemble::Error> {
+                Ok(vec![#(#generated)*])
+            }
+        }
+    });
+
+    let relationships = fields.relationships();
+    if relationships.is_empty() {
+        return quote! { #generated_columns };
+    }
+
+    let cached = relationships.iter().map(|field| {
+        let ident = &field.ident;
+
+        quote_spanned! {field.span() =>
+            if let Some(value) = self.#ident.cached()? {
+                relations.push((stringify!(#ident), value));
+            }
+        }
+    });
+
+    let restore = relationships.iter().map(|field| {
+        let ident = &field.ident;
+
+        quote_spanned! {field.span() =>
+            stringify!(#ident) => self.#ident.restore(value),
+        }
+    });
+
+    quote! {
+        #generated_columns
+
+        fn cached_relations(&self) -> Result<Vec<(&'static str, ::ensemble::rbs::Value)>, ::ensemble::Error> {
+            let mut relations = vec![];
+            #(#cached)*
+
+            Ok(relations)
+        }
+
+        fn restore_relation(&mut self, relation: &str, value: ::ensemble::rbs::Value) -> Result<(), ::ensemble::Error> {
+            match relation {
+                #(#restore)*
+                _ => Err(::ensemble::Error::InvalidCache(format!("{} has no {relation} relationship", Self::NAME))),
+            }
+        }
+    }
+}
+
 fn impl_fresh(primary_key: &Field) -> TokenStream {
     let ident = &primary_key.ident;
 
     quote! {
         async fn fresh(&self) -> Result<Self, ::ensemble::Error> {
-            Self::find(self.#ident.clone()).await
+            ::ensemble::identity_map::forget::<Self>(&self.#ident)?;
+
+            // read replicas may not have caught up with the model's latest writes
+            let model = Self::query()
+                .on_primary()
+                .r#where(Self::PRIMARY_KEY, "=", ::ensemble::value::for_db(&self.#ident)?)
+                .first()
+                .await?
+                .ok_or(::ensemble::Error::NotFound)?;
+            ::ensemble::identity_map::remember(&model)?;
+
+            Ok(model)
         }
     }
 }
@@ -142,7 +872,10 @@ fn impl_relationships(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
         let ident = &f.ident;
         let (r#type, related, _) = f.relationship(primary_key).unwrap();
         let return_type = match r#type {
-            Relationship::HasMany | Relationship::BelongsToMany => {
+            Relationship::HasMany
+            | Relationship::BelongsToMany
+            | Relationship::MorphToMany
+            | Relationship::MorphedByMany => {
                 quote! { ::std::vec::Vec<#related> }
             }
             Relationship::HasOne | Relationship::BelongsTo => {
@@ -150,11 +883,24 @@ fn impl_relationships(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
             }
         };
 
+        let query_ident = Ident::new(&format!("{ident}_query"), ident.span());
+        let exists_ident = Ident::new(&format!("{ident}_exists"), ident.span());
+
         quote_spanned! {f.span() =>
             #[allow(dead_code)]
             pub async fn #ident(&mut self) -> Result<&mut #return_type, ::ensemble::Error> {
                 self.#ident.get().await
             }
+
+            #[allow(dead_code)]
+            pub fn #query_ident(&self) -> ::ensemble::query::Builder {
+                self.#ident.query()
+            }
+
+            #[allow(dead_code)]
+            pub fn #exists_ident(&self) -> Option<bool> {
+                self.#ident.exists()
+            }
         }
     });
 
@@ -165,8 +911,120 @@ fn impl_relationships(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
     })
 }
 
-fn impl_save(fields: &Fields, primary_key: &Field) -> TokenStream {
-    let ident = &primary_key.ident;
+/// The model's JSON Schema, for models marked with `#[ensemble(json_schema)]`.
+#[allow(clippy::unnecessary_wraps)]
+#[cfg_attr(
+    not(all(feature = "schema", feature = "json")),
+    allow(unused_variables)
+)]
+fn impl_schema(ast: &DeriveInput, fields: &Fields, opts: &Opts) -> syn::Result<TokenStream> {
+    if !opts.json_schema {
+        return Ok(TokenStream::new());
+    }
+
+    #[cfg(all(feature = "schema", feature = "json"))]
+    return Ok(schema::r#impl(&ast.ident, fields));
+
+    #[cfg(not(all(feature = "schema", feature = "json")))]
+    Err(syn::Error::new_spanned(
+        ast,
+        "Generating a JSON Schema requires the `schema` and `json` features",
+    ))
+}
+
+/// Record the columns of a newly created model in its tracker, so its first save only counts the columns it changes.
+fn impl_sync_tracker(fields: &Fields) -> Option<TokenStream> {
+    fields.tracker.as_ref().map(|tracker| {
+        let ident = &tracker.ident;
+        quote_spanned! {tracker.span()=>
+            let columns = ::ensemble::value::for_db(&self)?;
+            self.#ident.sync(columns);
+        }
+    })
+}
+
+/// The `Model` methods exposing the tracker field, and the inherent methods inspecting the last save.
+fn impl_tracker(name: &Ident, fields: &Fields) -> (TokenStream, TokenStream) {
+    let Some(tracker) = &fields.tracker else {
+        return (TokenStream::new(), TokenStream::new());
+    };
+    let ident = &tracker.ident;
+
+    let tracker_impl = quote_spanned! {tracker.span()=>
+        fn tracker(&self) -> Option<&::ensemble::value::Tracker> {
+            Some(&self.#ident)
+        }
+
+        fn tracker_mut(&mut self) -> Option<&mut ::ensemble::value::Tracker> {
+            Some(&mut self.#ident)
+        }
+    };
+
+    let methods_impl = quote_spanned! {tracker.span()=>
+        impl #name {
+            /// Whether none of the model's columns changed since it was last loaded or saved.
+            #[allow(dead_code)]
+            pub fn is_clean(&self) -> bool {
+                ::ensemble::value::for_db(self).is_ok_and(|columns| self.#ident.is_clean(&columns))
+            }
+
+            /// Whether any of the model's columns changed since it was last loaded or saved, so that saving it would update them.
+            #[allow(dead_code)]
+            pub fn is_dirty(&self) -> bool {
+                !self.is_clean()
+            }
+
+            /// The columns changed since the model was last loaded or saved, with their current values.
+            #[allow(dead_code)]
+            pub fn get_dirty(&self) -> ::std::vec::Vec<(&'static str, ::ensemble::rbs::Value)> {
+                let columns = <Self as ::ensemble::Model>::columns();
+                let Ok(values) = ::ensemble::value::for_db(self) else {
+                    return ::std::vec::Vec::new();
+                };
+
+                self.#ident
+                    .dirty(&values)
+                    .into_iter()
+                    .filter_map(|change| {
+                        let column = columns.iter().find(|column| column.name == change.column)?;
+                        Some((column.name, change.new))
+                    })
+                    .collect()
+            }
+
+            /// Whether the last save changed any of the model's columns.
+            #[allow(dead_code)]
+            pub fn was_changed(&self) -> bool {
+                self.#ident.was_changed()
+            }
+
+            /// Whether the last save changed the given column.
+            #[allow(dead_code)]
+            pub fn was_changed_column(&self, column: &str) -> bool {
+                self.#ident.was_changed_column(column)
+            }
+
+            /// The columns changed by the last save, with their values before and after it.
+            #[allow(dead_code)]
+            pub fn changes(&self) -> ::std::vec::Vec<(&'static str, ::ensemble::rbs::Value, ::ensemble::rbs::Value)> {
+                let columns = <Self as ::ensemble::Model>::columns();
+
+                self.#ident
+                    .changes()
+                    .iter()
+                    .filter_map(|change| {
+                        let column = columns.iter().find(|column| column.name == change.column)?;
+                        Some((column.name, change.old.clone(), change.new.clone()))
+                    })
+                    .collect()
+            }
+        }
+    };
+
+    (tracker_impl, methods_impl)
+}
+
+fn impl_save(fields: &Fields, primary_key: &Field, opts: &Opts) -> TokenStream {
     let run_validation = if fields.should_validate() {
         quote! {
             self.validate()?;
@@ -187,21 +1045,76 @@ fn impl_save(fields: &Fields, primary_key: &Field) -> TokenStream {
         })
         .collect::<TokenStream>();
 
-    quote! {
-        async fn save(&mut self) -> Result<(), ::ensemble::Error> {
+    let prepare_save = quote! {
+        fn prepare_save(&mut self) -> Result<(), ::ensemble::Error> {
             #update_timestamp
             #run_validation
 
-            let rows_affected = Self::query()
+            Ok(())
+        }
+    };
+
+    if opts.read_only {
+        return quote! {
+            #prepare_save
+
+            async fn save(&mut self) -> Result<(), ::ensemble::Error> {
+                Err(::ensemble::Error::ReadOnly(Self::TABLE_NAME))
+            }
+        };
+    }
+
+    let ident = &primary_key.ident;
+    let update = if opts.outbox || opts.auditable {
+        quote! { ::ensemble::write::update(self).await? }
+    } else {
+        quote! {
+            Self::without_global_scopes()
                 .r#where(Self::PRIMARY_KEY, "=", &self.#ident)
-                .update(::ensemble::value::for_db(self)?)
-                .await?;
+                .update(::ensemble::value::for_update(&*self)?)
+                .await?
+        }
+    };
 
-            if rows_affected != 1 {
-                return Err(::ensemble::Error::UniqueViolation);
+    let after_commit = impl_after_commit(opts, &quote! { Updated });
+
+    let persist_tracker = fields.tracker.as_ref().map(|tracker| {
+        let ident = &tracker.ident;
+        quote_spanned! {tracker.span()=>
+            let columns = ::ensemble::value::for_db(&*self)?;
+            self.#ident.persist(columns);
+        }
+    });
+
+    // there's nothing to write when none of the tracked columns changed, so the timestamps aren't touched either
+    let skip_clean = fields.tracker.as_ref().map(|tracker| {
+        quote_spanned! {tracker.span()=>
+            if self.is_clean() {
+                return Ok(());
             }
+        }
+    });
 
-            Ok(())
+    quote! {
+        #prepare_save
+
+        async fn save(&mut self) -> Result<(), ::ensemble::Error> {
+            ::ensemble::query::operation("save", Self::QUALIFIED_TABLE, async move {
+                #skip_clean
+                self.prepare_save()?;
+
+                let rows_affected = #update;
+
+                if rows_affected != 1 {
+                    return Err(::ensemble::Error::UniqueViolation);
+                }
+                ::ensemble::identity_map::forget::<Self>(&self.#ident)?;
+                #persist_tracker
+                #after_commit
+
+                Ok(())
+            })
+            .await
         }
     }
 }
@@ -211,26 +1124,40 @@ fn impl_find(primary_key: &Field) -> TokenStream {
 
     quote! {
         async fn find(#ident: Self::PrimaryKey) -> Result<Self, ::ensemble::Error> {
-            Self::query()
+            if let Some(model) = ::ensemble::identity_map::get::<Self>(&#ident)? {
+                return Ok(model);
+            }
+
+            let model = Self::query()
                 .r#where(Self::PRIMARY_KEY, "=", ::ensemble::value::for_db(#ident)?)
                 .first()
                 .await?
-                .ok_or(::ensemble::Error::NotFound)
+                .ok_or(::ensemble::Error::NotFound)?;
+            ::ensemble::identity_map::remember(&model)?;
+
+            Ok(model)
         }
     }
 }
 
-fn impl_create(name: &Ident, fields: &Fields, primary_key: &Field) -> TokenStream {
-    let is_primary_u64 = (&primary_key.ty).into_token_stream().to_string() == "u64";
+fn impl_create(name: &Ident, fields: &Fields, primary_key: &Field, opts: &Opts) -> TokenStream {
+    if opts.read_only {
+        return quote! {
+            fn prepare_create(&mut self) -> Result<(), ::ensemble::Error> {
+                Err(::ensemble::Error::ReadOnly(Self::TABLE_NAME))
+            }
+
+            async fn create(self) -> Result<Self, ::ensemble::Error> {
+                Err(::ensemble::Error::ReadOnly(Self::TABLE_NAME))
+            }
+        };
+    }
 
     let required = fields
         .fields
         .iter()
-        .filter(|f| {
-            f.default(name, primary_key)
-                .map(|o| o.is_none())
-                .unwrap_or(false)
-        })
+        .filter(|f| !f.attr.db_generated)
+        .filter(|f| f.default(name, primary_key).is_ok_and(|o| o.is_none()))
         .map(|field| {
             let ty = &field.ty;
             let ident = &field.ident;
@@ -262,41 +1189,157 @@ fn impl_create(name: &Ident, fields: &Fields, primary_key: &Field) -> TokenStrea
             }
         });
 
-    let insert_and_return = if primary_key
-        .attr
-        .default
-        .incrementing
-        .unwrap_or(is_primary_u64)
-    {
-        let primary_key = &primary_key.ident;
-        quote! {
-            self.#primary_key = Self::query().insert(::ensemble::value::for_db(&self)?).await?;
+    // keys which were left unset (e.g. on models deserialized without them) are generated before the insert
+    let generate_keys = fields.fields.iter().filter_map(|field| {
+        let ty = field.generated_key().ok()??;
+        let ident = &field.ident;
 
-            Ok(self)
-        }
-    } else {
-        quote! {
-            Self::query().insert(::ensemble::value::for_db(&self)?).await?;
+        Some(quote_spanned! {field.span() =>
+            if self.#ident == <#ty>::nil() {
+                self.#ident = <#ty>::new();
+            }
+        })
+    });
 
-            Ok(self)
-        }
-    };
+    let primary_key_ident = &primary_key.ident;
+    let insert = impl_insert(fields, primary_key, opts);
+
+    let sync_tracker = impl_sync_tracker(fields);
+    let after_commit = impl_after_commit(opts, &quote! { Created });
+
+    // fill in the tenant before the required fields are checked, since it's usually one of them
+    let set_tenant = impl_set_tenant(fields, opts);
 
     quote! {
-        async fn create(mut self) -> Result<Self, ::ensemble::Error> {
+        fn prepare_create(&mut self) -> Result<(), ::ensemble::Error> {
             #(#update_timestamps)*
+            #(#generate_keys)*
+            #set_tenant
             #run_validation
             #(#required)*
-            #insert_and_return
+
+            Ok(())
+        }
+
+        async fn create(mut self) -> Result<Self, ::ensemble::Error> {
+            ::ensemble::query::operation("create", Self::QUALIFIED_TABLE, async move {
+                self.prepare_create()?;
+                #insert
+                ::ensemble::identity_map::forget::<Self>(&self.#primary_key_ident)?;
+                #sync_tracker
+                #after_commit
+
+                Ok(self)
+            })
+            .await
         }
     }
 }
 
+/// Inserts a new model, filling in its primary key (and the columns populated by the database) when the database generates them.
+fn impl_insert(fields: &Fields, primary_key: &Field, opts: &Opts) -> TokenStream {
+    let is_primary_u64 = (&primary_key.ty).into_token_stream().to_string() == "u64";
+    let primary_key_ident = &primary_key.ident;
+
+    let insert = |id: TokenStream| {
+        if opts.outbox || opts.auditable {
+            quote! { ::ensemble::write::insert::<Self, #id>(&self).await? }
+        } else {
+            quote! { Self::query().insert::<#id, _>(::ensemble::value::for_db(&self)?).await? }
+        }
+    };
+
+    impl_insert_returning(fields, primary_key, opts).unwrap_or_else(|| {
+        if primary_key
+            .attr
+            .default
+            .incrementing
+            .unwrap_or(is_primary_u64)
+        {
+            let insert = insert(quote! { _ });
+            quote! {
+                self.#primary_key_ident = #insert;
+            }
+        } else {
+            let insert = insert(quote! { ::ensemble::rbs::Value });
+            quote! {
+                #insert;
+            }
+        }
+    })
+}
+
+/// Inserts a model with columns populated by the database, reading them back along with its key.
+/// On Postgres and `SQLite`, that's a single `INSERT ... RETURNING` statement.
+fn impl_insert_returning(fields: &Fields, primary_key: &Field, opts: &Opts) -> Option<TokenStream> {
+    let db_generated = fields
+        .fields
+        .iter()
+        .filter(|f| f.attr.db_generated)
+        .collect::<Vec<_>>();
+    if db_generated.is_empty() {
+        return None;
+    }
+
+    let insert = if opts.outbox || opts.auditable {
+        quote! { ::ensemble::write::insert_and_fetch(&self).await? }
+    } else {
+        let columns = db_generated
+            .iter()
+            .map(|f| f.attr.column.clone().unwrap_or_else(|| f.ident.to_string()));
+
+        quote! { ::ensemble::query::insert_returning(&self, &[#(#columns),*]).await? }
+    };
+
+    let primary_key_ident = &primary_key.ident;
+    let db_generated = db_generated.iter().map(|f| &f.ident);
+    Some(quote! {
+        let __fresh = #insert;
+        self.#primary_key_ident = __fresh.#primary_key_ident;
+        #(self.#db_generated = __fresh.#db_generated;)*
+    })
+}
+
+/// Calls the model's `after_commit` hook once a write that doesn't run in a transaction succeeds, or queues it on the ambient transaction.
+/// Transactional writes (those of outbox and auditable models) queue their hooks on their own transaction instead.
+fn impl_after_commit(opts: &Opts, event: &TokenStream) -> Option<TokenStream> {
+    (!opts.outbox && !opts.auditable).then(|| {
+        quote! { ::ensemble::hooks::written::<Self>(&self, ::ensemble::hooks::EventType::#event).await?; }
+    })
+}
+
+fn impl_set_tenant(fields: &Fields, opts: &Opts) -> Option<TokenStream> {
+    opts.tenant_column
+        .as_ref()
+        .and_then(|column| {
+            fields.fields.iter().find(|f| {
+                f.attr
+                    .column
+                    .as_ref()
+                    .map_or_else(|| f.ident == column, |c| c == column)
+            })
+        })
+        .map(|field| {
+            let ident = &field.ident;
+
+            quote_spanned! {field.span() =>
+                if let Some(tenant) = ::ensemble::tenancy::current() {
+                    self.#ident = ::ensemble::rbs::from_value(tenant)?;
+                }
+            }
+        })
+}
+
 fn impl_primary_key(primary_key: &Field) -> TokenStream {
     let ident = &primary_key.ident;
+    let column = primary_key
+        .attr
+        .column
+        .clone()
+        .unwrap_or_else(|| ident.to_string());
 
     quote! {
-        const PRIMARY_KEY: &'static str = stringify!(#ident);
+        const PRIMARY_KEY: &'static str = #column;
 
         fn primary_key(&self) -> &Self::PrimaryKey {
             &self.#ident
@@ -304,11 +1347,174 @@ fn impl_primary_key(primary_key: &Field) -> TokenStream {
     }
 }
 
-fn impl_table_name(struct_name: &str, custom_name: Option<String>) -> TokenStream {
-    let table_name =
-        custom_name.unwrap_or_else(|| pluralize(&struct_name.to_snake_case(), 2, false));
+fn impl_columns(fields: &Fields, primary_key: &Field) -> TokenStream {
+    let columns = fields.fields.iter().filter_map(|field| {
+        let name = match field.relationship(primary_key) {
+            Some((Relationship::BelongsTo, _, (_, key_expr))) => {
+                quote_spanned! {field.span()=> #key_expr.leak() }
+            }
+            Some(_) => return None,
+            None => {
+                let column = field
+                    .attr
+                    .column
+                    .clone()
+                    .unwrap_or_else(|| field.ident.to_string());
+                quote! { #column }
+            }
+        };
+
+        let attrs = &field.attr.default;
+        let field_name = field.ident.to_string();
+        let is_primary = field.ident == primary_key.ident;
+        let nullable = field.ty.to_token_stream().to_string().starts_with("Option");
+        let is_u64 = field.ty.to_token_stream().to_string() == "u64";
+        let increments = attrs.incrementing.unwrap_or(is_primary && is_u64);
+        let has_default = increments
+            || attrs.value.is_some()
+            || attrs.uuid
+            || attrs.ulid
+            || attrs.created_at
+            || attrs.updated_at;
+        let db_generated = field.attr.db_generated;
+        let immutable = field.attr.immutable;
+        let unique = field.attr.unique;
+        let sensitive = field.attr.sensitive;
+        let r#type = field.column_type();
+
+        Some(quote_spanned! {field.span()=>
+            ::ensemble::value::ColumnDef {
+                name: #name,
+                field: #field_name,
+                primary_key: #is_primary,
+                nullable: #nullable,
+                has_default: #has_default,
+                increments: #increments,
+                db_generated: #db_generated,
+                immutable: #immutable,
+                unique: #unique,
+                sensitive: #sensitive,
+                r#type: #r#type,
+            }
+        })
+    });
 
     quote! {
-        const TABLE_NAME: &'static str = #table_name;
+        fn columns() -> &'static [::ensemble::value::ColumnDef] {
+            // foreign keys are named at runtime, using the configured naming strategy
+            static COLUMNS: ::std::sync::OnceLock<::std::vec::Vec<::ensemble::value::ColumnDef>> = ::std::sync::OnceLock::new();
+
+            COLUMNS.get_or_init(|| ::std::vec![#(#columns),*])
+        }
+
+        fn keys() -> &'static [&'static str] {
+            static KEYS: ::std::sync::OnceLock<::std::vec::Vec<&'static str>> = ::std::sync::OnceLock::new();
+
+            KEYS.get_or_init(|| {
+                <Self as ::ensemble::Model>::columns()
+                    .iter()
+                    .filter(|column| !column.db_generated)
+                    .map(|column| column.name)
+                    .collect()
+            })
+        }
+    }
+}
+
+/// How table names are inferred from struct names.
+enum Inflection {
+    /// The English plural of the snake case name (`Flight` is stored in `flights`).
+    English,
+    /// The snake case name, as-is.
+    None,
+    /// The snake case name, followed by a fixed suffix.
+    Suffix(String),
+}
+
+impl Inflection {
+    /// The environment variable setting the inflection of every model in the crate being compiled.
+    const VAR: &'static str = "ENSEMBLE_TABLE_INFLECTION";
+
+    fn parse(rule: &str) -> Result<Self, String> {
+        match rule {
+            "english" => Ok(Self::English),
+            "none" => Ok(Self::None),
+            _ => rule
+                .strip_prefix("suffix:")
+                .map(|suffix| Self::Suffix(suffix.to_string()))
+                .ok_or_else(|| {
+                    format!("Unknown table inflection `{rule}`. Expected one of `english`, `none` or `suffix:<suffix>`.")
+                }),
+        }
     }
+
+    fn from_env() -> Result<Self, String> {
+        std::env::var(Self::VAR).map_or(Ok(Self::English), |rule| {
+            Self::parse(&rule).map_err(|message| {
+                format!("{message} (set by the {} environment variable)", Self::VAR)
+            })
+        })
+    }
+
+    fn table_name(&self, struct_name: &str) -> String {
+        let name = struct_name.to_snake_case();
+
+        match self {
+            Self::English => pluralize(&name, 2, false),
+            Self::None => name,
+            Self::Suffix(suffix) => format!("{name}{suffix}"),
+        }
+    }
+}
+
+fn impl_table_name(
+    struct_name: &Ident,
+    custom_name: Option<String>,
+    schema: Option<String>,
+    inflection: Option<String>,
+) -> syn::Result<TokenStream> {
+    let error = |message| syn::Error::new_spanned(struct_name, message);
+    let (table_name, tracked) = match (custom_name, inflection) {
+        (Some(table_name), _) => (table_name, false),
+        (None, Some(rule)) => {
+            let inflection = Inflection::parse(&rule).map_err(error)?;
+            (inflection.table_name(&struct_name.to_string()), false)
+        }
+        (None, None) => {
+            let inflection = Inflection::from_env().map_err(error)?;
+            (inflection.table_name(&struct_name.to_string()), true)
+        }
+    };
+
+    // reading the variable from the generated code makes cargo recompile the crate when it changes
+    let var = Inflection::VAR;
+    let value = if tracked {
+        quote! {{
+            let _ = ::core::option_env!(#var);
+            #table_name
+        }}
+    } else {
+        quote! { #table_name }
+    };
+
+    let Some(schema) = schema else {
+        return Ok(quote! {
+            const TABLE_NAME: &'static str = #value;
+        });
+    };
+
+    // the schema is quoted (keeping its case) for the database Ensemble is built for, which is only known when it's compiled
+    let quoted = |quote: char| {
+        let schema = schema.replace(quote, &format!("{quote}{quote}"));
+        format!("{quote}{schema}{quote}.{table_name}")
+    };
+    let (mysql, ansi) = (quoted('`'), quoted('"'));
+    Ok(quote! {
+        const TABLE_NAME: &'static str = #value;
+        const SCHEMA: Option<&'static str> = Some(#schema);
+        const QUALIFIED_TABLE: &'static str = match ::ensemble::query::Dialect::current() {
+            ::ensemble::query::Dialect::Mysql => #mysql,
+            ::ensemble::query::Dialect::Postgres | ::ensemble::query::Dialect::Sqlite => #ansi,
+        };
+    })
 }
@@ -0,0 +1,185 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::Visibility;
+
+use super::{
+    field::{Field, Fields},
+    Opts,
+};
+
+pub fn r#impl(
+    name: &Ident,
+    vis: &Visibility,
+    fields: &Fields,
+    opts: &Opts,
+) -> syn::Result<TokenStream> {
+    let primary_key = fields.primary_key()?;
+    let patch = format_ident!("{name}Patch");
+
+    // keys, relationships and the columns the model maintains itself can't be patched
+    let patchable = fields
+        .fields
+        .iter()
+        .filter(|field| {
+            field.ident != primary_key.ident
+                && !field.has_relationship()
+                && !field.attr.db_generated
+                && !field.attr.immutable
+                && !field.attr.default.created_at
+                && !field.attr.default.updated_at
+        })
+        .collect::<Vec<_>>();
+
+    let patch_fields = patchable.iter().map(|field| {
+        let (ident, ty) = (&field.ident, &field.ty);
+        let column = field
+            .attr
+            .column
+            .clone()
+            .unwrap_or_else(|| ident.to_string());
+
+        // a nullable column set to `null` is still provided, so it can't deserialize as a missing field
+        let nullable = ty.to_token_stream().to_string().starts_with("Option");
+        let deserialize_with = nullable.then(|| {
+            quote! { deserialize_with = "::ensemble::value::provided", }
+        });
+
+        quote_spanned! {field.span()=>
+            #[serde(default, rename = #column, #deserialize_with)]
+            pub #ident: ::std::option::Option<#ty>
+        }
+    });
+
+    let idents = patchable
+        .iter()
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+
+    let columns = impl_columns(&patchable);
+    let update = impl_update(fields, opts);
+
+    let primary_key_type = &primary_key.ty;
+
+    Ok(quote! {
+        #[doc = concat!("A set of changes to a [`", stringify!(#name), "`], setting only the fields that are `Some`. Created with `Default`, or deserialized from the body of a `PATCH` request.")]
+        ///
+        /// The primary key can't be patched: deserializing a patch that has it (or any other unknown field) fails.
+        /// Nullable columns are doubly optional, so `Some(None)` sets them to `NULL`.
+        #[derive(Debug, Default, ::ensemble::serde::Deserialize)]
+        #[serde(crate = "::ensemble::serde", deny_unknown_fields)]
+        #vis struct #patch {
+            #(#patch_fields,)*
+        }
+
+        impl #patch {
+            /// Whether the patch doesn't change any field.
+            #[must_use]
+            pub const fn is_empty(&self) -> bool {
+                true #(&& self.#idents.is_none())*
+            }
+
+            /// The columns changed by the patch, serialized for the database.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if one of the values cannot be serialized.
+            pub fn columns(&self) -> Result<::std::vec::Vec<(&'static str, ::ensemble::rbs::Value)>, ::ensemble::Error> {
+                let mut columns = ::std::vec::Vec::new();
+                #(#columns)*
+
+                Ok(columns)
+            }
+        }
+
+        impl #name {
+            /// Set the fields provided by `patch`, leaving every other field untouched. The model isn't saved.
+            #vis fn apply(&mut self, patch: #patch) {
+                #(
+                    if let ::std::option::Option::Some(value) = patch.#idents {
+                        self.#idents = value;
+                    }
+                )*
+            }
+
+            /// Update the fields provided by `patch` for the model with the given primary key, without retrieving it first. Returns the number of affected rows.
+            /// An empty patch succeeds without running any query.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the query fails, or if a connection to the database cannot be established.
+            #vis async fn update_by_id(id: #primary_key_type, patch: #patch) -> Result<u64, ::ensemble::Error> {
+                if patch.is_empty() {
+                    return Ok(0);
+                }
+
+                #update
+            }
+        }
+    })
+}
+
+/// Push the value of every field the patch includes onto `columns`, through the field's setter if it has one.
+fn impl_columns(patchable: &[&Field]) -> Vec<TokenStream> {
+    patchable
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            let column = field
+                .attr
+                .column
+                .clone()
+                .unwrap_or_else(|| ident.to_string());
+            let value = match field.setter() {
+                Ok(Some(set)) => quote_spanned! {field.span()=> #set(value) },
+                Ok(None) => quote_spanned! {field.span()=> value },
+                Err(e) => e.into_compile_error(),
+            };
+
+            quote_spanned! {field.span()=>
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    columns.push((#column, ::ensemble::value::for_db(#value)?));
+                }
+            }
+        })
+        .collect()
+}
+
+/// The body of `update_by_id`, for a patch that isn't empty.
+fn impl_update(fields: &Fields, opts: &Opts) -> TokenStream {
+    let touch = fields
+        .fields
+        .iter()
+        .find(|field| field.attr.default.updated_at)
+        .map(|field| {
+            let column = field
+                .attr
+                .column
+                .clone()
+                .unwrap_or_else(|| field.ident.to_string());
+
+            quote_spanned! {field.span()=>
+                columns.push((#column, ::ensemble::value::for_db(::ensemble::types::DateTime::now())?));
+            }
+        });
+
+    // models recording their changes are saved as a whole, so the changes are recorded too
+    if opts.outbox || opts.auditable {
+        quote! {
+            let mut model = <Self as ::ensemble::Model>::find(id).await?;
+            model.apply(patch);
+            model.save().await?;
+
+            Ok(1)
+        }
+    } else {
+        quote! {
+            let mut columns = patch.columns()?;
+            #touch
+
+            <Self as ::ensemble::Model>::query()
+                .r#where(<Self as ::ensemble::Model>::PRIMARY_KEY, "=", ::ensemble::value::for_db(id)?)
+                .update(columns)
+                .await
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned};
+
+use super::field::Fields;
+
+/// A `JsonSchema` implementation describing the model as it's serialized outside of the database.
+pub fn r#impl(name: &Ident, fields: &Fields) -> TokenStream {
+    let properties = fields.fields.iter().filter_map(|field| {
+        if field.attr.hide && !field.attr.show {
+            return None;
+        }
+
+        let ty = &field.ty;
+        let column = field
+            .attr
+            .column
+            .clone()
+            .unwrap_or_else(|| field.ident.to_string());
+
+        // relationships are only serialized once they're loaded
+        let required = (!field.has_relationship()).then(|| {
+            quote_spanned! {field.span()=> object.required.insert(#column.to_string()); }
+        });
+
+        Some(quote_spanned! {field.span()=>
+            object.properties.insert(#column.to_string(), gen.subschema_for::<#ty>());
+            #required
+        })
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl ::ensemble::schemars::JsonSchema for #name {
+            fn schema_name() -> ::std::string::String {
+                stringify!(#name).to_string()
+            }
+
+            fn json_schema(gen: &mut ::ensemble::schemars::gen::SchemaGenerator) -> ::ensemble::schemars::schema::Schema {
+                let mut schema = ::ensemble::schemars::schema::SchemaObject {
+                    instance_type: Some(::ensemble::schemars::schema::InstanceType::Object.into()),
+                    ..Default::default()
+                };
+
+                let object = schema.object();
+                object.additional_properties = Some(Box::new(false.into()));
+                #(#properties)*
+
+                schema.into()
+            }
+        }
+
+        impl #name {
+            /// The JSON Schema of the model, as it's serialized to JSON.
+            #[allow(dead_code)]
+            pub fn json_schema() -> ::ensemble::serde_json::Value {
+                ::ensemble::value::json_schema::<Self>()
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@ use proc_macro2::{Ident, TokenStream};
 use quote::{quote, quote_spanned};
 use std::rc::Rc;
 
-use super::field::Fields;
+use super::field::{Field, Fields};
 use crate::Relationship;
 
 pub fn r#impl(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
@@ -18,23 +18,34 @@ pub fn impl_serialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
     let primary_key = fields.primary_key()?;
 
     let serialize_for_db = fields.fields.iter().filter_map(|field| {
+        // the database populates these columns itself
+        if field.attr.db_generated {
+            return None;
+        }
+
         let ident = &field.ident;
         let column = field
             .attr
             .column
             .as_ref()
-            .map_or(field.ident.clone(), |v| Ident::new(v, field.span()));
+            .map_or_else(|| field.ident.clone(), |v| Ident::new(v, field.span()));
 
         let Some((relationship_type, _, (_, key_expr))) = field.relationship(primary_key) else {
-            return Some(quote_spanned! {field.span()=>
-                state.serialize_field(stringify!(#column), &self.#ident)?;
+            return Some(match field.setter() {
+                Ok(Some(set)) => quote_spanned! {field.span()=>
+                    state.serialize_field(stringify!(#column), &#set(&self.#ident))?;
+                },
+                Ok(None) => quote_spanned! {field.span()=>
+                    state.serialize_field(stringify!(#column), &self.#ident)?;
+                },
+                Err(e) => e.into_compile_error(),
             });
         };
 
         match relationship_type {
             Relationship::BelongsTo => {}
             _ => return None,
-        };
+        }
 
         Some(quote_spanned! {field.span()=> {
             let key: &'static str = #key_expr.leak();
@@ -42,20 +53,56 @@ pub fn impl_serialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
         }})
     });
 
-    let general_serialize = fields.fields.iter().filter_map(|field| {
-        #[cfg(feature = "json")]
-        if field.attr.hide && !field.attr.show {
-            return None;
-        }
+    #[cfg(feature = "json")]
+    let general_serialize = quote! {
+        return <Self as ::ensemble::Model>::serialize_visible(
+            self,
+            serializer,
+            &::ensemble::value::Visibility::default(),
+        );
+    };
+
+    #[cfg(not(feature = "json"))]
+    let general_serialize = general_serialize(fields);
+    #[cfg(not(feature = "json"))]
+    let general_serialize = quote! {
+        let mut state = serializer.serialize_struct(stringify!(#name), #count)?;
+        #general_serialize
+        state.end()
+    };
+
+    Ok(quote! {
+        const _: () = {
+            use ::ensemble::Inflector;
+            use ::ensemble::serde::ser::SerializeStruct;
+            #[automatically_derived]
+            impl ::ensemble::serde::Serialize for #name {
+                fn serialize<S: ::ensemble::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    // ugly hack to figure out if we're serializing for rbs. might break in future (or previous) versions of rust.
+                    if ::std::any::type_name::<S::Error>() == ::std::any::type_name::<::ensemble::rbs::Error>() {
+                        let mut state = serializer.serialize_struct(stringify!(#name), #count)?;
+                        #(#serialize_for_db)*
+                        return state.end();
+                    }
 
+                    #general_serialize
+                }
+            }
+        };
+    })
+}
+
+/// Serializes every field outside of the database. With the `json` feature, fields are checked against a `visibility` in scope.
+fn general_serialize(fields: &Fields) -> TokenStream {
+    let fields = fields.fields.iter().map(|field| {
         let ident = &field.ident;
         let column = field
             .attr
             .column
             .as_ref()
-            .map_or(field.ident.clone(), |v| Ident::new(v, field.span()));
+            .map_or_else(|| field.ident.clone(), |v| Ident::new(v, field.span()));
 
-        Some(if field.has_relationship() {
+        let serialize = if field.has_relationship() {
             quote_spanned! {field.span()=>
                 if self.#ident.is_loaded() {
                     state.serialize_field(stringify!(#column), &self.#ident)?;
@@ -65,32 +112,47 @@ pub fn impl_serialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStream>
             quote_spanned! {field.span()=>
                 state.serialize_field(stringify!(#column), &self.#ident)?;
             }
-        })
-    });
+        };
 
-    let serialize_fields = quote! {
-        // ugly hack to figure out if we're serializing for rbs. might break in future (or previous) versions of rust.
-        if ::std::any::type_name::<S::Error>() == ::std::any::type_name::<::ensemble::rbs::Error>() {
-            #(#serialize_for_db)*
-        } else {
-            #(#general_serialize)*
-        }
-    };
+        #[cfg(feature = "json")]
+        let serialize = {
+            let hidden = field.attr.hide && !field.attr.show;
+            let shows_relations = field
+                .has_relationship()
+                .then(|| quote! { visibility.shows_relations() && });
 
-    Ok(quote! {
-        const _: () = {
-            use ::ensemble::Inflector;
-            use ::ensemble::serde::ser::SerializeStruct;
-            #[automatically_derived]
-            impl ::ensemble::serde::Serialize for #name {
-                fn serialize<S: ::ensemble::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-                    let mut state = serializer.serialize_struct(stringify!(#name), #count)?;
-                    #serialize_fields
-                    state.end()
+            quote_spanned! {field.span()=>
+                if #shows_relations visibility.shows(stringify!(#column), #hidden) {
+                    #serialize
                 }
             }
         };
-    })
+
+        serialize
+    });
+
+    quote! { #(#fields)* }
+}
+
+/// The body of `Model::serialize_visible`, serializing every field that `visibility` shows.
+#[cfg(feature = "json")]
+pub fn impl_serialize_visible(name: &Ident, fields: &Fields) -> TokenStream {
+    let count = fields.fields.len();
+    let general_serialize = general_serialize(fields);
+
+    quote! {
+        fn serialize_visible<S: ::ensemble::serde::Serializer>(
+            &self,
+            serializer: S,
+            visibility: &::ensemble::value::Visibility<'_>,
+        ) -> Result<S::Ok, S::Error> {
+            use ::ensemble::serde::ser::SerializeStruct;
+
+            let mut state = serializer.serialize_struct(stringify!(#name), #count)?;
+            #general_serialize
+            state.end()
+        }
+    }
 }
 
 pub fn impl_deserialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
@@ -122,7 +184,7 @@ pub fn impl_deserialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStrea
                 f.attr
                     .column
                     .as_ref()
-                    .map_or(f.ident.clone(), |v| Ident::new(v, f.span())),
+                    .map_or_else(|| f.ident.clone(), |v| Ident::new(v, f.span())),
             )
         })
         .collect::<Rc<_>>();
@@ -158,7 +220,7 @@ pub fn impl_deserialize(name: &Ident, fields: &Fields) -> syn::Result<TokenStrea
 fn field_deserialize(column: &Rc<[Ident]>, enum_key: &Rc<[Ident]>) -> TokenStream {
     let expecting_str = column
         .iter()
-        .map(|f| format!("`{}`", f.to_string()))
+        .map(|f| format!("`{f}`"))
         .collect::<Rc<_>>()
         .join(" or ");
 
@@ -198,7 +260,9 @@ fn visitor_deserialize(
     column: &Rc<[Ident]>,
     enum_key: &Rc<[Ident]>,
 ) -> syn::Result<TokenStream> {
-    let primary_key = fields.primary_key()?;
+    // structs that aren't models don't have a primary key, and so can't have relationships either
+    let primary_key = fields.primary_key();
+    let relationship = |f: &Field| primary_key.as_ref().ok().and_then(|pk| f.relationship(pk));
     let key = &fields
         .fields
         .iter()
@@ -206,8 +270,34 @@ fn visitor_deserialize(
         .map(|f| &f.ident)
         .collect::<Rc<_>>();
 
+    let next_value = fields
+        .fields
+        .iter()
+        .filter(|f| !f.has_relationship())
+        .map(|f| {
+            let Some(get) = f.getter()? else {
+                return Ok(quote! { map.next_value()? });
+            };
+
+            let column = f
+                .attr
+                .column
+                .as_ref()
+                .map_or_else(|| f.ident.clone(), |v| Ident::new(v, f.span()));
+
+            // accessors only apply to values coming from the database
+            Ok(quote_spanned! {f.span()=>
+                if ::std::any::type_name::<V::Error>() == ::std::any::type_name::<::ensemble::rbs::Error>() {
+                    map.next_value_seed(::ensemble::value::Accessor::new(stringify!(#column), #get))?
+                } else {
+                    map.next_value()?
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
     let needs_collect = fields.fields.iter().any(|f| {
-        let Some((relationship_type, _, _)) = f.relationship(primary_key) else {
+        let Some((relationship_type, _, _)) = relationship(f) else {
             return false;
         };
 
@@ -220,7 +310,7 @@ fn visitor_deserialize(
             .attr
             .column
             .as_ref()
-            .map_or(f.ident.clone(), |v| Ident::new(v, f.span()));
+            .map_or_else(|| f.ident.clone(), |v| Ident::new(v, f.span()));
 
 
         if f.has_relationship() {
@@ -245,7 +335,7 @@ fn visitor_deserialize(
         let ident = &f.ident;
         let ty = &f.ty;
 
-        let Some((relationship_type, related, (relationship_key, relationship_expr))) = &f.relationship(primary_key) else {
+        let Some((relationship_type, related, (relationship_key, relationship_expr))) = &relationship(f) else {
             return if f.attr.used_in_relationship {
                 quote_spanned! {f.span()=> #ident: #ident.clone() }
             } else {
@@ -272,12 +362,29 @@ fn visitor_deserialize(
             }, |key| quote_spanned! {f.span()=> #key });
 
         let foreign_key = f.foreign_key(*relationship_type, related);
+        let options = f
+            .relationship_options(*relationship_type)
+            .unwrap_or_else(syn::Error::into_compile_error);
 
-        quote_spanned! {f.span()=> #ident: <#relationship_ident<#name, #related>>::build(#key_ident.clone(), #foreign_key) }
+        quote_spanned! {f.span()=> #ident: <#relationship_ident<#name, #related>>::build(#key_ident.clone(), #foreign_key) #options }
+    });
+
+    let tracker_init = fields.tracker_init();
+    // models loaded from the database remember their columns, to tell what their next save changes
+    let sync_tracker = fields.tracker.as_ref().map(|tracker| {
+        let ident = &tracker.ident;
+        quote_spanned! {tracker.span()=>
+            let mut __model = __model;
+            if ::std::any::type_name::<V::Error>() == ::std::any::type_name::<::ensemble::rbs::Error>() {
+                let columns = ::ensemble::value::for_db(&__model).map_err(_serde::de::Error::custom)?;
+                __model.#ident.sync(columns);
+            }
+        }
     });
 
     let build_model = quote! {
-        let __model = #name { #(#model_keys),* };
+        let __model = #name { #(#model_keys,)* #tracker_init };
+        #sync_tracker
         #ensure_no_leftovers
         Ok(__model)
     };
@@ -320,7 +427,7 @@ fn visitor_deserialize(
                                 if #key.is_some() {
                                     return Err(_serde::de::Error::duplicate_field(stringify!(#column)));
                                 }
-                                #key = Some(map.next_value()?);
+                                #key = Some(#next_value);
                             },
                         )*
                         Field::Other(name) => {
@@ -0,0 +1,86 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, to_value, value_map};
+use ensemble::Model;
+use serde_json::json;
+
+fn parse_tags(tags: String) -> Result<Vec<String>, String> {
+    if tags.is_empty() {
+        return Err("tags cannot be empty".to_string());
+    }
+
+    Ok(tags.split(',').map(ToString::to_string).collect())
+}
+
+fn join_tags(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+fn lowercase(email: &str) -> String {
+    email.to_lowercase()
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u8,
+    #[model(set = "lowercase")]
+    email: String,
+    #[model(get = "parse_tags", set = "join_tags")]
+    tags: Vec<String>,
+}
+
+#[test]
+fn setters_are_applied_when_serializing_for_the_database() {
+    let post = Post {
+        id: 1,
+        email: "Jane@Example.com".to_string(),
+        tags: vec!["rust".to_string(), "orm".to_string()],
+    };
+
+    assert_eq!(
+        to_value!(post),
+        rbs::Value::Map(value_map! {
+            "id" : 1u32,
+            "email" : "jane@example.com",
+            "tags" : "rust,orm",
+        })
+    );
+}
+
+#[test]
+fn getters_are_applied_when_hydrating_from_the_database() {
+    let post: Post = rbs::from_value(rbs::Value::Map(value_map! {
+        "id" : 1u32,
+        "email" : "jane@example.com",
+        "tags" : "rust,orm",
+    }))
+    .unwrap();
+
+    assert_eq!(post.tags, vec!["rust".to_string(), "orm".to_string()]);
+}
+
+#[test]
+fn getter_errors_name_the_column() {
+    let error = rbs::from_value::<Post>(rbs::Value::Map(value_map! {
+        "id" : 1u32,
+        "email" : "jane@example.com",
+        "tags" : "",
+    }))
+    .unwrap_err();
+
+    assert!(error.to_string().contains("tags"));
+}
+
+#[test]
+fn accessors_are_not_applied_to_json() {
+    let post = Post {
+        id: 1,
+        email: "Jane@Example.com".to_string(),
+        tags: vec!["rust".to_string()],
+    };
+
+    assert_eq!(
+        post.json(),
+        json!({ "id": 1, "email": "Jane@Example.com", "tags": ["rust"] })
+    );
+}
@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+
+use ensemble::query::Builder;
+use ensemble::types::DateTime;
+use ensemble::{testing, Error, Model};
+use serde_json::json;
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+#[ensemble(soft_deletes, global_scope = "Order::paid")]
+struct Order {
+    id: u64,
+    total: u64,
+    paid: bool,
+    deleted_at: Option<DateTime>,
+}
+
+impl Order {
+    fn paid(query: Builder) -> Builder {
+        query.r#where("paid", "=", true)
+    }
+}
+
+fn aggregate(value: serde_json::Value) -> Vec<ensemble::rbs::Value> {
+    rows(json!([{ "aggregate": value }]))
+}
+
+#[tokio::test]
+async fn aggregates_apply_the_model_scopes() {
+    let fake = testing::fake_connection()
+        .returning(aggregate(json!(1250)))
+        .returning(aggregate(json!(125.5)))
+        .returning(aggregate(json!(10)))
+        .returning(aggregate(json!(500)));
+
+    let (sum, avg, min, max) = fake
+        .run(async {
+            Ok::<_, Error>((
+                Order::sum::<u64>("total").await?,
+                Order::avg("total").await?,
+                Order::min::<u64>("total").await?,
+                Order::max::<u64>("total").await?,
+            ))
+        })
+        .await
+        .unwrap();
+    assert_eq!((sum, avg, min, max), (1250, Some(125.5), Some(10), Some(500)));
+
+    let statements = fake
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statements,
+        ["SUM", "AVG", "MIN", "MAX"].map(|function| format!(
+            "SELECT {function}(total) AS aggregate FROM orders WHERE orders.deleted_at IS NULL AND (paid = ?)"
+        ))
+    );
+}
+
+#[tokio::test]
+async fn counting_applies_the_model_scopes() {
+    let fake = testing::fake_connection().returning(rows(json!([{ "count": 3 }])));
+
+    assert_eq!(fake.run(Order::count()).await.unwrap(), 3);
+    assert_eq!(
+        fake.statements()[0].sql,
+        "SELECT COUNT(*) FROM orders WHERE orders.deleted_at IS NULL AND (paid = ?)"
+    );
+}
+
+#[tokio::test]
+async fn decimal_sums_are_parsed() {
+    // MySQL returns the sums of integer and DECIMAL columns as DECIMAL, which the driver reads as a string
+    let fake = testing::fake_connection()
+        .returning(aggregate(json!("9007199254740993")))
+        .returning(aggregate(json!("1250.75")))
+        .returning(aggregate(json!("1250.75")))
+        .returning(aggregate(json!("40.5000")));
+
+    let sum = fake.run(Order::sum::<u64>("total")).await.unwrap();
+    // above 2^53, so it would lose precision as a float
+    assert_eq!(sum, 9_007_199_254_740_993);
+
+    let sum = fake.run(Order::sum::<f64>("total")).await.unwrap();
+    assert!((sum - 1250.75).abs() < f64::EPSILON);
+
+    let sum = fake.run(Order::sum::<String>("total")).await.unwrap();
+    assert_eq!(sum, "1250.75");
+
+    assert_eq!(fake.run(Order::avg("total")).await.unwrap(), Some(40.5));
+}
+
+#[tokio::test]
+async fn empty_aggregates_are_zero_or_none() {
+    let fake = testing::fake_connection()
+        .returning(aggregate(json!(null)))
+        .returning(vec![])
+        .returning(aggregate(json!(null)))
+        .returning(aggregate(json!(null)));
+
+    assert_eq!(fake.run(Order::sum::<u64>("total")).await.unwrap(), 0);
+    assert_eq!(fake.run(Order::sum::<f64>("total")).await.unwrap(), 0.0);
+    assert_eq!(fake.run(Order::avg("total")).await.unwrap(), None);
+    assert_eq!(fake.run(Order::max::<u64>("total")).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn unparseable_aggregates_are_errors() {
+    let fake = testing::fake_connection()
+        .returning(aggregate(json!("not a number")))
+        .returning(aggregate(json!("12.5")))
+        .returning(aggregate(json!("n/a")));
+
+    assert!(matches!(
+        fake.run(Order::sum::<u64>("total")).await,
+        Err(Error::Serialization(_))
+    ));
+    // fractional sums don't fit integers
+    assert!(matches!(
+        fake.run(Order::sum::<u64>("total")).await,
+        Err(Error::Serialization(_))
+    ));
+    assert!(matches!(
+        fake.run(Order::avg("total")).await,
+        Err(Error::Serialization(_))
+    ));
+}
@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+
+use ensemble::{
+    audit::{self, Audit},
+    Error, Model,
+};
+
+#[derive(Debug, Model)]
+#[ensemble(auditable)]
+struct Invoice {
+    id: u64,
+    total: u64,
+}
+
+#[test]
+fn models_opt_into_auditing() {
+    const { assert!(Invoice::AUDITABLE) };
+    const { assert!(!Invoice::OUTBOX) };
+    const { assert!(Audit::READ_ONLY) };
+    assert_eq!(Audit::TABLE_NAME, "audits");
+}
+
+#[tokio::test]
+async fn changes_are_attributed_to_the_current_user() {
+    assert_eq!(audit::current_user(), None);
+
+    let user = audit::as_user(42_u64, async { audit::current_user() }).await;
+    assert_eq!(user, Some(42_u64.into()));
+}
+
+#[tokio::test]
+async fn audited_writes_need_a_connection() {
+    let error = Invoice { id: 0, total: 100 }.create().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    let error = Audit::for_model::<Invoice>(1).await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+}
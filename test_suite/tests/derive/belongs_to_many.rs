@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::BelongsToMany;
+use ensemble::{rbs, testing, Model};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    roles: BelongsToMany<User, Role>,
+}
+
+#[derive(Debug, Clone, Model)]
+struct Role {
+    #[model(incrementing = false)]
+    id: u64,
+    name: String,
+}
+
+fn role(id: u64, name: &str) -> Role {
+    Role {
+        id,
+        name: name.to_string(),
+    }
+}
+
+fn user() -> rbs::Value {
+    rbs::to_value(serde_json::json!({ "id": 1 })).unwrap()
+}
+
+/// The statements run after finding the user.
+fn sql(fake: &testing::FakeConnection) -> Vec<String> {
+    fake.statements()
+        .into_iter()
+        .skip(1)
+        .map(|statement| statement.sql)
+        .collect()
+}
+
+#[tokio::test]
+async fn created_models_are_attached_in_a_transaction() {
+    let fake = testing::fake_connection()
+        .returning(vec![user()])
+        .affecting(1);
+
+    let mut user = fake.run(User::find(1)).await.unwrap();
+    fake.run(user.roles.create(role(1, "admin"))).await.unwrap();
+
+    let statements = sql(&fake);
+    assert_eq!(statements.len(), 4);
+    assert_eq!(statements[0], "BEGIN");
+    assert!(statements[1].starts_with("INSERT INTO roles"));
+    assert!(statements[2].starts_with("INSERT INTO role_user"));
+    assert_eq!(statements[3], "COMMIT");
+}
+
+#[tokio::test]
+async fn creating_many_models_is_all_or_nothing() {
+    let fake = testing::fake_connection()
+        .returning(vec![user()])
+        .affecting(1);
+
+    let mut user = fake.run(User::find(1)).await.unwrap();
+    fake.run(
+        user.roles
+            .create_many(vec![role(1, "admin"), role(2, "editor")]),
+    )
+    .await
+    .unwrap();
+
+    let statements = sql(&fake);
+    assert_eq!(statements.len(), 6);
+    assert_eq!(statements.first().unwrap(), "BEGIN");
+    assert_eq!(
+        statements
+            .iter()
+            .filter(|sql| *sql == "BEGIN" || sql.starts_with("SAVEPOINT"))
+            .count(),
+        1
+    );
+    assert_eq!(statements.last().unwrap(), "COMMIT");
+}
+
+#[tokio::test]
+async fn models_are_not_created_for_unsaved_parents() {
+    let fake = testing::fake_connection();
+
+    let mut user = User::default();
+    assert!(fake.run(user.roles.create(role(1, "admin"))).await.is_err());
+    assert!(fake.statements().is_empty());
+}
@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+use ensemble::Model;
+
+#[test]
+fn builder_sets_required_and_optional_fields() {
+    #[derive(Debug, Model)]
+    #[ensemble(builder)]
+    struct User {
+        id: u64,
+        name: String,
+        email: String,
+        #[model(default = 18)]
+        age: u8,
+    }
+
+    let user = User::builder()
+        .email("jane@example.com".to_string())
+        .name("Jane".to_string())
+        .build();
+
+    assert_eq!(user.id, 0);
+    assert_eq!(user.age, 18);
+    assert_eq!(user.name, "Jane");
+    assert_eq!(user.email, "jane@example.com");
+
+    let user = User::builder()
+        .age(42)
+        .name("John".to_string())
+        .email("john@example.com".to_string())
+        .build();
+
+    assert_eq!(user.age, 42);
+}
+
+#[test]
+fn builder_can_be_built_when_every_field_is_required() {
+    #[derive(Debug, Model)]
+    #[ensemble(builder)]
+    struct Tag {
+        #[model(primary)]
+        slug: String,
+    }
+
+    let tag: Tag = Tag::builder().slug("rust".to_string()).build();
+
+    assert_eq!(tag.slug, "rust");
+}
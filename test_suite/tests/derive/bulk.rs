@@ -0,0 +1,149 @@
+#![allow(dead_code)]
+
+use ensemble::query::Dialect;
+use ensemble::rbs::{self, value_map};
+use ensemble::{testing, ChunkError, Error, Model};
+use futures_util::TryStreamExt;
+
+#[derive(Debug, Model)]
+struct Order {
+    id: u64,
+    status: String,
+    total: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(auditable)]
+struct Payment {
+    id: u64,
+    total: u64,
+}
+
+fn order(status: &str, total: u64) -> Order {
+    Order {
+        status: status.to_string(),
+        total,
+        ..Order::default()
+    }
+}
+
+fn orders(ids: &[u64]) -> Vec<rbs::Value> {
+    ids.iter()
+        .map(|id| rbs::Value::Map(value_map! { "id": *id, "status": "paid", "total": 10u64, }))
+        .collect()
+}
+
+#[tokio::test]
+async fn models_are_inserted_with_a_single_statement() {
+    let fake = testing::fake_connection().affecting(2);
+
+    let inserted = fake
+        .run(Order::insert_many(vec![
+            order("paid", 10),
+            order("refunded", 20),
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(inserted, 2);
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 1);
+    let quote = |name| Dialect::default().quote_identifier(name);
+    assert_eq!(
+        statements[0].sql,
+        format!(
+            "INSERT INTO {} ({}, {}) VALUES (?, ?), (?, ?)",
+            quote("orders"),
+            quote("status"),
+            quote("total")
+        )
+    );
+    assert_eq!(statements[0].bindings.len(), 4);
+}
+
+#[tokio::test]
+async fn inserting_no_models_runs_no_query() {
+    let fake = testing::fake_connection();
+
+    assert_eq!(fake.run(Order::insert_many(vec![])).await.unwrap(), 0);
+    assert!(fake.statements().is_empty());
+
+    let error = Payment::insert_many(vec![Payment::default()])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Unsupported(_)));
+}
+
+#[tokio::test]
+async fn queries_can_update_every_matching_row() {
+    let fake = testing::fake_connection().affecting(3);
+
+    let updated = fake
+        .run(
+            Order::query()
+                .r#where("status", "=", "pending")
+                .update([("status", "cancelled")]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 3);
+
+    let statements = fake.statements();
+    assert!(statements[0]
+        .sql
+        .starts_with("UPDATE orders SET status = ?"));
+    assert_eq!(
+        statements[0].bindings,
+        vec![rbs::to_value!("cancelled"), rbs::to_value!("pending")]
+    );
+}
+
+#[tokio::test]
+async fn models_are_streamed_one_chunk_at_a_time() {
+    let fake = testing::fake_connection()
+        .returning(orders(&[1, 2]))
+        .returning(orders(&[3]));
+
+    let ids = fake
+        .run(
+            Order::stream_by_id(2)
+                .map_ok(|order| order.id)
+                .try_collect::<Vec<_>>(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ids, [1, 2, 3]);
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 2);
+    assert!(statements[1].sql.contains("WHERE id > ?"));
+}
+
+#[tokio::test]
+async fn chunks_are_passed_to_the_callback() {
+    let fake = testing::fake_connection()
+        .returning(orders(&[1, 2]))
+        .returning(orders(&[]));
+
+    let mut sizes = vec![];
+    fake.run(Order::chunk(2, |chunk| {
+        sizes.push(chunk.len());
+        async { Ok::<_, ()>(()) }
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(sizes, [2]);
+    assert_eq!(fake.statements().len(), 2);
+
+    let error = Order::chunk(2, |_| async { Ok::<_, ()>(()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ChunkError::Database {
+            error: Error::Connection(_),
+            ..
+        }
+    ));
+}
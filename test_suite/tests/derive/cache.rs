@@ -0,0 +1,148 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::{BelongsTo, HasMany, Relationship};
+use ensemble::types::DateTime;
+use ensemble::{Error, Model};
+use std::collections::HashMap;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    password: String,
+    bio: Option<String>,
+    #[model(db_generated)]
+    post_count: u32,
+    created_at: DateTime,
+    posts: HasMany<User, Post>,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    author: BelongsTo<Post, User>,
+}
+
+fn user() -> User {
+    let mut user: User = rbs::from_value(rbs::Value::Map(value_map! {
+        "id": 1u64,
+        "name": "Taylor",
+        "password": "hunter2",
+        "bio": rbs::Value::Null,
+        "post_count": 2u32,
+        "created_at": DateTime::now(),
+    }))
+    .unwrap();
+
+    let post = |id: u64, title: &str| {
+        HashMap::from([
+            ("id".to_string(), rbs::to_value!(id)),
+            ("title".to_string(), rbs::to_value!(title)),
+            ("user_id".to_string(), rbs::to_value!(1u64)),
+        ])
+    };
+    user.posts
+        .r#match(&[post(1, "Hello"), post(2, "World")])
+        .unwrap();
+
+    user
+}
+
+#[test]
+fn every_column_is_cached() {
+    let user = user();
+    let cached = User::from_cached_bytes(&user.to_cached_bytes().unwrap()).unwrap();
+
+    assert_eq!(cached.id, 1);
+    assert_eq!(cached.password, "hunter2");
+    assert_eq!(cached.bio, None);
+    assert_eq!(cached.post_count, 2);
+    assert_eq!(cached.created_at, user.created_at);
+    assert!(!cached.posts.is_loaded());
+}
+
+#[test]
+fn loaded_relationships_can_be_cached() {
+    let bytes = user().to_cached_bytes_with_relations().unwrap();
+    let mut cached = User::from_cached_bytes(&bytes).unwrap();
+
+    assert!(cached.posts.is_loaded());
+    let titles = loaded_titles(&mut cached);
+    assert_eq!(titles, ["Hello", "World"]);
+    assert_eq!(cached.posts_query().get_bindings(), user().posts_query().get_bindings());
+}
+
+fn loaded_titles(user: &mut User) -> Vec<String> {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(user.posts())
+        .unwrap()
+        .iter()
+        .map(|post| post.title.clone())
+        .collect()
+}
+
+#[test]
+fn bytes_of_other_models_and_versions_are_rejected() {
+    let mut bytes = user().to_cached_bytes().unwrap();
+
+    assert!(matches!(
+        Post::from_cached_bytes(&bytes),
+        Err(Error::InvalidCache(reason)) if reason == "it holds a User, not a Post"
+    ));
+    assert!(matches!(
+        User::from_cached_bytes(&bytes[..bytes.len() - 1]),
+        Err(Error::InvalidCache(_))
+    ));
+
+    bytes[0] = 0;
+    assert!(matches!(
+        User::from_cached_bytes(&bytes),
+        Err(Error::InvalidCache(reason)) if reason == "it was encoded with version 0, not 1"
+    ));
+}
+
+mod v1 {
+    use ensemble::Model;
+
+    #[derive(Debug, Model)]
+    pub struct Flight {
+        pub id: u64,
+        pub name: String,
+    }
+}
+
+mod v2 {
+    use ensemble::Model;
+
+    #[derive(Debug, Model)]
+    pub struct Flight {
+        pub id: u64,
+        pub name: String,
+        pub delayed: bool,
+    }
+}
+
+#[test]
+fn changed_models_fail_to_decode() {
+    let old = v1::Flight {
+        id: 1,
+        name: "London to Paris".to_string(),
+    };
+    let new = v2::Flight {
+        id: 1,
+        name: "London to Paris".to_string(),
+        delayed: true,
+    };
+
+    // a field was added since the model was cached
+    let error = v2::Flight::from_cached_bytes(&old.to_cached_bytes().unwrap()).unwrap_err();
+    assert!(matches!(error, Error::InvalidCache(reason) if reason.contains("delayed")));
+
+    // a field was removed since the model was cached
+    let error = v1::Flight::from_cached_bytes(&new.to_cached_bytes().unwrap()).unwrap_err();
+    assert!(matches!(error, Error::InvalidCache(reason) if reason.contains("delayed")));
+}
@@ -0,0 +1,40 @@
+use ensemble::{ChunkError, Error, Model};
+
+#[derive(Debug, Model)]
+struct Order {
+    id: u64,
+    total: u64,
+}
+
+#[tokio::test]
+async fn failures_report_the_checkpoint_to_resume_from() {
+    let error = Order::chunk_by_id_from(Some(41), 100, |_| async { Ok::<_, ()>(()) })
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.last_key(), Some(&41));
+    assert!(matches!(
+        error,
+        ChunkError::Database {
+            error: Error::Connection(_),
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn progress_is_only_reported_for_processed_chunks() {
+    let mut reported = vec![];
+
+    let error = Order::chunk_by_id_from_with_progress(
+        None,
+        100,
+        |_| async { Ok::<_, ()>(()) },
+        |processed, key| reported.push((processed, *key)),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(error.last_key(), None);
+    assert!(reported.is_empty());
+}
@@ -0,0 +1,133 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::{BelongsTo, HasMany};
+use ensemble::types::{DateTime, Uuid};
+use ensemble::value::{self, ColumnDef, ColumnType};
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    posts: HasMany<User, Post>,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    #[model(uuid)]
+    reference: Uuid,
+    title: String,
+    #[model(column = "content")]
+    body: Option<String>,
+    author: BelongsTo<Post, User>,
+    #[model(db_generated)]
+    word_count: u32,
+    #[model(default = true)]
+    published: bool,
+    created_at: DateTime,
+}
+
+fn column(name: &str) -> ColumnDef {
+    *Post::columns()
+        .iter()
+        .find(|column| column.name == name)
+        .unwrap()
+}
+
+#[test]
+fn columns_follow_declaration_order() {
+    let names = Post::columns()
+        .iter()
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        [
+            "id",
+            "reference",
+            "title",
+            "content",
+            "user_id",
+            "word_count",
+            "published",
+            "created_at"
+        ]
+    );
+}
+
+#[test]
+fn columns_describe_their_fields() {
+    assert_eq!(
+        column("id"),
+        ColumnDef {
+            name: "id",
+            field: "id",
+            primary_key: true,
+            nullable: false,
+            has_default: true,
+            increments: true,
+            db_generated: false,
+            immutable: false,
+            unique: false,
+            sensitive: false,
+            r#type: ColumnType::UnsignedInteger,
+        }
+    );
+
+    let body = column("content");
+    assert_eq!(body.field, "body");
+    assert!(body.nullable);
+    assert!(!body.has_default);
+    assert_eq!(body.r#type, ColumnType::String);
+
+    assert_eq!(column("user_id").field, "author");
+    assert_eq!(column("user_id").r#type, ColumnType::Other);
+    assert_eq!(column("reference").r#type, ColumnType::Uuid);
+    assert!(column("reference").has_default);
+    assert!(column("published").has_default);
+    assert!(column("word_count").db_generated);
+    assert_eq!(column("created_at").r#type, ColumnType::DateTime);
+}
+
+#[test]
+fn relationships_without_a_column_are_skipped() {
+    assert_eq!(User::columns().len(), 1);
+    assert_eq!(User::keys(), ["id"]);
+}
+
+#[test]
+fn keys_match_the_columns_written_on_insert() {
+    let post = Post {
+        title: "Hello".to_string(),
+        ..Post::default()
+    };
+
+    let ensemble::rbs::Value::Map(written) = value::for_db(&post).unwrap() else {
+        unreachable!()
+    };
+    let written = written
+        .into_iter()
+        .map(|(column, _)| column.into_string().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        Post::keys(),
+        [
+            "id",
+            "reference",
+            "title",
+            "content",
+            "user_id",
+            "published",
+            "created_at"
+        ]
+    );
+    assert_eq!(Post::keys(), written);
+}
+
+#[test]
+fn keys_are_only_collected_once() {
+    assert!(std::ptr::eq(Post::keys(), Post::keys()));
+    assert!(!std::ptr::eq(Post::keys(), User::keys()));
+}
@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+use ensemble::query::Dialect;
+use ensemble::rbs::{self, to_value, value_map};
+use ensemble::{testing, Error, Model};
+use serde_json::json;
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct Invoice {
+    id: u64,
+    total: u32,
+    #[model(db_generated)]
+    total_with_tax: u32,
+    #[model(db_generated)]
+    reference: Option<String>,
+}
+
+#[test]
+fn db_generated_columns_are_not_written_to_the_database() {
+    let invoice = Invoice {
+        id: 1,
+        total: 100,
+        ..Invoice::default()
+    };
+
+    assert_eq!(
+        to_value!(invoice),
+        rbs::Value::Map(value_map! {
+            "id" : 1u64,
+            "total" : 100u32,
+        })
+    );
+}
+
+#[test]
+fn db_generated_columns_are_still_hydrated() {
+    let invoice: Invoice = rbs::from_value(rbs::Value::Map(value_map! {
+        "id" : 1u64,
+        "total" : 100u32,
+        "total_with_tax" : 120u32,
+        "reference" : "INV-0001",
+    }))
+    .unwrap();
+
+    assert_eq!(invoice.total_with_tax, 120);
+    assert_eq!(invoice.reference.as_deref(), Some("INV-0001"));
+}
+
+#[derive(Debug, Model)]
+#[ensemble(table = "invoice_totals", read_only)]
+struct InvoiceTotal {
+    id: u64,
+    total: u32,
+}
+
+#[tokio::test]
+async fn create_and_fetch_checks_the_model_before_inserting() {
+    let error = Invoice::default().create_and_fetch().await.unwrap_err();
+    assert!(matches!(error, Error::Required("total")));
+
+    let error = InvoiceTotal::default().create_and_fetch().await.unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("invoice_totals")));
+}
+
+#[tokio::test]
+async fn creating_a_model_reads_back_its_generated_columns() {
+    let generated = json!({ "id": 5, "total_with_tax": 120, "reference": "INV-0005" });
+    let fake = match Dialect::default() {
+        Dialect::Postgres | Dialect::Sqlite => testing::fake_connection().returning(rows(json!([generated]))),
+        // the whole row is selected after the insert
+        Dialect::Mysql => testing::fake_connection().affecting(1).returning(rows(json!([
+            { "id": 5, "total": 100, "total_with_tax": 120, "reference": "INV-0005" },
+        ]))),
+    };
+
+    let invoice = Invoice {
+        id: 5,
+        total: 100,
+        ..Invoice::default()
+    };
+    let invoice = fake.run(invoice.create()).await.unwrap();
+
+    assert_eq!(invoice.total, 100);
+    assert_eq!(invoice.total_with_tax, 120);
+    assert_eq!(invoice.reference.as_deref(), Some("INV-0005"));
+
+    let statements = fake
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect::<Vec<_>>();
+    match Dialect::default() {
+        Dialect::Postgres | Dialect::Sqlite => assert_eq!(
+            statements,
+            ["INSERT INTO invoices (id, total) VALUES (?, ?) RETURNING id, total_with_tax, reference"]
+        ),
+        Dialect::Mysql => assert_eq!(
+            statements,
+            [
+                "INSERT INTO invoices (id, total) VALUES (?, ?)",
+                "SELECT * FROM invoices WHERE id = ? LIMIT 1"
+            ]
+        ),
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use ensemble::types::{DateTime, Uuid};
+use ensemble::types::{DateTime, Ulid, Uuid};
 use ensemble::Model;
 
 #[test]
@@ -36,6 +36,30 @@ fn respects_custom_default_values_via_attributes() {
     assert_eq!(model.name, "custom_string".to_string());
 }
 
+#[test]
+fn evaluates_default_expressions_for_every_instance() {
+    #[derive(Debug, Model)]
+    struct MyModel {
+        id: u8,
+
+        #[model(default = "Uuid::new()")]
+        token: Uuid,
+
+        #[model(default = DateTime::now() + std::time::Duration::from_secs(30 * 24 * 60 * 60))]
+        expires_at: DateTime,
+
+        #[model(default = "pending")]
+        status: String,
+    }
+
+    let (first, second) = (MyModel::default(), MyModel::default());
+
+    assert_ne!(first.token, Uuid::nil());
+    assert_ne!(first.token, second.token);
+    assert!(first.expires_at.unix_timestamp() > DateTime::now().unix_timestamp());
+    assert_eq!(first.status, "pending");
+}
+
 #[test]
 fn initialises_marked_uuids_automatically() {
     #[derive(Debug, Model)]
@@ -49,6 +73,20 @@ fn initialises_marked_uuids_automatically() {
     assert_ne!(model.id, Uuid::nil());
 }
 
+#[test]
+fn initialises_marked_ulids_automatically() {
+    #[derive(Debug, Model)]
+    struct MyModel {
+        #[model(ulid)]
+        id: Ulid,
+    }
+
+    let (first, second) = (MyModel::default(), MyModel::default());
+
+    assert_ne!(first.id, Ulid::nil());
+    assert_ne!(first.id, second.id);
+}
+
 #[test]
 fn initialises_created_at_and_updated_at_when_marked() {
     #[derive(Debug, Model)]
@@ -0,0 +1,77 @@
+use ensemble::{types::DateTime, value::Change, Model};
+
+#[derive(Debug, Model, Clone)]
+struct Flight {
+    id: u64,
+    name: String,
+    delayed: bool,
+    created_at: DateTime,
+    updated_at: DateTime,
+}
+
+fn flight() -> Flight {
+    Flight {
+        id: 1,
+        name: "EK 202".to_string(),
+        delayed: false,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    }
+}
+
+#[test]
+fn identical_models_have_no_changes() {
+    let flight = flight();
+
+    assert!(flight.diff(&flight.clone()).is_empty());
+}
+
+#[test]
+fn diff_lists_every_changed_column() {
+    let old = flight();
+    let mut new = old.clone();
+    new.name = "EK 203".to_string();
+    new.delayed = true;
+
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            Change {
+                column: "name".to_string(),
+                old: "EK 202".into(),
+                new: "EK 203".into(),
+            },
+            Change {
+                column: "delayed".to_string(),
+                old: false.into(),
+                new: true.into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn timestamps_can_be_ignored() {
+    assert_eq!(Flight::TIMESTAMPS, ["created_at", "updated_at"]);
+
+    let old = flight();
+    let mut new = old.clone();
+    new.updated_at = DateTime::from_timestamp(0);
+
+    assert_eq!(old.diff(&new).len(), 1);
+    assert!(old.diff_without_timestamps(&new).is_empty());
+}
+
+#[test]
+fn changes_serialize_to_json() {
+    let change = Change {
+        column: "name".to_string(),
+        old: "EK 202".into(),
+        new: "EK 203".into(),
+    };
+
+    assert_eq!(
+        serde_json::to_value(change).unwrap(),
+        serde_json::json!({ "column": "name", "old": "EK 202", "new": "EK 203" })
+    );
+}
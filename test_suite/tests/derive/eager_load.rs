@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::{BelongsTo, HasMany};
+use ensemble::{testing, Model};
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    #[model(eager)]
+    posts: HasMany<User, Post>,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    user: BelongsTo<Post, User>,
+}
+
+#[test]
+fn eager_relationships_are_listed() {
+    assert_eq!(User::EAGER_LOAD, ["posts"]);
+    assert!(Post::EAGER_LOAD.is_empty());
+}
+
+#[tokio::test]
+async fn eager_relationships_are_loaded_in_a_single_query() {
+    let fake = testing::fake_connection()
+        .returning(rows(serde_json::json!([
+            { "id": 1, "name": "Taylor" },
+            { "id": 2, "name": "Abbey" },
+        ])))
+        .returning(rows(serde_json::json!([
+            { "id": 1, "title": "Hello", "user_id": 1 },
+            { "id": 2, "title": "World", "user_id": 1 },
+            { "id": 3, "title": "Again", "user_id": 2 },
+        ])));
+
+    let mut users = fake.run(User::all()).await.unwrap();
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].sql, "SELECT * FROM users");
+    assert!(statements[1].sql.starts_with("SELECT * FROM posts WHERE"));
+
+    // the loaded posts are used instead of running another query
+    let posts = testing::assert_query_count(0, users[0].posts())
+        .await
+        .unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(users[1].posts().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn queries_can_opt_out_of_eager_relationships() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "id": 1, "name": "Taylor" },
+    ])));
+
+    fake.run(User::query().without("posts").get::<User>())
+        .await
+        .unwrap();
+
+    assert_eq!(fake.statements().len(), 1);
+}
@@ -0,0 +1,37 @@
+use ensemble::query::{QueryPlan, Row};
+use ensemble::rbs::{self, value_map};
+
+#[test]
+fn query_plans_display_as_an_aligned_table() {
+    let plan = QueryPlan::from(vec![
+        rbs::Value::Map(value_map! { "id" : 1u64, "table" : "users", "key" : rbs::Value::Null, }),
+        rbs::Value::Map(value_map! { "id" : 2u64, "table" : "posts", "key" : "user_id", }),
+    ]);
+
+    assert_eq!(
+        plan.to_string(),
+        "| id | table | key     |\n\
+         |----|-------|---------|\n\
+         | 1  | users | NULL    |\n\
+         | 2  | posts | user_id |\n"
+    );
+}
+
+#[test]
+fn multi_line_plans_are_split_across_rows() {
+    let plan = QueryPlan::from(vec![rbs::Value::Map(
+        value_map! { "QUERY PLAN" : "Seq Scan on users\n  Filter: (id = 1)", },
+    )]);
+
+    assert_eq!(
+        plan.to_string(),
+        "| QUERY PLAN         |\n\
+         |--------------------|\n\
+         | Seq Scan on users  |\n\
+         |   Filter: (id = 1) |\n"
+    );
+    assert_eq!(
+        plan.rows.first().and_then(|row: &Row| row.get("QUERY PLAN")),
+        Some(&rbs::to_value!("Seq Scan on users\n  Filter: (id = 1)"))
+    );
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use ensemble::factory::Factory;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    admin: bool,
+}
+
+impl Factory for User {
+    fn definition() -> Self {
+        Self {
+            name: "Taylor".to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+#[test]
+fn models_are_made_from_their_definition() {
+    let users = User::factory().count(3).make();
+
+    assert_eq!(users.len(), 3);
+    assert!(users
+        .iter()
+        .all(|user| user.name == "Taylor" && !user.admin));
+    assert_eq!(User::factory().make().len(), 1);
+}
+
+#[test]
+fn states_are_applied_in_order() {
+    let user = User::factory()
+        .state(|user| user.admin = true)
+        .state(|user| user.name.push_str(" Otwell"))
+        .make_one();
+
+    assert!(user.admin);
+    assert_eq!(user.name, "Taylor Otwell");
+}
+
+#[test]
+fn sequences_are_given_the_position_of_each_model() {
+    let names = User::factory()
+        .count(2)
+        .sequence(|user, index| user.name = format!("User {index}"))
+        .make()
+        .into_iter()
+        .map(|user| user.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["User 0", "User 1"]);
+}
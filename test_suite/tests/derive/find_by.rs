@@ -0,0 +1,95 @@
+#![allow(dead_code)]
+
+use ensemble::{rbs, testing, Error, Model};
+use serde_json::json;
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    #[model(unique)]
+    email: String,
+    provider: String,
+    provider_id: u64,
+}
+
+#[test]
+fn unique_columns_are_described() {
+    let unique = User::columns()
+        .iter()
+        .filter(|column| column.unique)
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(unique, ["email"]);
+}
+
+#[tokio::test]
+async fn lookups_only_accept_columns_of_the_model() {
+    assert!(matches!(
+        User::find_by("name", "Taylor").await,
+        Err(Error::InvalidQuery)
+    ));
+    assert!(matches!(
+        User::find_by_columns(&[("provider", "github"), ("login", "taylor")]).await,
+        Err(Error::InvalidQuery)
+    ));
+    assert!(matches!(
+        User::find_by_columns::<&str>(&[]).await,
+        Err(Error::InvalidQuery)
+    ));
+}
+
+#[tokio::test]
+async fn models_are_looked_up_by_their_columns() {
+    let user = json!([{ "id": 1, "email": "taylor@example.com", "provider": "github", "provider_id": 42 }]);
+    let fake = testing::fake_connection()
+        .returning(rows(user.clone()))
+        .returning(rows(user.clone()))
+        .returning(rows(user))
+        .returning(vec![]);
+
+    let found = fake
+        .run(User::find_by("email", "taylor@example.com"))
+        .await
+        .unwrap();
+    assert_eq!(found.map(|user| user.id), Some(1));
+
+    let found = fake
+        .run(User::find_by_email("taylor@example.com"))
+        .await
+        .unwrap();
+    assert_eq!(found.map(|user| user.provider_id), Some(42));
+
+    let found = fake
+        .run(User::find_by_columns(&[
+            ("provider", rbs::to_value!("github")),
+            ("provider_id", rbs::to_value!(42u64)),
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(found.map(|user| user.email), Some("taylor@example.com".to_string()));
+
+    // no match isn't an error
+    let found = fake
+        .run(User::find_by_email("otwell@example.com"))
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let statements = fake.statements();
+    assert_eq!(
+        statements.iter().map(|s| s.sql.as_str()).collect::<Vec<_>>(),
+        [
+            "SELECT * FROM users WHERE email = ? LIMIT 1",
+            "SELECT * FROM users WHERE email = ? LIMIT 1",
+            "SELECT * FROM users WHERE provider = ? AND provider_id = ? LIMIT 1",
+            "SELECT * FROM users WHERE email = ? LIMIT 1",
+        ]
+    );
+    assert_eq!(
+        statements[2].bindings,
+        [rbs::to_value!("github"), rbs::to_value!(42u64)]
+    );
+}
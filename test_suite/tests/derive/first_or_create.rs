@@ -0,0 +1,114 @@
+#![allow(dead_code)]
+
+use ensemble::{testing, transaction, Error, Model};
+use serde_json::json;
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    email: String,
+    name: String,
+}
+
+fn user() -> User {
+    User {
+        id: 0,
+        email: "taylor@example.com".to_string(),
+        name: "Taylor".to_string(),
+    }
+}
+
+fn statements(fake: &testing::FakeConnection) -> Vec<String> {
+    fake.statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect()
+}
+
+#[tokio::test]
+async fn the_model_is_looked_up_first() {
+    let fake = testing::fake_connection().returning(rows(json!([
+        { "id": 1, "email": "taylor@example.com", "name": "Taylor Otwell" },
+    ])));
+
+    let found = fake.run(user().first_or_create(&["email"])).await.unwrap();
+    assert_eq!((found.id, found.name.as_str()), (1, "Taylor Otwell"));
+
+    assert_eq!(
+        statements(&fake),
+        ["SELECT * FROM users WHERE email = ? LIMIT 1"]
+    );
+}
+
+#[tokio::test]
+async fn racing_inserts_find_the_winning_row() {
+    // another request inserts the user between the lookup and the insert
+    let fake = testing::fake_connection()
+        .returning(vec![])
+        .failing(Error::UniqueViolation)
+        .returning(rows(json!([
+            { "id": 7, "email": "taylor@example.com", "name": "Taylor Otwell" },
+        ])));
+
+    let found = fake.run(user().first_or_create(&["email"])).await.unwrap();
+    assert_eq!(found.id, 7);
+
+    let statements = statements(&fake);
+    assert_eq!(statements.len(), 3);
+    assert!(statements[1].starts_with("INSERT INTO users"));
+    assert_eq!(statements[0], statements[2]);
+}
+
+#[tokio::test]
+async fn racing_inserts_in_a_transaction_are_rolled_back_to_a_savepoint() {
+    // BEGIN and SAVEPOINT are the first statements to modify rows
+    let fake = testing::fake_connection()
+        .affecting(0)
+        .returning(vec![])
+        .affecting(0)
+        .failing(Error::UniqueViolation)
+        .returning(rows(json!([
+            { "id": 7, "email": "taylor@example.com", "name": "Taylor Otwell" },
+        ])));
+
+    let found = fake
+        .run(transaction(|| user().first_or_create(&["email"])))
+        .await
+        .unwrap();
+    assert_eq!(found.id, 7);
+
+    let statements = statements(&fake);
+    assert_eq!(statements[..3], ["BEGIN", "SELECT * FROM users WHERE email = ? LIMIT 1", "SAVEPOINT ensemble_1"]);
+    assert!(statements[3].starts_with("INSERT INTO users"));
+    assert_eq!(
+        statements[4..],
+        [
+            "ROLLBACK TO SAVEPOINT ensemble_1",
+            "SELECT * FROM users WHERE email = ? LIMIT 1",
+            "COMMIT"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let fake = testing::fake_connection()
+        .failing(Error::Database("disk full".to_string()));
+
+    let error = fake
+        .run(user().first_or_create(&["email"]))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Database(_)));
+    assert_eq!(fake.statements().len(), 2);
+}
+
+#[tokio::test]
+async fn lookup_columns_must_exist() {
+    let error = user().first_or_create(&["username"]).await.unwrap_err();
+
+    assert!(matches!(error, Error::InvalidQuery));
+}
@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use ensemble::query::Type;
+use ensemble::types::DateTime;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    #[model(flag = "publish")]
+    published_at: Option<DateTime>,
+    #[model(flag = "archive", column = "archived_on")]
+    archived_at: Option<DateTime>,
+}
+
+#[test]
+fn flags_are_set_and_cleared() {
+    let mut post = Post::default();
+    assert!(!post.is_published());
+
+    post.publish();
+    assert!(post.is_published());
+    assert!(post.published_at.is_some());
+
+    post.unpublish();
+    assert!(!post.is_published());
+    assert!(post.published_at.is_none());
+
+    post.archive();
+    assert!(post.is_archived());
+    assert!(!post.is_published());
+}
+
+#[test]
+fn flags_add_query_scopes() {
+    assert_eq!(
+        Post::published().to_sql(Type::Select),
+        "SELECT * FROM posts WHERE published_at IS NOT NULL"
+    );
+    assert_eq!(
+        Post::unpublished()
+            .r#where("title", "=", "Hello")
+            .to_sql(Type::Select),
+        "SELECT * FROM posts WHERE published_at IS NULL AND title = ?"
+    );
+    assert_eq!(
+        Post::unarchived().to_sql(Type::Select),
+        "SELECT * FROM posts WHERE archived_on IS NULL"
+    );
+}
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, value_map};
+use ensemble::FromRow;
+
+fn cents(total: f64) -> Result<u64, String> {
+    if total < 0.0 {
+        return Err("totals cannot be negative".to_string());
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok((total * 100.0).round() as u64)
+}
+
+#[derive(Debug, PartialEq, Eq, FromRow)]
+struct CustomerTotal {
+    customer_id: u64,
+    #[model(column = "total", get = "cents")]
+    total_cents: u64,
+    nickname: Option<String>,
+}
+
+#[test]
+fn rows_are_hydrated_without_a_primary_key() {
+    let row: CustomerTotal = rbs::from_value(rbs::Value::Map(value_map! {
+        "customer_id" : 7u64,
+        "total" : 12.5f64,
+        "nickname" : rbs::Value::Null,
+    }))
+    .unwrap();
+
+    assert_eq!(
+        row,
+        CustomerTotal {
+            customer_id: 7,
+            total_cents: 1250,
+            nickname: None,
+        }
+    );
+}
+
+#[test]
+fn missing_and_invalid_columns_are_reported() {
+    let error = rbs::from_value::<CustomerTotal>(rbs::Value::Map(value_map! {
+        "total" : 1f64,
+        "nickname" : rbs::Value::Null,
+    }))
+    .unwrap_err();
+    assert!(error.to_string().contains("missing field `customer_id`"));
+
+    let error = rbs::from_value::<CustomerTotal>(rbs::Value::Map(value_map! {
+        "customer_id" : 7u64,
+        "total" : -1f64,
+        "nickname" : rbs::Value::Null,
+    }))
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("failed to hydrate the total column: totals cannot be negative"));
+}
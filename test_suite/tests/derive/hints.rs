@@ -0,0 +1,74 @@
+use ensemble::query::Type;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Booking {
+    id: u64,
+    nights: u32,
+}
+
+#[test]
+#[cfg(not(feature = "sqlite"))]
+fn index_hints_follow_the_table_name() {
+    let query = Booking::query()
+        .use_index("idx_nights")
+        .ignore_index("PRIMARY")
+        .r#where("nights", ">", 2);
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT * FROM bookings USE INDEX (idx_nights) IGNORE INDEX (PRIMARY) WHERE nights > ?"
+    );
+    assert_eq!(
+        Booking::query()
+            .force_index("idx_nights")
+            .to_sql(Type::Count),
+        "SELECT COUNT(*) FROM bookings FORCE INDEX (idx_nights)"
+    );
+}
+
+#[test]
+#[cfg(not(feature = "sqlite"))]
+fn query_hints_follow_select() {
+    let query = Booking::query()
+        .query_hint("/*+ MAX_EXECUTION_TIME(1000) */")
+        .straight_join();
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT /*+ MAX_EXECUTION_TIME(1000) */ STRAIGHT_JOIN * FROM bookings"
+    );
+}
+
+#[test]
+#[cfg(feature = "sqlite")]
+fn hints_are_left_out_on_sqlite() {
+    let query = Booking::query()
+        .use_index("idx_nights")
+        .force_index("idx_nights")
+        .ignore_index("PRIMARY")
+        .query_hint("/*+ MAX_EXECUTION_TIME(1000) */")
+        .straight_join()
+        .r#where("nights", ">", 2);
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT * FROM bookings WHERE nights > ?"
+    );
+    assert_eq!(
+        Booking::query().use_index("idx_nights").to_sql(Type::Count),
+        "SELECT COUNT(*) FROM bookings"
+    );
+}
+
+#[test]
+#[should_panic(expected = "The index name `idx) WHERE 1=1 --` is invalid.")]
+fn index_names_are_validated() {
+    let _ = Booking::query().use_index("idx) WHERE 1=1 --");
+}
+
+#[test]
+#[should_panic(expected = "must be a single `/*+ ... */` comment")]
+fn query_hints_must_be_a_single_comment() {
+    let _ = Booking::query().query_hint("/*+ BKA() */ * FROM users; --*/");
+}
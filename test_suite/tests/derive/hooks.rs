@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+
+use ensemble::hooks::EventType;
+use ensemble::{testing, transaction, Error, Model};
+use std::sync::Mutex;
+
+static CALLS: Mutex<Vec<(u64, EventType, bool)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Model)]
+#[ensemble(after_commit = "Self::committed", after_rollback = "Self::rolled_back")]
+struct Booking {
+    id: u64,
+    seat: String,
+}
+
+impl Booking {
+    fn committed(&self, event: EventType) {
+        CALLS.lock().unwrap().push((self.id, event, true));
+    }
+
+    fn rolled_back(&self, event: EventType) {
+        CALLS.lock().unwrap().push((self.id, event, false));
+    }
+}
+
+#[derive(Debug, Model)]
+#[ensemble(after_commit = "notify")]
+struct Flight {
+    id: u64,
+}
+
+fn notify(_: &Flight, _: EventType) {}
+
+#[derive(Debug, Model)]
+struct Seat {
+    id: u64,
+}
+
+#[test]
+fn hooks_are_declared() {
+    const { assert!(Booking::HOOKS) };
+    const { assert!(Flight::HOOKS) };
+    const { assert!(!Seat::HOOKS) };
+}
+
+/// The hooks called for the booking with the given id, since tests run concurrently.
+fn calls_of(id: u64) -> Vec<(EventType, bool)> {
+    CALLS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(booking, ..)| *booking == id)
+        .map(|(_, event, committed)| (*event, *committed))
+        .collect()
+}
+
+#[tokio::test]
+async fn writes_outside_of_a_transaction_call_their_hook_right_away() {
+    let fake = testing::fake_connection().affecting(1);
+
+    let mut booking = Booking {
+        id: 1,
+        seat: "12A".to_string(),
+    };
+    fake.run(booking.save()).await.unwrap();
+
+    assert_eq!(calls_of(1), [(EventType::Updated, true)]);
+}
+
+#[tokio::test]
+async fn writes_in_a_rolled_back_transaction_are_never_committed() {
+    // BEGIN is the first statement to modify rows
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    let error = fake
+        .run(transaction(|| async {
+            let mut booking = Booking {
+                id: 2,
+                seat: "14C".to_string(),
+            };
+            booking.save().await?;
+
+            Err::<(), _>(Error::NotFound)
+        }))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+
+    assert_eq!(calls_of(2), [(EventType::Updated, false)]);
+}
+
+#[tokio::test]
+async fn writes_that_fail_call_no_hook() {
+    let fake = testing::fake_connection();
+
+    let booking = Booking {
+        id: 3,
+        seat: "16F".to_string(),
+    };
+    assert!(fake.run(booking.delete()).await.is_err());
+
+    assert!(calls_of(3).is_empty());
+}
@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, to_value};
+use ensemble::value::Tracker;
+use ensemble::Model;
+use std::collections::HashMap;
+
+use super::support::row;
+
+fn parse_tags(tags: String) -> Result<Vec<String>, String> {
+    Ok(tags.split(',').map(ToString::to_string).collect())
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    #[model(column = "content")]
+    body: String,
+    #[model(get = "parse_tags")]
+    tags: Vec<String>,
+    published: bool,
+    subtitle: Option<String>,
+    tracker: Tracker,
+}
+
+fn post_row(id: u64) -> HashMap<String, rbs::Value> {
+    row(&[
+        ("id", to_value!(id)),
+        ("content", to_value!("Hello")),
+        ("tags", to_value!("rust,orm")),
+        // MySQL returns `TINYINT(1)` columns as integers
+        ("published", to_value!(1i64)),
+        ("subtitle", rbs::Value::Null),
+    ])
+}
+
+#[test]
+fn rows_are_hydrated_like_query_results() {
+    let post = Post::hydrate(post_row(1)).unwrap();
+
+    assert_eq!(post.id, 1);
+    assert_eq!(post.body, "Hello");
+    assert_eq!(post.tags, ["rust", "orm"]);
+    assert!(post.published);
+    assert_eq!(post.subtitle, None);
+}
+
+#[test]
+fn rows_with_a_primary_key_are_treated_as_stored() {
+    let mut post = Post::hydrate(post_row(1)).unwrap();
+    assert!(post.is_clean());
+
+    post.body = "Hello, world!".to_string();
+    assert!(!post.is_clean());
+
+    assert!(!Post::hydrate(post_row(0)).unwrap().is_clean());
+}
+
+#[test]
+fn many_rows_can_be_hydrated_at_once() {
+    let posts = Post::hydrate_many(vec![post_row(1), post_row(2)]).unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), [1, 2]);
+
+    assert!(Post::hydrate_many(vec![]).unwrap().is_empty());
+}
+
+#[test]
+fn invalid_rows_are_rejected() {
+    let mut missing = post_row(1);
+    missing.remove("content");
+    assert!(Post::hydrate(missing).is_err());
+
+    let mut unknown = post_row(1);
+    unknown.insert("views".to_string(), to_value!(1u64));
+    assert!(Post::hydrate(unknown).is_err());
+
+    let mut null = post_row(1);
+    null.insert("content".to_string(), rbs::Value::Null);
+    assert!(Post::hydrate(null).is_err());
+
+    assert!(Post::hydrate_many(vec![post_row(1), row(&[])]).is_err());
+}
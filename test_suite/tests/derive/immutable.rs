@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::BelongsTo;
+use ensemble::value::{self, Tracker};
+use ensemble::{Error, Model};
+
+#[derive(Debug, Model)]
+struct Tenant {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Invoice {
+    id: u64,
+    #[model(immutable)]
+    external_id: String,
+    #[model(immutable)]
+    tenant: BelongsTo<Invoice, Tenant>,
+    total: u64,
+    tracker: Tracker,
+}
+
+#[derive(Debug, Model)]
+struct Receipt {
+    id: u64,
+    #[model(immutable)]
+    external_id: String,
+    total: u64,
+}
+
+fn invoice() -> Invoice {
+    rbs::from_value(rbs::Value::Map(value_map! {
+        "id": 1u64, "external_id": "inv_1", "tenant_id": 7u64, "total": 100u64,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn immutable_columns_are_described() {
+    let immutable = Invoice::columns()
+        .iter()
+        .filter(|column| column.immutable)
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(immutable, ["external_id", "tenant_id"]);
+}
+
+#[test]
+fn immutable_columns_are_left_out_of_updates() {
+    let mut invoice = invoice();
+    invoice.total = 200;
+
+    let rbs::Value::Map(columns) = value::for_update(&invoice).unwrap() else {
+        unreachable!()
+    };
+    let names = columns
+        .into_iter()
+        .map(|(column, _)| column.into_string().unwrap())
+        .collect::<Vec<_>>();
+    // the tracker also leaves out the columns that didn't change
+    assert_eq!(names, ["total"]);
+
+    // without a tracker, changes to immutable columns can't be told apart, so they're dropped
+    let receipt = Receipt {
+        id: 1,
+        external_id: "changed".to_string(),
+        total: 100,
+    };
+    assert!(value::for_update(&receipt).is_ok());
+}
+
+#[tokio::test]
+async fn changing_an_immutable_column_fails() {
+    let mut renamed = invoice();
+    renamed.external_id = "inv_2".to_string();
+
+    assert!(matches!(
+        value::for_update(&renamed),
+        Err(Error::ImmutableColumn("external_id"))
+    ));
+    assert!(matches!(
+        renamed.save().await,
+        Err(Error::ImmutableColumn("external_id"))
+    ));
+
+    let mut moved = invoice();
+    moved.tenant = BelongsTo::default();
+    assert!(matches!(
+        value::for_update(&moved),
+        Err(Error::ImmutableColumn("tenant_id"))
+    ));
+}
+
+#[tokio::test]
+async fn mass_updates_reject_immutable_columns() {
+    let result = Invoice::query()
+        .r#where("total", "=", 100)
+        .update(vec![("external_id", "inv_2")])
+        .await;
+
+    assert!(matches!(result, Err(Error::ImmutableColumn("external_id"))));
+}
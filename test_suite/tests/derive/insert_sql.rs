@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use ensemble::query::Dialect;
+use ensemble::{rbs, tenancy, Error, Model};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    bio: Option<String>,
+    active: bool,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(read_only)]
+struct Report {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(tenant_column = "team_id")]
+struct Project {
+    id: u64,
+    team_id: u64,
+}
+
+fn user(id: u64, name: &str) -> User {
+    User {
+        id,
+        name: name.to_string(),
+        bio: None,
+        active: true,
+    }
+}
+
+#[test]
+fn models_are_rendered_for_each_dialect() {
+    let user = user(1, "O'Brien");
+
+    assert_eq!(
+        user.to_insert_sql(Dialect::Mysql).unwrap(),
+        "INSERT INTO `users` (`id`, `name`, `bio`, `active`) VALUES (1, 'O''Brien', NULL, TRUE);"
+    );
+    assert_eq!(
+        user.to_insert_sql(Dialect::Postgres).unwrap(),
+        "INSERT INTO \"users\" (\"id\", \"name\", \"bio\", \"active\") VALUES (1, 'O''Brien', NULL, TRUE);"
+    );
+}
+
+#[test]
+fn unset_keys_are_left_for_the_database() {
+    assert_eq!(
+        user(0, "Taylor").to_insert_sql(Dialect::Sqlite).unwrap(),
+        "INSERT INTO \"users\" (\"name\", \"bio\", \"active\") VALUES ('Taylor', NULL, TRUE);"
+    );
+
+    assert_eq!(
+        User::to_insert_sql_many(&[user(0, "Taylor"), user(2, "Abigail")], Dialect::Postgres)
+            .unwrap(),
+        "INSERT INTO \"users\" (\"name\", \"bio\", \"active\", \"id\") VALUES ('Taylor', NULL, TRUE, DEFAULT), ('Abigail', NULL, TRUE, 2);"
+    );
+    assert_eq!(User::to_insert_sql_many(&[], Dialect::Mysql).unwrap(), "");
+}
+
+#[test]
+fn statements_can_be_rendered_with_bindings() {
+    let (sql, bindings) = user(1, "Taylor")
+        .to_insert_statement(Dialect::Postgres)
+        .unwrap();
+
+    assert_eq!(
+        sql,
+        "INSERT INTO \"users\" (\"id\", \"name\", \"bio\", \"active\") VALUES ($1, $2, $3, $4)"
+    );
+    assert_eq!(
+        bindings,
+        vec![
+            rbs::to_value!(1i64),
+            rbs::to_value!("Taylor"),
+            rbs::Value::Null,
+            rbs::to_value!(true)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn inserts_are_checked_like_create() {
+    let error = Report { id: 1 }.to_insert_sql(Dialect::Mysql).unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("reports")));
+
+    let project = Project { id: 1, team_id: 0 };
+    assert!(matches!(
+        project.to_insert_sql(Dialect::Mysql).unwrap_err(),
+        Error::NoTenant("projects")
+    ));
+
+    let sql = tenancy::with_tenant(42_u64, async { project.to_insert_sql(Dialect::Mysql) })
+        .await
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO `projects` (`id`, `team_id`) VALUES (1, 42);"
+    );
+}
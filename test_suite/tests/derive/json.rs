@@ -1,9 +1,12 @@
 #![allow(dead_code)]
 
 use ensemble::rbs::{self, to_value, value_map};
+use ensemble::relationships::{HasMany, Relationship};
 use ensemble::Model;
 use serde_json::json;
 
+use super::support::row;
+
 #[test]
 fn properly_serializes_model_to_json() {
     #[derive(Debug, Model)]
@@ -92,3 +95,154 @@ fn hidden_fields_hidden_when_serializing_to_json() {
 
     assert_eq!(serde_json::to_value(model).unwrap(), json!({ "id": 123 }));
 }
+
+#[test]
+fn visibility_can_be_overridden_for_a_single_serialization() {
+    #[derive(Debug, Clone, Model)]
+    struct MyModel {
+        id: u8,
+        name: String,
+        password: String,
+    }
+
+    let model = MyModel {
+        id: 123,
+        name: "JSON Test".to_string(),
+        password: "secret".to_string(),
+    };
+
+    assert_eq!(
+        model.make_visible(&["password"]).json(),
+        json!({ "id": 123, "name": "JSON Test", "password": "secret" })
+    );
+    assert_eq!(
+        model.make_hidden(&["name"]).make_visible(&["password"]).json(),
+        json!({ "id": 123, "password": "secret" })
+    );
+    assert_eq!(
+        model.make_visible(&["name"]).make_hidden(&["name"]).json(),
+        json!({ "id": 123 })
+    );
+
+    // overrides belong to the wrapper, so neither the model nor its clones keep them
+    assert_eq!(model.json(), json!({ "id": 123, "name": "JSON Test" }));
+    assert_eq!(model.clone().json(), json!({ "id": 123, "name": "JSON Test" }));
+}
+
+#[test]
+fn visibility_overrides_do_not_affect_the_database() {
+    #[derive(Debug, Model)]
+    struct MyModel {
+        id: u8,
+        name: String,
+    }
+
+    let model = MyModel {
+        id: 123,
+        name: "JSON Test".to_string(),
+    };
+
+    assert_eq!(
+        to_value!(model.make_hidden(&["name"])),
+        to_value!(&model)
+    );
+}
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    password: String,
+    posts: HasMany<User, Post>,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    #[model(hide)]
+    notes: String,
+    user_id: u64,
+    comments: HasMany<Post, Comment>,
+}
+
+#[derive(Debug, Model)]
+struct Comment {
+    id: u64,
+    body: String,
+    post_id: u64,
+}
+
+async fn user_with_posts() -> User {
+    let mut user = User::hydrate(row(&[
+        ("id", to_value!(1u64)),
+        ("name", to_value!("Taylor")),
+        ("password", to_value!("secret")),
+    ]))
+    .unwrap();
+
+    user.posts
+        .r#match(&[row(&[
+            ("id", to_value!(1u64)),
+            ("title", to_value!("Hello")),
+            ("notes", to_value!("draft")),
+            ("user_id", to_value!(1u64)),
+        ])])
+        .unwrap();
+
+    let post = &mut user.posts.get().await.unwrap()[0];
+    post.comments
+        .r#match(&[row(&[
+            ("id", to_value!(1u64)),
+            ("body", to_value!("First!")),
+            ("post_id", to_value!(1u64)),
+        ])])
+        .unwrap();
+
+    user
+}
+
+#[test]
+fn unloaded_relationships_are_left_out() {
+    let user = User {
+        id: 1,
+        name: "Taylor".to_string(),
+        ..User::default()
+    };
+
+    assert_eq!(user.json(), json!({ "id": 1, "name": "Taylor" }));
+}
+
+#[tokio::test]
+async fn loaded_relationships_are_nested_under_their_name() {
+    let user = user_with_posts().await;
+
+    assert_eq!(
+        user.json(),
+        json!({
+            "id": 1,
+            "name": "Taylor",
+            "posts": [{
+                "id": 1,
+                "title": "Hello",
+                "user_id": 1,
+                "comments": [{ "id": 1, "body": "First!", "post_id": 1 }]
+            }]
+        })
+    );
+}
+
+#[tokio::test]
+async fn relationships_can_be_left_out_of_a_single_serialization() {
+    let user = user_with_posts().await;
+
+    assert_eq!(
+        user.json_without_relations(),
+        json!({ "id": 1, "name": "Taylor" })
+    );
+    assert_eq!(
+        user.make_visible(&["password"]).without_relations().json(),
+        json!({ "id": 1, "name": "Taylor", "password": "secret" })
+    );
+    assert!(user.json()["posts"].is_array());
+}
@@ -0,0 +1,135 @@
+#![allow(dead_code)]
+
+use ensemble::query::{Dialect, Type};
+use ensemble::rbs::{self, to_value};
+use ensemble::types::Json;
+use ensemble::{testing, Error, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    theme: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Model)]
+struct Account {
+    id: u64,
+    #[model(default)]
+    settings: Json<Settings>,
+}
+
+fn settings() -> Settings {
+    Settings {
+        theme: "dark".to_string(),
+        tags: vec!["rust".to_string()],
+    }
+}
+
+#[tokio::test]
+async fn typed_json_columns_are_written_as_json() {
+    let fake = testing::fake_connection().affecting(1);
+
+    fake.run(Account::insert_many(vec![Account {
+        settings: Json(settings()),
+        ..Account::default()
+    }]))
+    .await
+    .unwrap();
+
+    assert_eq!(
+        fake.statements()[0].bindings,
+        [rbs::Value::Ext(
+            "Json",
+            Box::new(rbs::Value::String(
+                r#"{"theme":"dark","tags":["rust"]}"#.to_string()
+            ))
+        )]
+    );
+}
+
+#[tokio::test]
+async fn typed_json_columns_are_read_from_json() {
+    let fake = testing::fake_connection().returning(vec![rbs::Value::Map(rbs::value_map! {
+        "id": 1u64,
+        "settings": r#"{"theme":"dark","tags":["rust"]}"#,
+    })]);
+
+    let account = fake.run(Account::find(1)).await.unwrap();
+    assert_eq!(*account.settings, settings());
+}
+
+#[test]
+fn values_inside_json_columns_can_be_compared() {
+    let query = Account::query().where_json("settings->theme", "=", "dark");
+
+    let expected = match Dialect::default() {
+        Dialect::Mysql => {
+            r#"SELECT * FROM accounts WHERE JSON_UNQUOTE(JSON_EXTRACT(settings, '$."theme"')) = ?"#
+        }
+        Dialect::Postgres => "SELECT * FROM accounts WHERE settings->>'theme' = ?",
+        Dialect::Sqlite => {
+            r#"SELECT * FROM accounts WHERE json_extract(settings, '$."theme"') = ?"#
+        }
+    };
+    assert_eq!(query.to_sql(Type::Select), expected);
+    assert_eq!(query.get_bindings(), [to_value!("dark")]);
+}
+
+#[test]
+fn json_arrays_can_be_searched() {
+    let query = Account::query().where_json_contains("settings->tags", "rust");
+
+    let expected = match Dialect::default() {
+        Dialect::Mysql => r#"SELECT * FROM accounts WHERE JSON_CONTAINS(settings, ?, '$."tags"')"#,
+        Dialect::Postgres => "SELECT * FROM accounts WHERE (settings->'tags')::jsonb @> ?::jsonb",
+        Dialect::Sqlite => {
+            r#"SELECT * FROM accounts WHERE EXISTS (SELECT 1 FROM json_each(settings, '$."tags"') WHERE json_each.value = ?)"#
+        }
+    };
+    assert_eq!(query.to_sql(Type::Select), expected);
+    // SQLite compares the array's elements one by one, so the value isn't encoded as JSON there
+    let binding = if Dialect::default() == Dialect::Sqlite {
+        "rust"
+    } else {
+        r#""rust""#
+    };
+    assert_eq!(query.get_bindings(), [to_value!(binding)]);
+}
+
+#[tokio::test]
+async fn invalid_json_paths_fail_when_the_query_runs() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(
+            Account::query()
+                .where_json("settings->theme') OR ('1", "=", "dark")
+                .get::<Account>(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidQuery));
+
+    let error = fake
+        .run(
+            Account::query()
+                .where_group(|query| query.where_json_contains("settings->", "rust"))
+                .count(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidQuery));
+
+    let error = fake
+        .run(
+            Account::query()
+                .where_json("settings->->theme", "=", "dark")
+                .delete(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidQuery));
+
+    assert!(fake.statements().is_empty());
+}
@@ -0,0 +1,74 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::{BelongsTo, HasMany, Relationship};
+use ensemble::Model;
+use serde_json::{json, Value};
+
+#[derive(Debug, Model)]
+#[ensemble(json_schema)]
+struct User {
+    id: u64,
+    name: String,
+    #[model(column = "mail")]
+    email: String,
+    password: String,
+    bio: Option<String>,
+    posts: HasMany<User, Post>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(json_schema)]
+struct Post {
+    id: u64,
+    title: String,
+    author: BelongsTo<Post, User>,
+}
+
+fn keys(value: &Value) -> Vec<String> {
+    let mut keys = value.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn schema_matches_the_serialized_model() {
+    let user = User {
+        id: 1,
+        name: "Taylor".to_string(),
+        email: "taylor@example.com".to_string(),
+        password: "hunter2".to_string(),
+        ..User::default()
+    };
+
+    let schema = User::json_schema();
+
+    let mut required = serde_json::from_value::<Vec<String>>(schema["required"].clone()).unwrap();
+    required.sort();
+
+    assert_eq!(required, keys(&user.json()));
+    assert_eq!(schema["additionalProperties"], json!(false));
+    assert_eq!(schema["properties"]["id"]["type"], json!("integer"));
+    assert_eq!(schema["properties"]["mail"]["type"], json!("string"));
+    assert_eq!(schema["properties"]["bio"]["type"], json!(["string", "null"]));
+}
+
+#[test]
+fn hidden_fields_are_not_described() {
+    let schema = User::json_schema();
+
+    assert!(schema["properties"].get("password").is_none());
+    assert!(schema["properties"].get("email").is_none());
+}
+
+#[test]
+fn relationships_are_optional() {
+    let schema = User::json_schema();
+
+    assert!(schema["properties"].get("posts").is_some());
+    assert!(!schema["required"].as_array().unwrap().contains(&json!("posts")));
+
+    // once loaded, relationships are serialized along with the model
+    let mut user = User::default();
+    user.posts.r#match(&[]).unwrap();
+    assert_eq!(keys(&schema["properties"]), keys(&user.json()));
+}
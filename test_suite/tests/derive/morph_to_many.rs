@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use ensemble::query::Type;
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::{MorphToMany, MorphedByMany, Relationship};
+use ensemble::{Error, FromRow, Model};
+
+#[derive(Debug, Clone, Model)]
+struct Tag {
+    id: u64,
+    #[model(morph_name = "taggable")]
+    posts: MorphedByMany<Tag, Post>,
+    #[model(morph_name = "taggable")]
+    videos: MorphedByMany<Tag, Video>,
+}
+
+#[derive(Debug, Clone, Model)]
+struct Post {
+    id: u64,
+    #[model(morph_name = "taggable")]
+    tags: MorphToMany<Post, Tag>,
+}
+
+#[derive(Debug, Clone, Model)]
+struct Video {
+    id: u64,
+    #[model(morph_name = "taggable", pivot_table = "video_labels", foreign_key = "label_id")]
+    tags: MorphToMany<Video, Tag>,
+}
+
+#[derive(Debug, FromRow)]
+#[allow(dead_code)]
+struct Taggable {
+    tag_id: u64,
+    taggable_id: u64,
+    taggable_type: String,
+}
+
+fn post() -> Post {
+    rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap()
+}
+
+fn tag() -> Tag {
+    rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap()
+}
+
+#[test]
+fn morph_to_many_queries_are_scoped_to_the_parent_type() {
+    let query = post().tags_query();
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT * FROM tags INNER JOIN taggables ON tags.id = taggables.tag_id WHERE taggables.taggable_id = ? AND taggables.taggable_type = ?"
+    );
+    assert_eq!(query.get_bindings(), vec![rbs::to_value!(1u64), rbs::to_value!("Post")]);
+}
+
+#[test]
+fn morphed_by_many_queries_are_scoped_to_the_related_type() {
+    let tag = tag();
+
+    assert_eq!(
+        tag.posts_query().to_sql(Type::Select),
+        "SELECT * FROM posts INNER JOIN taggables ON posts.id = taggables.taggable_id WHERE taggables.tag_id = ? AND taggables.taggable_type = ?"
+    );
+    assert_eq!(tag.posts_query().get_bindings()[1], rbs::to_value!("Post"));
+    assert_eq!(tag.videos_query().get_bindings()[1], rbs::to_value!("Video"));
+}
+
+#[test]
+fn the_pivot_table_and_keys_can_be_overridden() {
+    let video: Video = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    assert_eq!(
+        video.tags_query().to_sql(Type::Select),
+        "SELECT * FROM tags INNER JOIN video_labels ON tags.id = video_labels.label_id WHERE video_labels.taggable_id = ? AND video_labels.taggable_type = ?"
+    );
+}
+
+#[tokio::test]
+async fn eager_loading_matches_rows_to_their_parent() {
+    let mut post = post();
+
+    assert_eq!(
+        post.tags.eager_query(vec![1, 2]).to_sql(Type::Select),
+        "SELECT * FROM tags INNER JOIN taggables ON tags.id = taggables.tag_id WHERE taggables.taggable_id IN (?, ?) AND taggables.taggable_type = ?"
+    );
+
+    let row = |tag: u64, post: u64| {
+        HashMap::from([
+            ("id".to_string(), rbs::to_value!(tag)),
+            ("tag_id".to_string(), rbs::to_value!(tag)),
+            ("taggable_id".to_string(), rbs::to_value!(post)),
+            ("taggable_type".to_string(), rbs::to_value!("Post")),
+        ])
+    };
+
+    post.tags.r#match(&[row(3, 1), row(4, 2), row(5, 1)]).unwrap();
+
+    assert!(post.tags.is_loaded());
+    let tags = post.tags().await.unwrap().iter().map(|tag| tag.id).collect::<Vec<_>>();
+    assert_eq!(tags, vec![3, 5]);
+}
+
+#[tokio::test]
+async fn changing_attachments_needs_a_saved_parent() {
+    let mut post = Post::default();
+
+    let error = post.tags.attach(1).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+
+    let error = post.tags.detach(vec![1]).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+
+    let error = post.tags.sync(vec![1, 2]).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+}
+
+#[tokio::test]
+async fn changing_attachments_queries_the_pivot_table() {
+    let mut post = post();
+
+    let error = post.tags.sync(vec![1, 2]).await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    let error = post.tags.pivots::<Taggable>().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    // no query is needed when there are no keys to detach
+    assert_eq!(post.tags.detach(vec![]).await.unwrap(), 0);
+}
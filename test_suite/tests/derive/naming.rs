@@ -0,0 +1,31 @@
+use ensemble::naming::{DefaultNamingStrategy, NamingStrategy};
+
+struct Plural;
+
+impl NamingStrategy for Plural {
+    fn foreign_key(&self, model: &str, _primary_key: &str) -> String {
+        format!("fk_{}", model.to_lowercase())
+    }
+
+    fn pivot_table(&self, first: &str, second: &str) -> String {
+        format!("{}s_{}s", first.to_lowercase(), second.to_lowercase())
+    }
+}
+
+#[test]
+fn default_strategy_follows_laravel_conventions() {
+    assert_eq!(DefaultNamingStrategy.foreign_key("BlogPost", "id"), "blog_post_id");
+    assert_eq!(DefaultNamingStrategy.pivot_table("User", "Role"), "role_user");
+    assert_eq!(
+        DefaultNamingStrategy.morph_columns("commentable"),
+        ("commentable_type".to_string(), "commentable_id".to_string())
+    );
+    assert_eq!(DefaultNamingStrategy.morph_pivot_table("taggable"), "taggables");
+}
+
+#[test]
+fn strategies_only_override_what_they_implement() {
+    assert_eq!(Plural.foreign_key("User", "id"), "fk_user");
+    assert_eq!(Plural.pivot_table("User", "Role"), "users_roles");
+    assert_eq!(Plural.morph_columns("taggable").0, "taggable_type");
+}
@@ -0,0 +1,97 @@
+use ensemble::{testing, transaction, Error, Model};
+use serde_json::json;
+
+use super::support::{capture_logs, rows};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+fn operations(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("span ensemble.operation "))
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn model_operations_are_recorded_as_spans() {
+    let fake = testing::fake_connection()
+        .returning(rows(json!([{ "id": 1, "name": "Taylor" }])))
+        .returning(rows(json!([{ "id": 1, "name": "Taylor" }])))
+        .affecting(1)
+        .affecting(1);
+
+    let (result, logs) = capture_logs(fake.run(async {
+        let mut user = User::query()
+            .r#where("name", "=", "Taylor")
+            .first::<User>()
+            .await?
+            .unwrap();
+        assert_eq!(User::query().get::<User>().await?.len(), 1);
+
+        user.name = "Otwell".to_string();
+        user.save().await?;
+        user.delete().await
+    }))
+    .await;
+    result.unwrap();
+
+    assert_eq!(
+        operations(&logs.lines()),
+        [
+            "otel.name=first users ensemble.operation=first db.table=users",
+            "otel.name=get users ensemble.operation=get db.table=users",
+            "otel.name=save users ensemble.operation=save db.table=users",
+            "otel.name=delete users ensemble.operation=delete db.table=users",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn creating_a_model_is_recorded_as_a_span() {
+    let fake = testing::fake_connection().affecting(1);
+    let user = User {
+        id: 0,
+        name: "Taylor".to_string(),
+    };
+
+    // the fake connection has no insert id to return, but the span is recorded before it's read
+    let (_, logs) = capture_logs(fake.run(user.create())).await;
+
+    assert_eq!(
+        operations(&logs.lines()),
+        ["otel.name=create users ensemble.operation=create db.table=users"]
+    );
+}
+
+#[tokio::test]
+async fn transactions_record_how_they_ended() {
+    // BEGIN is the first statement to modify rows
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    let (result, logs) = capture_logs(fake.run(transaction(|| async {
+        User::query().r#where("name", "=", "Taylor").delete().await
+    })))
+    .await;
+    assert_eq!(result.unwrap(), 1);
+
+    let fake = testing::fake_connection().affecting(0);
+    let (result, rolled_back) = capture_logs(fake.run(transaction(|| async {
+        Err::<(), _>(Error::NotFound)
+    })))
+    .await;
+    assert!(matches!(result, Err(Error::NotFound)));
+
+    assert_eq!(
+        operations(&logs.lines()),
+        ["otel.name=commit transaction ensemble.operation=commit db.table=transaction"]
+    );
+    assert_eq!(
+        operations(&rolled_back.lines()),
+        ["otel.name=rollback transaction ensemble.operation=rollback db.table=transaction"]
+    );
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use ensemble::{
+    outbox::{self, DrainError, EventType},
+    query::Dialect,
+    rbs, testing, Error, Model,
+};
+
+#[derive(Debug, Model)]
+#[ensemble(outbox)]
+struct Order {
+    id: u64,
+    total: u64,
+}
+
+#[derive(Debug, Model)]
+struct Cart {
+    id: u64,
+}
+
+#[test]
+fn models_opt_into_the_outbox() {
+    const { assert!(Order::OUTBOX) };
+    const { assert!(!Cart::OUTBOX) };
+}
+
+#[test]
+fn event_types_are_stored_in_lowercase() {
+    assert_eq!(EventType::Created.to_string(), "created");
+    assert_eq!(
+        serde_json::from_str::<EventType>("\"deleted\"").unwrap(),
+        EventType::Deleted
+    );
+}
+
+#[tokio::test]
+async fn outbox_writes_need_a_connection() {
+    let error = Order { id: 0, total: 100 }.create().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    let error = outbox::drain(10, |_| async { Ok::<_, std::io::Error>(()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DrainError::Database(Error::Connection(_))));
+}
+
+#[tokio::test]
+async fn draining_locks_a_batch_and_deletes_it_once_handled() {
+    let event = rbs::to_value(serde_json::json!({
+        "id": 7,
+        "model": "Order",
+        "model_key": "1",
+        "event": "created",
+        "payload": "{}",
+        "created_at": "2024-01-01 00:00:00",
+    }))
+    .unwrap();
+    let fake = testing::fake_connection().returning(vec![event]);
+
+    let handled = fake
+        .run(outbox::drain(10, |events| async move {
+            assert_eq!(events[0].id, 7);
+            Ok::<_, std::io::Error>(())
+        }))
+        .await
+        .unwrap();
+    assert_eq!(handled, 1);
+
+    // SQLite has no row locks, since the transaction locks the whole database once it deletes the batch
+    let select = if Dialect::default() == Dialect::Sqlite {
+        "SELECT * FROM outbox ORDER BY id LIMIT 10"
+    } else {
+        "SELECT * FROM outbox ORDER BY id LIMIT 10 FOR UPDATE SKIP LOCKED"
+    };
+    assert_eq!(
+        fake.statements()
+            .into_iter()
+            .map(|statement| statement.sql)
+            .collect::<Vec<_>>(),
+        [
+            "BEGIN",
+            select,
+            "DELETE FROM outbox WHERE id IN (?)",
+            "COMMIT",
+        ]
+    );
+}
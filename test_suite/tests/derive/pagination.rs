@@ -0,0 +1,100 @@
+use ensemble::{rbs, testing, Error, Model};
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+}
+
+#[tokio::test]
+async fn pages_are_counted_then_retrieved() {
+    let fake = testing::fake_connection()
+        .returning(rows(serde_json::json!([{ "COUNT(*)": 5 }])))
+        .returning(rows(serde_json::json!([
+            { "id": 3, "title": "Third" },
+            { "id": 4, "title": "Fourth" },
+        ])));
+
+    let page = fake
+        .run(
+            Post::query()
+                .r#where("title", "!=", "Draft")
+                .order_by("id", "asc")
+                .paginate::<Post>(2, 2),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(page.total, 5);
+    assert_eq!(page.current_page, 2);
+    assert_eq!(page.last_page, 3);
+    assert_eq!(page.data.len(), 2);
+
+    let statements = fake
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statements,
+        vec![
+            "SELECT COUNT(*) FROM posts WHERE title <> ?",
+            "SELECT * FROM posts WHERE title <> ? ORDER BY id ASC LIMIT 2 OFFSET 2",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn pages_past_the_end_are_empty() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([{ "COUNT(*)": 1 }])));
+
+    let page = fake
+        .run(Post::query().paginate::<Post>(3, 10))
+        .await
+        .unwrap();
+
+    assert!(page.data.is_empty());
+    assert_eq!(page.last_page, 1);
+    assert_eq!(fake.statements().len(), 1);
+}
+
+#[tokio::test]
+async fn cursor_pages_continue_after_the_last_key() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "id": 11, "title": "Eleventh" },
+        { "id": 12, "title": "Twelfth" },
+        { "id": 13, "title": "Thirteenth" },
+    ])));
+
+    let page = fake
+        .run(Post::query().cursor_paginate::<Post>(2, Some(10)))
+        .await
+        .unwrap();
+
+    assert_eq!(page.data.len(), 2);
+    assert_eq!(page.next_cursor, Some(12));
+    assert_eq!(
+        serde_json::to_value(&page).unwrap()["next_cursor"],
+        serde_json::json!(12)
+    );
+
+    let statement = fake.statements().remove(0);
+    assert_eq!(
+        statement.sql,
+        "SELECT * FROM posts WHERE id > ? ORDER BY id ASC LIMIT 3"
+    );
+    assert_eq!(statement.bindings, vec![rbs::Value::from(10_u64)]);
+}
+
+#[tokio::test]
+async fn cursor_pages_are_always_ordered_by_primary_key() {
+    let error = Post::query()
+        .order_by("title", "asc")
+        .cursor_paginate::<Post>(10, None)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::InvalidQuery));
+}
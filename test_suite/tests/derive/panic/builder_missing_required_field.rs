@@ -0,0 +1,13 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(builder)]
+struct User {
+    id: u64,
+    name: String,
+    email: String,
+}
+
+fn main() {
+    let _ = User::builder().name("Jane".to_string()).build();
+}
@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Token {
+    id: u64,
+    #[model(default = "1.5")]
+    uses: u64,
+}
+
+fn main() {}
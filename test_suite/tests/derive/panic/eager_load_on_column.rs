@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    #[model(eager)]
+    name: String,
+}
+
+fn main() {}
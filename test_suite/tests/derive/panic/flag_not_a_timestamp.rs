@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    #[model(flag = "publish")]
+    published_at: bool,
+}
+
+fn main() {}
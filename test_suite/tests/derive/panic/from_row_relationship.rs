@@ -0,0 +1,20 @@
+use ensemble::relationships::HasMany;
+use ensemble::{FromRow, Model};
+
+#[derive(Debug, Model)]
+struct Customer {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Order {
+    id: u64,
+}
+
+#[derive(FromRow)]
+struct CustomerTotal {
+    customer_id: u64,
+    orders: HasMany<Customer, Order>,
+}
+
+fn main() {}
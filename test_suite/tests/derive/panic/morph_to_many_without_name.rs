@@ -0,0 +1,15 @@
+use ensemble::relationships::MorphToMany;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Tag {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    tags: MorphToMany<Post, Tag>,
+}
+
+fn main() {}
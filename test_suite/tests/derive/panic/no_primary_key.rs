@@ -1,6 +1,6 @@
 use ensemble::Model;
 
-#[derive(Model)]
+#[derive(Debug, Model)]
 struct MyModel {
     name: String,
 }
@@ -0,0 +1,17 @@
+use ensemble::relationships::HasMany;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Comment {
+    id: u64,
+    post_id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    #[model(pivot_timestamps)]
+    comments: HasMany<Post, Comment>,
+}
+
+fn main() {}
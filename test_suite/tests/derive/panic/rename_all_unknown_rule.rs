@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(rename_all = "kebab-case")]
+struct Customer {
+    id: u64,
+    first_name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(soft_deletes)]
+struct Comment {
+    id: u64,
+    body: String,
+}
+
+fn main() {}
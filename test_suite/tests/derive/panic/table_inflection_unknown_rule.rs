@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(inflection = "german")]
+struct Customer {
+    id: u64,
+    first_name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(upsert)]
+struct Product {
+    id: u64,
+    sku: String,
+}
+
+fn main() {}
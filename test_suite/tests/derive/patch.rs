@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use ensemble::{types::DateTime, Model};
+
+#[derive(Debug, Model)]
+#[ensemble(patch)]
+struct User {
+    id: u64,
+    name: String,
+    #[model(column = "email_address")]
+    email: String,
+    bio: Option<String>,
+    #[model(immutable)]
+    username: String,
+    created_at: DateTime,
+    updated_at: DateTime,
+}
+
+fn user() -> User {
+    User {
+        id: 1,
+        name: "Taylor".to_string(),
+        email: "taylor@example.com".to_string(),
+        bio: Some("Creator of Laravel".to_string()),
+        username: "taylorotwell".to_string(),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    }
+}
+
+#[test]
+fn patches_can_be_deserialized() {
+    let patch: UserPatch =
+        serde_json::from_str(r#"{ "name": "Abbey", "email_address": "abbey@example.com" }"#).unwrap();
+
+    assert_eq!(patch.name.as_deref(), Some("Abbey"));
+    assert_eq!(patch.email.as_deref(), Some("abbey@example.com"));
+    assert_eq!(patch.bio, None);
+    assert!(!patch.is_empty());
+}
+
+#[test]
+fn null_is_told_apart_from_missing_fields() {
+    let missing: UserPatch = serde_json::from_str("{}").unwrap();
+    assert_eq!(missing.bio, None);
+    assert!(missing.is_empty());
+
+    let null: UserPatch = serde_json::from_str(r#"{ "bio": null }"#).unwrap();
+    assert_eq!(null.bio, Some(None));
+    assert_eq!(null.columns().unwrap(), vec![("bio", ensemble::rbs::Value::Null)]);
+}
+
+#[test]
+fn the_primary_key_cannot_be_patched() {
+    let error = serde_json::from_str::<UserPatch>(r#"{ "id": 2, "name": "Abbey" }"#).unwrap_err();
+    assert!(error.to_string().contains("unknown field `id`"));
+
+    // neither can the columns the model maintains itself
+    for column in ["username", "created_at", "updated_at"] {
+        assert!(serde_json::from_str::<UserPatch>(&format!(r#"{{ "{column}": null }}"#)).is_err());
+    }
+}
+
+#[test]
+fn patches_only_change_the_fields_they_include() {
+    let mut user = user();
+
+    user.apply(UserPatch {
+        name: Some("Abbey".to_string()),
+        bio: Some(None),
+        ..Default::default()
+    });
+
+    assert_eq!(user.id, 1);
+    assert_eq!(user.name, "Abbey");
+    assert_eq!(user.email, "taylor@example.com");
+    assert_eq!(user.bio, None);
+}
+
+#[tokio::test]
+async fn empty_patches_are_a_no_op() {
+    // no connection is set up, so this would fail if it ran a query
+    assert_eq!(User::update_by_id(1, UserPatch::default()).await.unwrap(), 0);
+    assert!(User::update_by_id(1, UserPatch {
+        name: Some("Abbey".to_string()),
+        ..Default::default()
+    })
+    .await
+    .is_err());
+}
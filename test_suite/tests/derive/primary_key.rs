@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use ensemble::Model;
+use ensemble::rbs::{self, value_map};
+use ensemble::types::Ulid;
+use ensemble::{testing, Model};
 
 #[test]
 fn returns_labeled_primary_key() {
@@ -24,3 +26,53 @@ fn marks_id_as_primary_key_if_found() {
 
     assert_eq!(MyModel::PRIMARY_KEY, "id");
 }
+
+#[derive(Debug, Model)]
+struct Order {
+    #[model(ulid)]
+    id: Ulid,
+    total: u64,
+}
+
+#[tokio::test]
+async fn ulid_keys_are_generated_before_the_insert() {
+    let fake = testing::fake_connection().affecting(1);
+
+    let order = fake
+        .run(
+            Order {
+                id: Ulid::nil(),
+                total: 10,
+            }
+            .create(),
+        )
+        .await
+        .unwrap();
+    assert_ne!(order.id, Ulid::nil());
+
+    let statements = fake.statements();
+    assert_eq!(
+        statements[0].sql,
+        "INSERT INTO orders (id, total) VALUES (?, ?)"
+    );
+    assert_eq!(
+        statements[0].bindings[0],
+        rbs::Value::String(order.id.to_string())
+    );
+}
+
+#[tokio::test]
+async fn models_are_found_by_their_ulid() {
+    let id = Ulid::new();
+    let fake = testing::fake_connection().returning(vec![rbs::Value::Map(value_map! {
+        "id": id.to_string(),
+        "total": 10u64,
+    })]);
+
+    let order = fake.run(Order::find(id)).await.unwrap();
+    assert_eq!(order.id, id);
+    assert_eq!(
+        fake.statements()[0].bindings,
+        [rbs::Value::String(id.to_string())]
+    );
+}
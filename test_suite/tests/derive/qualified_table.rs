@@ -0,0 +1,134 @@
+#![allow(dead_code)]
+
+use ensemble::query::{Dialect, Type};
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::{BelongsTo, BelongsToMany, HasMany};
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(schema = "auth")]
+struct User {
+    id: u64,
+    name: String,
+    invoices: HasMany<User, Invoice>,
+    roles: BelongsToMany<User, Role>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(schema = "billing")]
+struct Invoice {
+    id: u64,
+    total: u64,
+    user: BelongsTo<Invoice, User>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(schema = "auth", table = "access_roles")]
+struct Role {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(schema = "Accounting")]
+struct Payment {
+    id: u64,
+    amount: u64,
+}
+
+#[derive(Debug, Model)]
+struct Country {
+    id: u64,
+}
+
+fn user() -> User {
+    rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, "name": "Taylor", })).unwrap()
+}
+
+/// The schema, quoted for the database the tests are built for.
+fn schema(name: &str) -> String {
+    Dialect::default().quote_identifier(name)
+}
+
+#[test]
+fn tables_are_qualified_with_their_schema() {
+    assert_eq!(Invoice::TABLE_NAME, "invoices");
+    assert_eq!(Invoice::SCHEMA, Some("billing"));
+    assert_eq!(Invoice::QUALIFIED_TABLE, format!("{}.invoices", schema("billing")));
+    assert_eq!(Role::QUALIFIED_TABLE, format!("{}.access_roles", schema("auth")));
+
+    assert_eq!(Country::SCHEMA, None);
+    assert_eq!(Country::QUALIFIED_TABLE, "countries");
+}
+
+#[test]
+fn schemas_keep_their_case() {
+    assert_eq!(Payment::SCHEMA, Some("Accounting"));
+    assert_eq!(
+        Payment::QUALIFIED_TABLE,
+        match Dialect::default() {
+            Dialect::Mysql => "`Accounting`.payments",
+            Dialect::Postgres | Dialect::Sqlite => "\"Accounting\".payments",
+        }
+    );
+    assert_eq!(
+        Payment::query().r#where("amount", ">", 100).to_sql(Type::Select),
+        format!("SELECT * FROM {}.payments WHERE amount > ?", schema("Accounting"))
+    );
+
+    let payment = Payment { id: 1, amount: 100 };
+    assert_eq!(
+        payment.to_insert_sql(Dialect::Postgres).unwrap(),
+        "INSERT INTO \"Accounting\".\"payments\" (\"id\", \"amount\") VALUES (1, 100);"
+    );
+    assert_eq!(
+        payment.to_insert_sql(Dialect::Mysql).unwrap(),
+        "INSERT INTO `Accounting`.`payments` (`id`, `amount`) VALUES (1, 100);"
+    );
+}
+
+#[test]
+fn queries_use_the_qualified_table() {
+    assert_eq!(
+        Invoice::query()
+            .r#where("total", ">", 100)
+            .to_sql(Type::Select),
+        format!("SELECT * FROM {}.invoices WHERE total > ?", schema("billing"))
+    );
+
+    let invoice = Invoice {
+        id: 1,
+        total: 100,
+        user: BelongsTo::default(),
+    };
+    assert_eq!(
+        invoice.to_insert_sql(Dialect::Postgres).unwrap(),
+        "INSERT INTO \"billing\".\"invoices\" (\"id\", \"total\", \"user_id\") VALUES (1, 100, NULL);"
+    );
+    assert_eq!(
+        invoice.to_insert_sql(Dialect::Mysql).unwrap(),
+        "INSERT INTO `billing`.`invoices` (`id`, `total`, `user_id`) VALUES (1, 100, NULL);"
+    );
+}
+
+#[test]
+fn relationships_qualify_both_sides() {
+    let (auth, billing) = (schema("auth"), schema("billing"));
+    let user = user();
+    let invoice: Invoice =
+        rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, "total": 100u64, "user_id": 1u64, }))
+            .unwrap();
+
+    assert_eq!(
+        invoice.user_query().to_sql(Type::Select),
+        format!("SELECT * FROM {auth}.users WHERE {auth}.users.id = ? LIMIT 1")
+    );
+
+    assert_eq!(
+        user.invoices_query().to_sql(Type::Select),
+        format!("SELECT * FROM {billing}.invoices WHERE {billing}.invoices.user_id = ? AND {billing}.invoices.user_id IS NOT NULL")
+    );
+    assert_eq!(
+        user.roles_query().to_sql(Type::Select),
+        format!("SELECT * FROM {auth}.access_roles INNER JOIN {auth}.role_user ON {auth}.access_roles.id = {auth}.role_user.role_id WHERE {auth}.role_user.user_id = ?")
+    );
+}
@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::{BelongsTo, HasMany};
+use ensemble::{query, rbs, testing, Error, Model};
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+#[ensemble(global_scope = "Post::published")]
+struct Post {
+    id: u64,
+    title: String,
+    published: bool,
+    author: BelongsTo<Post, Author>,
+}
+
+impl Post {
+    fn published(query: query::Builder) -> query::Builder {
+        query.r#where("published", "=", true)
+    }
+}
+
+#[derive(Debug, Model)]
+struct Author {
+    id: u64,
+    name: String,
+    #[model(eager)]
+    posts: HasMany<Author, Post>,
+}
+
+#[tokio::test]
+async fn raw_queries_hydrate_models() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "id": 1, "title": "Hello", "published": false, "author_id": 1 },
+        { "id": 2, "title": "World", "published": true, "author_id": 1 },
+    ])));
+
+    let posts = fake
+        .run(Post::from_raw(
+            "SELECT * FROM posts WHERE title LIKE ?",
+            vec![rbs::to_value!("%o%")],
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), [1, 2]);
+    assert!(!posts[0].published);
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].sql, "SELECT * FROM posts WHERE title LIKE ?");
+    assert_eq!(statements[0].bindings, [rbs::to_value!("%o%")]);
+}
+
+#[tokio::test]
+async fn raw_queries_eager_load_relationships() {
+    let fake = testing::fake_connection()
+        .returning(rows(serde_json::json!([
+            { "id": 1, "name": "Taylor" },
+        ])))
+        .returning(rows(serde_json::json!([
+            { "id": 1, "title": "Hello", "published": true, "author_id": 1 },
+        ])));
+
+    let mut authors = fake
+        .run(Author::from_raw("SELECT * FROM authors", vec![]))
+        .await
+        .unwrap();
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 2);
+    assert!(statements[1].sql.starts_with("SELECT * FROM posts WHERE"));
+
+    let posts = testing::assert_query_count(0, authors[0].posts())
+        .await
+        .unwrap();
+    assert_eq!(posts.len(), 1);
+}
+
+#[tokio::test]
+async fn raw_queries_return_rows() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "author_id": 1, "posts": 3 },
+    ])));
+
+    let rows = fake
+        .run(query::raw(
+            "SELECT author_id, COUNT(*) AS posts FROM posts GROUP BY author_id",
+            vec![],
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(rows[0].try_get::<u64>("author_id").unwrap(), 1);
+    assert_eq!(rows[0].try_get::<i32>("posts").unwrap(), 3);
+    assert!(rows[0].get("posts").is_some());
+}
+
+#[tokio::test]
+async fn row_getters_report_missing_and_mismatched_columns() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "title": "Hello" },
+    ])));
+
+    let rows = fake
+        .run(query::raw("SELECT title FROM posts", vec![]))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        rows[0].try_get::<String>("body"),
+        Err(Error::UnknownColumn(column)) if column == "body"
+    ));
+    assert!(rows[0].try_get::<u64>("title").is_err());
+    assert_eq!(rows[0].try_get::<String>("title").unwrap(), "Hello");
+}
@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+use ensemble::{testing, Error, Model};
+
+#[test]
+fn models_are_writable_by_default() {
+    #[derive(Debug, Model)]
+    struct User {
+        id: u8,
+    }
+
+    const { assert!(!User::READ_ONLY) };
+}
+
+#[derive(Debug, Model)]
+#[ensemble(table = "report_rows", read_only)]
+struct ReportRow {
+    id: u8,
+    total: u64,
+}
+
+fn report_row() -> ReportRow {
+    ReportRow { id: 1, total: 42 }
+}
+
+#[test]
+fn models_can_be_marked_as_read_only() {
+    const { assert!(ReportRow::READ_ONLY) };
+}
+
+#[tokio::test]
+async fn read_only_models_cannot_be_written() {
+    let fake = testing::fake_connection();
+
+    let error = fake.run(report_row().create()).await.unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("report_rows")));
+
+    let error = fake.run(report_row().save()).await.unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("report_rows")));
+
+    let error = fake.run(report_row().delete()).await.unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("report_rows")));
+
+    assert!(fake.statements().is_empty());
+}
+
+#[tokio::test]
+async fn read_only_models_cannot_be_written_through_the_query_builder() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(
+            ReportRow::query()
+                .r#where("id", "=", 1)
+                .update(vec![("total", 0)]),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("report_rows")));
+
+    let error = fake
+        .run(ReportRow::query().r#where("id", "=", 1).delete())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("report_rows")));
+
+    assert!(fake.statements().is_empty());
+}
+
+#[tokio::test]
+async fn read_only_models_can_be_read() {
+    let fake = testing::fake_connection();
+
+    fake.run(
+        ReportRow::query()
+            .r#where("total", ">", 10)
+            .get::<ReportRow>(),
+    )
+    .await
+    .unwrap();
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 1);
+    assert_eq!(
+        statements[0].sql,
+        "SELECT * FROM report_rows WHERE total > ?"
+    );
+}
@@ -0,0 +1,118 @@
+#![allow(dead_code)]
+
+use ensemble::relationships::{
+    self, BelongsTo, BelongsToMany, HasMany, HasOne, MorphToMany, RelationDef, RelationKind,
+};
+use ensemble::{Error, Model};
+
+#[derive(Debug, Model)]
+struct Author {
+    id: u64,
+    posts: HasMany<Author, Post>,
+    #[model(foreign_key = "owner_id")]
+    profile: HasOne<Author, Profile>,
+    roles: BelongsToMany<Author, Role>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(schema = "blog")]
+struct Post {
+    id: u64,
+    #[model(foreign_key = "writer_id")]
+    author: BelongsTo<Post, Author>,
+    #[model(morph_name = "taggable")]
+    tags: MorphToMany<Post, Tag>,
+}
+
+#[derive(Debug, Model)]
+struct Profile {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Role {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Tag {
+    id: u64,
+}
+
+#[test]
+fn relationships_are_described_in_declaration_order() {
+    let names = Author::relations()
+        .iter()
+        .map(|relation| (relation.name, relation.kind))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        [
+            ("posts", RelationKind::HasMany),
+            ("profile", RelationKind::HasOne),
+            ("roles", RelationKind::BelongsToMany),
+        ]
+    );
+    assert!(Profile::relations().is_empty());
+}
+
+#[test]
+fn relationships_describe_their_keys() {
+    assert_eq!(
+        Author::relation("posts"),
+        Some(&RelationDef {
+            name: "posts",
+            kind: RelationKind::HasMany,
+            related: "Post",
+            related_table: Post::QUALIFIED_TABLE,
+            foreign_key: "author_id".to_string(),
+            local_key: "id".to_string(),
+            pivot_table: None,
+            morph_type: None,
+        })
+    );
+    assert_eq!(Author::relation("profile").unwrap().foreign_key, "owner_id");
+
+    let roles = Author::relation("roles").unwrap();
+    assert_eq!(roles.pivot_table.as_deref(), Some("author_role"));
+    assert_eq!((roles.foreign_key.as_str(), roles.local_key.as_str()), ("role_id", "author_id"));
+
+    let author = Post::relation("author").unwrap();
+    assert_eq!(author.kind, RelationKind::BelongsTo);
+    assert_eq!(author.related_table, "authors");
+    assert_eq!((author.foreign_key.as_str(), author.local_key.as_str()), ("writer_id", "id"));
+
+    let tags = Post::relation("tags").unwrap();
+    assert_eq!(tags.kind, RelationKind::MorphToMany);
+    assert_eq!(tags.pivot_table.as_deref(), Some("taggables"));
+    assert_eq!(tags.morph_type, Some(("taggable_type".to_string(), "Post")));
+
+    assert_eq!(Author::relation("comments"), None);
+}
+
+#[test]
+fn models_can_be_registered_by_table() {
+    relationships::register::<Author>();
+    relationships::register::<Post>();
+
+    assert!(relationships::registered_tables().contains(&Post::QUALIFIED_TABLE));
+    assert_eq!(relationships::registered("authors"), Some(Author::relations()));
+    assert_eq!(relationships::registered("posts"), None);
+}
+
+#[tokio::test]
+async fn eager_loading_unknown_relationships_fails() {
+    let error = Author::with("comments").get::<Author>().await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::UnknownRelation { model: "Author", ref relation } if relation == "comments"
+    ));
+
+    let error = Author::query()
+        .with_exists("comments")
+        .get::<Author>()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::UnknownRelation { .. }));
+}
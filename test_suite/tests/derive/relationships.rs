@@ -0,0 +1,181 @@
+#![allow(dead_code)]
+
+use ensemble::query::Type;
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::{BelongsToMany, HasMany, Relationship};
+use ensemble::{testing, Error, Model};
+
+#[derive(Debug, Clone, Model)]
+struct Comment {
+    id: u64,
+    post_id: u64,
+}
+
+#[derive(Debug, Clone, Model)]
+struct Tag {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    comments: HasMany<Post, Comment>,
+    #[model(pivot_timestamps)]
+    tags: BelongsToMany<Post, Tag>,
+}
+
+#[derive(Debug, Clone, Model)]
+struct Role {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    #[model(with_pivot = ["expires_at"])]
+    roles: BelongsToMany<User, Role>,
+}
+
+#[test]
+fn relationships_expose_a_scoped_query() {
+    let post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    let query = post.comments_query().r#where("approved", "=", true);
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT * FROM comments WHERE comments.post_id = ? AND comments.post_id IS NOT NULL AND approved = ?"
+    );
+    assert_eq!(query.get_bindings(), vec![rbs::to_value!(1u64), rbs::to_value!(true)]);
+}
+
+#[test]
+fn belongs_to_many_queries_join_the_pivot_table() {
+    let post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    assert_eq!(
+        post.tags_query().to_sql(Type::Select),
+        "SELECT * FROM tags INNER JOIN post_tag ON tags.id = post_tag.tag_id WHERE post_tag.post_id = ?"
+    );
+}
+
+#[test]
+fn pivot_columns_are_selected_next_to_the_related_model() {
+    let user: User = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    assert_eq!(
+        user.roles_query().to_sql(Type::Select),
+        "SELECT roles.*, role_user.user_id, role_user.expires_at AS pivot_expires_at FROM roles INNER JOIN role_user ON roles.id = role_user.role_id WHERE role_user.user_id = ?"
+    );
+    assert_eq!(
+        user.roles.exists_query(vec![1]).to_sql(Type::Select),
+        "SELECT DISTINCT role_user.user_id FROM roles INNER JOIN role_user ON roles.id = role_user.role_id WHERE role_user.user_id IN (?)"
+    );
+}
+
+#[tokio::test]
+async fn pivot_columns_are_kept_for_each_loaded_model() {
+    let rows = rbs::to_value(serde_json::json!([
+        { "id": 2, "name": "Editor", "user_id": 1, "pivot_expires_at": "2026-01-01" },
+        { "id": 3, "name": "Author", "user_id": 1, "pivot_expires_at": null },
+    ]))
+    .unwrap();
+    let rbs::Value::Array(rows) = rows else { unreachable!() };
+
+    let mut user: User = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+    assert!(user.roles.pivot(&2).is_none());
+
+    let fake = testing::fake_connection().returning(rows);
+    let roles = fake.run(user.roles()).await.unwrap();
+    assert_eq!(roles.iter().map(|role| role.name.as_str()).collect::<Vec<_>>(), ["Editor", "Author"]);
+
+    let pivot = user.roles.pivot(&2).unwrap();
+    assert_eq!(pivot.columns().collect::<Vec<_>>(), ["expires_at"]);
+    assert_eq!(pivot.get("expires_at"), Some(&rbs::to_value!("2026-01-01")));
+    assert_eq!(user.roles.pivot(&3).unwrap().get("expires_at"), Some(&rbs::Value::Null));
+    assert!(user.roles.pivot(&4).is_none());
+}
+
+#[tokio::test]
+async fn creating_through_an_unsaved_parent_is_an_error() {
+    let mut post = Post::default();
+
+    let error = post.comments.create(Comment::default()).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+
+    let error = post.tags.create_many(vec![Tag::default()]).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+
+    let error = post.tags.attach(1).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+
+    let error = post.tags.sync_without_detaching(vec![1, 2]).await.unwrap_err();
+    assert!(matches!(error, Error::ParentUnpersisted("Post")));
+}
+
+#[test]
+fn existence_is_unknown_until_checked() {
+    let mut post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+    assert_eq!(post.comments_exists(), None);
+
+    post.comments.match_exists(&[rbs::to_value!(2u64)]).unwrap();
+    assert_eq!(post.comments_exists(), Some(false));
+
+    post.comments.match_exists(&[rbs::to_value!(2u64), rbs::to_value!(1u64)]).unwrap();
+    assert_eq!(post.comments_exists(), Some(true));
+}
+
+#[test]
+fn existence_is_checked_with_a_single_query() {
+    let post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    assert_eq!(
+        post.comments.exists_query(vec![1, 2]).to_sql(Type::Select),
+        "SELECT DISTINCT comments.post_id FROM comments WHERE comments.post_id IN (?, ?) AND comments.post_id IS NOT NULL"
+    );
+    assert_eq!(
+        post.tags.exists_query(vec![1]).to_sql(Type::Select),
+        "SELECT DISTINCT post_tag.post_id FROM tags INNER JOIN post_tag ON tags.id = post_tag.tag_id WHERE post_tag.post_id IN (?)"
+    );
+}
+
+#[tokio::test]
+async fn existence_checks_need_a_connection() {
+    let error = Post::query()
+        .with_exists_where("comments", |query| query.r#where("approved", "=", true))
+        .get::<Post>()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Connection(_)));
+}
+
+#[tokio::test]
+async fn attaching_checks_the_pivot_table_first() {
+    let mut post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    let error = post
+        .tags
+        .attach_with(2, [("assigned_by", 7u64)])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    // no query is needed when there are no keys to attach
+    assert_eq!(post.tags.sync_without_detaching(vec![]).await.unwrap(), Vec::<u64>::new());
+}
+
+#[tokio::test]
+async fn relationship_queries_can_require_a_sole_model() {
+    let post: Post = rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap();
+
+    let error = post
+        .comments_query()
+        .r#where("approved", "=", true)
+        .sole::<Comment>()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Connection(_)));
+}
@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+
+use ensemble::query::Dialect;
+use ensemble::rbs::to_value;
+use ensemble::relationships::HasMany;
+use ensemble::types::DateTime;
+use ensemble::Model;
+
+use super::support::row;
+
+#[derive(Debug, Model)]
+#[ensemble(rename_all = "camelCase")]
+struct Customer {
+    id: u64,
+    first_name: String,
+    #[model(column = "surname")]
+    last_name: String,
+    is_active: bool,
+    orders: HasMany<Customer, Order>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(rename_all = "PascalCase")]
+struct Account {
+    id: u64,
+    display_name: String,
+    created_at: DateTime,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(rename_all = "snake_case")]
+struct Order {
+    id: u64,
+    customer_id: u64,
+    total_cents: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Vendor {
+    id: u64,
+    trade_name: String,
+}
+
+#[test]
+fn columns_are_renamed_with_camel_case() {
+    assert_eq!(Customer::keys(), ["id", "firstName", "surname", "isActive"]);
+    assert_eq!(Customer::PRIMARY_KEY, "id");
+
+    let customer = Customer::hydrate(row(&[
+        ("id", to_value!(1u64)),
+        ("firstName", to_value!("Taylor")),
+        ("surname", to_value!("Otwell")),
+        ("isActive", to_value!(true)),
+    ]))
+    .unwrap();
+    assert_eq!(customer.first_name, "Taylor");
+    assert_eq!(customer.last_name, "Otwell");
+
+    assert_eq!(
+        customer.to_insert_sql(Dialect::Mysql).unwrap(),
+        "INSERT INTO `customers` (`id`, `firstName`, `surname`, `isActive`) VALUES (1, 'Taylor', 'Otwell', TRUE);"
+    );
+}
+
+#[test]
+fn columns_are_renamed_with_pascal_case() {
+    assert_eq!(Account::keys(), ["Id", "DisplayName", "CreatedAt"]);
+    assert_eq!(Account::PRIMARY_KEY, "Id");
+    assert_eq!(Account::TIMESTAMPS, ["CreatedAt"]);
+
+    let account = Account::hydrate(row(&[
+        ("Id", to_value!(1u64)),
+        ("DisplayName", to_value!("Ensemble")),
+        ("CreatedAt", to_value!(DateTime::now())),
+    ]))
+    .unwrap();
+    assert_eq!(account.display_name, "Ensemble");
+    assert!(Account::hydrate(row(&[("id", to_value!(1u64))])).is_err());
+}
+
+#[test]
+fn snake_case_leaves_rust_names_unchanged() {
+    assert_eq!(Order::keys(), ["id", "customer_id", "total_cents"]);
+
+    let order = Order {
+        id: 1,
+        customer_id: 2,
+        total_cents: 999,
+    };
+    assert_eq!(
+        order.to_insert_sql(Dialect::Postgres).unwrap(),
+        "INSERT INTO \"orders\" (\"id\", \"customer_id\", \"total_cents\") VALUES (1, 2, 999);"
+    );
+}
+
+#[test]
+fn columns_are_renamed_with_screaming_snake_case() {
+    assert_eq!(Vendor::keys(), ["ID", "TRADE_NAME"]);
+
+    let vendor = Vendor::hydrate(row(&[
+        ("ID", to_value!(3u64)),
+        ("TRADE_NAME", to_value!("Acme")),
+    ]))
+    .unwrap();
+    assert_eq!(
+        vendor.to_insert_sql(Dialect::Sqlite).unwrap(),
+        "INSERT INTO \"vendors\" (\"ID\", \"TRADE_NAME\") VALUES (3, 'Acme');"
+    );
+}
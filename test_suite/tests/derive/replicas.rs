@@ -0,0 +1,121 @@
+use ensemble::{rbs, testing, transaction, Model};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    email: String,
+}
+
+fn user() -> rbs::Value {
+    rbs::to_value(serde_json::json!({ "id": 1, "email": "taylor@example.com" })).unwrap()
+}
+
+/// Whether each statement run by the fake connection goes to a read replica.
+fn routes(fake: &testing::FakeConnection) -> Vec<(String, bool)> {
+    fake.statements()
+        .into_iter()
+        .map(|statement| (statement.sql, statement.replica))
+        .collect()
+}
+
+#[tokio::test]
+async fn builder_reads_run_on_a_replica() {
+    let count = rbs::to_value(serde_json::json!({ "count": 1 })).unwrap();
+    let fake = testing::fake_connection()
+        .returning(vec![user()])
+        .returning(vec![count]);
+
+    fake.run(User::find(1)).await.unwrap();
+    fake.run(
+        User::query()
+            .r#where("email", "like", "%@example.com")
+            .count(),
+    )
+    .await
+    .unwrap();
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 2);
+    assert!(statements.iter().all(|statement| statement.replica));
+}
+
+#[tokio::test]
+async fn reads_can_ask_for_the_primary() {
+    let fake = testing::fake_connection();
+
+    fake.run(User::query().on_primary().get::<User>())
+        .await
+        .unwrap();
+
+    assert_eq!(routes(&fake), [("SELECT * FROM users".to_string(), false)]);
+}
+
+#[tokio::test]
+async fn writes_run_on_the_primary() {
+    let fake = testing::fake_connection().affecting(1);
+
+    fake.run(User::query().r#where("id", "=", 1).delete())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        routes(&fake),
+        [("DELETE FROM users WHERE id = ?".to_string(), false)]
+    );
+}
+
+#[tokio::test]
+async fn reads_in_a_transaction_run_on_the_primary() {
+    let fake = testing::fake_connection();
+
+    fake.run(transaction(|| async { User::query().get::<User>().await }))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        routes(&fake),
+        [
+            ("BEGIN".to_string(), false),
+            ("SELECT * FROM users".to_string(), false),
+            ("COMMIT".to_string(), false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn refreshing_a_model_reads_the_primary() {
+    let fake = testing::fake_connection().returning(vec![user()]);
+
+    let user = User {
+        id: 1,
+        ..User::default()
+    };
+    fake.run(user.fresh()).await.unwrap();
+
+    assert_eq!(
+        routes(&fake),
+        [(
+            "SELECT * FROM users WHERE id = ? LIMIT 1".to_string(),
+            false
+        )]
+    );
+}
+
+#[tokio::test]
+async fn first_or_create_looks_up_the_primary() {
+    let fake = testing::fake_connection().returning(vec![user()]);
+
+    let user = User {
+        email: "taylor@example.com".to_string(),
+        ..User::default()
+    };
+    fake.run(user.first_or_create(&["email"])).await.unwrap();
+
+    assert_eq!(
+        routes(&fake),
+        [(
+            "SELECT * FROM users WHERE email = ? LIMIT 1".to_string(),
+            false
+        )]
+    );
+}
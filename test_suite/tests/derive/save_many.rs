@@ -0,0 +1,46 @@
+#![allow(dead_code)]
+
+use ensemble::{Error, Model};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn save_many_rejects_models_that_have_not_been_inserted() {
+    let mut users = vec![
+        User {
+            id: 1,
+            name: "Jane".to_string(),
+        },
+        User {
+            id: 0,
+            name: "John".to_string(),
+        },
+        User {
+            id: 0,
+            name: "Jack".to_string(),
+        },
+    ];
+
+    let error = User::save_many(&mut users).await.unwrap_err();
+
+    assert!(matches!(error, Error::Unpersisted(indices) if indices == vec![1, 2]));
+}
+
+#[tokio::test]
+async fn save_many_rejects_read_only_models() {
+    #[derive(Debug, Model)]
+    #[ensemble(read_only)]
+    struct Report {
+        id: u64,
+    }
+
+    let mut reports = vec![Report { id: 1 }];
+
+    let error = Report::save_many(&mut reports).await.unwrap_err();
+
+    assert!(matches!(error, Error::ReadOnly("reports")));
+}
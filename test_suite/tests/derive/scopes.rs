@@ -0,0 +1,119 @@
+#![allow(dead_code)]
+
+use ensemble::query::{Builder, Type};
+use ensemble::relationships::{BelongsTo, HasMany, Relationship};
+use ensemble::{testing, Model};
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct Author {
+    id: u64,
+    name: String,
+    posts: HasMany<Author, Post>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(global_scope = "Post::published", global_scope = "Post::latest")]
+struct Post {
+    id: u64,
+    title: String,
+    published: bool,
+    views: u64,
+    author: BelongsTo<Post, Author>,
+}
+
+impl Post {
+    fn published(query: Builder) -> Builder {
+        query.r#where("published", "=", true)
+    }
+
+    fn latest(query: Builder) -> Builder {
+        query.order_by("id", "desc")
+    }
+
+    fn popular(query: Builder) -> Builder {
+        query.r#where("views", ">", 100)
+    }
+}
+
+#[test]
+fn scopes_add_their_constraints_to_the_query() {
+    assert_eq!(
+        Post::without_global_scopes()
+            .scope(Post::popular)
+            .to_sql(Type::Select),
+        "SELECT * FROM posts WHERE views > ?"
+    );
+}
+
+#[test]
+fn global_scopes_apply_to_every_query() {
+    assert_eq!(
+        Post::GLOBAL_SCOPES
+            .iter()
+            .map(|scope| scope.name)
+            .collect::<Vec<_>>(),
+        ["Post::published", "Post::latest"]
+    );
+    assert!(Author::GLOBAL_SCOPES.is_empty());
+
+    assert_eq!(
+        Post::query().scope(Post::popular).to_sql(Type::Select),
+        "SELECT * FROM posts WHERE published = ? AND views > ? ORDER BY id DESC"
+    );
+}
+
+#[test]
+fn queries_can_opt_out_of_global_scopes() {
+    assert_eq!(
+        Post::without_global_scope("Post::published").to_sql(Type::Select),
+        "SELECT * FROM posts ORDER BY id DESC"
+    );
+    assert_eq!(
+        Post::without_global_scopes().to_sql(Type::Select),
+        "SELECT * FROM posts"
+    );
+}
+
+#[tokio::test]
+async fn global_scopes_apply_to_finding_models() {
+    let fake = testing::fake_connection().returning(rows(serde_json::json!([
+        { "id": 1, "title": "Hello", "published": true, "views": 0, "author_id": 1 },
+    ])));
+
+    fake.run(Post::find(1)).await.unwrap();
+
+    assert!(fake.statements()[0]
+        .sql
+        .starts_with("SELECT * FROM posts WHERE published = ? AND id = ?"));
+}
+
+#[tokio::test]
+async fn global_scopes_apply_to_relationships() {
+    let author = Author {
+        id: 1,
+        ..Author::default()
+    };
+
+    assert!(author
+        .posts
+        .query()
+        .to_sql(Type::Select)
+        .starts_with("SELECT * FROM posts WHERE published = ? AND "));
+}
+
+#[tokio::test]
+async fn saving_a_model_ignores_its_global_scopes() {
+    let fake = testing::fake_connection().affecting(1);
+
+    let mut post = Post {
+        id: 1,
+        title: "Hello".to_string(),
+        ..Post::default()
+    };
+    post.published = false;
+    fake.run(post.save()).await.unwrap();
+
+    assert!(fake.statements()[0].sql.ends_with("WHERE id = ?"));
+}
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::Value;
+use ensemble::{testing, Model};
+
+use super::support::capture_logs;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    email: String,
+    #[model(sensitive)]
+    password: String,
+    #[model(sensitive, column = "token")]
+    api_token: Option<String>,
+}
+
+#[test]
+fn sensitive_columns_are_described() {
+    let sensitive = User::columns()
+        .iter()
+        .filter(|column| column.sensitive)
+        .map(|column| column.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(sensitive, ["password", "token"]);
+}
+
+#[test]
+fn sensitive_values_are_redacted_from_queries() {
+    let query = User::query()
+        .r#where("email", "=", "taylor@example.com")
+        .where_group(|query| {
+            query
+                .r#where("users.password", "=", "hunter2")
+                .or_where("token", "=", "tok_live_123")
+        });
+
+    let logged = format!("{query:?}");
+    assert!(!logged.contains("hunter2"));
+    assert!(!logged.contains("tok_live_123"));
+    assert!(logged.contains("[REDACTED]"));
+
+    // the values themselves are still bound as-is
+    assert_eq!(
+        query.get_bindings(),
+        vec![
+            Value::from("taylor@example.com"),
+            Value::from("hunter2"),
+            Value::from("tok_live_123"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn sensitive_values_are_left_out_of_logs() {
+    let fake = testing::fake_connection().affecting(1);
+    let user = User {
+        id: 1,
+        email: "taylor@example.com".to_string(),
+        password: "hunter2".to_string(),
+        api_token: Some("tok_live_123".to_string()),
+    };
+
+    // the fake connection has no insert id to return, but the insert is logged before it's read
+    let (_, logs) = capture_logs(fake.run(user.create())).await;
+
+    assert!(logs.contains("INSERT INTO users"));
+    assert!(logs.contains("taylor@example.com"));
+    assert!(logs.lines().iter().all(|line| !line.contains("hunter2")));
+    assert!(logs.lines().iter().all(|line| !line.contains("tok_live_123")));
+}
@@ -0,0 +1,259 @@
+#![allow(dead_code)]
+
+use ensemble::query::Dialect;
+use ensemble::types::DateTime;
+use ensemble::{testing, Error, Model};
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(soft_deletes)]
+struct Comment {
+    id: u64,
+    body: String,
+    deleted_at: Option<DateTime>,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(soft_deletes, outbox)]
+struct Review {
+    id: u64,
+    body: String,
+    deleted_at: Option<DateTime>,
+}
+
+fn statements(fake: &testing::FakeConnection) -> Vec<String> {
+    fake.statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect()
+}
+
+#[tokio::test]
+async fn bulk_soft_deletes_require_a_constraint() {
+    let error = Post::query().soft_delete().await.unwrap_err();
+    assert!(matches!(error, Error::Unconstrained));
+
+    let error = Post::query().restore().await.unwrap_err();
+    assert!(matches!(error, Error::Unconstrained));
+
+    // with an explicit opt-in, the query runs (and fails, since there's no database)
+    let error = Post::query()
+        .unconstrained()
+        .soft_delete()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    let error = Post::query().only_trashed().restore().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+}
+
+#[tokio::test]
+async fn queries_leave_out_soft_deleted_models() {
+    let fake = testing::fake_connection();
+
+    fake.run(async {
+        Comment::all().await?;
+        Comment::with_trashed().get::<Comment>().await?;
+        Comment::only_trashed().get::<Comment>().await
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "SELECT * FROM comments WHERE comments.deleted_at IS NULL",
+            "SELECT * FROM comments",
+            "SELECT * FROM comments WHERE comments.deleted_at IS NOT NULL",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn deleting_a_soft_deleting_model_only_marks_it() {
+    let fake = testing::fake_connection().affecting(1).affecting(1);
+
+    fake.run(async {
+        let comment = Comment {
+            id: 1,
+            ..Comment::default()
+        };
+        comment.delete().await?;
+
+        let comment = Comment {
+            id: 2,
+            ..Comment::default()
+        };
+        comment.force_delete().await
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "UPDATE comments SET deleted_at = ?  WHERE comments.deleted_at IS NULL AND (id = ?)",
+            "DELETE FROM comments WHERE id = ?",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn soft_deleting_a_model_records_its_deletion() {
+    // BEGIN is the first statement to modify rows
+    let fake = testing::fake_connection()
+        .affecting(0)
+        .affecting(1)
+        .affecting(0)
+        .affecting(0)
+        .affecting(0)
+        .affecting(1);
+
+    fake.run(async {
+        let review = Review {
+            id: 1,
+            ..Review::default()
+        };
+        review.delete().await?;
+
+        let review = Review {
+            id: 2,
+            ..Review::default()
+        };
+        review.force_delete().await
+    })
+    .await
+    .unwrap();
+
+    let statements = statements(&fake);
+    assert_eq!(statements.len(), 8);
+    assert_eq!(
+        statements[1],
+        "UPDATE reviews SET deleted_at = ?  WHERE reviews.deleted_at IS NULL AND (id = ?)"
+    );
+    assert!(statements[2].starts_with("INSERT INTO outbox "));
+    assert_eq!(statements[5], "DELETE FROM reviews WHERE id = ?");
+    assert!(statements[6].starts_with("INSERT INTO outbox "));
+}
+
+#[tokio::test]
+async fn restoring_a_model_records_its_update() {
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    let mut review = Review {
+        id: 1,
+        deleted_at: Some(DateTime::now()),
+        ..Review::default()
+    };
+    fake.run(review.restore()).await.unwrap();
+    assert!(!review.trashed());
+
+    let statements = statements(&fake);
+    assert_eq!(
+        statements[..2],
+        [
+            "BEGIN",
+            "UPDATE reviews SET deleted_at = ?  WHERE reviews.deleted_at IS NOT NULL AND (id = ?)"
+        ]
+    );
+    assert!(statements[2].starts_with("INSERT INTO outbox "));
+    assert_eq!(statements[3], "COMMIT");
+
+    // nothing was restored, so the model is left as it was
+    let mut review = Review {
+        id: 2,
+        deleted_at: Some(DateTime::now()),
+        ..Review::default()
+    };
+    let error = fake.run(review.restore()).await.unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+    assert!(review.trashed());
+}
+
+#[tokio::test]
+async fn soft_deleted_models_can_be_restored() {
+    let fake = testing::fake_connection().affecting(1);
+    let mut comment = Comment {
+        id: 1,
+        deleted_at: Some(DateTime::now()),
+        ..Comment::default()
+    };
+    assert!(comment.trashed());
+
+    fake.run(comment.restore()).await.unwrap();
+    assert!(!comment.trashed());
+
+    assert_eq!(
+        statements(&fake),
+        vec!["UPDATE comments SET deleted_at = ?  WHERE comments.deleted_at IS NOT NULL AND (id = ?)"]
+    );
+}
+
+#[tokio::test]
+async fn deleting_soft_deleting_models_in_chunks_only_marks_them() {
+    let fake = testing::fake_connection().affecting(0);
+
+    let deleted = fake
+        .run(
+            Comment::with_trashed()
+                .r#where("body", "=", "spam")
+                .delete_in_chunks(100),
+        )
+        .await
+        .unwrap();
+    assert_eq!(deleted, 0);
+
+    // only MySQL can limit an update itself, so other databases limit the keys it touches
+    let expected = if Dialect::default() == Dialect::Mysql {
+        "UPDATE comments SET deleted_at = ?  WHERE comments.deleted_at IS NULL AND (body = ?) ORDER BY id ASC LIMIT 100"
+    } else {
+        "UPDATE comments SET deleted_at = ?  WHERE id IN (SELECT id FROM comments WHERE comments.deleted_at IS NULL AND (body = ?) ORDER BY id ASC LIMIT 100)"
+    };
+    assert_eq!(statements(&fake), vec![expected]);
+}
+
+#[tokio::test]
+async fn deleting_soft_deleting_models_returns_them_marked() {
+    let row =
+        ensemble::rbs::to_value(serde_json::json!({ "id": 1, "body": "spam", "deleted_at": null }))
+            .unwrap();
+    let fake = testing::fake_connection()
+        .returning(vec![row.clone()])
+        .returning(vec![row]);
+
+    fake.run(
+        Comment::query()
+            .r#where("body", "=", "spam")
+            .delete_returning::<Comment>(),
+    )
+    .await
+    .unwrap();
+
+    let statements = statements(&fake);
+    assert!(statements
+        .iter()
+        .any(|sql| sql.starts_with("UPDATE comments SET deleted_at = ?")));
+    assert!(!statements.iter().any(|sql| sql.starts_with("DELETE")));
+}
+
+#[tokio::test]
+async fn soft_deleting_models_cannot_be_deleted_with_joins() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(
+            Comment::query()
+                .join("posts", "posts.id", "=", "comments.post_id")
+                .delete_with_join(&["posts"]),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Unsupported(_)));
+    assert!(statements(&fake).is_empty());
+}
@@ -0,0 +1,123 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::{BelongsTo, HasMany};
+use ensemble::{testing, Error, Model};
+use serde_json::json;
+
+use super::support::rows;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    email: String,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    comments: HasMany<Post, Comment>,
+}
+
+#[derive(Debug, Model)]
+struct Comment {
+    id: u64,
+    body: String,
+    post: BelongsTo<Comment, Post>,
+}
+
+fn post() -> Post {
+    rbs::from_value(rbs::Value::Map(value_map! {
+        "id": 1u64,
+        "title": "Hello",
+    }))
+    .unwrap()
+}
+
+fn statements(fake: &testing::FakeConnection) -> Vec<String> {
+    fake.statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect()
+}
+
+#[tokio::test]
+async fn the_only_matching_model_is_returned() {
+    let fake = testing::fake_connection()
+        .returning(rows(json!([{ "id": 1, "email": "taylor@example.com" }])))
+        .returning(rows(json!([{ "id": 1, "body": "First!", "post_id": 1 }])));
+
+    let user = fake
+        .run(User::sole_where("email", "=", "taylor@example.com"))
+        .await
+        .unwrap();
+    assert_eq!(user.email, "taylor@example.com");
+
+    let comment = fake
+        .run(post().comments_query().sole::<Comment>())
+        .await
+        .unwrap();
+    assert_eq!(comment.body, "First!");
+
+    // two rows are enough to tell whether the model is the only one
+    assert_eq!(
+        statements(&fake),
+        [
+            "SELECT * FROM users WHERE email = ? LIMIT 2",
+            "SELECT * FROM comments WHERE comments.post_id = ? AND comments.post_id IS NOT NULL LIMIT 2",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn missing_models_are_not_found() {
+    let fake = testing::fake_connection().returning(vec![]).returning(vec![]);
+
+    let error = fake
+        .run(User::sole_where("email", "=", "taylor@example.com"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+
+    let error = fake
+        .run(post().comments_query().sole::<Comment>())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+}
+
+#[tokio::test]
+async fn more_than_one_model_is_reported() {
+    let fake = testing::fake_connection()
+        .returning(rows(json!([
+            { "id": 1, "email": "taylor@example.com" },
+            { "id": 2, "email": "taylor@example.com" },
+        ])))
+        .returning(rows(json!([
+            { "id": 1, "body": "First!", "post_id": 1 },
+            { "id": 2, "body": "Second!", "post_id": 1 },
+        ])));
+
+    let error = fake
+        .run(User::sole_where("email", "=", "taylor@example.com"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::MultipleRecordsFound { count_hint: 2 }));
+
+    let error = fake
+        .run(post().comments_query().sole::<Comment>())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::MultipleRecordsFound { count_hint: 2 }));
+}
+
+#[test]
+fn multiple_records_are_reported() {
+    let error = Error::MultipleRecordsFound { count_hint: 2 };
+
+    assert_eq!(
+        error.to_string(),
+        "Expected a single model, but found at least 2."
+    );
+}
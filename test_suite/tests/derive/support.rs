@@ -0,0 +1,110 @@
+//! Helpers shared by the tests in this binary.
+
+use ensemble::rbs;
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// The rows a fake connection returns, written as a JSON array of objects.
+pub fn rows(rows: serde_json::Value) -> Vec<rbs::Value> {
+    match rbs::to_value(rows).unwrap() {
+        rbs::Value::Array(rows) => rows,
+        _ => unreachable!(),
+    }
+}
+
+/// A single row, as the columns and values a model is hydrated from.
+pub fn row(columns: &[(&str, rbs::Value)]) -> HashMap<String, rbs::Value> {
+    columns
+        .iter()
+        .map(|(column, value)| ((*column).to_string(), value.clone()))
+        .collect()
+}
+
+/// What was logged while running a future with [`capture_logs`], one line per span or event.
+///
+/// Spans are written as `span <name> <field>=<value>…`, with the fields recorded after they were created on lines of their own, and events as `event <field>=<value>…`.
+#[derive(Debug, Clone, Default)]
+pub struct Logs(Arc<Mutex<Vec<String>>>);
+
+impl Logs {
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        self.lines().iter().any(|line| line.contains(text))
+    }
+
+    fn push(&self, line: String) {
+        self.0.lock().unwrap().push(line);
+    }
+}
+
+/// Run `future`, capturing every span and event it logs (at any level).
+///
+/// The subscriber is only set for the current thread, so this needs the single-threaded runtime `#[tokio::test]` starts by default.
+pub async fn capture_logs<F: std::future::Future>(future: F) -> (F::Output, Logs) {
+    let logs = Logs::default();
+    let _guard = tracing::subscriber::set_default(Capture {
+        logs: logs.clone(),
+        next_id: AtomicU64::new(1),
+    });
+
+    (future.await, logs)
+}
+
+struct Capture {
+    logs: Logs,
+    next_id: AtomicU64,
+}
+
+struct Line(String);
+
+impl Visit for Line {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push_str(&format!(" {field}={value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {field}={value:?}"));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut line = Line(format!("span {}", span.metadata().name()));
+        span.record(&mut line);
+        self.logs.push(line.0);
+
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+        let mut line = Line("record".to_string());
+        values.record(&mut line);
+        self.logs.push(line.0);
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = Line("event".to_string());
+        event.record(&mut line);
+        self.logs.push(line.0);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use ensemble::query::Dialect;
 use ensemble::Model;
 
 #[test]
@@ -40,3 +41,43 @@ fn derived_table_name_can_be_overriden_with_attribute() {
 
     assert_eq!(ModelWithCustomTableName::TABLE_NAME, "custom_table");
 }
+
+#[test]
+fn pluralization_can_be_disabled() {
+    #[derive(Debug, Model)]
+    #[ensemble(inflection = "none")]
+    struct Rechnung {
+        id: u8,
+    }
+
+    #[derive(Debug, Model)]
+    #[ensemble(inflection = "none", schema = "buchhaltung")]
+    struct OffenerPosten {
+        id: u8,
+    }
+
+    assert_eq!(Rechnung::TABLE_NAME, "rechnung");
+    assert_eq!(OffenerPosten::TABLE_NAME, "offener_posten");
+    assert_eq!(
+        OffenerPosten::QUALIFIED_TABLE,
+        format!("{}.offener_posten", Dialect::default().quote_identifier("buchhaltung"))
+    );
+}
+
+#[test]
+fn table_names_can_be_inflected_with_a_suffix() {
+    #[derive(Debug, Model)]
+    #[ensemble(inflection = "suffix:en")]
+    struct Rechnung {
+        id: u8,
+    }
+
+    #[derive(Debug, Model)]
+    #[ensemble(inflection = "english")]
+    struct Invoice {
+        id: u8,
+    }
+
+    assert_eq!(Rechnung::TABLE_NAME, "rechnungen");
+    assert_eq!(Invoice::TABLE_NAME, "invoices");
+}
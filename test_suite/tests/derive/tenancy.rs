@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use ensemble::{query::Type, tenancy, Error, Model};
+
+#[derive(Debug, Model)]
+#[ensemble(tenant_column = "team_id")]
+struct Project {
+    id: u64,
+    team_id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn queries_are_scoped_to_the_current_tenant() {
+    let query = tenancy::with_tenant(42_u64, async {
+        Project::query()
+            .r#where("name", "=", "Ensemble")
+            .or_where("name", "=", "Rails")
+    })
+    .await;
+
+    let sql = query.to_sql(Type::Select);
+    assert!(sql.starts_with("SELECT * FROM projects WHERE projects.team_id = ? AND ("));
+    assert!(sql.ends_with(')'));
+    assert_eq!(query.get_bindings()[0], 42_u64.into());
+}
+
+#[tokio::test]
+async fn queries_without_a_tenant_are_rejected() {
+    let error = Project::all().await.unwrap_err();
+    assert!(matches!(error, Error::NoTenant("projects")));
+
+    let error = Project {
+        id: 0,
+        team_id: 1,
+        name: "Ensemble".to_string(),
+    }
+    .create()
+    .await
+    .unwrap_err();
+    assert!(matches!(error, Error::NoTenant("projects")));
+
+    // bypassing tenancy runs the query (which fails, since there's no database)
+    let error = Project::query().without_tenancy().get::<Project>().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+    assert_eq!(
+        Project::query().without_tenancy().to_sql(Type::Select),
+        "SELECT * FROM projects"
+    );
+}
@@ -0,0 +1,106 @@
+use ensemble::{rbs, testing, Model, Transaction};
+
+#[derive(Debug, Model, PartialEq, Eq)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn fake_connections_record_queries_without_running_them() {
+    let fake = testing::fake_connection();
+
+    let users = fake
+        .run(User::query().r#where("name", "=", "Taylor").get::<User>())
+        .await
+        .unwrap();
+    assert!(users.is_empty());
+
+    assert_eq!(
+        fake.statements(),
+        vec![testing::Statement {
+            sql: "SELECT * FROM users WHERE name = ?".to_string(),
+            bindings: vec![rbs::Value::from("Taylor")],
+            replica: true,
+            retry: 0,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn fake_connections_return_the_queued_results() {
+    let row = rbs::to_value(serde_json::json!({ "id": 1, "name": "Taylor" })).unwrap();
+    let fake = testing::fake_connection().returning(vec![row]).affecting(3);
+
+    let user = fake.run(User::find(1)).await.unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 1,
+            name: "Taylor".to_string()
+        }
+    );
+
+    let updated = fake
+        .run(
+            User::query()
+                .r#where("name", "=", "Taylor")
+                .update(vec![("name", "Abbey")]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 3);
+}
+
+#[tokio::test]
+async fn transactions_are_faked_too() {
+    let fake = testing::fake_connection();
+
+    fake.run(async {
+        let mut transaction = Transaction::begin("users").await?;
+        transaction
+            .exec("DELETE FROM users WHERE id = ?", vec![1.into()])
+            .await?;
+        transaction.commit().await
+    })
+    .await
+    .unwrap();
+
+    let statements = fake
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statements,
+        vec!["BEGIN", "DELETE FROM users WHERE id = ?", "COMMIT"]
+    );
+}
+
+#[tokio::test]
+async fn queries_can_be_counted() {
+    let count = rbs::to_value(serde_json::json!({ "COUNT(*)": 0 })).unwrap();
+    let fake = testing::fake_connection().returning(vec![count]);
+
+    let (count, users) = fake
+        .run(testing::assert_query_count(2, async {
+            (User::query().count().await, User::all().await)
+        }))
+        .await;
+    assert_eq!(count.unwrap(), 0);
+    assert!(users.unwrap().is_empty());
+}
+
+#[tokio::test]
+#[should_panic(
+    expected = "Expected 1 queries to run, but 2 did:\n  SELECT COUNT(*) FROM users\n  SELECT * FROM users"
+)]
+async fn unexpected_query_counts_list_the_queries() {
+    let fake = testing::fake_connection();
+
+    fake.run(testing::assert_query_count(1, async {
+        User::query().count().await.ok();
+        User::all().await.ok();
+    }))
+    .await;
+}
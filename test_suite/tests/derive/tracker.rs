@@ -0,0 +1,144 @@
+#![allow(dead_code)]
+
+use ensemble::rbs::{self, to_value, value_map};
+use ensemble::value::{self, Tracker};
+use ensemble::{testing, Model};
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    title: String,
+    views: u64,
+    tracker: Tracker,
+}
+
+#[derive(Debug, Model)]
+struct Comment {
+    id: u64,
+}
+
+fn post() -> Post {
+    rbs::from_value(rbs::Value::Map(
+        value_map! { "id" : 1u64, "title": "Hello", "views": 10u64, },
+    ))
+    .unwrap()
+}
+
+/// What a successful `save` does to the tracker, once the row is updated.
+fn saved(post: &mut Post) {
+    let columns = value::for_db(&*post).unwrap();
+    post.tracker_mut().unwrap().persist(columns);
+}
+
+#[test]
+fn the_tracker_is_not_a_column() {
+    assert!(Post::columns().iter().all(|column| column.field != "tracker"));
+    assert_eq!(Post::keys(), ["id", "title", "views"]);
+    assert!(Comment::default().tracker().is_none());
+
+    let post = post();
+    let rbs::Value::Map(columns) = value::for_db(&post).unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(columns.len(), 3);
+    assert_eq!(
+        post.json(),
+        serde_json::json!({ "id": 1, "title": "Hello", "views": 10 })
+    );
+}
+
+#[test]
+fn loaded_models_are_clean_until_changed() {
+    let mut post = post();
+    assert!(post.is_clean());
+    assert!(!post.was_changed());
+
+    post.title = "Hello, world!".to_string();
+    assert!(!post.is_clean());
+    assert!(!post.was_changed());
+
+    assert!(!Post::default().is_clean());
+}
+
+#[test]
+fn saving_records_the_changed_columns() {
+    let mut post = post();
+    post.title = "Hello, world!".to_string();
+    saved(&mut post);
+
+    assert!(post.is_clean());
+    assert!(post.was_changed());
+    assert!(post.was_changed_column("title"));
+    assert!(!post.was_changed_column("views"));
+    assert_eq!(
+        post.changes(),
+        [("title", to_value!("Hello"), to_value!("Hello, world!"))]
+    );
+
+    // the next save replaces the changes of the previous one
+    saved(&mut post);
+    assert!(!post.was_changed());
+    assert!(post.changes().is_empty());
+}
+
+#[test]
+fn every_column_of_a_model_that_was_never_loaded_counts_as_changed() {
+    let mut post = Post {
+        id: 1,
+        title: "Hello".to_string(),
+        ..Post::default()
+    };
+    saved(&mut post);
+
+    assert_eq!(
+        post.changes()
+            .into_iter()
+            .map(|(column, old, _)| (column, old))
+            .collect::<Vec<_>>(),
+        [
+            ("id", rbs::Value::Null),
+            ("title", rbs::Value::Null),
+            ("views", rbs::Value::Null)
+        ]
+    );
+}
+
+#[test]
+fn dirty_columns_are_listed_with_their_current_values() {
+    let mut post = post();
+    assert!(!post.is_dirty());
+    assert!(post.get_dirty().is_empty());
+
+    post.views = 11;
+    assert!(post.is_dirty());
+    assert_eq!(post.get_dirty(), [("views", to_value!(11u64))]);
+
+    assert_eq!(Post::default().get_dirty().len(), 3);
+}
+
+#[tokio::test]
+async fn saving_only_updates_the_dirty_columns() {
+    let mut post = post();
+    post.title = "Hello, world!".to_string();
+
+    let fake = testing::fake_connection().affecting(1);
+    fake.run(post.save()).await.unwrap();
+
+    let statements = fake.statements();
+    assert_eq!(statements.len(), 1);
+    assert!(statements[0].sql.starts_with("UPDATE posts SET title = ?"));
+    assert!(!statements[0].sql.contains("views"));
+    assert!(post.is_clean());
+}
+
+#[tokio::test]
+async fn saving_a_clean_model_runs_no_query() {
+    let mut posts = vec![post(), post()];
+
+    let fake = testing::fake_connection();
+    fake.run(posts[0].save()).await.unwrap();
+    assert_eq!(fake.run(Post::save_many(&mut posts)).await.unwrap(), 0);
+
+    assert!(fake.statements().is_empty());
+    assert!(!posts[0].was_changed());
+}
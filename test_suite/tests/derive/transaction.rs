@@ -0,0 +1,325 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use ensemble::{hooks::EventType, testing, transaction, Error, Model, Transaction};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+static HOOKS: Mutex<Vec<(u64, EventType, bool)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Model)]
+#[ensemble(after_commit = "Self::committed", after_rollback = "Self::rolled_back")]
+struct Invoice {
+    id: u64,
+    total: u64,
+}
+
+impl Invoice {
+    fn committed(&self, event: EventType) {
+        HOOKS.lock().unwrap().push((self.id, event, true));
+    }
+
+    fn rolled_back(&self, event: EventType) {
+        HOOKS.lock().unwrap().push((self.id, event, false));
+    }
+}
+
+/// The hooks called for the invoice with the given id, since tests run concurrently.
+fn hooks_of(id: u64) -> Vec<(EventType, bool)> {
+    HOOKS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(invoice, ..)| *invoice == id)
+        .map(|(_, event, committed)| (*event, *committed))
+        .collect()
+}
+
+fn statements(fake: &testing::FakeConnection) -> Vec<String> {
+    fake.statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect()
+}
+
+#[tokio::test]
+async fn transactions_commit_when_the_closure_succeeds() {
+    // BEGIN is the first statement to modify rows
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    let deleted = fake
+        .run(transaction(|| async {
+            User::query().r#where("name", "=", "Taylor").delete().await
+        }))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+
+    assert_eq!(
+        statements(&fake),
+        vec!["BEGIN", "DELETE FROM users WHERE name = ?", "COMMIT"]
+    );
+}
+
+#[tokio::test]
+async fn transactions_roll_back_when_the_closure_fails() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(transaction(|| async {
+            User::query()
+                .r#where("name", "=", "Taylor")
+                .delete()
+                .await?;
+
+            Err::<(), _>(Error::NotFound)
+        }))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+
+    assert_eq!(
+        statements(&fake),
+        vec!["BEGIN", "DELETE FROM users WHERE name = ?", "ROLLBACK"]
+    );
+}
+
+#[tokio::test]
+async fn nested_transactions_are_savepoints() {
+    let fake = testing::fake_connection();
+
+    fake.run(transaction(|| async {
+        let nested = transaction(|| async {
+            User::query().r#where("id", "=", 1).delete().await?;
+
+            Err::<(), _>(Error::NotFound)
+        })
+        .await;
+        assert!(nested.is_err());
+
+        User::query().r#where("id", "=", 2).delete().await
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "BEGIN",
+            "SAVEPOINT ensemble_1",
+            "DELETE FROM users WHERE id = ?",
+            "ROLLBACK TO SAVEPOINT ensemble_1",
+            "DELETE FROM users WHERE id = ?",
+            "COMMIT",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn committed_savepoints_wait_for_the_outer_transaction() {
+    let fake = testing::fake_connection();
+    let committed = Arc::new(AtomicBool::new(false));
+
+    fake.run(transaction(|| async {
+        let mut savepoint = Transaction::begin("users").await?;
+        let flag = Arc::clone(&committed);
+        savepoint.defer(move |ok| flag.store(ok, Ordering::SeqCst));
+        savepoint.commit().await?;
+
+        assert!(!committed.load(Ordering::SeqCst));
+        Ok::<_, Error>(())
+    }))
+    .await
+    .unwrap();
+
+    assert!(committed.load(Ordering::SeqCst));
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "BEGIN",
+            "SAVEPOINT ensemble_1",
+            "RELEASE SAVEPOINT ensemble_1",
+            "COMMIT"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn abandoned_savepoints_roll_the_transaction_back() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(transaction(|| async {
+            let savepoint = Transaction::begin("users").await?;
+            drop(savepoint);
+
+            Ok::<_, Error>(())
+        }))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::AbandonedSavepoint));
+
+    assert_eq!(
+        statements(&fake),
+        vec!["BEGIN", "SAVEPOINT ensemble_1", "ROLLBACK"]
+    );
+}
+
+#[tokio::test]
+#[cfg(not(feature = "sqlite"))]
+async fn rows_can_be_locked_in_a_transaction_closure() {
+    let fake = testing::fake_connection();
+
+    let jobs = fake
+        .run(transaction(|| async {
+            User::query()
+                .r#where("name", "=", "Taylor")
+                .lock_for_update()
+                .skip_locked()
+                .get::<User>()
+                .await
+        }))
+        .await
+        .unwrap();
+    assert!(jobs.is_empty());
+
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "BEGIN",
+            "SELECT * FROM users WHERE name = ? FOR UPDATE SKIP LOCKED",
+            "COMMIT"
+        ]
+    );
+}
+
+#[tokio::test]
+#[cfg(not(feature = "sqlite"))]
+async fn rows_cannot_be_locked_outside_of_a_transaction() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(User::query().lock_for_update().get::<User>())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::InvalidLock(_)));
+    assert!(fake.statements().is_empty());
+}
+
+#[tokio::test]
+#[cfg(not(feature = "sqlite"))]
+async fn rows_returned_by_writes_are_locked_once() {
+    let fake = testing::fake_connection();
+
+    fake.run(async {
+        User::query()
+            .r#where("name", "=", "Taylor")
+            .delete_returning::<User>()
+            .await?;
+
+        User::query()
+            .r#where("name", "=", "Taylor")
+            .lock_for_update()
+            .nowait()
+            .delete_returning::<User>()
+            .await
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        statements(&fake),
+        vec![
+            "BEGIN",
+            "SELECT * FROM users WHERE name = ? FOR UPDATE",
+            "COMMIT",
+            "BEGIN",
+            "SELECT * FROM users WHERE name = ? FOR UPDATE NOWAIT",
+            "COMMIT",
+        ]
+    );
+}
+
+#[tokio::test]
+#[cfg(feature = "sqlite")]
+async fn rows_cannot_be_locked_on_sqlite() {
+    let fake = testing::fake_connection();
+
+    let error = fake
+        .run(transaction(|| async {
+            User::query().lock_for_update().get::<User>().await
+        }))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Unsupported(_)));
+    assert_eq!(statements(&fake), vec!["BEGIN", "ROLLBACK"]);
+}
+
+#[tokio::test]
+async fn hooks_of_writes_in_a_transaction_wait_for_it_to_commit() {
+    // BEGIN is the first statement to modify rows
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    fake.run(transaction(|| async {
+        let mut invoice = Invoice { id: 1, total: 100 };
+        invoice.save().await?;
+
+        assert!(hooks_of(1).is_empty());
+        Ok::<_, Error>(())
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(hooks_of(1), [(EventType::Updated, true)]);
+}
+
+#[tokio::test]
+async fn hooks_of_writes_in_a_rolled_back_transaction_are_not_committed() {
+    let fake = testing::fake_connection().affecting(0).affecting(1);
+
+    let error = fake
+        .run(transaction(|| async {
+            let mut invoice = Invoice { id: 2, total: 100 };
+            invoice.save().await?;
+
+            Err::<(), _>(Error::NotFound)
+        }))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotFound));
+
+    assert_eq!(hooks_of(2), [(EventType::Updated, false)]);
+    assert_eq!(statements(&fake).last().unwrap(), "ROLLBACK");
+}
+
+#[tokio::test]
+async fn hooks_of_writes_in_a_rolled_back_savepoint_are_not_committed() {
+    let fake = testing::fake_connection()
+        .affecting(0)
+        .affecting(0)
+        .affecting(1);
+
+    fake.run(transaction(|| async {
+        let nested = transaction(|| async {
+            Invoice { id: 3, total: 100 }.delete().await?;
+
+            Err::<(), _>(Error::NotFound)
+        })
+        .await;
+        assert!(nested.is_err());
+
+        Ok::<_, Error>(())
+    }))
+    .await
+    .unwrap();
+
+    assert_eq!(hooks_of(3), [(EventType::Deleted, false)]);
+}
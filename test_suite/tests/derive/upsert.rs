@@ -0,0 +1,42 @@
+#![allow(dead_code)]
+
+use ensemble::types::DateTime;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+#[ensemble(upsert)]
+struct Product {
+    id: u64,
+    #[model(unique)]
+    sku: String,
+    name: String,
+    price: u64,
+    created_at: DateTime,
+    updated_at: DateTime,
+}
+
+#[tokio::test]
+async fn upserts_need_a_database() {
+    let product = Product {
+        sku: "SKU-1".to_string(),
+        name: "Keyboard".to_string(),
+        price: 4999,
+        ..Product::default()
+    };
+
+    assert!(product.upsert().await.is_err());
+}
+
+#[tokio::test]
+async fn upserts_check_required_fields_first() {
+    let product = Product {
+        name: "Keyboard".to_string(),
+        price: 4999,
+        ..Product::default()
+    };
+
+    assert!(matches!(
+        product.upsert().await,
+        Err(ensemble::Error::Required("sku"))
+    ));
+}
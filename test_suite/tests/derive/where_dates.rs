@@ -0,0 +1,59 @@
+use ensemble::query::{Dialect, Type};
+use ensemble::rbs::to_value;
+use ensemble::types::DateTime;
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Booking {
+    id: u64,
+    nights: u32,
+    created_at: DateTime,
+}
+
+#[test]
+fn between_is_inclusive_and_binds_both_ends() {
+    let query = Booking::query()
+        .where_between("nights", 2, 5)
+        .where_not_between("id", 10_u64, 20_u64);
+
+    assert_eq!(
+        query.to_sql(Type::Select),
+        "SELECT * FROM bookings WHERE nights BETWEEN ? AND ? AND id NOT BETWEEN ? AND ?"
+    );
+    assert_eq!(
+        query.get_bindings(),
+        vec![
+            to_value!(2),
+            to_value!(5),
+            to_value!(10_u64),
+            to_value!(20_u64)
+        ]
+    );
+}
+
+#[test]
+fn date_parts_are_compared_with_the_database_functions() {
+    let query = Booking::query()
+        .where_date("created_at", "2023-10-01")
+        .where_year("created_at", 2023)
+        .where_month("created_at", 10)
+        .where_day("created_at", 1)
+        .where_time("created_at", "14:30:00");
+
+    let expected = match Dialect::default() {
+        Dialect::Mysql => "SELECT * FROM bookings WHERE DATE(created_at) = ? AND YEAR(created_at) = ? AND MONTH(created_at) = ? AND DAY(created_at) = ? AND TIME(created_at) = ?",
+        Dialect::Postgres => "SELECT * FROM bookings WHERE created_at::date = ? AND EXTRACT(YEAR FROM created_at) = ? AND EXTRACT(MONTH FROM created_at) = ? AND EXTRACT(DAY FROM created_at) = ? AND created_at::time = ?",
+        Dialect::Sqlite => "SELECT * FROM bookings WHERE date(created_at) = ? AND CAST(strftime('%Y', created_at) AS INTEGER) = ? AND CAST(strftime('%m', created_at) AS INTEGER) = ? AND CAST(strftime('%d', created_at) AS INTEGER) = ? AND time(created_at) = ?",
+    };
+    assert_eq!(query.to_sql(Type::Select), expected);
+    assert_eq!(
+        query.get_bindings(),
+        vec![
+            to_value!("2023-10-01"),
+            to_value!(2023),
+            to_value!(10),
+            to_value!(1),
+            to_value!("14:30:00")
+        ]
+    );
+}
@@ -0,0 +1,17 @@
+//! The table inflection is read from the environment when models are compiled, so it's tested with `trybuild` in its own binary,
+//! instead of alongside the other derive tests.
+
+use trybuild::TestCases;
+
+const VAR: &str = "ENSEMBLE_TABLE_INFLECTION";
+
+#[test]
+fn table_names_follow_the_inflection_set_by_the_environment() {
+    std::env::set_var(VAR, "suffix:_tbl");
+    TestCases::new().pass("tests/inflection/suffix.rs");
+
+    std::env::set_var(VAR, "plural");
+    TestCases::new().compile_fail("tests/inflection/unknown.rs");
+
+    std::env::remove_var(VAR);
+}
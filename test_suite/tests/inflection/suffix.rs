@@ -0,0 +1,24 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Flight {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(inflection = "english")]
+struct Airport {
+    id: u64,
+}
+
+#[derive(Debug, Model)]
+#[ensemble(table = "crew")]
+struct CrewMember {
+    id: u64,
+}
+
+fn main() {
+    assert_eq!(Flight::TABLE_NAME, "flight_tbl");
+    assert_eq!(Airport::TABLE_NAME, "airports");
+    assert_eq!(CrewMember::TABLE_NAME, "crew");
+}
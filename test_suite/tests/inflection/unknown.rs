@@ -0,0 +1,8 @@
+use ensemble::Model;
+
+#[derive(Debug, Model)]
+struct Flight {
+    id: u64,
+}
+
+fn main() {}
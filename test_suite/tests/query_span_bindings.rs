@@ -0,0 +1,91 @@
+//! Records the bindings of queries run on an in-memory `SQLite` database in their spans, with `cargo test --features sqlite --test query_span_bindings`.
+//! The spans are configured when the database is set up, so this runs in its own process.
+#![cfg(feature = "sqlite")]
+
+use ensemble::migrations::{Error as MigrationError, Migration, Schema};
+use ensemble::redaction::BindingLogging;
+use ensemble::{ConnectionConfig, Model, TracingConfig};
+
+#[allow(dead_code)]
+#[path = "derive/support.rs"]
+mod support;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+    #[model(sensitive)]
+    password: String,
+}
+
+#[derive(Debug, Default)]
+struct CreateUsersTable;
+
+#[ensemble::async_trait]
+impl Migration for CreateUsersTable {
+    async fn up(&self) -> Result<(), MigrationError> {
+        Schema::create("users", |table| {
+            table.id();
+            table.string("name");
+            table.string("password");
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), MigrationError> {
+        Schema::drop("users").await
+    }
+}
+
+#[tokio::test]
+async fn bindings_are_recorded_when_enabled() {
+    let tracing = TracingConfig {
+        record_bindings: true,
+        max_statement_length: Some(20),
+    };
+    // a single connection keeps the in-memory database around
+    ensemble::setup_with(
+        ConnectionConfig::new("sqlite::memory:")
+            .max_connections(1)
+            .tracing(tracing),
+    )
+    .await
+    .unwrap();
+    ensemble::migrate!(CreateUsersTable).await.unwrap();
+
+    let (user, logs) = support::capture_logs(
+        User {
+            id: 0,
+            name: "Taylor".to_string(),
+            password: "hunter2".to_string(),
+        }
+        .create(),
+    )
+    .await;
+    user.unwrap();
+
+    let lines = logs.lines();
+    let insert = lines
+        .iter()
+        .find(|line| line.starts_with("span ensemble.query "))
+        .unwrap();
+    assert!(insert.contains("db.statement=INSERT INTO users (n... "));
+
+    let bindings = lines
+        .iter()
+        .find(|line| line.starts_with("record db.bindings="))
+        .unwrap();
+    assert!(bindings.contains("Taylor"));
+    assert!(!bindings.contains("hunter2"));
+
+    // the redaction policy still applies
+    ensemble::set_binding_logging(BindingLogging::None);
+    let (found, logs) =
+        support::capture_logs(User::query().r#where("name", "=", "Taylor").get::<User>()).await;
+    assert_eq!(found.unwrap().len(), 1);
+    assert!(logs.contains("span ensemble.query "));
+    assert!(logs
+        .lines()
+        .iter()
+        .all(|line| !line.contains("db.bindings")));
+}
@@ -0,0 +1,89 @@
+//! Records the spans of queries run on an in-memory `SQLite` database, with `cargo test --features sqlite --test query_spans`.
+//! The spans are configured when the database is set up, so this runs in its own process, and `query_span_bindings` covers recording bindings.
+#![cfg(feature = "sqlite")]
+
+use ensemble::migrations::{Error as MigrationError, Migration, Schema};
+use ensemble::{ConnectionConfig, Model};
+
+#[allow(dead_code)]
+#[path = "derive/support.rs"]
+mod support;
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Default)]
+struct CreateUsersTable;
+
+#[ensemble::async_trait]
+impl Migration for CreateUsersTable {
+    async fn up(&self) -> Result<(), MigrationError> {
+        Schema::create("users", |table| {
+            table.id();
+            table.string("name");
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), MigrationError> {
+        Schema::drop("users").await
+    }
+}
+
+#[tokio::test]
+async fn queries_are_recorded_without_their_bindings() {
+    // a single connection keeps the in-memory database around
+    ensemble::setup_with(ConnectionConfig::new("sqlite::memory:").max_connections(1))
+        .await
+        .unwrap();
+    ensemble::migrate!(CreateUsersTable).await.unwrap();
+
+    let (user, logs) = support::capture_logs(
+        User {
+            id: 0,
+            name: "Taylor".to_string(),
+        }
+        .create(),
+    )
+    .await;
+    let user = user.unwrap();
+
+    let lines = logs.lines();
+    let insert = lines
+        .iter()
+        .position(|line| line.starts_with("span ensemble.query "))
+        .unwrap();
+    assert!(lines[insert].contains("otel.name=INSERT users"));
+    assert!(lines[insert].contains("db.statement=INSERT INTO users (name) VALUES (?)"));
+    assert!(lines[insert + 1..]
+        .iter()
+        .any(|line| line.starts_with("record") && line.contains("db.rows_affected=1")));
+    // the bindings could be logged in full, but they're only recorded when asked to
+    assert!(lines.iter().all(|line| !line.contains("db.bindings")));
+
+    // statements are truncated to 2048 characters by default
+    let names = (0..1000).map(|i| format!("user {i}")).collect::<Vec<_>>();
+    let (found, logs) =
+        support::capture_logs(User::query().r#where("name", "IN", names).get::<User>()).await;
+    assert!(found.unwrap().is_empty());
+
+    let statement = logs
+        .lines()
+        .into_iter()
+        .find_map(|line| {
+            line.split_once(" db.statement=")
+                .map(|(_, rest)| rest.split(" db.table=").next().unwrap().to_string())
+        })
+        .unwrap();
+    assert!(statement.starts_with("SELECT * FROM users WHERE name IN (?, ?, "));
+    assert!(statement.ends_with("..."));
+    assert_eq!(statement.chars().count(), 2048 + 3);
+
+    // reads record how many rows they returned
+    let (found, logs) = support::capture_logs(User::find(user.id)).await;
+    assert_eq!(found.unwrap().name, "Taylor");
+    assert!(logs.contains("db.rows_affected=1"));
+}
@@ -0,0 +1,48 @@
+//! Retries queries on an in-memory `SQLite` database, with `cargo test --features sqlite --test retries`.
+//! The retry policy is process-wide, so this runs in its own process.
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use ensemble::{testing, ConnectionConfig, Error, Model, QueryRetryPolicy, RetryOn};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn retries_are_recorded_in_the_query_log() {
+    let config = ConnectionConfig::new("sqlite::memory:")
+        .max_connections(1)
+        .acquire_timeout(Duration::from_millis(20))
+        .retry_policy(QueryRetryPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+            retry_on: RetryOn::TIMEOUT,
+        });
+    ensemble::setup_with(config).await.unwrap();
+
+    // the transaction holds the only connection, so the query can't check one out of the pool
+    let (result, statements) = ensemble::transaction(|| async {
+        let query = tokio::spawn(testing::record_queries(User::all()));
+
+        Ok::<_, Error>(query.await.unwrap())
+    })
+    .await
+    .unwrap();
+
+    assert!(matches!(result, Err(Error::Connection(_))));
+    assert_eq!(
+        statements
+            .iter()
+            .map(|statement| (statement.sql.as_str(), statement.retry))
+            .collect::<Vec<_>>(),
+        [
+            ("SELECT * FROM users", 0),
+            ("SELECT * FROM users", 1),
+            ("SELECT * FROM users", 2),
+        ]
+    );
+}
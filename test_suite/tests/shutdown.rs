@@ -0,0 +1,31 @@
+//! Shuts down an in-memory `SQLite` database, with `cargo test --features sqlite --test shutdown`.
+//! Shutting down can't be undone, so this runs in its own process.
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use ensemble::{testing, Error, Model};
+
+#[derive(Debug, Model)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn queries_fail_fast_after_shutting_down() {
+    assert!(ensemble::shutdown(Duration::from_secs(1)).await.is_err());
+
+    let _database = testing::setup_test_db(async { Ok(()) }).await.unwrap();
+
+    assert!(ensemble::ping().await.is_ok());
+
+    ensemble::shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert!(matches!(ensemble::ping().await, Err(Error::ShuttingDown)));
+    assert!(matches!(User::all().await, Err(Error::ShuttingDown)));
+    assert!(matches!(
+        ensemble::transaction(|| async { User::query().count().await }).await,
+        Err(Error::ShuttingDown)
+    ));
+}
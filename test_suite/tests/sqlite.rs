@@ -0,0 +1,303 @@
+//! Runs models against an in-memory `SQLite` database, with `cargo test --features sqlite --test sqlite`.
+//! The database is process-wide, so everything runs in a single test.
+#![cfg(feature = "sqlite")]
+
+use ensemble::factory::Factory;
+use ensemble::migrations::{Command, Error as MigrationError, Migration, Schema};
+use ensemble::query::Explain;
+use ensemble::relationships::{BelongsTo, HasMany};
+use ensemble::types::{DateTime, Json};
+use ensemble::{rbs, testing, Error, Model};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Model)]
+#[ensemble(upsert)]
+struct Product {
+    id: u64,
+    #[model(unique)]
+    sku: String,
+    name: String,
+    price: u64,
+    in_stock: bool,
+    #[model(default)]
+    details: Json<Details>,
+    created_at: DateTime,
+    updated_at: DateTime,
+    reviews: HasMany<Product, Review>,
+}
+
+#[derive(Debug, Model)]
+struct Review {
+    id: u64,
+    rating: u64,
+    product: BelongsTo<Review, Product>,
+}
+
+impl Factory for Product {
+    fn definition() -> Self {
+        product("SKU-0", 999)
+    }
+}
+
+impl Factory for Review {
+    fn definition() -> Self {
+        Self {
+            rating: 5,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CreateProductsTable;
+
+#[ensemble::async_trait]
+impl Migration for CreateProductsTable {
+    async fn up(&self) -> Result<(), MigrationError> {
+        Schema::create("products", |table| {
+            table.id();
+            table.string("sku").unique(true);
+            table.string("name");
+            table.integer("price");
+            table.boolean("in_stock").default(true);
+            table.json("details");
+            table.timestamps();
+        })
+        .await?;
+
+        Schema::create("reviews", |table| {
+            table.id();
+            table.integer("rating");
+            table.integer("product_id");
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), MigrationError> {
+        Schema::drop("reviews").await?;
+        Schema::drop("products").await
+    }
+}
+
+#[derive(Debug, Default)]
+struct CreateCategoriesTable;
+
+#[ensemble::async_trait]
+impl Migration for CreateCategoriesTable {
+    async fn up(&self) -> Result<(), MigrationError> {
+        Schema::create("categories", |table| {
+            table.id();
+            table.string("name");
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), MigrationError> {
+        Schema::drop("categories").await
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Details {
+    color: Option<String>,
+    tags: Vec<String>,
+}
+
+fn product(sku: &str, price: u64) -> Product {
+    Product {
+        sku: sku.to_string(),
+        name: "Keyboard".to_string(),
+        price,
+        in_stock: true,
+        ..Product::default()
+    }
+}
+
+#[tokio::test]
+async fn models_round_trip_through_sqlite() {
+    let _database = testing::setup_test_db(ensemble::migrate!(CreateProductsTable))
+        .await
+        .unwrap();
+
+    let keyboard = product("SKU-1", 4999).create().await.unwrap();
+    assert_eq!(keyboard.id, 1);
+
+    let mut mouse = product("SKU-2", 1999).create_and_fetch().await.unwrap();
+    assert_eq!(mouse.id, 2);
+    assert!(mouse.in_stock);
+
+    // booleans are stored as integers, and read back as booleans
+    mouse.in_stock = false;
+    mouse.save().await.unwrap();
+    assert!(!Product::find(2).await.unwrap().in_stock);
+    assert_eq!(
+        Product::query()
+            .r#where("in_stock", "=", true)
+            .count()
+            .await
+            .unwrap(),
+        1
+    );
+
+    assert!(matches!(
+        product("SKU-1", 2999).create().await,
+        Err(Error::UniqueViolation)
+    ));
+
+    let upserted = product("SKU-1", 3999).upsert().await.unwrap();
+    assert_eq!((upserted.id, upserted.price), (1, 3999));
+
+    let year = keyboard.created_at.get_year();
+    assert_eq!(
+        Product::query()
+            .where_year("created_at", year)
+            .count()
+            .await
+            .unwrap(),
+        2
+    );
+
+    assert!(!Product::query()
+        .explain(Explain::Plan)
+        .await
+        .unwrap()
+        .rows
+        .is_empty());
+    assert!(matches!(
+        Product::query().explain(Explain::Analyze).await,
+        Err(Error::Unsupported(_))
+    ));
+
+    let updated: Vec<Product> = Product::query()
+        .r#where("price", "<", 2000)
+        .update_returning(vec![("price", 2499)])
+        .await
+        .unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].price, 2499);
+
+    let deleted: Vec<Product> = Product::query()
+        .r#where("sku", "=", "SKU-2")
+        .delete_returning()
+        .await
+        .unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(Product::count().await.unwrap(), 1);
+
+    let inserted = Product::insert_many(vec![product("SKU-3", 100), product("SKU-4", 200)])
+        .await
+        .unwrap();
+    assert_eq!(inserted, 2);
+
+    let skus = Product::stream_by_id(2)
+        .map_ok(|product| product.sku)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(skus, ["SKU-1", "SKU-3", "SKU-4"]);
+
+    // JSON columns are stored as text, and queried with SQLite's JSON functions
+    let mut headset = product("SKU-5", 5999);
+    headset.details = Json(Details {
+        color: Some("black".to_string()),
+        tags: vec!["wireless".to_string(), "audio".to_string()],
+    });
+    let headset = headset.create().await.unwrap();
+    assert_eq!(
+        Product::find(headset.id).await.unwrap().details.tags.len(),
+        2
+    );
+
+    let wireless = Product::query()
+        .where_json_contains("details->tags", "wireless")
+        .get::<Product>()
+        .await
+        .unwrap();
+    assert_eq!(wireless.len(), 1);
+    assert_eq!(wireless[0].sku, "SKU-5");
+    assert_eq!(
+        Product::query()
+            .where_json("details->color", "=", "black")
+            .count()
+            .await
+            .unwrap(),
+        1
+    );
+
+    // factories create the related models along with each model
+    let products = Product::factory()
+        .count(2)
+        .sequence(|product, index| product.sku = format!("SKU-F{index}"))
+        .has(Review::factory().count(3))
+        .create()
+        .await
+        .unwrap();
+    assert_eq!(products[1].sku, "SKU-F1");
+    assert_eq!(
+        Review::query()
+            .r#where("product_id", "=", products[1].id)
+            .count()
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(Review::count().await.unwrap(), 6);
+
+    // raw queries hydrate models, or return rows with typed getters
+    let reviewed = Product::from_raw(
+        "SELECT * FROM products WHERE id IN (SELECT product_id FROM reviews) ORDER BY id",
+        vec![],
+    )
+    .await
+    .unwrap();
+    assert_eq!(reviewed.len(), 2);
+    assert_eq!(reviewed[0].sku, "SKU-F0");
+
+    let rows = ensemble::query::raw(
+        "SELECT product_id, COUNT(*) AS reviews FROM reviews WHERE product_id = ? GROUP BY product_id",
+        vec![rbs::to_value!(products[1].id)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        rows[0].try_get::<u64>("product_id").unwrap(),
+        products[1].id
+    );
+    assert_eq!(rows[0].try_get::<u32>("reviews").unwrap(), 3);
+
+    // only the new migration runs, in a batch of its own
+    let command = Command::parse(["migrate"]).unwrap();
+    ensemble::migrator!(CreateProductsTable, CreateCategoriesTable)
+        .await
+        .unwrap()
+        .execute(command)
+        .await
+        .unwrap();
+
+    let migrator = ensemble::migrator!(CreateProductsTable, CreateCategoriesTable)
+        .await
+        .unwrap();
+    assert!(migrator.pending().is_empty());
+    assert_eq!(
+        migrator
+            .status()
+            .iter()
+            .map(|migration| migration.batch)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+
+    let command = Command::parse(["migrate:rollback", "--step=1"]).unwrap();
+    migrator.execute(command).await.unwrap();
+
+    let migrator = ensemble::migrator!(CreateProductsTable, CreateCategoriesTable)
+        .await
+        .unwrap();
+    assert_eq!(migrator.pending().len(), 1);
+    assert!(migrator.pending().contains_key("CreateCategoriesTable"));
+    assert!(matches!(
+        Command::parse(["migrate:fresh"]),
+        Err(MigrationError::InvalidCommand(_))
+    ));
+}
@@ -0,0 +1,50 @@
+//! Strict mode is process-wide, so it's tested in its own binary instead of alongside the other derive tests.
+
+use ensemble::rbs::{self, value_map};
+use ensemble::relationships::HasMany;
+use ensemble::{Error, Model};
+
+#[derive(Debug, Model)]
+struct Comment {
+    id: u64,
+    post_id: u64,
+}
+
+#[derive(Debug, Model)]
+struct Post {
+    id: u64,
+    comments: HasMany<Post, Comment>,
+}
+
+fn post() -> Post {
+    rbs::from_value(rbs::Value::Map(value_map! { "id" : 1u64, })).unwrap()
+}
+
+#[tokio::test]
+async fn strict_mode_forbids_lazy_loading() {
+    assert!(!ensemble::is_strict());
+    let error = post().comments().await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    ensemble::strict_mode(true);
+
+    let error = post().comments().await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::LazyLoadViolation {
+            model: "Post",
+            relation: "comments"
+        }
+    ));
+
+    // eager loaded relationships never query, even when nothing was found
+    let mut eager = post();
+    eager.fill_relation("comments", &[]).unwrap();
+    assert!(eager.comments().await.unwrap().is_empty());
+
+    let mut lazy = post();
+    let error = ensemble::allow_lazy(lazy.comments()).await.unwrap_err();
+    assert!(matches!(error, Error::Connection(_)));
+
+    ensemble::strict_mode(false);
+}
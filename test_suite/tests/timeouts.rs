@@ -0,0 +1,120 @@
+//! Times out queries on a temporary `SQLite` database, with `cargo test --features sqlite --test timeouts`.
+//! The database is process-wide, so everything runs in a single test.
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use ensemble::migrations::{Error as MigrationError, Migration, Schema};
+use ensemble::query::Expr;
+use ensemble::{metrics, Error, FromRow, Model};
+
+#[derive(Debug, Model)]
+struct Tick {
+    id: u64,
+    label: String,
+}
+
+#[derive(Debug, Model)]
+struct Order {
+    id: u64,
+    total: u64,
+}
+
+#[derive(Debug, FromRow)]
+struct Count {
+    n: u64,
+}
+
+#[derive(Debug, Default)]
+struct CreateTables;
+
+#[ensemble::async_trait]
+impl Migration for CreateTables {
+    async fn up(&self) -> Result<(), MigrationError> {
+        Schema::create("ticks", |table| {
+            table.id();
+            table.string("label");
+        })
+        .await?;
+
+        Schema::create("orders", |table| {
+            table.id();
+            table.integer("total");
+        })
+        .await
+    }
+
+    async fn down(&self) -> Result<(), MigrationError> {
+        Schema::drop("orders").await?;
+        Schema::drop("ticks").await
+    }
+}
+
+/// Counting this far takes SQLite a few seconds, which is far longer than the timeout.
+const SLOW: &str = "(WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c LIMIT 50000000) SELECT COUNT(*) FROM c) AS n";
+const SLOW_SQL: &str = "SELECT (WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c LIMIT 50000000) SELECT COUNT(*) FROM c) AS n FROM ticks";
+
+fn slow_query() -> ensemble::query::Builder {
+    Tick::query()
+        .select_raw(Expr::raw(SLOW))
+        .timeout(Duration::from_millis(100))
+}
+
+#[tokio::test]
+async fn timed_out_queries_close_their_connection() {
+    // timed out connections are closed, so the database needs to outlive them, unlike an in-memory one
+    let path = std::env::temp_dir().join(format!("ensemble-timeouts-{}.db", std::process::id()));
+    ensemble::setup(&format!("sqlite://{}?mode=rwc", path.display()))
+        .await
+        .unwrap();
+    ensemble::migrate!(CreateTables).await.unwrap();
+
+    Tick {
+        id: 0,
+        label: "first".to_string(),
+    }
+    .create()
+    .await
+    .unwrap();
+
+    // queries that finish in time are unaffected
+    let counted = Tick::query()
+        .select_raw(Expr::raw("COUNT(*) AS n"))
+        .timeout(Duration::from_secs(5))
+        .get_as::<Count>()
+        .await
+        .unwrap();
+    assert_eq!(counted[0].n, 1);
+
+    let evictions = metrics::snapshot().pool.evictions;
+
+    let error = slow_query().get_as::<Count>().await.unwrap_err();
+    assert!(matches!(error, Error::QueryTimeout(sql) if sql == SLOW_SQL));
+
+    // the connection may still be running the query, so it's closed instead of going back to the pool
+    assert_eq!(metrics::snapshot().pool.evictions, evictions + 1);
+    assert!(ensemble::ping().await.is_ok());
+
+    // inside a transaction, the transaction's connection is closed, rolling it back
+    let result = ensemble::transaction(|| async {
+        Order { id: 0, total: 100 }.create().await?;
+
+        let error = slow_query().get_as::<Count>().await.unwrap_err();
+        assert!(matches!(&error, Error::QueryTimeout(sql) if sql == SLOW_SQL));
+
+        // and every later query fails
+        assert!(matches!(
+            Order::query().count().await,
+            Err(Error::QueryTimeout(sql)) if sql == SLOW_SQL
+        ));
+
+        Ok::<_, Error>(())
+    })
+    .await;
+
+    assert!(matches!(result, Err(Error::QueryTimeout(sql)) if sql == SLOW_SQL));
+    assert_eq!(metrics::snapshot().pool.evictions, evictions + 2);
+    assert_eq!(Order::query().count().await.unwrap(), 0);
+
+    std::fs::remove_file(path).ok();
+}